@@ -1,6 +1,7 @@
 use criterion::{criterion_group, criterion_main, Criterion};
 use sampitor::dsp::Samples;
 use sampitor::io::audio;
+use sampitor::tab::Tab;
 use sampitor::App;
 use std::path::PathBuf;
 use tempfile::NamedTempFile;
@@ -17,7 +18,7 @@ pub fn init_benchmark(c: &mut Criterion) {
     let backend = TestBackend::new(20, 10);
     let mut terminal = Terminal::new(backend).unwrap();
 
-    let mut app = App::new(&mut [], Samples::default());
+    let mut app = App::new(&mut [], Tab::default());
     app.render(&mut terminal).unwrap();
 
     c.bench_function("render", |b| b.iter(|| app.render(&mut terminal).unwrap()));
@@ -0,0 +1,802 @@
+//! Named application commands and the keys that trigger them.
+
+use color_eyre::eyre;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+/// Application level commands triggered by key presses.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Action {
+    CloseTab,
+    CommandPalette,
+    CommitPreview,
+    CrossfadeLoop,
+    CycleTheme,
+    DecodeMidSide,
+    DeleteSelection,
+    EncodeMidSide,
+    EstimateTempo,
+    ExportAscii,
+    ExportCsv,
+    ExportImage,
+    ExportRegions,
+    Help,
+    InsertSilence,
+    InvertPolarity,
+    ListTabs,
+    MixTab,
+    NextTab,
+    NextView,
+    NextZeroCrossing,
+    OpenNewTab,
+    PitchShift,
+    Play,
+    PrevTab,
+    PrevZeroCrossing,
+    Quit,
+    Record,
+    Redo,
+    RemoveDcOffset,
+    ResetMeters,
+    RevertPreview,
+    SplitAtMarkers,
+    TimeStretch,
+    ToggleInfo,
+    TogglePreview,
+    ToggleScrub,
+    ToggleSnap,
+    ToggleStats,
+    Undo,
+}
+
+impl Action {
+    /// All actions in declaration order.
+    pub const ALL: [Self; 40] = [
+        Self::CloseTab,
+        Self::CommandPalette,
+        Self::CommitPreview,
+        Self::CrossfadeLoop,
+        Self::CycleTheme,
+        Self::DecodeMidSide,
+        Self::DeleteSelection,
+        Self::EncodeMidSide,
+        Self::EstimateTempo,
+        Self::ExportAscii,
+        Self::ExportCsv,
+        Self::ExportImage,
+        Self::ExportRegions,
+        Self::Help,
+        Self::InsertSilence,
+        Self::InvertPolarity,
+        Self::ListTabs,
+        Self::MixTab,
+        Self::NextTab,
+        Self::NextView,
+        Self::NextZeroCrossing,
+        Self::OpenNewTab,
+        Self::PitchShift,
+        Self::Play,
+        Self::PrevTab,
+        Self::PrevZeroCrossing,
+        Self::Quit,
+        Self::Record,
+        Self::Redo,
+        Self::RemoveDcOffset,
+        Self::ResetMeters,
+        Self::RevertPreview,
+        Self::SplitAtMarkers,
+        Self::TimeStretch,
+        Self::ToggleInfo,
+        Self::TogglePreview,
+        Self::ToggleScrub,
+        Self::ToggleSnap,
+        Self::ToggleStats,
+        Self::Undo,
+    ];
+
+    /// Get the help section the action is listed under.
+    #[must_use]
+    pub const fn category(self) -> Category {
+        match self {
+            Self::CloseTab
+            | Self::ExportAscii
+            | Self::ExportCsv
+            | Self::ExportImage
+            | Self::ExportRegions
+            | Self::ListTabs
+            | Self::NextTab
+            | Self::OpenNewTab
+            | Self::PrevTab
+            | Self::SplitAtMarkers => Category::Files,
+            Self::CommandPalette
+            | Self::CycleTheme
+            | Self::Help
+            | Self::NextView
+            | Self::Quit
+            | Self::ResetMeters
+            | Self::ToggleInfo
+            | Self::ToggleStats => Category::View,
+            Self::Play | Self::Record | Self::ToggleScrub => Category::Transport,
+            Self::CommitPreview
+            | Self::CrossfadeLoop
+            | Self::DecodeMidSide
+            | Self::DeleteSelection
+            | Self::EncodeMidSide
+            | Self::EstimateTempo
+            | Self::InsertSilence
+            | Self::InvertPolarity
+            | Self::MixTab
+            | Self::NextZeroCrossing
+            | Self::PitchShift
+            | Self::PrevZeroCrossing
+            | Self::Redo
+            | Self::RemoveDcOffset
+            | Self::RevertPreview
+            | Self::TimeStretch
+            | Self::TogglePreview
+            | Self::ToggleSnap
+            | Self::Undo => Category::Editing,
+        }
+    }
+
+    /// Get the name used for the action in configuration files.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::CloseTab => "close_tab",
+            Self::CommandPalette => "command_palette",
+            Self::CommitPreview => "commit_preview",
+            Self::CrossfadeLoop => "crossfade_loop",
+            Self::CycleTheme => "cycle_theme",
+            Self::DecodeMidSide => "decode_mid_side",
+            Self::DeleteSelection => "delete_selection",
+            Self::EncodeMidSide => "encode_mid_side",
+            Self::EstimateTempo => "estimate_tempo",
+            Self::ExportAscii => "export_ascii",
+            Self::ExportCsv => "export_csv",
+            Self::ExportImage => "export_image",
+            Self::ExportRegions => "export_regions",
+            Self::Help => "help",
+            Self::InsertSilence => "insert_silence",
+            Self::InvertPolarity => "invert_polarity",
+            Self::ListTabs => "list_tabs",
+            Self::MixTab => "mix_tab",
+            Self::NextTab => "next_tab",
+            Self::NextView => "next_view",
+            Self::NextZeroCrossing => "next_zero_crossing",
+            Self::OpenNewTab => "open_new_tab",
+            Self::PitchShift => "pitch_shift",
+            Self::Play => "play",
+            Self::PrevTab => "prev_tab",
+            Self::PrevZeroCrossing => "prev_zero_crossing",
+            Self::Quit => "quit",
+            Self::Record => "record",
+            Self::Redo => "redo",
+            Self::RemoveDcOffset => "remove_dc_offset",
+            Self::ResetMeters => "reset_meters",
+            Self::RevertPreview => "revert_preview",
+            Self::SplitAtMarkers => "split_at_markers",
+            Self::TimeStretch => "time_stretch",
+            Self::ToggleInfo => "toggle_info",
+            Self::TogglePreview => "toggle_preview",
+            Self::ToggleScrub => "toggle_scrub",
+            Self::ToggleSnap => "toggle_snap",
+            Self::ToggleStats => "toggle_stats",
+            Self::Undo => "undo",
+        }
+    }
+
+    /// Get a short description of the action for the help display.
+    #[must_use]
+    pub const fn description(self) -> &'static str {
+        match self {
+            Self::CloseTab => "Close tab",
+            Self::CommandPalette => "Search and run actions",
+            Self::CommitPreview => "Keep edit and hide original",
+            Self::CrossfadeLoop => "Crossfade selection end for seamless looping",
+            Self::CycleTheme => "Next color theme",
+            Self::DecodeMidSide => "Decode mid side to left and right",
+            Self::DeleteSelection => "Delete selected frames",
+            Self::EncodeMidSide => "Encode left and right to mid side",
+            Self::EstimateTempo => "Estimate tempo",
+            Self::ExportAscii => "Write waveform as ASCII art text",
+            Self::ExportCsv => "Write sample values as CSV",
+            Self::ExportImage => "Write waveform as PNG image",
+            Self::ExportRegions => "Write sounds between silences to files",
+            Self::Help => "Toggle key bindings help",
+            Self::InsertSilence => "Insert silence at the playhead",
+            Self::InvertPolarity => "Invert polarity of one or all channels",
+            Self::ListTabs => "List open tabs",
+            Self::MixTab => "Mix a tab in at the playhead",
+            Self::NextTab => "Next tab",
+            Self::NextView => "Next view",
+            Self::NextZeroCrossing => "Move edit point to next zero crossing",
+            Self::OpenNewTab => "Open new tab",
+            Self::PitchShift => "Change pitch by semitones keeping length",
+            Self::Play => "Play or pause",
+            Self::PrevTab => "Previous tab",
+            Self::PrevZeroCrossing => "Move edit point to previous zero crossing",
+            Self::Quit => "Quit or dismiss popup",
+            Self::Record => "Start or stop recording from the input device",
+            Self::Redo => "Redo edit",
+            Self::RemoveDcOffset => "Remove DC offset",
+            Self::ResetMeters => "Clear meter peak holds and clip indicators",
+            Self::RevertPreview => "Revert edit to original",
+            Self::SplitAtMarkers => "Write segments between markers to files",
+            Self::TimeStretch => "Change length by a percentage keeping pitch",
+            Self::ToggleInfo => "Toggle file metadata",
+            Self::TogglePreview => "Toggle original waveform overlay",
+            Self::ToggleScrub => "Toggle playing snippets when the playhead moves",
+            Self::ToggleSnap => "Toggle snapping to zero crossings",
+            Self::ToggleStats => "Toggle level statistics",
+            Self::Undo => "Undo edit",
+        }
+    }
+
+    /// Get the key bound to the action by default.
+    #[must_use]
+    pub const fn default_key(self) -> Key {
+        match self {
+            Self::CloseTab => Key::new(KeyCode::Char('w'), KeyModifiers::CONTROL),
+            Self::CommandPalette => Key::new(KeyCode::F(1), KeyModifiers::NONE),
+            Self::CommitPreview => Key::new(KeyCode::Char('k'), KeyModifiers::CONTROL),
+            Self::CrossfadeLoop => Key::new(KeyCode::Char('l'), KeyModifiers::ALT),
+            Self::CycleTheme => Key::new(KeyCode::F(2), KeyModifiers::NONE),
+            Self::DecodeMidSide => Key::new(KeyCode::Char('d'), KeyModifiers::ALT),
+            Self::DeleteSelection => Key::new(KeyCode::Delete, KeyModifiers::NONE),
+            Self::EncodeMidSide => Key::new(KeyCode::Char('e'), KeyModifiers::ALT),
+            Self::EstimateTempo => Key::new(KeyCode::Char('e'), KeyModifiers::CONTROL),
+            Self::ExportAscii => Key::new(KeyCode::Char('a'), KeyModifiers::ALT),
+            Self::ExportCsv => Key::new(KeyCode::Char('n'), KeyModifiers::ALT),
+            Self::ExportImage => Key::new(KeyCode::Char('p'), KeyModifiers::ALT),
+            Self::ExportRegions => Key::new(KeyCode::Char('g'), KeyModifiers::ALT),
+            Self::Help => Key::new(KeyCode::Char('?'), KeyModifiers::NONE),
+            Self::InsertSilence => Key::new(KeyCode::Insert, KeyModifiers::NONE),
+            Self::InvertPolarity => Key::new(KeyCode::Char('v'), KeyModifiers::ALT),
+            Self::ListTabs => Key::new(KeyCode::Char('b'), KeyModifiers::CONTROL),
+            Self::MixTab => Key::new(KeyCode::Char('m'), KeyModifiers::ALT),
+            Self::NextTab => Key::new(KeyCode::Right, KeyModifiers::CONTROL),
+            Self::NextView => Key::new(KeyCode::Tab, KeyModifiers::NONE),
+            Self::NextZeroCrossing => Key::new(KeyCode::Right, KeyModifiers::ALT),
+            Self::OpenNewTab => Key::new(KeyCode::Char('t'), KeyModifiers::CONTROL),
+            Self::PitchShift => Key::new(KeyCode::Char('h'), KeyModifiers::ALT),
+            Self::Play => Key::new(KeyCode::Char(' '), KeyModifiers::NONE),
+            Self::PrevTab => Key::new(KeyCode::Left, KeyModifiers::CONTROL),
+            Self::PrevZeroCrossing => Key::new(KeyCode::Left, KeyModifiers::ALT),
+            Self::Quit => Key::new(KeyCode::Esc, KeyModifiers::NONE),
+            Self::Record => Key::new(KeyCode::Char('r'), KeyModifiers::ALT),
+            Self::Redo => Key::new(KeyCode::Char('y'), KeyModifiers::CONTROL),
+            Self::RemoveDcOffset => Key::new(KeyCode::Char('o'), KeyModifiers::ALT),
+            Self::ResetMeters => Key::new(KeyCode::Char('c'), KeyModifiers::ALT),
+            Self::RevertPreview => Key::new(KeyCode::Char('r'), KeyModifiers::CONTROL),
+            Self::SplitAtMarkers => Key::new(KeyCode::Char('x'), KeyModifiers::ALT),
+            Self::TimeStretch => Key::new(KeyCode::Char('t'), KeyModifiers::ALT),
+            Self::ToggleInfo => Key::new(KeyCode::Char('i'), KeyModifiers::ALT),
+            Self::TogglePreview => Key::new(KeyCode::Char('p'), KeyModifiers::CONTROL),
+            Self::ToggleScrub => Key::new(KeyCode::Char('u'), KeyModifiers::CONTROL),
+            Self::ToggleSnap => Key::new(KeyCode::Char('g'), KeyModifiers::CONTROL),
+            Self::ToggleStats => Key::new(KeyCode::Char('s'), KeyModifiers::ALT),
+            Self::Undo => Key::new(KeyCode::Char('z'), KeyModifiers::CONTROL),
+        }
+    }
+
+    /// Find the actions whose description or configuration name fuzzily matches a query, best
+    /// matches first.
+    ///
+    /// Query characters must appear in order, ignoring case and whitespace. Matches score higher
+    /// for consecutive characters and characters that start words, and equal scores keep
+    /// declaration order, so an empty query lists every action.
+    #[must_use]
+    pub fn search(query: &str) -> Vec<Self> {
+        let mut scored: Vec<(usize, Self)> = Self::ALL
+            .iter()
+            .filter_map(|action| {
+                let score = fuzzy_score(query, action.description())
+                    .max(fuzzy_score(query, action.name()))?;
+                Some((score, *action))
+            })
+            .collect();
+
+        scored.sort_by_key(|(score, _action)| std::cmp::Reverse(*score));
+        scored.into_iter().map(|(_score, action)| action).collect()
+    }
+}
+
+/// Score how well a query matches text as a case insensitive subsequence, or `None` if some query
+/// character is missing.
+///
+/// Later query characters take the next matching character of the text, so every start of the
+/// first character is tried to find the best match.
+fn fuzzy_score(query: &str, text: &str) -> Option<usize> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let query: Vec<char> = query
+        .to_lowercase()
+        .chars()
+        .filter(|char| !char.is_whitespace())
+        .collect();
+    let first = match query.first() {
+        Some(first) => *first,
+        None => return Some(0),
+    };
+
+    let score_from = |start: usize| {
+        let mut score = 0;
+        let mut next = start;
+        for char in &query {
+            let index = next
+                + text[next..]
+                    .iter()
+                    .position(|candidate| candidate == char)?;
+            score += 1;
+            if index == 0 || matches!(text[index - 1], ' ' | '_') {
+                score += 2;
+            }
+            if index > start && index == next {
+                score += 3;
+            }
+            next = index + 1;
+        }
+        Some(score)
+    };
+
+    (0..text.len())
+        .filter(|start| text[*start] == first)
+        .filter_map(score_from)
+        .max()
+}
+
+impl FromStr for Action {
+    type Err = eyre::Report;
+
+    fn from_str(text: &str) -> eyre::Result<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|action| action.name() == text)
+            .ok_or_else(|| eyre::eyre!("Unknown action {:?}", text))
+    }
+}
+
+/// Groups of related actions in the help display.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Category {
+    Editing,
+    Files,
+    Transport,
+    View,
+}
+
+impl Category {
+    /// All categories in help display order.
+    pub const ALL: [Self; 4] = [Self::Transport, Self::Editing, Self::View, Self::Files];
+
+    /// Get the heading of the category in the help display.
+    #[must_use]
+    pub const fn title(self) -> &'static str {
+        match self {
+            Self::Editing => "Editing",
+            Self::Files => "Files",
+            Self::Transport => "Transport",
+            Self::View => "View",
+        }
+    }
+}
+
+/// A key press and its modifiers.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl Key {
+    /// Create a Key from a key code and modifiers.
+    #[must_use]
+    pub const fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Self { code, modifiers }
+    }
+}
+
+impl From<KeyEvent> for Key {
+    /// Convert a key event, ignoring the shift modifier for characters since their case already
+    /// encodes it.
+    fn from(event: KeyEvent) -> Self {
+        let modifiers = match event.code {
+            KeyCode::Char(_) => event.modifiers - KeyModifiers::SHIFT,
+            _ => event.modifiers,
+        };
+        Self::new(event.code, modifiers)
+    }
+}
+
+impl FromStr for Key {
+    type Err = eyre::Report;
+
+    /// Parse a key from text such as `ctrl+shift+left`, `space`, or `q`.
+    fn from_str(text: &str) -> eyre::Result<Self> {
+        let invalid = || eyre::eyre!("Invalid key {:?}", text);
+        let mut parts: Vec<&str> = text.split('+').collect();
+        // A trailing empty part means the key itself is a plus sign.
+        if text.ends_with("++") || text == "+" {
+            parts.pop();
+            parts.pop();
+            parts.push("+");
+        }
+        let (name, modifier_names) = parts.split_last().ok_or_else(invalid)?;
+
+        let mut modifiers = KeyModifiers::NONE;
+        for modifier in modifier_names {
+            modifiers |= match modifier.to_lowercase().as_str() {
+                "alt" => KeyModifiers::ALT,
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(invalid()),
+            };
+        }
+
+        let code = match name.to_lowercase().as_str() {
+            "backspace" => KeyCode::Backspace,
+            "backtab" => KeyCode::BackTab,
+            "delete" => KeyCode::Delete,
+            "down" => KeyCode::Down,
+            "end" => KeyCode::End,
+            "enter" => KeyCode::Enter,
+            "esc" | "escape" => KeyCode::Esc,
+            "home" => KeyCode::Home,
+            "insert" => KeyCode::Insert,
+            "left" => KeyCode::Left,
+            "pagedown" => KeyCode::PageDown,
+            "pageup" => KeyCode::PageUp,
+            "right" => KeyCode::Right,
+            "space" => KeyCode::Char(' '),
+            "tab" => KeyCode::Tab,
+            "up" => KeyCode::Up,
+            lower => {
+                let mut chars = name.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(char), None) => KeyCode::Char(char),
+                    _ => match lower.strip_prefix('f').map(str::parse::<u8>) {
+                        Some(Ok(number)) if (1..=24).contains(&number) => KeyCode::F(number),
+                        _ => return Err(invalid()),
+                    },
+                }
+            }
+        };
+
+        Ok(Self::from(KeyEvent::new(code, modifiers)))
+    }
+}
+
+impl Display for Key {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "ctrl+"),
+            (KeyModifiers::ALT, "alt+"),
+            (KeyModifiers::SHIFT, "shift+"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                formatter.write_str(name)?;
+            }
+        }
+
+        match self.code {
+            KeyCode::Backspace => formatter.write_str("backspace"),
+            KeyCode::BackTab => formatter.write_str("backtab"),
+            KeyCode::Char(' ') => formatter.write_str("space"),
+            KeyCode::Char(char) => write!(formatter, "{}", char),
+            KeyCode::Delete => formatter.write_str("delete"),
+            KeyCode::Down => formatter.write_str("down"),
+            KeyCode::End => formatter.write_str("end"),
+            KeyCode::Enter => formatter.write_str("enter"),
+            KeyCode::Esc => formatter.write_str("esc"),
+            KeyCode::F(number) => write!(formatter, "f{}", number),
+            KeyCode::Home => formatter.write_str("home"),
+            KeyCode::Insert => formatter.write_str("insert"),
+            KeyCode::Left => formatter.write_str("left"),
+            KeyCode::PageDown => formatter.write_str("pagedown"),
+            KeyCode::PageUp => formatter.write_str("pageup"),
+            KeyCode::Right => formatter.write_str("right"),
+            KeyCode::Tab => formatter.write_str("tab"),
+            KeyCode::Up => formatter.write_str("up"),
+            KeyCode::Null => formatter.write_str("null"),
+        }
+    }
+}
+
+/// Mapping from key presses to the actions they trigger.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Keymap {
+    bindings: HashMap<Key, Action>,
+}
+
+impl Keymap {
+    /// Create a Keymap from configured bindings of action names to keys.
+    ///
+    /// Actions without a configured binding keep their default key.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if a configured action name or key is invalid, or if two actions end up
+    /// bound to the same key.
+    pub fn try_new(keys: &BTreeMap<String, String>) -> eyre::Result<Self> {
+        let mut overrides = HashMap::new();
+
+        for (name, key) in keys {
+            let action: Action = name
+                .parse()
+                .map_err(|error| eyre::eyre!("Key binding keys.{}: {}", name, error))?;
+            let key: Key = key
+                .parse()
+                .map_err(|error| eyre::eyre!("Key binding keys.{}: {}", name, error))?;
+            overrides.insert(action, key);
+        }
+
+        let mut bindings = HashMap::new();
+        for action in Action::ALL {
+            let key = overrides
+                .get(&action)
+                .copied()
+                .unwrap_or_else(|| action.default_key());
+
+            if let Some(other) = bindings.insert(key, action) {
+                // Report the entry from the configuration file rather than a default binding.
+                let name = if overrides.contains_key(&action) {
+                    action.name()
+                } else {
+                    other.name()
+                };
+                eyre::bail!(
+                    "Key binding keys.{}: key {:?} is bound to both {} and {}",
+                    name,
+                    key.to_string(),
+                    other.name(),
+                    action.name()
+                );
+            }
+        }
+
+        Ok(Self { bindings })
+    }
+
+    /// Get the action bound to a key press.
+    #[must_use]
+    pub fn action(&self, event: KeyEvent) -> Option<Action> {
+        self.bindings.get(&Key::from(event)).copied()
+    }
+
+    /// Generate help lines of every action and its binding, grouped by category.
+    ///
+    /// Categories without any actions are omitted.
+    #[must_use]
+    pub fn help(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+
+        for category in Category::ALL {
+            let actions: Vec<Action> = Action::ALL
+                .iter()
+                .copied()
+                .filter(|action| action.category() == category)
+                .collect();
+            if actions.is_empty() {
+                continue;
+            }
+
+            if !lines.is_empty() {
+                lines.push(String::new());
+            }
+            lines.push(category.title().to_owned());
+            for action in actions {
+                let key = self
+                    .key(action)
+                    .map_or_else(String::new, |key| format!("{}", key));
+                lines.push(format!("{:>16}  {}", key, action.description()));
+            }
+            // Number keys are not configurable actions but still belong with the tab bindings.
+            if category == Category::Files {
+                lines.push(format!("{:>16}  {}", "alt+1-9", "Go to tab by number"));
+            }
+        }
+
+        lines
+    }
+
+    /// Get the key bound to an action.
+    #[must_use]
+    pub fn key(&self, action: Action) -> Option<Key> {
+        self.bindings
+            .iter()
+            .find(|(_key, bound)| **bound == action)
+            .map(|(key, _bound)| *key)
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = Action::ALL
+            .iter()
+            .map(|action| (action.default_key(), *action))
+            .collect();
+
+        Self { bindings }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(action, key)| (action.to_string(), key.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn help_lists_every_action() {
+        let expected = "\
+Transport
+           space  Play or pause
+           alt+r  Start or stop recording from the input device
+          ctrl+u  Toggle playing snippets when the playhead moves
+
+Editing
+          ctrl+k  Keep edit and hide original
+           alt+l  Crossfade selection end for seamless looping
+           alt+d  Decode mid side to left and right
+          delete  Delete selected frames
+           alt+e  Encode left and right to mid side
+          ctrl+e  Estimate tempo
+          insert  Insert silence at the playhead
+           alt+v  Invert polarity of one or all channels
+           alt+m  Mix a tab in at the playhead
+       alt+right  Move edit point to next zero crossing
+           alt+h  Change pitch by semitones keeping length
+        alt+left  Move edit point to previous zero crossing
+          ctrl+y  Redo edit
+           alt+o  Remove DC offset
+          ctrl+r  Revert edit to original
+           alt+t  Change length by a percentage keeping pitch
+          ctrl+p  Toggle original waveform overlay
+          ctrl+g  Toggle snapping to zero crossings
+          ctrl+z  Undo edit
+
+View
+              f1  Search and run actions
+              f2  Next color theme
+               ?  Toggle key bindings help
+             tab  Next view
+             esc  Quit or dismiss popup
+           alt+c  Clear meter peak holds and clip indicators
+           alt+i  Toggle file metadata
+           alt+s  Toggle level statistics
+
+Files
+          ctrl+w  Close tab
+           alt+a  Write waveform as ASCII art text
+           alt+n  Write sample values as CSV
+           alt+p  Write waveform as PNG image
+           alt+g  Write sounds between silences to files
+          ctrl+b  List open tabs
+      ctrl+right  Next tab
+          ctrl+t  Open new tab
+       ctrl+left  Previous tab
+           alt+x  Write segments between markers to files
+         alt+1-9  Go to tab by number";
+
+        let actual = Keymap::default().help().join("\n");
+        assert_eq!(actual, expected);
+        assert!(Action::ALL
+            .iter()
+            .all(|action| !action.description().is_empty()));
+    }
+
+    #[test]
+    fn key_round_trip() {
+        for text in ["ctrl+alt+left", "space", "q", "f5", "shift+tab", "+"] {
+            let key: Key = text.parse().unwrap();
+            assert_eq!(key.to_string(), text);
+        }
+        assert!("hyper+q".parse::<Key>().is_err());
+        assert!("ctrl+".parse::<Key>().is_err());
+    }
+
+    #[test]
+    fn keymap_configured_binding() {
+        let keymap = Keymap::try_new(&keys(&[("quit", "ctrl+q")])).unwrap();
+
+        let bound = KeyEvent::new(KeyCode::Char('q'), KeyModifiers::CONTROL);
+        let replaced = KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE);
+        let unspecified = KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE);
+
+        assert_eq!(keymap.action(bound), Some(Action::Quit));
+        assert_eq!(keymap.action(replaced), None);
+        assert_eq!(keymap.action(unspecified), Some(Action::NextView));
+    }
+
+    #[test]
+    fn keymap_duplicate_binding() {
+        let error = Keymap::try_new(&keys(&[("play", "tab")])).unwrap_err();
+        let message = format!("{}", error);
+
+        assert!(message.contains("keys.play"));
+        assert!(message.contains("next_view"));
+    }
+
+    #[test]
+    fn keymap_from_config_file() {
+        let text = r#"
+            [keys]
+            close_tab = "alt+w"
+            next_tab = "ctrl+shift+right"
+            quit = "q"
+        "#;
+        let config: crate::config::Config = toml::from_str(text).unwrap();
+        let keymap = Keymap::try_new(&config.keys).unwrap();
+
+        let close = KeyEvent::new(KeyCode::Char('w'), KeyModifiers::ALT);
+        let next = KeyEvent::new(KeyCode::Right, KeyModifiers::CONTROL | KeyModifiers::SHIFT);
+        let unmapped = KeyEvent::new(KeyCode::Char('w'), KeyModifiers::CONTROL);
+
+        assert_eq!(keymap.action(close), Some(Action::CloseTab));
+        assert_eq!(keymap.action(next), Some(Action::NextTab));
+        assert_eq!(keymap.action(unmapped), None);
+        assert_eq!(keymap.key(Action::Quit), Some("q".parse().unwrap()));
+    }
+
+    #[test]
+    fn keymap_unknown_action() {
+        let error = Keymap::try_new(&keys(&[("explode", "x")])).unwrap_err();
+
+        assert!(format!("{}", error).contains("keys.explode"));
+    }
+
+    #[test]
+    fn search_ranks_fuzzy_matches() {
+        assert_eq!(
+            Action::search("tab"),
+            [
+                Action::CloseTab,
+                Action::ListTabs,
+                Action::MixTab,
+                Action::NextTab,
+                Action::OpenNewTab,
+                Action::PrevTab,
+            ]
+        );
+        assert_eq!(
+            Action::search("ZC"),
+            [
+                Action::NextZeroCrossing,
+                Action::PrevZeroCrossing,
+                Action::ToggleSnap
+            ]
+        );
+        assert_eq!(
+            Action::search("rmdc"),
+            [Action::RemoveDcOffset, Action::Record, Action::ResetMeters]
+        );
+        assert_eq!(
+            Action::search("mid side"),
+            [Action::DecodeMidSide, Action::EncodeMidSide]
+        );
+        assert_eq!(
+            Action::search("undo"),
+            [Action::Undo, Action::ExportRegions]
+        );
+        assert_eq!(Action::search("xyzzy"), []);
+        assert_eq!(Action::search(""), Action::ALL);
+    }
+
+    #[test]
+    fn modifiers_distinguish_keys() {
+        let keymap = Keymap::default();
+        let plain = KeyEvent::new(KeyCode::Right, KeyModifiers::NONE);
+        let control = KeyEvent::new(KeyCode::Right, KeyModifiers::CONTROL);
+
+        assert_eq!(keymap.action(plain), None);
+        assert_eq!(keymap.action(control), Some(Action::NextTab));
+    }
+}
@@ -1,202 +1,2811 @@
 //! Application runners.
 
-use crate::dsp::Samples;
+use crate::action::{Action, Keymap};
+use crate::dsp::{self, filters, Samples, Selection};
+use crate::io::csv::{self, IndexColumn};
 use crate::io::event;
+use crate::recorder::{self, Recorder, Settings};
+use crate::session::Session;
+use crate::tab::{RegionDetection, Tab};
 use crate::ui;
+use crate::ui::meter::{self, Ballistics, Levels, Meter, Tap};
+use crate::ui::theme::{Palette, Theme};
+use crate::ui::unsaved::{self, Choice};
+use crate::view::chart;
 use crate::view::View;
 use color_eyre::eyre;
-use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
 use rodio::buffer::SamplesBuffer;
-use rodio::Sink;
+use rodio::{Sink, Source};
+use std::convert::TryFrom;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{self, TryRecvError};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tui::backend::Backend;
-use tui::layout::Constraint::Percentage;
+use tui::layout::Constraint::{Length, Min};
 use tui::layout::{Direction, Layout, Rect};
-use tui::style::{Modifier, Style};
 use tui::terminal::{Frame, Terminal};
-use tui::text::{Spans, Text};
-use tui::widgets::{Block, Borders, Clear, Paragraph, Tabs};
+use tui::text::{Span, Spans, Text};
+use tui::widgets::{
+    Block, Borders, Clear, List, ListItem, ListState, Paragraph, Row, Table, Tabs, Wrap,
+};
+
+/// Bounds and step size of the gain for mixing a tab into another.
+const MIX_GAIN: (f32, f32, f32) = (0.0, 4.0, 0.1);
+/// Width and height in pixels of exported waveform images.
+const IMAGE_SIZE: (u32, u32) = (1200, 400);
+/// Width and height in characters of exported waveform text.
+const ASCII_SIZE: (usize, usize) = (100, 24);
+/// Level in decibels at the empty end of the recording and playback level meters.
+const METER_FLOOR: f32 = -60.0;
+/// Shortest and longest snippets in milliseconds played while scrubbing.
+const SCRUB_LIMITS: (f32, f32) = (30.0, 80.0);
+/// Milliseconds that snippets played while scrubbing fade in and out.
+const SCRUB_FADE_MILLISECONDS: f32 = 3.0;
+
+/// Smallest and largest time stretch in percent of the original length.
+const STRETCH_PERCENT: (f32, f32) = (25.0, 400.0);
+/// Largest pitch shift in semitones in either direction.
+const TRANSPOSE_SEMITONES: f32 = 24.0;
+
+/// Default magnitude at which level statistics count samples as clipped.
+pub const CLIP_THRESHOLD: f32 = 1.0;
+
+/// Default number of frames searched in each direction for a zero crossing to snap to.
+pub const SNAP_SEARCH_FRAMES: usize = 512;
+
+/// Default length in milliseconds of snippets played while scrubbing.
+pub const SCRUB_MILLISECONDS: f32 = 50.0;
+
+/// Unsaved changes prompt shown before an action that discards tabs.
+struct Confirm {
+    /// Action to perform once changes are saved or discarded.
+    action: Action,
+    /// Error of a failed save, if any.
+    error: Option<String>,
+}
+
+impl Confirm {
+    /// Create a new Confirm prompt for an action.
+    const fn new(action: Action) -> Self {
+        Self {
+            action,
+            error: None,
+        }
+    }
+}
+
+/// Prompt shown before an export replaces an existing file.
+struct Overwrite {
+    /// Export action to perform once the overwrite is confirmed.
+    action: Action,
+    /// Existing file the export writes.
+    path: PathBuf,
+    /// Whether a CSV export writes the selected frames instead of the whole tab.
+    selected: bool,
+}
+
+/// Prompt for the factor of a time stretch or pitch shift, or the length of a loop crossfade.
+struct Factor {
+    /// Action the factor is typed for.
+    action: Action,
+    /// Error of the last entered factor, if any.
+    error: Option<String>,
+    text: String,
+}
+
+impl Factor {
+    /// Create a new Factor prompt for an action, starting from text that does not change the
+    /// samples.
+    fn new(action: Action) -> Self {
+        let text = match action {
+            Action::CrossfadeLoop => "10",
+            Action::PitchShift => "0",
+            _ => "100%",
+        };
+        Self {
+            action,
+            error: None,
+            text: String::from(text),
+        }
+    }
+
+    /// Get the title of the prompt.
+    const fn title(&self) -> &'static str {
+        match self.action {
+            Action::CrossfadeLoop => "Crossfade loop seam in milliseconds",
+            Action::PitchShift => "Change pitch by semitones",
+            _ => "Change length to a percentage",
+        }
+    }
+}
 
 /// Main runner for Sampitor application.
 pub struct App<'a, B: Backend> {
+    /// Magnitude at which level statistics count samples as clipped.
+    clip_threshold: f32,
+    confirm: Option<Confirm>,
+    /// Selected entry of the popup choosing whether to export the selected frames or the whole
+    /// tab as CSV, if it is shown. The first entry is the selected frames.
+    csv_export: Option<ListState>,
+    /// Leading column of exported CSV files.
+    csv_index: IndexColumn,
     error: eyre::Result<()>,
-    samples: Samples,
+    /// Typed time stretch, pitch shift, or loop crossfade length, if its prompt is shown.
+    factor: Option<Factor>,
+    help: bool,
+    /// Whether the metadata popup of the current tab is shown.
+    info: bool,
+    /// Names of input devices to record from and the selected entry, if the popup listing them is
+    /// shown. The first entry is the default device.
+    input_devices: Option<(Vec<String>, ListState)>,
+    keymap: Keymap,
+    layout: Vec<Rect>,
+    /// Level meters of playback, or of the whole active tab while it is not playing.
+    meter: Meter,
+    /// Peaks written by the source that is playing and the time the meter last read them.
+    meter_levels: (Levels, Instant),
+    /// Export waiting for confirmation to replace an existing file, if its prompt is shown.
+    overwrite: Option<Overwrite>,
+    /// Gain for mixing the tab picked from the open tabs popup, if it picks a tab to mix in.
+    mix_gain: Option<f32>,
+    /// Typed query and selected entry of the command palette, if it is shown.
+    palette: Option<(String, ListState)>,
+    /// Selected entry of the popup listing channels to invert the polarity of, if it is shown.
+    /// The first entry is all channels.
+    polarity: Option<ListState>,
+    /// Lines scrolled past in the help or metadata popup.
+    popup_scroll: u16,
+    /// Recording from the input device, if one is running.
+    recorder: Option<Recorder>,
+    /// Levels and times that tell regions of sound apart for exporting them.
+    region_detection: RegionDetection,
+    /// Channel count and sample rate of new recordings.
+    recording: Settings,
+    /// Whether moving the playhead plays a short snippet around it.
+    scrub: bool,
+    /// Length in milliseconds of snippets played while scrubbing.
+    scrub_milliseconds: f32,
+    /// Active tab and its playhead when scrubbing last checked for moves.
+    scrubbed: (usize, usize),
     shutdown: bool,
+    /// Typed silence length in milliseconds and its error, if the insert silence prompt is shown.
+    silence: Option<(String, Option<String>)>,
+    /// Flag that cancels the snippet queued by scrubbing, if the last queued sound is a snippet.
+    snippet: Option<Arc<AtomicBool>>,
+    /// Channel whose zero crossings edit points snap to, or all channels summed if `None`.
+    snap_channel: Option<u16>,
+    /// Frames searched in each direction for a zero crossing to snap edit points to.
+    snap_search_frames: usize,
+    /// Whether edit points such as the playhead and selection boundaries snap to zero crossings.
+    snap_to_zero_crossing: bool,
     state: usize,
+    /// Whether the level statistics popup of the current tab is shown.
+    stats: bool,
+    tab: usize,
+    /// Selected entry of the open tabs popup, if it is shown.
+    tab_list: Option<ListState>,
+    tabs: Vec<Tab>,
+    theme: Palette,
     views: &'a mut [(&'a str, &'a mut dyn View<B>)],
 }
 
-impl<'a, B: Backend> App<'a, B> {
-    /// Create a new App.
-    pub fn new(views: &'a mut [(&'a str, &'a mut dyn View<B>)], samples: Samples) -> Self {
-        Self {
-            error: Ok(()),
-            samples,
-            shutdown: false,
-            state: 0,
-            views,
-        }
+impl<'a, B: Backend> App<'a, B> {
+    /// Create a new App with a single open tab.
+    pub fn new(views: &'a mut [(&'a str, &'a mut dyn View<B>)], tab: Tab) -> Self {
+        Self {
+            clip_threshold: CLIP_THRESHOLD,
+            confirm: None,
+            csv_export: None,
+            csv_index: IndexColumn::default(),
+            error: Ok(()),
+            factor: None,
+            help: false,
+            info: false,
+            input_devices: None,
+            keymap: Keymap::default(),
+            layout: Vec::new(),
+            meter: Meter::new(Ballistics::default()),
+            meter_levels: (Levels::default(), Instant::now()),
+            overwrite: None,
+            mix_gain: None,
+            palette: None,
+            polarity: None,
+            popup_scroll: 0,
+            recorder: None,
+            region_detection: RegionDetection::default(),
+            recording: Settings::default(),
+            scrub: false,
+            scrub_milliseconds: SCRUB_MILLISECONDS,
+            scrubbed: (0, 0),
+            shutdown: false,
+            silence: None,
+            snippet: None,
+            snap_channel: None,
+            snap_search_frames: SNAP_SEARCH_FRAMES,
+            snap_to_zero_crossing: false,
+            state: 0,
+            stats: false,
+            tab: 0,
+            tab_list: None,
+            tabs: vec![tab],
+            theme: Palette::default(),
+            views,
+        }
+    }
+
+    /// Use a custom keymap instead of the default key bindings.
+    #[must_use]
+    pub fn with_keymap(mut self, keymap: Keymap) -> Self {
+        self.keymap = keymap;
+        self
+    }
+
+    /// Count samples as clipped in level statistics from a magnitude other than full scale.
+    #[must_use]
+    pub const fn with_clip_threshold(mut self, threshold: f32) -> Self {
+        self.clip_threshold = threshold;
+        self
+    }
+
+    /// Write a leading frame or time column in exported CSV files.
+    #[must_use]
+    pub const fn with_csv_index(mut self, index: IndexColumn) -> Self {
+        self.csv_index = index;
+        self
+    }
+
+    /// Move level meters with custom decay and peak hold times.
+    #[must_use]
+    pub fn with_meter(mut self, ballistics: Ballistics) -> Self {
+        self.meter = Meter::new(ballistics);
+        self
+    }
+
+    /// Tell regions of sound apart with custom levels and times when exporting them.
+    #[must_use]
+    pub const fn with_region_detection(mut self, detection: RegionDetection) -> Self {
+        self.region_detection = detection;
+        self
+    }
+
+    /// Snap edit points to the nearest zero crossing within a number of frames in either direction.
+    #[must_use]
+    pub const fn with_zero_crossing_snap(mut self, enabled: bool, search_frames: usize) -> Self {
+        self.snap_to_zero_crossing = enabled;
+        self.snap_search_frames = search_frames;
+        self
+    }
+
+    /// Record with a channel count and sample rate other than the defaults of the input device.
+    #[must_use]
+    pub const fn with_recording(mut self, channels: Option<u16>, sample_rate: Option<u32>) -> Self {
+        self.recording.channels = channels;
+        self.recording.sample_rate = sample_rate;
+        self
+    }
+
+    /// Play snippets of a length in milliseconds around the playhead whenever it moves.
+    ///
+    /// Lengths are clamped to between 30 and 80 milliseconds.
+    #[must_use]
+    pub fn with_scrub(mut self, enabled: bool, milliseconds: f32) -> Self {
+        self.scrub = enabled;
+        self.scrub_milliseconds = milliseconds.clamp(SCRUB_LIMITS.0, SCRUB_LIMITS.1);
+        self
+    }
+
+    /// Find zero crossings on one channel counted from zero instead of the sum of all channels.
+    #[must_use]
+    pub const fn with_snap_channel(mut self, channel: Option<u16>) -> Self {
+        self.snap_channel = channel;
+        self
+    }
+
+    /// Use custom theme colors instead of the default theme.
+    #[must_use]
+    pub fn with_theme(mut self, theme: Palette) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Perform an application level command.
+    pub fn action(&mut self, sink: &Sink, action: Action) {
+        match action {
+            Action::CloseTab => {
+                if self.tabs[self.tab].modified {
+                    self.confirm = Some(Confirm::new(action));
+                } else {
+                    self.close_tab(sink);
+                }
+            }
+            Action::CommandPalette => {
+                let mut state = ListState::default();
+                state.select(Some(0));
+                self.palette = Some((String::new(), state));
+            }
+            Action::CommitPreview => self.tabs[self.tab].commit_preview(),
+            Action::CrossfadeLoop => self.factor = Some(Factor::new(action)),
+            Action::CycleTheme => self.theme.next(),
+            Action::DecodeMidSide => self.filter_tab(sink, dsp::decode_mid_side),
+            Action::DeleteSelection => {
+                sink.stop();
+                if let Err(error) = self.tabs[self.tab].delete_selection() {
+                    self.report(error);
+                }
+            }
+            Action::EncodeMidSide => self.filter_tab(sink, dsp::encode_mid_side),
+            Action::EstimateTempo => self.estimate_tempo(),
+            Action::ExportAscii | Action::ExportImage => self.export(action, false),
+            Action::ExportCsv => {
+                if self.tabs[self.tab].selection.is_some() {
+                    let mut state = ListState::default();
+                    state.select(Some(0));
+                    self.csv_export = Some(state);
+                } else {
+                    self.export(action, false);
+                }
+            }
+            Action::ExportRegions => {
+                let tab = &self.tabs[self.tab];
+                if let Err(error) = tab.export_regions(&self.region_detection) {
+                    self.report(error);
+                }
+            }
+            Action::Help => {
+                self.help = !self.help;
+                self.info = false;
+                self.stats = false;
+                self.popup_scroll = 0;
+            }
+            Action::InsertSilence => self.silence = Some((String::from("100"), None)),
+            Action::InvertPolarity => {
+                if self.tabs[self.tab].samples.channels > 1 {
+                    let mut state = ListState::default();
+                    state.select(Some(0));
+                    self.polarity = Some(state);
+                } else {
+                    self.invert_polarity(sink, None);
+                }
+            }
+            Action::ListTabs | Action::MixTab => {
+                self.mix_gain = (action == Action::MixTab).then_some(1.0);
+                self.tab_list = match self.tab_list {
+                    Some(_) => None,
+                    None => Some(ListState::default()),
+                };
+                if let Some(state) = &mut self.tab_list {
+                    state.select(Some(self.tab));
+                }
+            }
+            Action::NextTab => {
+                sink.stop();
+                self.tab = (self.tab + 1) % self.tabs.len();
+            }
+            Action::NextView => self.next(),
+            Action::OpenNewTab => {
+                sink.stop();
+                self.tabs.push(Tab::default());
+                self.tab = self.tabs.len() - 1;
+            }
+            Action::NextZeroCrossing => self.nudge(true),
+            Action::Play => self.play(sink),
+            Action::Record => self.toggle_recording(),
+            Action::PrevTab => {
+                sink.stop();
+                self.tab = (self.tab + self.tabs.len() - 1) % self.tabs.len();
+            }
+            Action::PitchShift | Action::TimeStretch => self.factor = Some(Factor::new(action)),
+            Action::PrevZeroCrossing => self.nudge(false),
+            Action::Quit => {
+                if self.error.is_err() {
+                    self.error = Ok(());
+                } else if self.popup() {
+                    self.help = false;
+                    self.info = false;
+                    self.stats = false;
+                } else if self.tab_list.is_some() {
+                    self.tab_list = None;
+                } else if self.tabs.iter().any(|tab| tab.modified) {
+                    self.confirm = Some(Confirm::new(action));
+                } else {
+                    self.shutdown = true;
+                }
+            }
+            Action::Redo => {
+                sink.stop();
+                if let Err(error) = self.tabs[self.tab].redo() {
+                    self.report(error);
+                }
+            }
+            Action::RemoveDcOffset => self.filter_tab(sink, |samples| {
+                dsp::remove_dc_offset(samples);
+                Ok(())
+            }),
+            Action::ResetMeters => self.meter.reset(),
+            Action::RevertPreview => {
+                sink.stop();
+                if let Err(error) = self.tabs[self.tab].revert_preview() {
+                    self.report(error);
+                }
+            }
+            Action::SplitAtMarkers => {
+                if let Err(error) = self.tabs[self.tab].split_at_markers() {
+                    self.report(error);
+                }
+            }
+            Action::ToggleInfo => {
+                self.info = !self.info;
+                self.help = false;
+                self.stats = false;
+                self.popup_scroll = 0;
+            }
+            Action::TogglePreview => {
+                let tab = &mut self.tabs[self.tab];
+                tab.preview_hidden = !tab.preview_hidden;
+            }
+            Action::ToggleScrub => self.scrub = !self.scrub,
+            Action::ToggleSnap => self.snap_to_zero_crossing = !self.snap_to_zero_crossing,
+            Action::ToggleStats => {
+                self.stats = !self.stats;
+                self.help = false;
+                self.info = false;
+                self.popup_scroll = 0;
+            }
+            Action::Undo => {
+                sink.stop();
+                if let Err(error) = self.tabs[self.tab].undo() {
+                    self.report(error);
+                }
+            }
+        }
+    }
+
+    /// List input devices to start recording from, or stop recording and open the recording in
+    /// a new tab.
+    fn toggle_recording(&mut self) {
+        if self.recorder.is_some() {
+            self.stop_recording();
+            return;
+        }
+
+        match recorder::input_devices() {
+            Ok(names) => {
+                let mut state = ListState::default();
+                state.select(Some(0));
+                self.input_devices = Some((names, state));
+            }
+            Err(error) => self.report(error),
+        }
+    }
+
+    /// Start recording from an input device by name, or from the default device.
+    fn start_recording(&mut self, device: Option<String>) {
+        let settings = Settings {
+            device,
+            ..self.recording.clone()
+        };
+        match Recorder::start(&settings) {
+            Ok(recorder) => self.recorder = Some(recorder),
+            Err(error) => self.report(error),
+        }
+    }
+
+    /// Stop recording and open the recording as an unsaved tab.
+    ///
+    /// The recording replaces the active tab instead if it is blank.
+    fn stop_recording(&mut self) {
+        let samples = match self.recorder.take() {
+            Some(recorder) => recorder.stop(),
+            None => return,
+        };
+
+        let mut tab = Tab::new(samples, None);
+        tab.modified = true;
+        if self.tabs[self.tab].is_blank() {
+            self.tabs[self.tab] = tab;
+        } else {
+            self.tabs.push(tab);
+            self.tab = self.tabs.len() - 1;
+        }
+    }
+
+    /// Export the active tab next to its file, asking before overwriting an existing file.
+    ///
+    /// CSV exports write the selected frames if `selected` is set and the tab has a selection.
+    fn export(&mut self, action: Action, selected: bool) {
+        let result = self.export_path(action, selected).and_then(|path| {
+            if path.exists() {
+                self.overwrite = Some(Overwrite {
+                    action,
+                    path,
+                    selected,
+                });
+                Ok(())
+            } else {
+                self.write_export(action, selected, &path)
+            }
+        });
+        if let Err(error) = result {
+            self.report(error);
+        }
+    }
+
+    /// Get the path next to the file of the active tab that an export action writes.
+    fn export_path(&self, action: Action, selected: bool) -> eyre::Result<PathBuf> {
+        let tab = &self.tabs[self.tab];
+        let exported = match action {
+            Action::ExportCsv => "sample values",
+            _ => "waveform",
+        };
+        if tab.is_loading() {
+            eyre::bail!(
+                "Wait for {} to finish loading before exporting its {}",
+                tab.name(),
+                exported
+            );
+        }
+        tab.ensure_editable()?;
+        let path = tab.path.as_ref().ok_or_else(|| {
+            eyre::eyre!(
+                "Write {} to a file before exporting its {}",
+                tab.name(),
+                exported
+            )
+        })?;
+
+        match action {
+            Action::ExportCsv if selected && tab.selection.is_some() => {
+                Ok(path.with_extension("selection.csv"))
+            }
+            Action::ExportCsv => Ok(path.with_extension("csv")),
+            Action::ExportImage => Ok(path.with_extension("png")),
+            _ => Ok(path.with_extension("txt")),
+        }
+    }
+
+    /// Write the export of an action to a path.
+    fn write_export(&self, action: Action, selected: bool, path: &Path) -> eyre::Result<()> {
+        match action {
+            Action::ExportCsv => self.export_csv(selected, path),
+            _ => self.export_waveform(action == Action::ExportImage, path),
+        }
+    }
+
+    /// Write the sample values of the selected frames, or else of the whole active tab, to a path
+    /// as CSV.
+    fn export_csv(&self, selected: bool, path: &Path) -> eyre::Result<()> {
+        let tab = &self.tabs[self.tab];
+        match tab.selection.filter(|_selection| selected) {
+            Some(selection) => csv::write_csv(path, &tab.samples.slice(selection), self.csv_index),
+            None => csv::write_csv(path, &tab.samples, self.csv_index),
+        }
+    }
+
+    /// Write the waveform of the active tab to a path as a PNG image, or else as ASCII art text.
+    fn export_waveform(&self, image: bool, path: &Path) -> eyre::Result<()> {
+        let samples = &self.tabs[self.tab].samples;
+        if image {
+            let (width, height) = IMAGE_SIZE;
+            chart::render_to_png(samples, width, height, path)
+        } else {
+            let (width, height) = ASCII_SIZE;
+            fs::write(path, chart::render_to_ascii(samples, width, height))?;
+            Ok(())
+        }
+    }
+
+    /// Estimate the tempo of the active tab for the status bar.
+    fn estimate_tempo(&mut self) {
+        let tab = &mut self.tabs[self.tab];
+        if tab.is_loading() {
+            let error = eyre::eyre!(
+                "Wait for {} to finish loading before estimating its tempo",
+                tab.name()
+            );
+            self.report(error);
+            return;
+        }
+
+        tab.tempo = dsp::estimate_bpm(&tab.samples);
+        if tab.tempo.is_none() {
+            let error = eyre::eyre!("No steady tempo found in {}", tab.name());
+            self.report(error);
+        }
+    }
+
+    /// Run a filter on the active tab as an undoable edit.
+    fn filter_tab(&mut self, sink: &Sink, filter: fn(&mut Samples) -> eyre::Result<()>) {
+        sink.stop();
+        if let Err(error) = self.tabs[self.tab].apply(filter) {
+            self.report(error);
+        }
+    }
+
+    /// Flip the polarity of one channel of the active tab, or of all channels if `None`.
+    fn invert_polarity(&mut self, sink: &Sink, channel: Option<u16>) {
+        sink.stop();
+        if let Err(error) = self.tabs[self.tab].invert_polarity(channel) {
+            self.report(error);
+        }
+    }
+
+    /// Close the active tab, replacing it with an empty tab if it was the last one.
+    fn close_tab(&mut self, sink: &Sink) {
+        sink.stop();
+        self.tabs.remove(self.tab);
+        if self.tabs.is_empty() {
+            self.tabs.push(Tab::default());
+        }
+        self.tab = self.tab.min(self.tabs.len() - 1);
+    }
+
+    /// Answer the unsaved changes prompt and perform its action unless canceled.
+    ///
+    /// The action is canceled and the error is shown in the prompt if saving any tab fails.
+    fn confirm(&mut self, sink: &Sink, choice: Choice) {
+        let action = match &self.confirm {
+            Some(confirm) => confirm.action,
+            None => return,
+        };
+
+        match choice {
+            Choice::Cancel => {
+                self.confirm = None;
+                return;
+            }
+            Choice::Discard => (),
+            Choice::Save => {
+                let current = self.tab;
+                let result = self
+                    .tabs
+                    .iter_mut()
+                    .enumerate()
+                    .filter(|(index, tab)| {
+                        tab.modified && (action == Action::Quit || *index == current)
+                    })
+                    .try_for_each(|(_index, tab)| tab.save());
+
+                if let Err(error) = result {
+                    self.confirm = Some(Confirm {
+                        action,
+                        error: Some(format!("{}", error)),
+                    });
+                    return;
+                }
+            }
+        }
+
+        self.confirm = None;
+        match action {
+            Action::CloseTab => self.close_tab(sink),
+            _ => self.shutdown = true,
+        }
+    }
+
+    /// Handle keyboard input while the insert silence prompt is shown.
+    fn key_event_silence(&mut self, sink: &Sink, event: KeyEvent) {
+        let (text, error) = match &mut self.silence {
+            Some(silence) => silence,
+            None => return,
+        };
+
+        match event.code {
+            KeyCode::Backspace | KeyCode::Delete => {
+                text.pop();
+            }
+            KeyCode::Enter => match text.trim().parse::<f32>() {
+                Ok(milliseconds) => {
+                    sink.stop();
+                    match self.tabs[self.tab].insert_silence(milliseconds) {
+                        Ok(()) => self.silence = None,
+                        Err(report) => *error = Some(format!("{}", report)),
+                    }
+                }
+                Err(_error) => {
+                    *error = Some(String::from(
+                        "Silence length must be a number of milliseconds",
+                    ));
+                }
+            },
+            KeyCode::Esc => self.silence = None,
+            KeyCode::Char(char) => text.push(char),
+            _ => (),
+        }
+    }
+
+    /// Handle keyboard input while the time stretch, pitch shift, or loop crossfade prompt is
+    /// shown.
+    fn key_event_factor(&mut self, sink: &Sink, event: KeyEvent) {
+        let factor = match &mut self.factor {
+            Some(factor) => factor,
+            None => return,
+        };
+
+        match event.code {
+            KeyCode::Backspace | KeyCode::Delete => {
+                factor.text.pop();
+            }
+            KeyCode::Enter => {
+                let tab = &mut self.tabs[self.tab];
+                let result = match factor.action {
+                    Action::CrossfadeLoop => parse_milliseconds(&factor.text).and_then(|length| {
+                        sink.stop();
+                        tab.crossfade_loop(length)
+                    }),
+                    Action::PitchShift => parse_semitones(&factor.text).and_then(|semitones| {
+                        sink.stop();
+                        tab.edit_in_background("Transposing", move |samples, progress| {
+                            filters::pitch_shift_with_progress(semitones, samples, progress);
+                        })
+                    }),
+                    _ => parse_percent(&factor.text).and_then(|percent| {
+                        sink.stop();
+                        tab.edit_in_background("Stretching", move |samples, progress| {
+                            filters::time_stretch_with_progress(percent / 100.0, samples, progress);
+                        })
+                    }),
+                };
+                match result {
+                    Ok(()) => self.factor = None,
+                    Err(report) => factor.error = Some(format!("{}", report)),
+                }
+            }
+            KeyCode::Esc => self.factor = None,
+            KeyCode::Char(char) => factor.text.push(char),
+            _ => (),
+        }
+    }
+
+    /// Handle keyboard input while the popup choosing what to export as CSV is shown.
+    fn key_event_csv_export(&mut self, event: KeyEvent) {
+        let state = match &mut self.csv_export {
+            Some(state) => state,
+            None => return,
+        };
+
+        match (self.keymap.action(event), event.code) {
+            (Some(Action::ExportCsv | Action::Quit), _) | (_, KeyCode::Esc) => {
+                self.csv_export = None;
+            }
+            (_, KeyCode::Down) => state.select(ui::util::step(state.selected(), 2, true)),
+            (_, KeyCode::Enter) => {
+                let selected = state.selected() == Some(0);
+                self.csv_export = None;
+                self.export(Action::ExportCsv, selected);
+            }
+            (_, KeyCode::Up) => state.select(ui::util::step(state.selected(), 2, false)),
+            _ => (),
+        }
+    }
+
+    /// Handle keyboard input while confirming that an export overwrites an existing file.
+    fn key_event_overwrite(&mut self, event: KeyEvent) {
+        let overwrite = match event.code {
+            KeyCode::Char('y') => self.overwrite.take(),
+            KeyCode::Char('n') | KeyCode::Esc => {
+                self.overwrite = None;
+                return;
+            }
+            _ => return,
+        };
+
+        if let Some(overwrite) = overwrite {
+            let (action, selected) = (overwrite.action, overwrite.selected);
+            if let Err(error) = self.write_export(action, selected, &overwrite.path) {
+                self.report(error);
+            }
+        }
+    }
+
+    /// Handle keyboard input while the input devices popup is shown.
+    fn key_event_input_devices(&mut self, event: KeyEvent) {
+        let (names, state) = match &mut self.input_devices {
+            Some(devices) => devices,
+            None => return,
+        };
+        let length = names.len() + 1;
+
+        match (self.keymap.action(event), event.code) {
+            (Some(Action::Quit | Action::Record), _) | (_, KeyCode::Esc) => {
+                self.input_devices = None;
+            }
+            (_, KeyCode::Down) => state.select(ui::util::step(state.selected(), length, true)),
+            (_, KeyCode::Enter) => {
+                let device = state
+                    .selected()
+                    .and_then(|index| index.checked_sub(1))
+                    .and_then(|index| names.get(index).cloned());
+                self.input_devices = None;
+                self.start_recording(device);
+            }
+            (_, KeyCode::Up) => state.select(ui::util::step(state.selected(), length, false)),
+            _ => (),
+        }
+    }
+
+    /// Handle keyboard input while the command palette is shown.
+    ///
+    /// Typed characters filter the actions, and enter runs the selected one.
+    fn key_event_palette(&mut self, sink: &Sink, event: KeyEvent) {
+        let (query, state) = match &mut self.palette {
+            Some(palette) => palette,
+            None => return,
+        };
+        let length = Action::search(query).len();
+
+        match (self.keymap.action(event), event.code) {
+            (Some(Action::CommandPalette), _) | (_, KeyCode::Esc) => self.palette = None,
+            (_, KeyCode::Backspace | KeyCode::Delete) => {
+                query.pop();
+                state.select(Some(0));
+            }
+            (_, KeyCode::Down) => state.select(ui::util::step(state.selected(), length, true)),
+            (_, KeyCode::Enter) => {
+                let action = state
+                    .selected()
+                    .and_then(|index| Action::search(query).get(index).copied());
+                if let Some(action) = action {
+                    self.palette = None;
+                    self.action(sink, action);
+                }
+            }
+            (_, KeyCode::Up) => state.select(ui::util::step(state.selected(), length, false)),
+            (_, KeyCode::Char(char))
+                if !event
+                    .modifiers
+                    .intersects(KeyModifiers::ALT | KeyModifiers::CONTROL) =>
+            {
+                query.push(char);
+                state.select(Some(0));
+            }
+            _ => (),
+        }
+    }
+
+    /// Handle keyboard input while the popup listing channels to invert is shown.
+    fn key_event_polarity(&mut self, sink: &Sink, event: KeyEvent) {
+        let state = match &mut self.polarity {
+            Some(state) => state,
+            None => return,
+        };
+        let length = usize::from(self.tabs[self.tab].samples.channels) + 1;
+
+        match (self.keymap.action(event), event.code) {
+            (Some(Action::InvertPolarity | Action::Quit), _) | (_, KeyCode::Esc) => {
+                self.polarity = None;
+            }
+            (_, KeyCode::Down) => state.select(ui::util::step(state.selected(), length, true)),
+            (_, KeyCode::Enter) => {
+                let channel = state
+                    .selected()
+                    .and_then(|index| index.checked_sub(1))
+                    .and_then(|index| u16::try_from(index).ok());
+                self.polarity = None;
+                self.invert_polarity(sink, channel);
+            }
+            (_, KeyCode::Up) => state.select(ui::util::step(state.selected(), length, false)),
+            _ => (),
+        }
+    }
+
+    /// Handle keyboard input while the open tabs popup is shown.
+    fn key_event_tab_list(&mut self, sink: &Sink, event: KeyEvent) {
+        let length = self.tabs.len();
+
+        match self.keymap.action(event) {
+            Some(Action::ListTabs | Action::MixTab | Action::Quit) => self.tab_list = None,
+            _ => {
+                if let Some(state) = &mut self.tab_list {
+                    let (minimum, maximum, step) = MIX_GAIN;
+                    match (event.code, &mut self.mix_gain) {
+                        (KeyCode::Down, _) => {
+                            state.select(ui::util::step(state.selected(), length, true))
+                        }
+                        (KeyCode::Enter, gain) => {
+                            if let Some(index) = state.selected() {
+                                self.tab_list = None;
+                                match gain.take() {
+                                    Some(gain) => self.mix_tab(sink, index, gain),
+                                    None => self.select_tab(sink, index),
+                                }
+                            }
+                        }
+                        (KeyCode::Left, Some(gain)) => *gain = (*gain - step).max(minimum),
+                        (KeyCode::Right, Some(gain)) => *gain = (*gain + step).min(maximum),
+                        (KeyCode::Up, _) => {
+                            state.select(ui::util::step(state.selected(), length, false))
+                        }
+                        _ => (),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Pass keyboard input to current view or perform its bound action.
+    ///
+    /// While an error is shown, any key only dismisses it.
+    pub fn key_event(&mut self, sink: &Sink, event: KeyEvent) {
+        if self.error.is_err() {
+            self.error = Ok(());
+            return;
+        }
+        if self.confirm.is_some() {
+            if let Some(choice) = Choice::from_key(event) {
+                self.confirm(sink, choice);
+            }
+            return;
+        }
+        if self.overwrite.is_some() {
+            self.key_event_overwrite(event);
+            return;
+        }
+        if self.csv_export.is_some() && !self.popup() {
+            self.key_event_csv_export(event);
+            return;
+        }
+        if self.factor.is_some() && !self.popup() {
+            self.key_event_factor(sink, event);
+            return;
+        }
+        if self.input_devices.is_some() && !self.popup() {
+            self.key_event_input_devices(event);
+            return;
+        }
+        if self.palette.is_some() && !self.popup() {
+            self.key_event_palette(sink, event);
+            return;
+        }
+        if self.polarity.is_some() && !self.popup() {
+            self.key_event_polarity(sink, event);
+            return;
+        }
+        if self.tab_list.is_some() && !self.popup() {
+            self.key_event_tab_list(sink, event);
+            return;
+        }
+        if self.silence.is_some() && !self.popup() {
+            self.key_event_silence(sink, event);
+            return;
+        }
+
+        let captured = self
+            .views
+            .get(self.state)
+            .is_some_and(|view| view.1.captures_input());
+
+        // Tabs can also be chosen by their position with alt and a number key.
+        if let (KeyCode::Char(digit), KeyModifiers::ALT) = (event.code, event.modifiers) {
+            if let Some(number) = digit.to_digit(10).filter(|number| *number > 0) {
+                let index = number as usize - 1;
+                if !captured && !self.popup() && index < self.tabs.len() {
+                    self.select_tab(sink, index);
+                    return;
+                }
+            }
+        }
+
+        match self.keymap.action(event) {
+            // Popups cover the view, so only the actions that dismiss or switch them apply.
+            Some(
+                action @ (Action::Help | Action::Quit | Action::ToggleInfo | Action::ToggleStats),
+            ) if self.popup() => self.action(sink, action),
+            _ if self.popup() => match event.code {
+                KeyCode::Down => self.popup_scroll = self.popup_scroll.saturating_add(1),
+                KeyCode::PageDown => self.popup_scroll = self.popup_scroll.saturating_add(10),
+                KeyCode::PageUp => self.popup_scroll = self.popup_scroll.saturating_sub(10),
+                KeyCode::Up => self.popup_scroll = self.popup_scroll.saturating_sub(1),
+                _ => (),
+            },
+            Some(action) if !captured => self.action(sink, action),
+            _ => {
+                if let Some(view) = self.views.get_mut(self.state) {
+                    view.1.key_event(event);
+                }
+            }
+        }
+    }
+
+    /// Select tabs or menu options under mouse clicks or pass mouse input to current view.
+    pub fn mouse_event(&mut self, sink: &Sink, event: MouseEvent) {
+        if self.confirm.is_some()
+            || self.csv_export.is_some()
+            || self.input_devices.is_some()
+            || self.overwrite.is_some()
+            || self.palette.is_some()
+            || self.polarity.is_some()
+            || self.tab_list.is_some()
+        {
+            return;
+        }
+        if self.popup() {
+            match event.kind {
+                MouseEventKind::ScrollDown => {
+                    self.popup_scroll = self.popup_scroll.saturating_add(1)
+                }
+                MouseEventKind::ScrollUp => self.popup_scroll = self.popup_scroll.saturating_sub(1),
+                _ => (),
+            }
+            return;
+        }
+        if self.error.is_err() {
+            return;
+        }
+
+        let (column, row) = (event.column, event.row);
+        let clicked = |area: Option<&Rect>| {
+            if let (MouseEventKind::Down(MouseButton::Left), Some(area)) = (event.kind, area) {
+                let inner = Block::default().borders(Borders::ALL).inner(*area);
+                if ui::util::contains(inner, column, row) {
+                    return Some(column - inner.x);
+                }
+            }
+            None
+        };
+
+        if let Some(offset) = clicked(self.layout.first()) {
+            let names: Vec<&str> = self.tabs.iter().map(Tab::name).collect();
+            if let Some(index) = ui::util::tab_at(&names, offset) {
+                if index != self.tab {
+                    sink.stop();
+                    self.tab = index;
+                }
+            }
+        } else if let Some(offset) = clicked(self.layout.get(1)) {
+            let labels: Vec<&str> = self.views.iter().map(|view| view.0).collect();
+            if let Some(index) = ui::util::tab_at(&labels, offset) {
+                self.state = index;
+            }
+        } else if let Some(view) = self.views.get_mut(self.state) {
+            let tab = &mut self.tabs[self.tab];
+            let (playhead, selection) = (tab.playhead, tab.selection);
+            view.1.mouse_event(event, tab);
+            if self.snap_to_zero_crossing {
+                self.snap(playhead, selection);
+            }
+        }
+    }
+
+    /// Move edit points of the active tab that changed from their previous values to the nearest
+    /// zero crossings.
+    fn snap(&mut self, playhead: usize, selection: Option<Selection>) {
+        let (channel, search_frames) = (self.snap_channel, self.snap_search_frames);
+        let tab = &mut self.tabs[self.tab];
+        let samples = &tab.samples;
+        let snap = |frame: usize| {
+            dsp::find_zero_crossing_on(
+                &samples.data,
+                samples.channels,
+                channel,
+                frame,
+                search_frames,
+            )
+            .unwrap_or(frame)
+        };
+
+        if tab.playhead != playhead {
+            tab.playhead = snap(tab.playhead);
+        }
+        if tab.selection != selection {
+            tab.selection = tab
+                .selection
+                .map(|selection| Selection::new(snap(selection.start), snap(selection.end)))
+                .filter(|selection| !selection.is_empty());
+        }
+    }
+
+    /// Move the playhead, or the selection edge away from the playhead, to the next or previous
+    /// zero crossing.
+    ///
+    /// Clicking sets the playhead and dragging the other selection edge, so the dragged edge is
+    /// the one moved. Edit points without a crossing within the search window stay put.
+    fn nudge(&mut self, forward: bool) {
+        let (channel, search_frames) = (self.snap_channel, self.snap_search_frames);
+        let tab = &mut self.tabs[self.tab];
+        let samples = &tab.samples;
+        let last = samples.frames().saturating_sub(1);
+        let step = |frame: usize| {
+            dsp::step_zero_crossing(
+                &samples.data,
+                samples.channels,
+                channel,
+                frame.min(last),
+                forward,
+                search_frames,
+            )
+            .unwrap_or(frame)
+        };
+
+        match tab.selection {
+            Some(selection) => {
+                let (anchor, edge) = if selection.start == tab.playhead {
+                    (selection.start, selection.end)
+                } else {
+                    (selection.end, selection.start)
+                };
+                let moved = Selection::new(anchor, step(edge));
+                tab.selection = (!moved.is_empty()).then_some(moved);
+            }
+            None => tab.playhead = step(tab.playhead),
+        }
+    }
+
+    /// Show an error in the error popup until it is dismissed.
+    pub fn report(&mut self, error: eyre::Report) {
+        self.error = Err(error);
+    }
+
+    /// Modular move menu state to next option.
+    pub fn next(&mut self) {
+        self.state = (self.state + 1) % self.views.len();
+    }
+
+    /// Check whether the help, metadata, or level statistics popup covers the view.
+    const fn popup(&self) -> bool {
+        self.help || self.info || self.stats
+    }
+
+    /// Capture the state of the active tab and view to restore on the next start, if the tab
+    /// holds a file.
+    #[must_use]
+    pub fn session(&self) -> Option<Session> {
+        let view = self.views.get(self.state).map(|view| view.0);
+        Session::capture(&self.tabs[self.tab], view)
+    }
+
+    /// Display the view with the given menu label, if it exists.
+    pub fn select(&mut self, label: &str) {
+        if let Some(index) = self.views.iter().position(|view| view.0 == label) {
+            self.state = index;
+        }
+    }
+
+    /// Mix the tab at an index into the active tab at its playhead, warning if the result clips.
+    fn mix_tab(&mut self, sink: &Sink, index: usize, gain: f32) {
+        sink.stop();
+        let incoming = &self.tabs[index];
+        if incoming.is_loading() {
+            let error = eyre::eyre!(
+                "Wait for {} to finish loading before mixing it",
+                incoming.name()
+            );
+            self.report(error);
+            return;
+        }
+
+        let incoming = incoming.samples.clone();
+        match self.tabs[self.tab].mix(&incoming, gain) {
+            Ok(peak) if peak > 1.0 => self.report(eyre::eyre!(
+                "Mixed audio peaks at {:.2} and clips, so lower the gain or normalize it",
+                peak
+            )),
+            Ok(_peak) => (),
+            Err(error) => self.report(error),
+        }
+    }
+
+    /// Make the tab at an index active, stopping playback of the previous tab.
+    fn select_tab(&mut self, sink: &Sink, index: usize) {
+        sink.stop();
+        self.tab = index.min(self.tabs.len() - 1);
+    }
+
+    /// Play active tab's signal from its playhead.
+    ///
+    /// The source is tapped for the peaks of the playback level meters.
+    pub fn play(&mut self, sink: &Sink) {
+        // Snippets end within milliseconds once cancelled, so playback queued after them starts
+        // right away.
+        let snippet = self.snippet.take();
+        if let Some(stop) = &snippet {
+            stop.store(true, Ordering::Relaxed);
+        }
+
+        if sink.empty() || snippet.is_some() {
+            let tab = &self.tabs[self.tab];
+            let levels = Levels::new(tab.samples.channels);
+            if let Some(windowed) = &tab.windowed {
+                match windowed.source(tab.playhead) {
+                    Ok(source) => {
+                        self.meter_levels = (levels.clone(), Instant::now());
+                        sink.append(Tap::new(source, levels));
+                    }
+                    Err(error) => self.report(error),
+                }
+                return;
+            }
+
+            let start =
+                (tab.playhead * usize::from(tab.samples.channels)).min(tab.samples.data.len());
+            let source = SamplesBuffer::new(
+                tab.samples.channels,
+                tab.samples.sample_rate,
+                &tab.samples.data[start..],
+            );
+            self.meter_levels = (levels.clone(), Instant::now());
+            sink.append(Tap::new(source, levels));
+        } else if sink.is_paused() {
+            sink.play();
+        } else {
+            sink.pause();
+        }
+    }
+
+    /// Play a short snippet around the playhead of the active tab if it moved since the last
+    /// check, cancelling the previous snippet so rapid moves do not queue up.
+    ///
+    /// Nothing plays while scrubbing is off, during playback or recording, after switching tabs,
+    /// or for tabs that are loading or opened windowed.
+    fn scrub(&mut self, sink: &Sink) {
+        let tab = &self.tabs[self.tab];
+        let (previous, position) = (self.scrubbed, (self.tab, tab.playhead));
+        self.scrubbed = position;
+        if !self.scrub
+            || previous.0 != position.0
+            || previous == position
+            || self.recorder.is_some()
+            || tab.is_loading()
+            || tab.windowed.is_some()
+            || (!sink.empty() && self.snippet.is_none())
+        {
+            return;
+        }
+
+        if let Some(stop) = self.snippet.take() {
+            stop.store(true, Ordering::Relaxed);
+        }
+        let stop = Arc::new(AtomicBool::new(false));
+        let snippet = tab.samples.snippet(
+            tab.playhead,
+            self.scrub_milliseconds,
+            SCRUB_FADE_MILLISECONDS,
+        );
+        let cancel = Arc::clone(&stop);
+        let source = SamplesBuffer::new(snippet.channels, snippet.sample_rate, snippet.data)
+            .stoppable()
+            .periodic_access(Duration::from_millis(1), move |source| {
+                if cancel.load(Ordering::Relaxed) {
+                    source.stop();
+                }
+            });
+
+        let levels = Levels::new(tab.samples.channels);
+        self.meter_levels = (levels.clone(), Instant::now());
+        sink.append(Tap::new(source, levels));
+        self.snippet = Some(stop);
+    }
+
+    /// Move the level meters to the peaks played since the last update.
+    ///
+    /// While nothing plays, the meters show the peak of each channel of the whole active tab.
+    pub fn update_meter(&mut self, sink: &Sink) {
+        let (levels, updated) = &mut self.meter_levels;
+        let seconds = updated.elapsed().as_secs_f32();
+        *updated = Instant::now();
+
+        if let Some(recorder) = &mut self.recorder {
+            let (peaks, rms) = recorder.take_levels();
+            self.meter
+                .update(&peaks, &rms, seconds, self.clip_threshold);
+            return;
+        }
+        if !sink.empty() && !sink.is_paused() {
+            self.meter.update(
+                &levels.take(),
+                &levels.take_rms(),
+                seconds,
+                self.clip_threshold,
+            );
+            return;
+        }
+
+        let tab = &mut self.tabs[self.tab];
+        if tab.is_loading() {
+            return;
+        }
+        if tab.windowed.is_none()
+            && !tab
+                .peaks
+                .as_ref()
+                .is_some_and(|peaks| peaks.fits(&tab.samples))
+        {
+            tab.peaks = Some(ui::peaks::Peaks::new(&tab.samples));
+        }
+        let peaks = match &tab.windowed {
+            Some(windowed) => Some(&windowed.peaks),
+            None => tab.peaks.as_ref(),
+        };
+        if let Some(peaks) = peaks {
+            let channels: Vec<f32> = (0..usize::from(tab.samples.channels))
+                .map(|channel| peaks.peak(channel).unwrap_or_default())
+                .collect();
+            self.meter.show(&channels);
+        }
+    }
+
+    /// Update internal signal state.
+    pub fn process(&mut self) {
+        for tab in &mut self.tabs {
+            if let Err(error) = tab.poll() {
+                self.error = Err(error);
+            }
+        }
+        if let Some(Err(error)) = self.recorder.as_mut().map(Recorder::drain) {
+            self.stop_recording();
+            self.error = Err(eyre::eyre!(
+                "{}, keeping the samples recorded so far",
+                error
+            ));
+        }
+
+        if self.error.is_ok() {
+            let tab = &mut self.tabs[self.tab];
+            let mut opened = Vec::new();
+            for (_name, view) in &mut self.views.iter_mut() {
+                if let Err(error) = view.process(tab) {
+                    self.error = Err(error);
+                    view.reset();
+                    break;
+                }
+                opened.extend(view.opened());
+            }
+
+            if !opened.is_empty() {
+                self.tabs.extend(opened);
+                self.tab = self.tabs.len() - 1;
+            }
+        }
+    }
+
+    /// Render all UI views in terminal screen.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `terminal` cannot draw frames.
+    pub fn render(&mut self, terminal: &mut Terminal<B>) -> eyre::Result<()> {
+        let theme = self.theme.theme();
+
+        terminal.draw(|frame| {
+            let size = frame.size();
+            frame.render_widget(Block::default().style(theme.base()), size);
+
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .margin(1)
+                .constraints([Length(3), Length(3), Min(0), Length(1), Length(1)].as_ref())
+                .split(size);
+            self.layout = chunks.clone();
+
+            self.render_tabs(frame, chunks[0], &theme);
+            self.render_menu(frame, chunks[1], &theme);
+
+            if let Some(view) = self.views.get_mut(self.state) {
+                view.1.render(frame, chunks[2], &theme);
+            }
+            self.render_meter(frame, chunks[3], &theme);
+            self.render_status(frame, chunks[4], &theme);
+
+            self.render_help(frame, size, &theme);
+            self.render_info(frame, size, &theme);
+            self.render_stats(frame, size, &theme);
+            self.render_csv_export(frame, size, &theme);
+            self.render_input_devices(frame, size, &theme);
+            self.render_palette(frame, size, &theme);
+            self.render_polarity(frame, size, &theme);
+            self.render_tab_list(frame, size, &theme);
+            self.render_silence(frame, size, &theme);
+            self.render_factor(frame, size, &theme);
+            self.render_overwrite(frame, size, &theme);
+            if let Some(confirm) = &self.confirm {
+                let current = self.tab;
+                let names: Vec<&str> = self
+                    .tabs
+                    .iter()
+                    .enumerate()
+                    .filter(|(index, tab)| {
+                        tab.modified && (confirm.action == Action::Quit || *index == current)
+                    })
+                    .map(|(_index, tab)| tab.name())
+                    .collect();
+                let verb = match confirm.action {
+                    Action::CloseTab => "close",
+                    _ => "quit",
+                };
+                unsaved::render(frame, size, &theme, verb, &names, confirm.error.as_deref());
+            }
+            self.render_error(frame, size, &theme);
+        })?;
+
+        Ok(())
+    }
+
+    /// Render all UI views in terminal screen.
+    pub fn render_error<'b>(&mut self, frame: &mut Frame<'b, B>, area: Rect, theme: &Theme) {
+        if let Err(error) = &self.error {
+            let area = ui::util::centered_rectangle(60, 20, area);
+            frame.render_widget(Clear, area);
+
+            let block = Block::default()
+                .title("Error")
+                .borders(Borders::ALL)
+                .style(theme.warning());
+            let text = Text::from(format!("{}\n\nPress any key to dismiss", error));
+            let line = Paragraph::new(text).block(block).wrap(Wrap { trim: false });
+
+            frame.render_widget(line, area);
+        }
+    }
+
+    /// Render effective key bindings of application level commands.
+    fn render_help<'b>(&mut self, frame: &mut Frame<'b, B>, area: Rect, theme: &Theme) {
+        if self.help {
+            let area = ui::util::centered_rectangle(60, 60, area);
+            frame.render_widget(Clear, area);
+
+            let lines: Vec<Spans> = self.keymap.help().into_iter().map(Spans::from).collect();
+
+            // Stop scrolling once the last line is visible.
+            let visible = area.height.saturating_sub(2);
+            let total = u16::try_from(lines.len()).unwrap_or(u16::MAX);
+            self.popup_scroll = self.popup_scroll.min(total.saturating_sub(visible));
+
+            let block = Block::default()
+                .title("Keys")
+                .borders(Borders::ALL)
+                .style(theme.base());
+            let text = Paragraph::new(lines)
+                .block(block)
+                .scroll((self.popup_scroll, 0));
+
+            frame.render_widget(text, area);
+        }
+    }
+
+    /// Render tags and format details of the current tab's file.
+    fn render_info<'b>(&mut self, frame: &mut Frame<'b, B>, area: Rect, theme: &Theme) {
+        if self.info {
+            let area = ui::util::centered_rectangle(60, 60, area);
+            frame.render_widget(Clear, area);
+
+            let tab = &self.tabs[self.tab];
+            let offsets: Vec<String> = dsp::dc_offset(&tab.samples)
+                .iter()
+                .map(|offset| format!("{:+.4}", offset))
+                .collect();
+            let mut lines = vec![
+                Spans::from(format!("DC offset: {}", offsets.join(", "))),
+                Spans::from(""),
+            ];
+            lines.extend(tab.metadata.lines().into_iter().map(Spans::from));
+
+            let visible = area.height.saturating_sub(2);
+            let total = u16::try_from(lines.len()).unwrap_or(u16::MAX);
+            self.popup_scroll = self.popup_scroll.min(total.saturating_sub(visible));
+
+            let block = Block::default()
+                .title(format!("Metadata of {}", tab.name()))
+                .borders(Borders::ALL)
+                .style(theme.base());
+            let text = Paragraph::new(lines)
+                .block(block)
+                .scroll((self.popup_scroll, 0));
+
+            frame.render_widget(text, area);
+        }
+    }
+
+    /// Render level statistics of the current tab's selection or samples in a table.
+    ///
+    /// Statistics are cached on the tab until its samples or selection change.
+    fn render_stats<'b>(&mut self, frame: &mut Frame<'b, B>, area: Rect, theme: &Theme) {
+        if !self.stats {
+            return;
+        }
+        let area = ui::util::centered_rectangle(60, 60, area);
+        frame.render_widget(Clear, area);
+
+        let tab = &mut self.tabs[self.tab];
+        let selection = tab.selection;
+        if tab.stats.as_ref().map(|(measured, _stats)| *measured) != Some(selection) {
+            let stats = match selection {
+                Some(selection) => {
+                    dsp::level_stats(&tab.samples.slice(selection), self.clip_threshold)
+                }
+                None => dsp::level_stats(&tab.samples, self.clip_threshold),
+            };
+            tab.stats = Some((selection, stats));
+        }
+        let stats = match &tab.stats {
+            Some((_selection, stats)) => stats,
+            None => return,
+        };
+
+        let title = match selection {
+            Some(_) => format!("Levels of selection in {}", tab.name()),
+            None => format!("Levels of {}", tab.name()),
+        };
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .style(theme.base());
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let rows: Vec<Row> = stats
+            .channels
+            .iter()
+            .enumerate()
+            .map(|(index, channel)| {
+                Row::new(vec![
+                    format!("{}", index + 1),
+                    format!("{:.2}", channel.peak_db),
+                    format!("{:.2}", channel.rms_db),
+                    format!("{:.2}", channel.crest_factor_db),
+                    format!("{:+.4}", channel.dc_offset),
+                    format!("{}", channel.clipped),
+                ])
+            })
+            .collect();
+        let height = u16::try_from(rows.len() + 1).unwrap_or(u16::MAX);
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Length(height), Length(1), Length(2), Min(0)].as_ref())
+            .split(inner);
+
+        let table = Table::new(rows)
+            .header(Row::new(vec![
+                "Channel",
+                "Peak dB",
+                "RMS dB",
+                "Crest dB",
+                "DC offset",
+                "Clipped",
+            ]))
+            .widths(&[
+                Length(8),
+                Length(9),
+                Length(9),
+                Length(9),
+                Length(10),
+                Length(8),
+            ]);
+        frame.render_widget(table, chunks[0]);
+        let mut loudness = format!("Integrated Loudness: {:.1} LUFS", stats.integrated_lufs);
+        // Negative correlation means the first two channels partly cancel when summed to mono.
+        match (stats.correlation, stats.channels.len()) {
+            (Some(correlation), _) => {
+                let phase = if correlation < 0.0 {
+                    "out of phase"
+                } else {
+                    "in phase"
+                };
+                loudness.push_str(&format!("\nCorrelation: {:+.2} {}", correlation, phase));
+            }
+            (None, channels) if channels > 1 => loudness.push_str("\nCorrelation: silent channel"),
+            (None, _) => (),
+        }
+        frame.render_widget(Paragraph::new(loudness), chunks[2]);
+
+        // Clipped positions are shown as frames of the whole tab, not of the selection.
+        let offset = selection.map_or(0, |selection| selection.start);
+        let text = if stats.clipped_frames.is_empty() {
+            String::from("No clipped samples")
+        } else {
+            let frames: Vec<String> = stats
+                .clipped_frames
+                .iter()
+                .map(|frame| format!("{}", frame + offset))
+                .collect();
+            format!("Clipped at frames {}", frames.join(", "))
+        };
+        // Stop scrolling once the last wrapped line is visible.
+        let width = usize::from(chunks[3].width.max(1));
+        let total = u16::try_from(text.len() / width + 1).unwrap_or(u16::MAX);
+        self.popup_scroll = self
+            .popup_scroll
+            .min(total.saturating_sub(chunks[3].height));
+        let lines = Paragraph::new(text)
+            .wrap(Wrap { trim: true })
+            .scroll((self.popup_scroll, 0));
+        frame.render_widget(lines, chunks[3]);
+    }
+
+    fn render_menu<'b>(&mut self, frame: &mut Frame<'b, B>, area: Rect, theme: &Theme) {
+        let options: Vec<Spans> = self.views.iter().map(|view| Spans::from(view.0)).collect();
+
+        let block = Block::default().title("Menu").borders(Borders::ALL);
+
+        let tabs = Tabs::new(options)
+            .select(self.state)
+            .block(block)
+            .highlight_style(theme.menu());
+
+        frame.render_widget(tabs, area);
+    }
+
+    /// Render a level meter per channel with its peak hold and a latched clip indicator.
+    fn render_meter<'b>(&mut self, frame: &mut Frame<'b, B>, area: Rect, theme: &Theme) {
+        let channels = self.meter.channels();
+        let count = channels.len().max(1);
+        // Each meter needs room for its label, brackets, and clip indicator besides the gauge.
+        let width = (usize::from(area.width) / count)
+            .saturating_sub(11)
+            .clamp(4, 40);
+
+        let mut spans = Vec::new();
+        for (index, channel) in channels.iter().enumerate() {
+            spans.push(Span::raw(format!(
+                " {} {}",
+                index + 1,
+                meter::gauge(channel, METER_FLOOR, width)
+            )));
+            spans.push(if channel.clipped {
+                Span::styled(" CLIP", theme.clip())
+            } else {
+                Span::raw("     ")
+            });
+        }
+
+        frame.render_widget(Paragraph::new(Spans::from(spans)), area);
+    }
+
+    /// Render properties of the active tab and a hint for finding key bindings.
+    fn render_status<'b>(&mut self, frame: &mut Frame<'b, B>, area: Rect, theme: &Theme) {
+        let tab = &self.tabs[self.tab];
+        let samples = &tab.samples;
+        let seconds = tab.frames() as f64 / f64::from(samples.sample_rate.max(1));
+
+        // Levels of recordings are drawn by the meters above the status bar.
+        let mut text = if let Some(recorder) = &self.recorder {
+            format!(
+                " Recording {}",
+                ui::util::timecode(recorder.frames(), recorder.sample_rate())
+            )
+        } else if let Some(fraction) = tab.load_progress() {
+            format!(
+                " Loading {}... {}",
+                tab.name(),
+                ui::util::progress_bar(fraction, 20)
+            )
+        } else if let Some((label, fraction)) = tab.edit_progress() {
+            format!(
+                " {} {}... {}",
+                label,
+                tab.name(),
+                ui::util::progress_bar(fraction, 20)
+            )
+        } else {
+            let mut text = format!(
+                " {}{} | {} Hz | {} ch | {:.2} s | cursor {} {}",
+                tab.name(),
+                if tab.modified { "*" } else { "" },
+                samples.sample_rate,
+                samples.channels,
+                seconds,
+                tab.playhead,
+                ui::util::timecode(tab.playhead, samples.sample_rate)
+            );
+            if let Some(selection) = tab.selection {
+                text.push_str(&format!(
+                    " | selection {} frames {:.3} s",
+                    selection.len(),
+                    selection.len() as f64 / f64::from(samples.sample_rate.max(1))
+                ));
+            }
+            text
+        };
+        if let Some(tempo) = tab.tempo {
+            text.push_str(&format!(" | {:.1} BPM", tempo));
+        }
+        if self.snap_to_zero_crossing {
+            text.push_str(" | snap");
+        }
+        if let Some(key) = self.keymap.key(Action::Help) {
+            text.push_str(&format!(" | {} help", key));
+        }
+
+        frame.render_widget(Paragraph::new(text).style(theme.status()), area);
+    }
+
+    /// Draw the popup listing input devices to record from, if it is shown.
+    fn render_input_devices<'b>(&mut self, frame: &mut Frame<'b, B>, area: Rect, theme: &Theme) {
+        let (names, state) = match &mut self.input_devices {
+            Some(devices) => devices,
+            None => return,
+        };
+
+        let items: Vec<ListItem> = std::iter::once("Default input device")
+            .chain(names.iter().map(String::as_str))
+            .map(ListItem::new)
+            .collect();
+        let area = ui::util::centered_rectangle(60, 50, area);
+        let block = Block::default()
+            .title("Record from")
+            .borders(Borders::ALL)
+            .style(theme.base());
+        let list = List::new(items).block(block).highlight_style(theme.menu());
+
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(list, area, state);
+    }
+
+    /// Draw the command palette with the typed query above the keys and descriptions of matching
+    /// actions, if it is shown.
+    fn render_palette<'b>(&mut self, frame: &mut Frame<'b, B>, area: Rect, theme: &Theme) {
+        let (query, state) = match &mut self.palette {
+            Some(palette) => palette,
+            None => return,
+        };
+
+        let keymap = &self.keymap;
+        let items: Vec<ListItem> = Action::search(query)
+            .into_iter()
+            .map(|action| {
+                let key = keymap
+                    .key(action)
+                    .map_or_else(String::new, |key| format!("{}", key));
+                ListItem::new(format!("{:>12}  {}", key, action.description()))
+            })
+            .collect();
+        let area = ui::util::centered_rectangle(60, 60, area);
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Length(3), Min(0)].as_ref())
+            .split(area);
+        let prompt = Block::default()
+            .title("Run action")
+            .borders(Borders::ALL)
+            .style(theme.base());
+        let block = Block::default().borders(Borders::ALL).style(theme.base());
+        let list = List::new(items).block(block).highlight_style(theme.menu());
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(Paragraph::new(query.as_str()).block(prompt), chunks[0]);
+        frame.render_stateful_widget(list, chunks[1], state);
+    }
+
+    /// Draw the popup choosing whether to export the selected frames or the whole tab as CSV, if
+    /// it is shown.
+    fn render_csv_export<'b>(&mut self, frame: &mut Frame<'b, B>, area: Rect, theme: &Theme) {
+        let state = match &mut self.csv_export {
+            Some(state) => state,
+            None => return,
+        };
+
+        let items = vec![ListItem::new("Selected frames"), ListItem::new("Whole tab")];
+        let area = ui::util::centered_rectangle(40, 40, area);
+        let block = Block::default()
+            .title("Export sample values of")
+            .borders(Borders::ALL)
+            .style(theme.base());
+        let list = List::new(items).block(block).highlight_style(theme.menu());
+
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(list, area, state);
+    }
+
+    /// Draw the popup listing channels to invert the polarity of, if it is shown.
+    fn render_polarity<'b>(&mut self, frame: &mut Frame<'b, B>, area: Rect, theme: &Theme) {
+        let state = match &mut self.polarity {
+            Some(state) => state,
+            None => return,
+        };
+
+        let channels = self.tabs[self.tab].samples.channels;
+        let items: Vec<ListItem> = std::iter::once(String::from("All channels"))
+            .chain((1..=channels).map(|channel| format!("Channel {}", channel)))
+            .map(ListItem::new)
+            .collect();
+        let area = ui::util::centered_rectangle(40, 40, area);
+        let block = Block::default()
+            .title("Invert polarity of")
+            .borders(Borders::ALL)
+            .style(theme.base());
+        let list = List::new(items).block(block).highlight_style(theme.menu());
+
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(list, area, state);
+    }
+
+    /// Draw the popup listing open tabs with their durations, if it is shown.
+    fn render_tab_list<'b>(&mut self, frame: &mut Frame<'b, B>, area: Rect, theme: &Theme) {
+        let state = match &mut self.tab_list {
+            Some(state) => state,
+            None => return,
+        };
+
+        let items: Vec<ListItem> = self
+            .tabs
+            .iter()
+            .enumerate()
+            .map(|(index, tab)| {
+                let seconds = tab.frames() as f64 / f64::from(tab.samples.sample_rate.max(1));
+                let length = if tab.is_loading() {
+                    String::from("loading")
+                } else {
+                    format!("{:.2} s", seconds)
+                };
+                let marker = if tab.modified { "*" } else { "" };
+                ListItem::new(format!(
+                    "{}  {}{}  {}",
+                    index + 1,
+                    tab.name(),
+                    marker,
+                    length
+                ))
+            })
+            .collect();
+
+        let title = match self.mix_gain {
+            Some(gain) => format!("Mix into current tab at gain {:.1} (left/right)", gain),
+            None => String::from("Open tabs"),
+        };
+        let area = ui::util::centered_rectangle(60, 50, area);
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .style(theme.base());
+        let list = List::new(items).block(block).highlight_style(theme.menu());
+
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(list, area, state);
+    }
+
+    /// Render the prompt for the length of silence to insert, if it is shown.
+    fn render_silence<'b>(&self, frame: &mut Frame<'b, B>, area: Rect, theme: &Theme) {
+        let (text, error) = match &self.silence {
+            Some(silence) => silence,
+            None => return,
+        };
+
+        let area = ui::util::centered_rectangle(60, 20, area);
+        frame.render_widget(Clear, area);
+
+        let block = Block::default()
+            .title("Insert silence in milliseconds")
+            .borders(Borders::ALL)
+            .style(theme.base());
+        let mut lines = vec![Spans::from(text.as_str())];
+        if let Some(error) = error {
+            lines.push(Spans::from(Span::styled(error.as_str(), theme.warning())));
+        }
+
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+    }
+
+    /// Render the prompt confirming that an export overwrites an existing file, if it is shown.
+    fn render_overwrite<'b>(&self, frame: &mut Frame<'b, B>, area: Rect, theme: &Theme) {
+        let overwrite = match &self.overwrite {
+            Some(overwrite) => overwrite,
+            None => return,
+        };
+
+        let area = ui::util::centered_rectangle(60, 20, area);
+        frame.render_widget(Clear, area);
+
+        let block = Block::default()
+            .title("Confirm overwrite")
+            .borders(Borders::ALL)
+            .style(theme.base());
+        let text = format!(
+            "File {} already exists. Overwrite it? (y/n)",
+            overwrite.path.display()
+        );
+
+        frame.render_widget(Paragraph::new(text).block(block), area);
+    }
+
+    /// Render the prompt for the factor of a time stretch or pitch shift, if it is shown.
+    fn render_factor<'b>(&self, frame: &mut Frame<'b, B>, area: Rect, theme: &Theme) {
+        let factor = match &self.factor {
+            Some(factor) => factor,
+            None => return,
+        };
+
+        let area = ui::util::centered_rectangle(60, 20, area);
+        frame.render_widget(Clear, area);
+
+        let block = Block::default()
+            .title(factor.title())
+            .borders(Borders::ALL)
+            .style(theme.base());
+        let mut lines = vec![Spans::from(factor.text.as_str())];
+        if let Some(error) = &factor.error {
+            lines.push(Spans::from(Span::styled(error.as_str(), theme.warning())));
+        }
+
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+    }
+
+    fn render_tabs<'b>(&mut self, frame: &mut Frame<'b, B>, area: Rect, theme: &Theme) {
+        let options: Vec<Spans> = self
+            .tabs
+            .iter()
+            .map(|tab| {
+                let marker = if tab.modified { "*" } else { "" };
+                Spans::from(format!("{}{}", tab.name(), marker))
+            })
+            .collect();
+
+        let block = Block::default().title("Tabs").borders(Borders::ALL);
+
+        let tabs = Tabs::new(options)
+            .select(self.tab)
+            .block(block)
+            .highlight_style(theme.menu());
+
+        frame.render_widget(tabs, area);
+    }
+
+    /// Loop and wait for user keyboard input.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `terminal` cannot draw frames.
+    pub fn run(&mut self, terminal: &mut Terminal<B>, sink: &Sink) -> eyre::Result<()> {
+        let (sender, receiver) = mpsc::channel::<Event>();
+        let _thread_handle = event::handler(sender);
+
+        while !self.shutdown {
+            let active = self.tab;
+            self.process();
+            if self.tab != active {
+                sink.stop();
+            }
+            self.scrub(sink);
+            self.update_meter(sink);
+            self.render(terminal)?;
+
+            match receiver.try_recv() {
+                Ok(Event::Key(key_event)) => self.key_event(sink, key_event),
+                Ok(Event::Mouse(mouse_event)) => self.mouse_event(sink, mouse_event),
+                Ok(_) | Err(TryRecvError::Empty) => (),
+                Err(TryRecvError::Disconnected) => return Err(TryRecvError::Disconnected.into()),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Parse a length typed as milliseconds.
+///
+/// # Errors
+///
+/// Will return `Err` if the text is not a number.
+fn parse_milliseconds(text: &str) -> eyre::Result<f32> {
+    text.trim()
+        .parse()
+        .map_err(|_error| eyre::eyre!("Length must be a number of milliseconds such as 10"))
+}
+
+/// Parse a time stretch typed as a percentage of the original length, with or without a percent
+/// sign.
+///
+/// # Errors
+///
+/// Will return `Err` if the text is not a number or lies outside [`STRETCH_PERCENT`].
+fn parse_percent(text: &str) -> eyre::Result<f32> {
+    let (minimum, maximum) = STRETCH_PERCENT;
+    let percent: f32 = text
+        .trim()
+        .trim_end_matches('%')
+        .trim_end()
+        .parse()
+        .map_err(|_error| eyre::eyre!("Length must be a percentage such as 150%"))?;
+    if !(minimum..=maximum).contains(&percent) {
+        eyre::bail!(
+            "Length {}% must be between {}% and {}%",
+            percent,
+            minimum,
+            maximum
+        );
+    }
+    Ok(percent)
+}
+
+/// Parse a pitch shift typed as semitones, negative to lower the pitch.
+///
+/// # Errors
+///
+/// Will return `Err` if the text is not a number or shifts by more than
+/// [`TRANSPOSE_SEMITONES`].
+fn parse_semitones(text: &str) -> eyre::Result<f32> {
+    let semitones: f32 = text
+        .trim()
+        .parse()
+        .map_err(|_error| eyre::eyre!("Pitch must be a number of semitones such as -3"))?;
+    if semitones.is_nan() || semitones.abs() > TRANSPOSE_SEMITONES {
+        eyre::bail!(
+            "Pitch change of {} semitones must be between -{} and {}",
+            semitones,
+            TRANSPOSE_SEMITONES,
+            TRANSPOSE_SEMITONES
+        );
+    }
+    Ok(semitones)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::metadata::Metadata;
+    use crate::util;
+    use crate::util::test::MockView;
+    use rodio::Sink;
+    use std::thread;
+    use tui::backend::TestBackend;
+
+    #[test]
+    fn handle_view_error() {
+        let sink = Sink::new_idle().0;
+        let backend = TestBackend::new(20, 10);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        let mut mock = MockView::new(true);
+        let mut views: Vec<(&str, &mut dyn View<TestBackend>)> = Vec::new();
+        views.push(("", &mut mock));
+
+        let mut app = App::new(&mut views, Tab::default());
+        app.process();
+        app.render(&mut terminal).unwrap();
+
+        let actual = util::test::buffer_view(terminal.backend().buffer());
+        assert!(actual.contains("Error"));
+
+        app.key_event(&sink, KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+        app.process();
+        app.render(&mut terminal).unwrap();
+
+        let actual = util::test::buffer_view(terminal.backend().buffer());
+        assert!(!actual.contains("Error"));
+
+        // Any key dismisses errors without reaching the view or triggering actions.
+        let mut terminal = Terminal::new(TestBackend::new(80, 30)).unwrap();
+        app.report(eyre::eyre!("Disk full"));
+        app.render(&mut terminal).unwrap();
+        let actual = util::test::buffer_view(terminal.backend().buffer());
+        assert!(actual.contains("Disk full"));
+        assert!(actual.contains("Press any key"));
+        app.key_event(&sink, KeyEvent::new(KeyCode::Char('?'), KeyModifiers::NONE));
+        assert!(app.error.is_ok());
+        assert!(!app.help);
+    }
+
+    #[test]
+    fn help_shows_effective_bindings() {
+        let sink = Sink::new_idle().0;
+        let backend = TestBackend::new(80, 60);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        let keys = [(String::from("quit"), String::from("ctrl+q"))].into();
+        let mut app =
+            App::new(&mut [], Tab::default()).with_keymap(Keymap::try_new(&keys).unwrap());
+        app.key_event(&sink, KeyEvent::new(KeyCode::Char('?'), KeyModifiers::NONE));
+        app.render(&mut terminal).unwrap();
+
+        let actual = util::test::buffer_view(terminal.backend().buffer());
+        assert!(actual.contains("ctrl+q  Quit"));
+        assert!(!actual.contains(" esc "));
+
+        app.key_event(
+            &sink,
+            KeyEvent::new(KeyCode::Char('q'), KeyModifiers::CONTROL),
+        );
+        assert!(!app.help);
+        assert!(!app.shutdown);
+    }
+
+    #[test]
+    fn help_scrolls_and_dismisses() {
+        let sink = Sink::new_idle().0;
+        let backend = TestBackend::new(80, 10);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        let mut app = App::new(&mut [], Tab::default());
+        app.key_event(&sink, KeyEvent::new(KeyCode::Char('?'), KeyModifiers::NONE));
+        app.render(&mut terminal).unwrap();
+        let actual = util::test::buffer_view(terminal.backend().buffer());
+        assert!(actual.contains("Transport"));
+
+        for _ in 0..60 {
+            app.key_event(&sink, KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+        }
+        app.render(&mut terminal).unwrap();
+        let actual = util::test::buffer_view(terminal.backend().buffer());
+        assert!(!actual.contains("Transport"));
+        assert!(actual.contains("Go to tab by number"));
+
+        app.key_event(&sink, KeyEvent::new(KeyCode::Char('?'), KeyModifiers::NONE));
+        assert!(!app.help);
+        app.key_event(&sink, KeyEvent::new(KeyCode::Char('?'), KeyModifiers::NONE));
+        app.key_event(&sink, KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+        assert!(!app.help);
+        assert!(!app.shutdown);
+    }
+
+    #[test]
+    fn info_shows_tab_metadata() {
+        let sink = Sink::new_idle().0;
+        let mut terminal = Terminal::new(TestBackend::new(80, 20)).unwrap();
+        let info = KeyEvent::new(KeyCode::Char('i'), KeyModifiers::ALT);
+
+        let mut app = App::new(&mut [], Tab::default());
+        app.key_event(&sink, info);
+        app.render(&mut terminal).unwrap();
+        let actual = util::test::buffer_view(terminal.backend().buffer());
+        assert!(actual.contains("Metadata of untitled"));
+        assert!(actual.contains("DC offset: +0.0000"));
+        assert!(actual.contains("No metadata"));
+
+        app.key_event(&sink, KeyEvent::new(KeyCode::Char('?'), KeyModifiers::NONE));
+        assert!(app.help && !app.info);
+        app.key_event(&sink, info);
+        assert!(app.info && !app.help);
+
+        app.tabs[0].metadata = Metadata {
+            details: vec![(String::from("Format"), String::from("FLAC"))],
+            tags: vec![(String::from("Artist"), String::from("The Band"))],
+        };
+        app.render(&mut terminal).unwrap();
+        let actual = util::test::buffer_view(terminal.backend().buffer());
+        assert!(actual.contains("Format: FLAC"));
+        assert!(actual.contains("Artist: The Band"));
+
+        app.key_event(&sink, KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+        assert!(!app.info);
+        assert!(!app.shutdown);
+    }
+
+    #[test]
+    fn stats_show_levels_of_selection() {
+        let sink = Sink::new_idle().0;
+        let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+        let stats = KeyEvent::new(KeyCode::Char('s'), KeyModifiers::ALT);
+
+        let samples = Samples::new(2, 8, vec![0.5, -0.25, 1.0, 0.0, 0.5, -0.25, 0.5, -0.25]);
+        let mut app = App::new(&mut [], Tab::new(samples, None)).with_clip_threshold(0.9);
+        app.key_event(&sink, stats);
+        app.render(&mut terminal).unwrap();
+        let actual = util::test::buffer_view(terminal.backend().buffer());
+        assert!(actual.contains("Levels of untitled"));
+        assert!(actual.contains("Peak dB"));
+        assert!(actual.contains("-12.04"));
+        assert!(actual.contains("Clipped at frames 1"));
+        assert!(actual.contains(&format!(
+            "Integrated Loudness: {:.1} LUFS",
+            app.tabs[0].stats.as_ref().unwrap().1.integrated_lufs
+        )));
+        assert!(actual.contains("Correlation: -0.65 out of phase"));
+
+        app.tabs[0].selection = Some(Selection::new(2, 4));
+        app.render(&mut terminal).unwrap();
+        let actual = util::test::buffer_view(terminal.backend().buffer());
+        assert!(actual.contains("Levels of selection in untitled"));
+        assert!(actual.contains("No clipped samples"));
+        assert_eq!(
+            app.tabs[0]
+                .stats
+                .as_ref()
+                .map(|(selection, _stats)| *selection),
+            Some(Some(Selection::new(2, 4)))
+        );
+
+        app.key_event(&sink, KeyEvent::new(KeyCode::Char('i'), KeyModifiers::ALT));
+        assert!(app.info && !app.stats);
+        app.key_event(&sink, stats);
+        app.key_event(&sink, KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+        assert!(!app.popup());
+    }
+
+    #[test]
+    fn quit_confirms_unsaved_changes() {
+        let sink = Sink::new_idle().0;
+        let mut terminal = Terminal::new(TestBackend::new(80, 30)).unwrap();
+        let key = |char| KeyEvent::new(KeyCode::Char(char), KeyModifiers::NONE);
+        let quit = KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE);
+
+        let mut tab = Tab::new(Samples::new(1, 8, vec![0.5; 4]), None);
+        tab.modified = true;
+        let mut app = App::new(&mut [], tab);
+
+        app.key_event(&sink, quit);
+        app.render(&mut terminal).unwrap();
+        let actual = util::test::buffer_view(terminal.backend().buffer());
+        assert!(actual.contains("untitled has unsaved changes"));
+        assert!(actual.contains(" untitled* | 8 Hz"));
+        assert!(actual.contains("| cursor 0 00:00:00.000"));
+
+        app.key_event(&sink, key('c'));
+        assert!(app.confirm.is_none());
+        assert!(!app.shutdown);
+
+        // Untitled tabs have no file to save to, so quitting is canceled.
+        app.key_event(&sink, quit);
+        app.key_event(&sink, key('s'));
+        assert!(!app.shutdown);
+        app.render(&mut terminal).unwrap();
+        let actual = util::test::buffer_view(terminal.backend().buffer());
+        assert!(actual.contains("Write untitled to a file"));
+
+        app.key_event(&sink, key('d'));
+        assert!(app.shutdown);
     }
 
-    /// Pass keyboard input to current view.
-    pub fn key_event(&mut self, sink: &Sink, event: KeyEvent) {
-        if let Some(view) = self.views.get_mut(self.state) {
-            view.1.key_event(event);
+    #[test]
+    fn quit_saves_unsaved_changes() {
+        let sink = Sink::new_idle().0;
+        let folder = tempfile::tempdir().unwrap();
+        let path = folder.path().join("edited.wav");
+
+        let mut tab = Tab::new(Samples::new(1, 8, vec![0.5; 4]), Some(path.clone()));
+        tab.modified = true;
+        let mut app: App<TestBackend> = App::new(&mut [], tab);
+
+        app.key_event(&sink, KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+        app.key_event(&sink, KeyEvent::new(KeyCode::Char('s'), KeyModifiers::NONE));
+
+        assert!(app.shutdown);
+        assert!(!app.tabs[0].modified);
+        assert!(path.is_file());
+    }
+
+    #[test]
+    fn close_tab_confirms_unsaved_changes() {
+        let sink = Sink::new_idle().0;
+        let key = |char| KeyEvent::new(KeyCode::Char(char), KeyModifiers::NONE);
+        let close = KeyEvent::new(KeyCode::Char('w'), KeyModifiers::CONTROL);
+
+        let mut app: App<TestBackend> = App::new(&mut [], Tab::default());
+        app.tabs
+            .push(Tab::new(Samples::new(1, 8, vec![0.5; 4]), None));
+        app.tabs[1].modified = true;
+        app.tab = 1;
+
+        app.key_event(&sink, close);
+        app.key_event(&sink, key('c'));
+        assert_eq!(app.tabs.len(), 2);
+
+        app.key_event(&sink, close);
+        app.key_event(&sink, key('s'));
+        assert_eq!(app.tabs.len(), 2);
+        assert!(app.confirm.as_ref().unwrap().error.is_some());
+
+        app.key_event(&sink, key('d'));
+        assert!(app.confirm.is_none());
+        assert_eq!(app.tabs.len(), 1);
+        assert!(!app.shutdown);
+    }
+
+    #[test]
+    fn switch_tabs_by_number_and_list() {
+        let sink = Sink::new_idle().0;
+        let mut terminal = Terminal::new(TestBackend::new(80, 30)).unwrap();
+        let alt = |char| KeyEvent::new(KeyCode::Char(char), KeyModifiers::ALT);
+
+        let mut app = App::new(&mut [], Tab::new(Samples::new(1, 8, vec![0.5; 16]), None));
+        app.tabs.push(Tab::default());
+        app.tabs[0].playhead = 3;
+
+        app.key_event(&sink, alt('2'));
+        assert_eq!(app.tab, 1);
+        app.key_event(&sink, alt('9'));
+        assert_eq!(app.tab, 1);
+
+        app.key_event(
+            &sink,
+            KeyEvent::new(KeyCode::Char('b'), KeyModifiers::CONTROL),
+        );
+        app.render(&mut terminal).unwrap();
+        let actual = util::test::buffer_view(terminal.backend().buffer());
+        assert!(actual.contains("1  untitled  2.00 s"));
+
+        app.key_event(&sink, KeyEvent::new(KeyCode::Up, KeyModifiers::NONE));
+        app.key_event(&sink, KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert!(app.tab_list.is_none());
+        assert_eq!(app.tab, 0);
+        assert_eq!(app.tabs[0].playhead, 3);
+    }
+
+    #[test]
+    fn palette_runs_selected_action() {
+        let sink = Sink::new_idle().0;
+        let mut terminal = Terminal::new(TestBackend::new(80, 30)).unwrap();
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let palette = key(KeyCode::F(1));
+        let mut app: App<TestBackend> = App::new(&mut [], Tab::default());
+
+        app.key_event(&sink, palette);
+        for char in "scrub".chars() {
+            app.key_event(&sink, key(KeyCode::Char(char)));
         }
+        app.render(&mut terminal).unwrap();
+        let actual = util::test::buffer_view(terminal.backend().buffer());
+        assert!(actual.contains("Run action"));
+        assert!(actual.contains("ctrl+u  Toggle playing snippets"));
+        assert!(!actual.contains("Close tab"));
 
-        match event.code {
-            KeyCode::Char(' ') => self.play(sink),
-            KeyCode::Esc => {
-                if self.error.is_err() {
-                    self.error = Ok(());
-                } else {
-                    self.shutdown = true;
-                }
-            }
-            KeyCode::Tab => self.next(),
-            _ => (),
+        app.key_event(&sink, key(KeyCode::Enter));
+        assert!(app.palette.is_none());
+        assert!(app.scrub);
+
+        // Typed keys bound to actions filter instead of running, and the best match comes first.
+        app.key_event(&sink, palette);
+        for char in "toggle".chars() {
+            app.key_event(&sink, key(KeyCode::Char(char)));
         }
+        app.key_event(&sink, key(KeyCode::Down));
+        app.key_event(&sink, key(KeyCode::Enter));
+        assert!(app.info);
+        assert!(!app.help);
+        app.key_event(&sink, KeyEvent::new(KeyCode::Char('i'), KeyModifiers::ALT));
+
+        app.key_event(&sink, palette);
+        app.key_event(&sink, key(KeyCode::Char('x')));
+        app.key_event(&sink, key(KeyCode::Char('q')));
+        app.key_event(&sink, key(KeyCode::Enter));
+        assert_eq!(
+            app.palette.as_ref().map(|palette| palette.0.as_str()),
+            Some("xq")
+        );
+        app.key_event(&sink, key(KeyCode::Esc));
+        assert!(app.palette.is_none());
+        assert!(!app.shutdown);
     }
 
-    /// Modular move menu state to next option.
-    pub fn next(&mut self) {
-        self.state = (self.state + 1) % self.views.len();
+    #[test]
+    fn polarity_popup_inverts_chosen_channel() {
+        let sink = Sink::new_idle().0;
+        let mut terminal = Terminal::new(TestBackend::new(80, 30)).unwrap();
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let invert = KeyEvent::new(KeyCode::Char('v'), KeyModifiers::ALT);
+
+        let stereo = Samples::new(2, 8, vec![0.5, 0.25, -0.5, 0.75]);
+        let mut app: App<TestBackend> = App::new(&mut [], Tab::new(stereo, None));
+        app.key_event(&sink, invert);
+        app.render(&mut terminal).unwrap();
+        let actual = util::test::buffer_view(terminal.backend().buffer());
+        assert!(actual.contains("Invert polarity of"));
+        assert!(actual.contains("All channels"));
+        assert!(actual.contains("Channel 2"));
+
+        app.key_event(&sink, key(KeyCode::Up));
+        app.key_event(&sink, key(KeyCode::Enter));
+        assert!(app.polarity.is_none());
+        assert_eq!(app.tabs[0].samples.data, vec![0.5, -0.25, -0.5, -0.75]);
+
+        app.key_event(&sink, invert);
+        app.key_event(&sink, key(KeyCode::Enter));
+        assert_eq!(app.tabs[0].samples.data, vec![-0.5, 0.25, 0.5, 0.75]);
+        app.key_event(&sink, invert);
+        app.key_event(&sink, key(KeyCode::Esc));
+        assert!(app.polarity.is_none());
+
+        app.tabs
+            .push(Tab::new(Samples::new(1, 8, vec![0.5, -0.25]), None));
+        app.tab = 1;
+        app.key_event(&sink, invert);
+        assert!(app.polarity.is_none());
+        assert_eq!(app.tabs[1].samples.data, vec![-0.5, 0.25]);
     }
 
-    /// Play currently loaded signal.
-    pub fn play(&self, sink: &Sink) {
-        if sink.empty() {
-            let source = SamplesBuffer::from(&self.samples);
-            sink.append(source);
-        } else if sink.is_paused() {
-            sink.play();
-        } else {
-            sink.pause();
-        }
+    #[test]
+    fn input_devices_popup_picks_device() {
+        let sink = Sink::new_idle().0;
+        let mut terminal = Terminal::new(TestBackend::new(80, 30)).unwrap();
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+
+        let mut app: App<TestBackend> =
+            App::new(&mut [], Tab::default()).with_recording(Some(1), Some(8000));
+        let mut state = ListState::default();
+        state.select(Some(0));
+        app.input_devices = Some((vec![String::from("Missing microphone")], state));
+
+        app.render(&mut terminal).unwrap();
+        let actual = util::test::buffer_view(terminal.backend().buffer());
+        assert!(actual.contains("Record from"));
+        assert!(actual.contains("Default input device"));
+        assert!(actual.contains("Missing microphone"));
+
+        app.key_event(&sink, key(KeyCode::Down));
+        app.key_event(&sink, KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE));
+        assert_eq!(app.input_devices.as_ref().unwrap().1.selected(), Some(1));
+        app.key_event(&sink, key(KeyCode::Enter));
+        assert!(app.input_devices.is_none());
+        assert!(app.recorder.is_none());
+        assert!(app.error.is_err());
+
+        app.error = Ok(());
+        app.input_devices = Some((Vec::new(), ListState::default()));
+        app.key_event(&sink, key(KeyCode::Esc));
+        assert!(app.input_devices.is_none());
+        assert_eq!(app.recording.sample_rate, Some(8000));
     }
 
-    /// Update internal signal state.
-    pub fn process(&mut self) {
-        if self.error.is_ok() {
-            for (_name, view) in &mut self.views.iter_mut() {
-                if let Err(error) = view.process(&mut self.samples) {
-                    self.error = Err(error);
-                    view.reset();
-                    break;
-                }
-            }
-        }
+    #[test]
+    fn export_waveform_next_to_file() {
+        let folder = tempfile::tempdir().unwrap();
+        let sink = Sink::new_idle().0;
+        let samples = Samples::new(2, 8000, vec![0.5, -0.5, -1.0, 1.0, 0.0, 0.25]);
+        let mut app = App::<TestBackend>::new(&mut [], Tab::new(samples.clone(), None));
+
+        app.key_event(&sink, KeyEvent::new(KeyCode::Char('p'), KeyModifiers::ALT));
+        assert!(app.error.is_err());
+
+        app.error = Ok(());
+        app.tabs[0].path = Some(folder.path().join("take.wav"));
+        app.key_event(&sink, KeyEvent::new(KeyCode::Char('p'), KeyModifiers::ALT));
+        app.key_event(&sink, KeyEvent::new(KeyCode::Char('a'), KeyModifiers::ALT));
+        assert!(app.error.is_ok());
+
+        let image = fs::read(folder.path().join("take.png")).unwrap();
+        assert_eq!(image[1..4], *b"PNG");
+        assert_eq!(image[16..24], [0, 0, 4, 176, 0, 0, 1, 144]);
+        let text = fs::read_to_string(folder.path().join("take.txt")).unwrap();
+        let (width, height) = ASCII_SIZE;
+        assert_eq!(text, chart::render_to_ascii(&samples, width, height));
+
+        let ascii = KeyEvent::new(KeyCode::Char('a'), KeyModifiers::ALT);
+        fs::write(folder.path().join("take.txt"), "old").unwrap();
+        app.key_event(&sink, ascii);
+        assert!(app.overwrite.is_some());
+        app.key_event(&sink, KeyEvent::new(KeyCode::Char('n'), KeyModifiers::NONE));
+        assert!(app.overwrite.is_none());
+        let text = fs::read_to_string(folder.path().join("take.txt")).unwrap();
+        assert_eq!(text, "old");
+
+        app.key_event(&sink, ascii);
+        app.key_event(&sink, KeyEvent::new(KeyCode::Char('y'), KeyModifiers::NONE));
+        assert!(app.overwrite.is_none());
+        let text = fs::read_to_string(folder.path().join("take.txt")).unwrap();
+        assert_eq!(text, chart::render_to_ascii(&samples, width, height));
     }
 
-    /// Render all UI views in terminal screen.
-    ///
-    /// # Errors
-    ///
-    /// Will return `Err` if `terminal` cannot draw frames.
-    pub fn render(&mut self, terminal: &mut Terminal<B>) -> eyre::Result<()> {
-        terminal.draw(|frame| {
-            let size = frame.size();
-            let chunks = Layout::default()
-                .direction(Direction::Vertical)
-                .margin(1)
-                .constraints([Percentage(16), Percentage(84)].as_ref())
-                .split(size);
+    #[test]
+    fn export_csv_of_selection_or_whole_tab() {
+        let folder = tempfile::tempdir().unwrap();
+        let sink = Sink::new_idle().0;
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let export = KeyEvent::new(KeyCode::Char('n'), KeyModifiers::ALT);
+        let data = vec![0.5, -0.5, -1.0, 1.0, 0.0, 0.25, 0.75, -0.75];
+        let samples = Samples::new(2, 4, data.clone());
+        let mut app = App::<TestBackend>::new(&mut [], Tab::new(samples.clone(), None))
+            .with_csv_index(IndexColumn::Time);
 
-            self.render_menu(frame, chunks[0]);
+        app.key_event(&sink, export);
+        assert!(app.error.is_err());
 
-            if let Some(view) = self.views.get_mut(self.state) {
-                view.1.render(frame, chunks[1]);
-            }
+        app.error = Ok(());
+        app.tabs[0].path = Some(folder.path().join("take.wav"));
+        app.key_event(&sink, export);
+        assert!(app.error.is_ok());
+        let path = folder.path().join("take.csv");
+        let text = fs::read_to_string(&path).unwrap();
+        let rows: Vec<Vec<&str>> = text.lines().map(|line| line.split(',').collect()).collect();
+        assert_eq!(rows.len(), 5);
+        assert!(rows.iter().all(|row| row.len() == 3));
+        let times: Vec<&str> = rows[1..].iter().map(|row| row[0]).collect();
+        assert_eq!(times, ["0", "0.25", "0.5", "0.75"]);
+        assert_eq!(csv::read_csv(&path, 4).unwrap(), samples);
 
-            self.render_error(frame, size);
-        })?;
+        app.tabs[0].selection = Some(Selection::new(1, 3));
+        app.key_event(&sink, export);
+        assert!(app.csv_export.is_some());
+        app.key_event(&sink, key(KeyCode::Enter));
+        assert!(app.csv_export.is_none());
+        let path = folder.path().join("take.selection.csv");
+        let text = fs::read_to_string(&path).unwrap();
+        assert_eq!(text.lines().nth(1), Some("0,-1,1"));
+        let exported = csv::read_csv(&path, 4).unwrap();
+        assert_eq!(exported.data, data[2..6]);
 
-        Ok(())
+        // Choosing the whole tab ignores the selection, once its file may be overwritten.
+        let path = folder.path().join("take.csv");
+        fs::write(&path, "old").unwrap();
+        app.key_event(&sink, export);
+        app.key_event(&sink, key(KeyCode::Down));
+        app.key_event(&sink, key(KeyCode::Enter));
+        assert!(app.overwrite.is_some());
+        app.key_event(&sink, key(KeyCode::Esc));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "old");
+
+        app.key_event(&sink, export);
+        app.key_event(&sink, key(KeyCode::Down));
+        app.key_event(&sink, key(KeyCode::Enter));
+        app.key_event(&sink, key(KeyCode::Char('y')));
+        assert!(app.overwrite.is_none());
+        assert_eq!(csv::read_csv(&path, 4).unwrap(), samples);
     }
 
-    /// Render all UI views in terminal screen.
-    pub fn render_error<'b>(&mut self, frame: &mut Frame<'b, B>, area: Rect) {
-        if let Err(error) = &self.error {
-            let area = ui::util::centered_rectangle(60, 20, area);
-            frame.render_widget(Clear, area);
+    #[test]
+    fn mix_tab_reports_clipping() {
+        let sink = Sink::new_idle().0;
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let mix = KeyEvent::new(KeyCode::Char('m'), KeyModifiers::ALT);
+        let close = |actual: &[f32], expected: &[f32]| {
+            actual.len() == expected.len()
+                && actual
+                    .iter()
+                    .zip(expected)
+                    .all(|(x, y)| (x - y).abs() < 1e-5)
+        };
 
-            let block = Block::default().title("Error").borders(Borders::ALL);
-            let text = Text::from(format!("{}", error));
-            let line = Paragraph::new(text).block(block);
+        let mut app: App<TestBackend> =
+            App::new(&mut [], Tab::new(Samples::new(1, 8, vec![0.5; 4]), None));
+        app.tabs
+            .push(Tab::new(Samples::new(1, 8, vec![0.5; 2]), None));
 
-            frame.render_widget(line, area);
+        app.key_event(&sink, mix);
+        app.key_event(&sink, key(KeyCode::Down));
+        for _ in 0..5 {
+            app.key_event(&sink, key(KeyCode::Left));
         }
+        app.key_event(&sink, key(KeyCode::Enter));
+        assert!(app.tab_list.is_none());
+        assert_eq!(app.tab, 0);
+        assert!(app.error.is_ok());
+        assert!(close(&app.tabs[0].samples.data, &[0.75, 0.75, 0.5, 0.5]));
+
+        app.key_event(&sink, mix);
+        app.key_event(&sink, key(KeyCode::Enter));
+        assert!(app.error.is_err());
+        assert!(close(&app.tabs[0].samples.data, &[1.5, 1.5, 1.0, 1.0]));
     }
 
-    fn render_menu<'b>(&mut self, frame: &mut Frame<'b, B>, area: Rect) {
-        let options: Vec<Spans> = self.views.iter().map(|view| Spans::from(view.0)).collect();
+    #[test]
+    fn stretch_prompt_edits_in_background() {
+        let sink = Sink::new_idle().0;
+        let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let stretch = KeyEvent::new(KeyCode::Char('t'), KeyModifiers::ALT);
+        let samples = Samples::new(2, 8000, vec![0.25; 8000]);
+        let mut app: App<TestBackend> = App::new(&mut [], Tab::new(samples, None));
 
-        let block = Block::default().title("Menu").borders(Borders::ALL);
+        app.key_event(&sink, stretch);
+        for _ in 0..4 {
+            app.key_event(&sink, key(KeyCode::Backspace));
+        }
+        for char in "500".chars() {
+            app.key_event(&sink, key(KeyCode::Char(char)));
+        }
+        app.key_event(&sink, key(KeyCode::Enter));
+        app.render(&mut terminal).unwrap();
+        let actual = util::test::buffer_view(terminal.backend().buffer());
+        assert!(actual.contains("Change length to a percentage"));
+        assert!(actual.contains("Length 500% must be between 25% and 400%"));
 
-        let tabs = Tabs::new(options)
-            .select(self.state)
-            .block(block)
-            .highlight_style(Style::default().add_modifier(Modifier::BOLD));
+        for _ in 0..3 {
+            app.key_event(&sink, key(KeyCode::Backspace));
+        }
+        for char in "150%".chars() {
+            app.key_event(&sink, key(KeyCode::Char(char)));
+        }
+        app.key_event(&sink, key(KeyCode::Enter));
+        assert!(app.factor.is_none());
+        app.render(&mut terminal).unwrap();
+        let actual = util::test::buffer_view(terminal.backend().buffer());
+        assert!(actual.contains("Stretching untitled..."));
 
-        frame.render_widget(tabs, area);
-    }
+        let start = Instant::now();
+        while app.tabs[0].edit_progress().is_some() && start.elapsed() < Duration::from_secs(10) {
+            app.process();
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(app.tabs[0].samples.frames(), 6000);
+        assert!(app.error.is_ok());
 
-    /// Loop and wait for user keyboard input.
-    ///
-    /// # Errors
-    ///
-    /// Will return `Err` if `terminal` cannot draw frames.
-    pub fn run(&mut self, terminal: &mut Terminal<B>, sink: &Sink) -> eyre::Result<()> {
-        let (sender, receiver) = mpsc::channel::<Option<KeyEvent>>();
-        let _thread_handle = event::handler(sender);
+        assert_eq!(parse_semitones(" -12 ").unwrap(), -12.0);
+        assert!(parse_semitones("25").is_err());
+        assert!(parse_semitones("NaN").is_err());
+        assert_eq!(parse_percent("25").unwrap(), 25.0);
+        assert!(parse_percent("fast").is_err());
+    }
 
-        while !self.shutdown {
-            self.process();
-            self.render(terminal)?;
+    #[test]
+    fn insert_silence_prompt_edits_tab() {
+        let sink = Sink::new_idle().0;
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let mut app: App<TestBackend> =
+            App::new(&mut [], Tab::new(Samples::new(1, 1000, vec![0.5; 4]), None));
+        app.tabs[0].playhead = 2;
 
-            match receiver.try_recv() {
-                Ok(Some(key_event)) => self.key_event(sink, key_event),
-                Ok(None) | Err(TryRecvError::Empty) => (),
-                Err(TryRecvError::Disconnected) => return Err(TryRecvError::Disconnected.into()),
-            }
+        app.key_event(&sink, key(KeyCode::Insert));
+        assert!(app.silence.is_some());
+        for _ in 0..3 {
+            app.key_event(&sink, key(KeyCode::Backspace));
         }
+        app.key_event(&sink, key(KeyCode::Char('x')));
+        app.key_event(&sink, key(KeyCode::Enter));
+        assert!(matches!(&app.silence, Some((_text, Some(_error)))));
+        assert_eq!(app.tabs[0].samples.frames(), 4);
 
-        Ok(())
+        app.key_event(&sink, key(KeyCode::Backspace));
+        app.key_event(&sink, key(KeyCode::Char('3')));
+        app.key_event(&sink, key(KeyCode::Enter));
+        assert!(app.silence.is_none());
+        assert_eq!(
+            app.tabs[0].samples.data,
+            vec![0.5, 0.5, 0.0, 0.0, 0.0, 0.5, 0.5]
+        );
+
+        app.key_event(&sink, key(KeyCode::Insert));
+        app.key_event(&sink, key(KeyCode::Esc));
+        assert!(app.silence.is_none());
+        assert!(!app.shutdown);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::util;
-    use crate::util::test::MockView;
-    use crossterm::event::KeyModifiers;
-    use rodio::Sink;
-    use tui::backend::TestBackend;
+    #[test]
+    fn mid_side_actions_edit_stereo_tabs() {
+        let sink = Sink::new_idle().0;
+        let encode = KeyEvent::new(KeyCode::Char('e'), KeyModifiers::ALT);
+        let decode = KeyEvent::new(KeyCode::Char('d'), KeyModifiers::ALT);
+
+        let mut app: App<TestBackend> = App::new(
+            &mut [],
+            Tab::new(Samples::new(2, 8, vec![0.5, 0.25, -0.5, 0.5]), None),
+        );
+        app.key_event(&sink, encode);
+        assert!(app.error.is_ok());
+        assert_eq!(app.tabs[0].samples.data, vec![0.375, 0.125, 0.0, -0.5]);
+        assert!(app.tabs[0].modified);
+        assert!(app.tabs[0].history.can_undo());
+
+        app.key_event(&sink, decode);
+        assert_eq!(app.tabs[0].samples.data, vec![0.5, 0.25, -0.5, 0.5]);
+
+        app.tabs[0] = Tab::new(Samples::new(1, 8, vec![0.5; 4]), None);
+        app.key_event(&sink, encode);
+        assert!(app.error.is_err());
+        assert!(!app.tabs[0].modified);
+    }
 
     #[test]
-    fn handle_view_error() {
+    fn estimate_tempo_until_edit() {
         let sink = Sink::new_idle().0;
-        let backend = TestBackend::new(20, 10);
-        let mut terminal = Terminal::new(backend).unwrap();
+        let estimate = KeyEvent::new(KeyCode::Char('e'), KeyModifiers::CONTROL);
+        // Clicks of a decaying 2 kHz tone every half second make a 120 BPM track.
+        let data = (0..80_000)
+            .map(|frame| {
+                let offset = (frame % 4000) as f32 / 8000.0;
+                (std::f32::consts::TAU * 2000.0 * offset).sin() * (-offset * 200.0).exp()
+            })
+            .collect();
 
-        let mut mock = MockView::new(true);
-        let mut views: Vec<(&str, &mut dyn View<TestBackend>)> = Vec::new();
-        views.push(("", &mut mock));
+        let mut app: App<TestBackend> =
+            App::new(&mut [], Tab::new(Samples::new(1, 8000, data), None));
+        app.key_event(&sink, estimate);
+        assert!(app.error.is_ok());
+        let tempo = app.tabs[0].tempo.unwrap();
+        assert!((tempo - 120.0).abs() < 1.0, "tempo {}", tempo);
 
-        let mut app = App::new(&mut views, Samples::default());
-        app.process();
-        app.render(&mut terminal).unwrap();
+        app.tabs[0]
+            .mix(&Samples::new(1, 8000, vec![0.0; 4]), 1.0)
+            .unwrap();
+        assert_eq!(app.tabs[0].tempo, None);
 
-        let actual = util::test::buffer_view(terminal.backend().buffer());
-        assert!(actual.contains("Error"));
+        app.tabs[0] = Tab::new(Samples::new(1, 8000, vec![0.0; 80_000]), None);
+        app.key_event(&sink, estimate);
+        assert!(app.error.is_err());
+        assert_eq!(app.tabs[0].tempo, None);
+    }
+
+    #[test]
+    fn status_shows_cursor_and_selection() {
+        let mut terminal = Terminal::new(TestBackend::new(120, 10)).unwrap();
+        let mut tab = Tab::new(Samples::new(1, 8000, vec![0.0; 8000]), None);
+        tab.playhead = 4004;
+        let mut app = App::new(&mut [], tab);
 
-        app.key_event(&sink, KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
-        app.process();
         app.render(&mut terminal).unwrap();
+        let actual = util::test::buffer_view(terminal.backend().buffer());
+        assert!(actual.contains("| cursor 4004 00:00:00.500 |"));
+        assert!(!actual.contains("selection"));
 
+        app.tabs[0].selection = Some(Selection::new(1000, 3000));
+        app.render(&mut terminal).unwrap();
         let actual = util::test::buffer_view(terminal.backend().buffer());
-        assert!(!actual.contains("Error"));
+        assert!(actual.contains("| selection 2000 frames 0.250 s |"));
+    }
+
+    #[test]
+    fn scrub_replaces_snippet_on_each_move() {
+        let sink = Sink::new_idle().0;
+        let samples = Samples::new(1, 1000, vec![0.5; 1000]);
+        let mut app: App<TestBackend> =
+            App::new(&mut [], Tab::new(samples, None)).with_scrub(true, 10.0);
+        assert_eq!(app.scrub_milliseconds, 30.0);
+
+        app.scrub(&sink);
+        assert!(sink.empty());
+
+        app.tabs[0].playhead = 500;
+        app.scrub(&sink);
+        assert_eq!(sink.len(), 1);
+        let first = Arc::clone(app.snippet.as_ref().unwrap());
+        app.scrub(&sink);
+        assert_eq!(sink.len(), 1);
+
+        app.tabs[0].playhead = 510;
+        app.scrub(&sink);
+        assert_eq!(sink.len(), 2);
+        assert!(first.load(Ordering::Relaxed));
+        let second = Arc::clone(app.snippet.as_ref().unwrap());
+        assert!(!second.load(Ordering::Relaxed));
+
+        // Playback cancels the snippet, and moves during playback do not play snippets.
+        app.play(&sink);
+        assert_eq!(sink.len(), 3);
+        assert!(second.load(Ordering::Relaxed));
+        app.tabs[0].playhead = 520;
+        app.scrub(&sink);
+        assert_eq!(sink.len(), 3);
+
+        let sink = Sink::new_idle().0;
+        app.action(&sink, Action::ToggleScrub);
+        app.tabs[0].playhead = 530;
+        app.scrub(&sink);
+        assert!(sink.empty());
+    }
+
+    #[test]
+    fn snap_moves_changed_edit_points() {
+        let data = vec![0.5, 0.4, 0.2, -0.1, -0.3, -0.2, 0.3, 0.6];
+        let mut app: App<TestBackend> = App::new(&mut [], Tab::new(Samples::new(1, 8, data), None))
+            .with_zero_crossing_snap(true, 2);
+
+        app.tabs[0].playhead = 1;
+        app.tabs[0].selection = Some(Selection::new(1, 7));
+        app.snap(0, None);
+        assert_eq!(app.tabs[0].playhead, 3);
+        assert_eq!(app.tabs[0].selection, Some(Selection::new(3, 5)));
+
+        // Unchanged edit points stay where they are even if they are not zero crossings.
+        app.tabs[0].selection = Some(Selection::new(6, 7));
+        app.snap(1, Some(Selection::new(6, 7)));
+        assert_eq!(app.tabs[0].playhead, 3);
+        assert_eq!(app.tabs[0].selection, Some(Selection::new(6, 7)));
+
+        app.tabs[0].selection = Some(Selection::new(4, 6));
+        app.snap(3, None);
+        assert_eq!(app.tabs[0].selection, None);
+    }
+
+    #[test]
+    fn nudge_steps_to_zero_crossings() {
+        // The left channel crosses every 10 frames and the right channel holds an offset.
+        let data = (0..40)
+            .flat_map(|frame| [if (frame / 10) % 2 == 0 { 0.5 } else { -0.5 }, 0.75])
+            .collect();
+        let mut app: App<TestBackend> = App::new(&mut [], Tab::new(Samples::new(2, 8, data), None))
+            .with_zero_crossing_snap(false, 16)
+            .with_snap_channel(Some(0));
+        let sink = Sink::new_idle().0;
+
+        app.action(&sink, Action::NextZeroCrossing);
+        assert_eq!(app.tabs[0].playhead, 9);
+        app.action(&sink, Action::NextZeroCrossing);
+        assert_eq!(app.tabs[0].playhead, 10);
+        app.action(&sink, Action::PrevZeroCrossing);
+        assert_eq!(app.tabs[0].playhead, 9);
+
+        // The selection edge away from the playhead moves.
+        app.tabs[0].selection = Some(Selection::new(9, 25));
+        app.action(&sink, Action::PrevZeroCrossing);
+        assert_eq!(app.tabs[0].selection, Some(Selection::new(9, 20)));
+        app.action(&sink, Action::NextZeroCrossing);
+        assert_eq!(app.tabs[0].selection, Some(Selection::new(9, 29)));
+
+        // The summed channels never cross zero, so edit points stay put.
+        app.snap_channel = None;
+        app.tabs[0].selection = None;
+        app.action(&sink, Action::NextZeroCrossing);
+        assert_eq!(app.tabs[0].playhead, 9);
     }
 
     #[test]
@@ -204,13 +2813,39 @@ mod tests {
         let backend = TestBackend::new(20, 10);
         let mut terminal = Terminal::new(backend).unwrap();
 
-        let mut app = App::new(&mut [], Samples::default());
+        let mut app = App::new(&mut [], Tab::default());
         app.render(&mut terminal).unwrap();
 
         let actual = util::test::buffer_view(terminal.backend().buffer());
         assert!(actual.contains("Menu"));
     }
 
+    #[test]
+    fn menu_click_selects_view() {
+        let sink = Sink::new_idle().0;
+        let mut terminal = Terminal::new(TestBackend::new(40, 20)).unwrap();
+
+        let mut mock1 = MockView::default();
+        let mut mock2 = MockView::default();
+        let mut views: Vec<(&str, &mut dyn View<TestBackend>)> = Vec::new();
+        views.push(("A", &mut mock1));
+        views.push(("B", &mut mock2));
+
+        let mut app = App::new(&mut views, Tab::default());
+        app.render(&mut terminal).unwrap();
+
+        // Menu titles start inside the border of the second row of the margined layout.
+        let click = MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column: 7,
+            row: 5,
+            modifiers: KeyModifiers::NONE,
+        };
+        app.mouse_event(&sink, click);
+
+        assert_eq!(app.state, 1);
+    }
+
     #[test]
     fn menu_switch_view() {
         let sink = Sink::new_idle().0;
@@ -224,7 +2859,7 @@ mod tests {
         views.push(("", &mut mock2));
         views.push(("", &mut mock3));
 
-        let mut app = App::new(&mut views, Samples::default());
+        let mut app = App::new(&mut views, Tab::default());
         (0..7).for_each(|_| {
             app.key_event(&sink, KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE));
         });
@@ -232,10 +2867,49 @@ mod tests {
         assert_eq!(1, app.state);
     }
 
+    #[test]
+    fn tabs_keep_separate_samples() {
+        let sink = Sink::new_idle().0;
+        let first = Tab::new(Samples::new(1, 8, vec![0.5]), None);
+        let mut app = App::<TestBackend>::new(&mut [], first);
+
+        app.action(&sink, Action::OpenNewTab);
+        assert_eq!(app.tabs.len(), 2);
+        assert_eq!(app.tab, 1);
+        assert!(app.tabs[app.tab].samples.data.is_empty());
+
+        app.action(&sink, Action::NextTab);
+        assert_eq!(app.tabs[app.tab].samples.data, vec![0.5]);
+
+        app.action(&sink, Action::CloseTab);
+        assert_eq!(app.tabs.len(), 1);
+        assert!(app.tabs[app.tab].samples.data.is_empty());
+
+        app.action(&sink, Action::CloseTab);
+        assert_eq!(app.tabs.len(), 1);
+    }
+
+    #[test]
+    fn select_view_by_label() {
+        let mut mock1 = MockView::default();
+        let mut mock2 = MockView::default();
+
+        let mut views: Vec<(&str, &mut dyn View<TestBackend>)> = Vec::new();
+        views.push(("Chart", &mut mock1));
+        views.push(("File", &mut mock2));
+
+        let mut app = App::new(&mut views, Tab::default());
+        app.select("File");
+        assert_eq!(app.state, 1);
+
+        app.select("Missing");
+        assert_eq!(app.state, 1);
+    }
+
     #[test]
     fn play_and_pause() {
         let sink = Sink::new_idle().0;
-        let app = App::<TestBackend>::new(&mut [], Samples::default());
+        let mut app = App::<TestBackend>::new(&mut [], Tab::default());
 
         app.play(&sink);
         assert!(!sink.empty());
@@ -246,4 +2920,37 @@ mod tests {
         app.play(&sink);
         assert!(!sink.is_paused());
     }
+
+    #[test]
+    fn meters_follow_playback_and_latch_clips() {
+        let (sink, mut output) = Sink::new_idle();
+        let samples = Samples::new(2, 8000, vec![0.25, -1.0, 0.5, 0.125]);
+        let mut app = App::<TestBackend>::new(&mut [], Tab::new(samples, None));
+
+        app.update_meter(&sink);
+        let levels: Vec<f32> = app
+            .meter
+            .channels()
+            .iter()
+            .map(|meter| meter.level)
+            .collect();
+        assert_eq!(levels, vec![0.5, 1.0]);
+        assert!(!app.meter.channels()[1].clipped);
+
+        // Pulling samples from the idle sink's output plays them like an audio device would.
+        app.play(&sink);
+        let played: Vec<f32> = output.by_ref().take(2).collect();
+        assert_eq!(played, vec![0.25, -1.0]);
+        app.meter_levels.1 = Instant::now() - std::time::Duration::from_secs(10);
+        app.update_meter(&sink);
+        let channels = app.meter.channels();
+        assert_eq!(channels[0].level, 0.25);
+        assert_eq!(channels[1].level, 1.0);
+        assert!(channels[1].clipped);
+
+        sink.stop();
+        app.key_event(&sink, KeyEvent::new(KeyCode::Char('c'), KeyModifiers::ALT));
+        app.update_meter(&sink);
+        assert!(!app.meter.channels()[1].clipped);
+    }
 }
@@ -0,0 +1,437 @@
+//! Command line parsers.
+
+use crate::dsp::{self, Samples, Waveform};
+use crate::io::{aiff, audio, path};
+use crate::ui::theme::ThemeName;
+use clap::{AppSettings, Args, Parser, Subcommand, ValueEnum};
+use color_eyre::eyre;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+#[derive(Debug, Parser)]
+#[clap(
+    about = env!("CARGO_PKG_DESCRIPTION"),
+    global_setting = AppSettings::ColorAuto,
+    global_setting = AppSettings::ColoredHelp,
+    version = env!("CARGO_PKG_VERSION"),
+)]
+pub struct Options {
+    /// Command to run instead of the interface
+    #[clap(subcommand)]
+    pub command: Option<Command>,
+    /// Audio file to load or directory to browse
+    #[clap(conflicts_with_all = &["dir", "file"])]
+    pub path: Option<PathBuf>,
+    /// Audio file search directory
+    #[clap(short, long)]
+    pub dir: Option<PathBuf>,
+    /// Audio sample file path
+    #[clap(short, long)]
+    pub file: Option<PathBuf>,
+    /// Audio file to read with --no-tui
+    #[clap(short, long, requires = "no-tui")]
+    pub input: Option<PathBuf>,
+    /// Apply operations from --input to --output and exit without starting the interface
+    #[clap(
+        long,
+        conflicts_with_all = &["dir", "file", "path", "theme", "view"],
+        requires_all = &["input", "output"]
+    )]
+    pub no_tui: bool,
+    /// Operation to apply with --no-tui, repeatable and applied in order, as for batch
+    #[clap(long = "operation", requires = "no-tui", value_name = "OPERATION")]
+    pub operations: Vec<Operation>,
+    /// WAV file to write with --no-tui
+    #[clap(short, long, requires = "no-tui")]
+    pub output: Option<PathBuf>,
+    /// Color theme overriding the configuration file
+    #[clap(long, value_enum)]
+    pub theme: Option<ThemeName>,
+    /// Initial view to display
+    #[clap(long, value_enum)]
+    pub view: Option<ViewName>,
+    /// Playback volume multiplier
+    #[clap(long, default_value_t = 1.0)]
+    pub volume: f32,
+}
+
+impl Options {
+    /// Get the batch processing requested with --no-tui instead of the interface.
+    ///
+    /// Unlike the batch command, no operations are required, which converts the input to WAV.
+    #[must_use]
+    pub fn headless(&self) -> Option<Batch> {
+        match (self.no_tui, &self.input, &self.output) {
+            (true, Some(input), Some(output)) => Some(Batch {
+                input: input.clone(),
+                output: output.clone(),
+                operations: self.operations.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Resolve options into initial application state relative to a working directory.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if a path does not exist or has the wrong file type, or the volume is
+    /// negative.
+    pub fn startup(&self, cwd: &Path) -> eyre::Result<Startup> {
+        if !self.volume.is_finite() || self.volume < 0.0 {
+            eyre::bail!("Volume {} must be a non-negative number", self.volume);
+        }
+
+        let (directory, file) = match &self.path {
+            Some(target) => {
+                let (directory, name) = path::locate(&cwd.join(target))?;
+                let file = name.map(|name| directory.join(name));
+                (directory, file)
+            }
+            None => {
+                let file = match &self.file {
+                    Some(file) if cwd.join(file).is_file() => Some(cwd.join(file)),
+                    Some(file) => eyre::bail!("File {:?} does not exist", file),
+                    None => None,
+                };
+
+                let directory = match &self.dir {
+                    Some(dir) if cwd.join(dir).is_dir() => cwd.join(dir),
+                    Some(dir) => eyre::bail!("Directory {:?} does not exist", dir),
+                    None => cwd.to_owned(),
+                };
+
+                (directory, file)
+            }
+        };
+
+        Ok(Startup {
+            directory,
+            file,
+            theme: self.theme,
+            view: self.view,
+            volume: self.volume,
+        })
+    }
+}
+
+/// Commands that run without the interface.
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Apply operations to an audio file and write the result as WAV without the interface
+    Batch(Batch),
+    /// Convert audio files to WAV or AIFF files with a sample rate and bit depth
+    Convert(Convert),
+    /// Write a generated test tone or noise as WAV without the interface
+    Generate(Generate),
+}
+
+/// Operations to apply to an input file and the file to write the result to.
+#[derive(Debug, Args)]
+pub struct Batch {
+    /// Audio file to read
+    pub input: PathBuf,
+    /// WAV file to write
+    pub output: PathBuf,
+    /// Operations in the order to apply them: normalize[=AMPLITUDE], gain=DECIBELS,
+    /// trim-silence[=DECIBELS], resample=RATE, or to-mono
+    #[clap(required = true)]
+    pub operations: Vec<Operation>,
+}
+
+impl Batch {
+    /// Read the input file, apply the operations in order, and write the output file.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the input is unreadable, an operation fails, or the output is
+    /// unwritable.
+    pub fn run(&self) -> eyre::Result<()> {
+        let mut samples = audio::read_samples(&self.input)?;
+        for operation in &self.operations {
+            operation.apply(&mut samples)?;
+        }
+        audio::write_samples(&self.output, &samples)
+    }
+}
+
+/// Audio files to convert and the format to write them in.
+#[derive(Debug, Args)]
+pub struct Convert {
+    /// Audio files to read, whose names may hold * and ? wildcards, followed by the file to write
+    /// for a single input or the directory to write WAV files with the input names to
+    #[clap(min_values = 2, required = true, value_name = "PATH")]
+    pub paths: Vec<PathBuf>,
+    /// Peak amplitude relative to full scale that --normalize scales to
+    #[clap(long, default_value_t = 1.0, requires = "normalize")]
+    pub amplitude: f32,
+    /// Bits per sample of written files, 16 or 24 for integers or 32 for floats in WAV files
+    #[clap(long, default_value_t = 32)]
+    pub bits: u16,
+    /// Stop at the first file that fails instead of converting the rest
+    #[clap(long)]
+    pub fail_fast: bool,
+    /// Scale so that the loudest sample reaches the amplitude
+    #[clap(long)]
+    pub normalize: bool,
+    /// Sample rate in hertz to convert to
+    #[clap(short, long)]
+    pub rate: Option<u32>,
+}
+
+impl Convert {
+    /// Get the input paths or patterns, which precede the output path.
+    #[must_use]
+    pub fn inputs(&self) -> &[PathBuf] {
+        self.paths
+            .split_last()
+            .map_or(&[], |(_output, inputs)| inputs)
+    }
+
+    /// Get the file or directory to write to, which is the last path.
+    #[must_use]
+    pub fn output(&self) -> &Path {
+        self.paths.last().map_or(Path::new(""), PathBuf::as_path)
+    }
+
+    /// Convert every input file, printing a summary line per file.
+    ///
+    /// Files after a failed one are still converted unless `fail_fast` is set.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if a pattern matches no files, the output directory cannot be created, or
+    /// any file fails to convert.
+    pub fn run(&self) -> eyre::Result<()> {
+        let mut inputs = Vec::new();
+        for pattern in self.inputs() {
+            inputs.extend(path::glob(pattern)?);
+        }
+
+        let directory = inputs.len() > 1 || self.output().is_dir();
+        if directory {
+            fs::create_dir_all(self.output())?;
+        }
+
+        let mut failures = 0;
+        for input in &inputs {
+            let output = if directory {
+                self.output()
+                    .join(input.file_stem().unwrap_or_default())
+                    .with_extension("wav")
+            } else {
+                self.output().to_owned()
+            };
+
+            match self.convert(input, &output) {
+                Ok(samples) => println!(
+                    "{} -> {}: {} ch, {} Hz, {} bit, {:.2} s",
+                    input.display(),
+                    output.display(),
+                    samples.channels,
+                    samples.sample_rate,
+                    self.bits,
+                    samples.frames() as f64 / f64::from(samples.sample_rate.max(1))
+                ),
+                Err(error) => {
+                    failures += 1;
+                    eprintln!("{}: {}", input.display(), error);
+                    if self.fail_fast {
+                        break;
+                    }
+                }
+            }
+        }
+
+        match failures {
+            0 => Ok(()),
+            _ => eyre::bail!("{} of {} files failed to convert", failures, inputs.len()),
+        }
+    }
+
+    /// Read an input file, apply the conversion, and write it to an output file.
+    ///
+    /// The sample rate is changed before normalizing, since resampling moves the peaks.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the input is unreadable, an operation fails, or the output is
+    /// unwritable or cannot hold the bit depth.
+    pub fn convert(&self, input: &Path, output: &Path) -> eyre::Result<Samples> {
+        if !self.amplitude.is_finite() || self.amplitude <= 0.0 {
+            eyre::bail!("Normalize amplitude {} must be above zero", self.amplitude);
+        }
+        let mut samples = audio::read_samples(input)?;
+        let operations = [
+            self.rate.map(Operation::Resample),
+            self.normalize
+                .then_some(Operation::Normalize(self.amplitude)),
+        ];
+        for operation in operations.iter().flatten() {
+            operation.apply(&mut samples)?;
+        }
+
+        let extension = output
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_lowercase);
+        match extension.as_deref() {
+            Some("aif" | "aiff") => aiff::write_aiff(output, &samples, self.bits)?,
+            _ => audio::write_wav(output, &samples, self.bits)?,
+        }
+        Ok(samples)
+    }
+}
+
+/// Signal to synthesize and the file to write it to.
+#[derive(Debug, Args)]
+pub struct Generate {
+    /// Shape of the signal
+    #[clap(value_enum)]
+    pub waveform: Waveform,
+    /// WAV file to write
+    pub output: PathBuf,
+    /// Peak amplitude relative to full scale
+    #[clap(short, long, default_value_t = 0.5)]
+    pub amplitude: f32,
+    /// Number of channels
+    #[clap(short, long, default_value_t = 1)]
+    pub channels: u16,
+    /// Length in seconds
+    #[clap(short, long, default_value_t = 1.0)]
+    pub duration: f32,
+    /// Frequency in hertz of periodic waveforms, or where sweeps end
+    #[clap(short, long, default_value_t = 440.0)]
+    pub frequency: f32,
+    /// Sample rate in hertz
+    #[clap(short = 'r', long, default_value_t = 44_100)]
+    pub sample_rate: u32,
+}
+
+impl Generate {
+    /// Generate the signal and write it to the output file.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the signal parameters are invalid or the output is unwritable.
+    pub fn run(&self) -> eyre::Result<()> {
+        let samples = dsp::generate(
+            self.waveform,
+            self.frequency,
+            self.amplitude,
+            self.duration,
+            self.channels,
+            self.sample_rate,
+        )?;
+        audio::write_samples(&self.output, &samples)
+    }
+}
+
+/// Sample edits available to batch processing.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Operation {
+    /// Scale by a gain in decibels.
+    Gain(f32),
+    /// Scale so that the loudest sample reaches an amplitude.
+    Normalize(f32),
+    /// Convert to a sample rate.
+    Resample(u32),
+    /// Average all channels into one.
+    ToMono,
+    /// Remove leading and trailing frames below a level in decibels.
+    TrimSilence(f32),
+}
+
+impl Operation {
+    /// Apply the operation to samples with the matching function of [`dsp`].
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the underlying function fails.
+    pub fn apply(self, samples: &mut Samples) -> eyre::Result<()> {
+        match self {
+            Self::Gain(decibels) => dsp::gain(decibels, samples),
+            Self::Normalize(amplitude) => dsp::normalize(amplitude, samples),
+            Self::Resample(sample_rate) => dsp::change_sample_rate(sample_rate, samples)?,
+            Self::ToMono => dsp::to_mono(samples),
+            Self::TrimSilence(threshold_db) => dsp::trim_silence(threshold_db, samples),
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Operation {
+    type Err = eyre::Report;
+
+    fn from_str(text: &str) -> eyre::Result<Self> {
+        let (name, value) = match text.split_once('=') {
+            Some((name, value)) => (name, Some(value)),
+            None => (text, None),
+        };
+        let number = |default: Option<f32>| match value.map(str::parse::<f32>) {
+            Some(Ok(number)) if number.is_finite() => Ok(number),
+            Some(_) => Err(eyre::eyre!(
+                "Operation {} has invalid value {:?}",
+                name,
+                text
+            )),
+            None => default
+                .ok_or_else(|| eyre::eyre!("Operation {} needs a value such as {}=-3", name, name)),
+        };
+
+        match name {
+            "gain" => Ok(Self::Gain(number(None)?)),
+            "normalize" => match number(Some(1.0))? {
+                amplitude if amplitude > 0.0 => Ok(Self::Normalize(amplitude)),
+                amplitude => eyre::bail!("Normalize amplitude {} must be above zero", amplitude),
+            },
+            "resample" => match value.map(str::parse::<u32>) {
+                Some(Ok(sample_rate)) if sample_rate > 0 => Ok(Self::Resample(sample_rate)),
+                _ => eyre::bail!("Operation resample needs a sample rate such as resample=44100"),
+            },
+            "to-mono" if value.is_none() => Ok(Self::ToMono),
+            "to-mono" => eyre::bail!("Operation to-mono takes no value"),
+            "trim-silence" => Ok(Self::TrimSilence(number(Some(-60.0))?)),
+            _ => eyre::bail!(
+                "Operation {:?} is not one of gain, normalize, resample, to-mono, or trim-silence",
+                name
+            ),
+        }
+    }
+}
+
+/// Initial application state requested from the command line.
+#[derive(Debug, PartialEq)]
+pub struct Startup {
+    /// Directory for the file browser to start in.
+    pub directory: PathBuf,
+    /// Audio file to load into the first tab.
+    pub file: Option<PathBuf>,
+    /// Color theme to use instead of the configured one.
+    pub theme: Option<ThemeName>,
+    /// View to display first.
+    pub view: Option<ViewName>,
+    /// Playback volume multiplier.
+    pub volume: f32,
+}
+
+/// Views that can be selected from the command line.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+pub enum ViewName {
+    Chart,
+    File,
+    Filters,
+}
+
+impl ViewName {
+    /// Get menu label of the view.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Chart => "Chart",
+            Self::File => "File",
+            Self::Filters => "Filters",
+        }
+    }
+}
@@ -0,0 +1,263 @@
+//! Persistent user configuration.
+
+use crate::io::csv::IndexColumn;
+use crate::ui::theme::ThemeName;
+use color_eyre::eyre;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// User settings stored as TOML in the platform configuration directory.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct Config {
+    pub bookmarks: Vec<PathBuf>,
+    /// Megabytes of decoded audio kept in memory for reopening files, if not the default.
+    pub cache_megabytes: Option<usize>,
+    /// Magnitude at which level statistics count samples as clipped, if not the default.
+    pub clip_threshold: Option<f32>,
+    /// Theme colors overriding those of the built in theme.
+    pub colors: BTreeMap<String, String>,
+    /// Leading column of exported CSV files.
+    pub csv_index: IndexColumn,
+    /// Level in decibels at the bottom of the chart's decibel scale, if not the default.
+    pub decibel_floor: Option<f32>,
+    /// Key bindings as action names mapped to keys such as `ctrl+t`.
+    pub keys: BTreeMap<String, String>,
+    /// Fall of the level meters in decibels per second, if not the default.
+    pub meter_decay: Option<f32>,
+    /// Seconds that level meter peak holds stay before falling, if not the default.
+    pub meter_hold: Option<f32>,
+    pub recent_files: RecentFiles,
+    /// Milliseconds kept before the first loud frame of exported regions, if not the default.
+    pub region_attack_milliseconds: Option<f32>,
+    /// Milliseconds of silence that separate exported regions, if not the default.
+    pub region_hold_milliseconds: Option<f32>,
+    /// Milliseconds kept after the last loud frame of exported regions, if not the default.
+    pub region_release_milliseconds: Option<f32>,
+    /// Level in decibels below which exported regions count frames as silent, if not the default.
+    pub region_threshold_db: Option<f32>,
+    /// Channel count of recordings, if not the default of the input device.
+    pub record_channels: Option<u16>,
+    /// Sample rate of recordings in hertz, if not the default of the input device.
+    pub record_sample_rate: Option<u32>,
+    /// Whether moving the playhead plays a short snippet around it.
+    pub scrub: bool,
+    /// Length in milliseconds of snippets played while scrubbing, if not the default.
+    pub scrub_milliseconds: Option<f32>,
+    /// Channel counted from zero whose zero crossings edit points snap to, instead of the sum of
+    /// all channels.
+    pub snap_channel: Option<u16>,
+    /// Frames searched in each direction for a zero crossing, if not the default.
+    pub snap_search_frames: Option<usize>,
+    /// Whether edit points snap to the nearest zero crossing.
+    pub snap_to_zero_crossing: bool,
+    /// Name of the built in color theme.
+    pub theme: ThemeName,
+}
+
+impl Config {
+    /// Get the default configuration file location, if the platform has one.
+    #[must_use]
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|directory| directory.join("sampitor").join("config.toml"))
+    }
+
+    /// Read a configuration file, falling back to defaults if it does not exist.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `path` is unreadable or contains invalid TOML.
+    pub fn read(path: &Path) -> eyre::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(text) => {
+                let mut config: Self = toml::from_str(&text).map_err(|error| {
+                    eyre::eyre!("Configuration file {:?} is invalid: {}", path, error)
+                })?;
+                config.recent_files.prune();
+                Ok(config)
+            }
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Remove bookmarks of directories that no longer exist and return them.
+    pub fn prune_bookmarks(&mut self) -> Vec<PathBuf> {
+        let (kept, missing) = self.bookmarks.drain(..).partition(|path| path.is_dir());
+        self.bookmarks = kept;
+        missing
+    }
+
+    /// Write configuration to a file, creating its parent directories if necessary.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `path` is unwritable.
+    pub fn write(&self, path: &Path) -> eyre::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        // Conversion to a TOML value first places plain values ahead of tables, as TOML requires.
+        fs::write(path, toml::to_string(&toml::Value::try_from(self)?)?)?;
+        Ok(())
+    }
+}
+
+/// Most recently opened audio files, newest first.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct RecentFiles {
+    /// Maximum number of remembered files.
+    pub limit: usize,
+    pub paths: Vec<PathBuf>,
+}
+
+impl RecentFiles {
+    /// Move a file to the front of the list, dropping the oldest files beyond the limit.
+    pub fn push(&mut self, path: PathBuf) {
+        self.paths.retain(|recent| recent != &path);
+        self.paths.insert(0, path);
+        self.paths.truncate(self.limit);
+    }
+
+    /// Remove duplicate and no longer existing files.
+    pub fn prune(&mut self) {
+        let mut seen = Vec::with_capacity(self.paths.len());
+
+        self.paths.retain(|path| {
+            if path.is_file() && !seen.contains(path) {
+                seen.push(path.clone());
+                true
+            } else {
+                false
+            }
+        });
+        self.paths.truncate(self.limit);
+    }
+}
+
+impl Default for RecentFiles {
+    fn default() -> Self {
+        Self {
+            limit: 10,
+            paths: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_corrupt_file() {
+        let folder = tempfile::tempdir().unwrap();
+        let path = folder.path().join("config.toml");
+        fs::write(&path, "bookmarks = [").unwrap();
+
+        assert!(Config::read(&path).is_err());
+    }
+
+    #[test]
+    fn read_missing_file() {
+        let folder = tempfile::tempdir().unwrap();
+        let path = folder.path().join("config.toml");
+
+        assert_eq!(Config::read(&path).unwrap(), Config::default());
+    }
+
+    #[test]
+    fn read_prunes_recent_files() {
+        let folder = tempfile::tempdir().unwrap();
+        let path = folder.path().join("config.toml");
+        let sample = folder.path().join("kick.wav");
+        fs::File::create(&sample).unwrap();
+
+        let config = Config {
+            recent_files: RecentFiles {
+                limit: 10,
+                paths: vec![
+                    sample.clone(),
+                    folder.path().join("gone.wav"),
+                    sample.clone(),
+                ],
+            },
+            ..Config::default()
+        };
+        config.write(&path).unwrap();
+
+        let actual = Config::read(&path).unwrap().recent_files.paths;
+        assert_eq!(actual, vec![sample]);
+    }
+
+    #[test]
+    fn prune_bookmarks_returns_missing() {
+        let folder = tempfile::tempdir().unwrap();
+        let gone = folder.path().join("gone");
+        let mut config = Config {
+            bookmarks: vec![gone.clone(), folder.path().to_owned()],
+            ..Config::default()
+        };
+
+        assert_eq!(config.prune_bookmarks(), vec![gone]);
+        assert_eq!(config.bookmarks, vec![folder.path().to_owned()]);
+        assert!(config.prune_bookmarks().is_empty());
+    }
+
+    #[test]
+    fn recent_files_push() {
+        let mut recent = RecentFiles {
+            limit: 2,
+            paths: Vec::new(),
+        };
+        for name in ["a.wav", "b.wav", "a.wav", "c.wav"] {
+            recent.push(PathBuf::from(name));
+            assert!(recent.paths.len() <= recent.limit);
+        }
+
+        let expected = vec![PathBuf::from("c.wav"), PathBuf::from("a.wav")];
+        assert_eq!(recent.paths, expected);
+
+        // Files already in the list move to the front instead of appearing twice.
+        recent.push(PathBuf::from("a.wav"));
+        let expected = vec![PathBuf::from("a.wav"), PathBuf::from("c.wav")];
+        assert_eq!(recent.paths, expected);
+    }
+
+    #[test]
+    fn write_and_read() {
+        let folder = tempfile::tempdir().unwrap();
+        let path = folder.path().join("sampitor").join("config.toml");
+        let expected = Config {
+            bookmarks: vec![PathBuf::from("/samples"), PathBuf::from("/recordings")],
+            cache_megabytes: Some(64),
+            clip_threshold: Some(0.99),
+            colors: [(String::from("waveform"), String::from("#ff8800"))].into(),
+            csv_index: IndexColumn::Time,
+            decibel_floor: Some(-48.0),
+            keys: [(String::from("quit"), String::from("ctrl+q"))].into(),
+            meter_decay: Some(12.0),
+            meter_hold: Some(3.0),
+            recent_files: RecentFiles::default(),
+            region_attack_milliseconds: Some(5.0),
+            region_hold_milliseconds: Some(500.0),
+            region_release_milliseconds: Some(200.0),
+            region_threshold_db: Some(-40.0),
+            record_channels: Some(1),
+            record_sample_rate: Some(48_000),
+            scrub: true,
+            scrub_milliseconds: Some(40.0),
+            snap_channel: Some(1),
+            snap_search_frames: Some(256),
+            snap_to_zero_crossing: true,
+            theme: ThemeName::Light,
+        };
+
+        expected.write(&path).unwrap();
+        assert_eq!(Config::read(&path).unwrap(), expected);
+    }
+}
@@ -0,0 +1,886 @@
+//! Measurements of audio signals.
+
+use crate::dsp::buffer::{Samples, Selection};
+
+/// Duration in seconds of each step of the onset detection function.
+const ONSET_HOP: f64 = 0.002;
+/// Weights of the moving average applied to the onset detection function.
+const ONSET_SMOOTHING: [f64; 5] = [1.0, 2.0, 3.0, 2.0, 1.0];
+/// Range of tempos in beats per minute that are detected.
+const TEMPO_RANGE: (f64, f64) = (60.0, 200.0);
+/// Fraction of the strongest periodicity that a tempo at a whole multiple needs to be preferred.
+const TEMPO_MULTIPLE: f64 = 0.8;
+/// Largest number of clipped frame positions that level statistics record.
+const CLIP_POSITIONS: usize = 100;
+/// Duration in seconds of the gating blocks of loudness measurements.
+const LOUDNESS_BLOCK: f64 = 0.4;
+/// Fraction of each gating block that the next one overlaps.
+const LOUDNESS_OVERLAP: f64 = 0.75;
+/// Loudness in LUFS below which gating blocks are always ignored.
+const LOUDNESS_ABSOLUTE_GATE: f64 = -70.0;
+/// Loudness in LU below the ungated loudness at which gating blocks are ignored.
+const LOUDNESS_RELATIVE_GATE: f64 = -10.0;
+
+/// Level measurements of one channel.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ChannelStats {
+    /// Number of samples whose magnitude reaches the clipping threshold.
+    pub clipped: usize,
+    /// Difference between the peak and RMS levels in decibels.
+    pub crest_factor_db: f32,
+    /// Mean of the samples.
+    pub dc_offset: f32,
+    /// Largest magnitude in decibels relative to full scale.
+    pub peak_db: f32,
+    /// Root mean square level in decibels relative to full scale.
+    pub rms_db: f32,
+}
+
+/// Level measurements of all channels of samples.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Stats {
+    pub channels: Vec<ChannelStats>,
+    /// Indices of the first frames with a clipped sample in any channel.
+    pub clipped_frames: Vec<usize>,
+    /// Correlation of the first two channels from -1 for opposite polarity to 1 for the same
+    /// signal, if there are at least two channels and neither is silent.
+    pub correlation: Option<f32>,
+    /// Integrated loudness in LUFS as measured by [`integrated_lufs`].
+    pub integrated_lufs: f64,
+}
+
+/// Measure peak, RMS, crest factor, DC offset, and clipping of each channel and the correlation of
+/// the first two channels in a single pass, and the integrated loudness of all channels.
+///
+/// Samples count as clipped if their magnitude is at least `clip_threshold`, and the positions of
+/// the first hundred clipped frames are kept. Silent channels have levels of negative infinity
+/// and a crest factor of zero instead of NaN.
+#[must_use]
+pub fn level_stats(samples: &Samples, clip_threshold: f32) -> Stats {
+    let width = usize::from(samples.channels.max(1));
+    let mut peaks = vec![0.0_f32; width];
+    let mut sums = vec![0.0_f64; width];
+    let mut squares = vec![0.0_f64; width];
+    let mut clipped = vec![0_usize; width];
+    let mut clipped_frames = Vec::new();
+    let mut products = 0.0_f64;
+
+    for (index, frame) in samples.iter_frames().enumerate() {
+        if let [left, right, ..] = frame {
+            products += f64::from(*left) * f64::from(*right);
+        }
+        let mut clips = false;
+        for (channel, sample) in frame.iter().enumerate() {
+            let magnitude = sample.abs();
+            peaks[channel] = peaks[channel].max(magnitude);
+            sums[channel] += f64::from(*sample);
+            squares[channel] += f64::from(*sample) * f64::from(*sample);
+            if magnitude >= clip_threshold {
+                clipped[channel] += 1;
+                clips = true;
+            }
+        }
+        if clips && clipped_frames.len() < CLIP_POSITIONS {
+            clipped_frames.push(index);
+        }
+    }
+
+    let frames = (samples.data.len() / width).max(1) as f64;
+    let decibels = |level: f64| (20.0 * level.log10()) as f32;
+    let channels = (0..width)
+        .map(|channel| {
+            let peak = f64::from(peaks[channel]);
+            let rms = (squares[channel] / frames).sqrt();
+            ChannelStats {
+                clipped: clipped[channel],
+                crest_factor_db: if rms > 0.0 { decibels(peak / rms) } else { 0.0 },
+                // Adding positive zero keeps empty channels from reporting negative zero.
+                dc_offset: (sums[channel] / frames + 0.0) as f32,
+                peak_db: decibels(peak),
+                rms_db: decibels(rms),
+            }
+        })
+        .collect();
+
+    let correlation = match squares.get(..2) {
+        Some([left, right]) if *left > 0.0 && *right > 0.0 => {
+            Some((products / (left * right).sqrt()).clamp(-1.0, 1.0) as f32)
+        }
+        _ => None,
+    };
+
+    Stats {
+        channels,
+        clipped_frames,
+        correlation,
+        integrated_lufs: integrated_lufs(samples),
+    }
+}
+
+/// Measure integrated loudness in LUFS following ITU-R BS.1770-4.
+///
+/// Channels are K-weighted and their mean squares over 400 millisecond blocks, overlapping by
+/// 75 percent, are summed with the weights of the standard, which count the surround channels of
+/// 5.1 audio more and leave out their LFE channel. Blocks below -70 LUFS, and then blocks more
+/// than 10 LU below the loudness of the remaining ones, are ignored. Samples that are silent or
+/// shorter than a block have a loudness of negative infinity.
+#[must_use]
+pub fn integrated_lufs(samples: &Samples) -> f64 {
+    let width = usize::from(samples.channels.max(1));
+    let rate = f64::from(samples.sample_rate.max(1));
+    let filtered = k_weight(samples);
+
+    // Blocks are rounded to whole frames, so truncation is intended.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let block = (LOUDNESS_BLOCK * rate).round() as usize;
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let step = ((1.0 - LOUDNESS_OVERLAP) * LOUDNESS_BLOCK * rate).round() as usize;
+    let frames = filtered.len() / width;
+    if block == 0 || frames < block {
+        return f64::NEG_INFINITY;
+    }
+
+    let weights: Vec<f64> = (0..width)
+        .map(|channel| match (width, channel) {
+            (6, 3) => 0.0,
+            (6, 4 | 5) => 1.41,
+            _ => 1.0,
+        })
+        .collect();
+    let powers: Vec<Vec<f64>> = (0..=(frames - block) / step.max(1))
+        .map(|index| {
+            let start = index * step.max(1);
+            let mut power = vec![0.0; width];
+            for frame in filtered[start * width..(start + block) * width].chunks_exact(width) {
+                for (channel, sample) in frame.iter().enumerate() {
+                    power[channel] += sample * sample;
+                }
+            }
+            power.iter().map(|sum| sum / block as f64).collect()
+        })
+        .collect();
+
+    let loudness = |blocks: &[&Vec<f64>]| {
+        let count = blocks.len().max(1) as f64;
+        let sum: f64 = weights
+            .iter()
+            .enumerate()
+            .map(|(channel, weight)| {
+                weight * blocks.iter().map(|power| power[channel]).sum::<f64>() / count
+            })
+            .sum();
+        if blocks.is_empty() || sum <= 0.0 {
+            f64::NEG_INFINITY
+        } else {
+            -0.691 + 10.0 * sum.log10()
+        }
+    };
+
+    let absolute: Vec<&Vec<f64>> = powers
+        .iter()
+        .filter(|power| loudness(&[*power]) > LOUDNESS_ABSOLUTE_GATE)
+        .collect();
+    let threshold = loudness(&absolute) + LOUDNESS_RELATIVE_GATE;
+    let relative: Vec<&Vec<f64>> = absolute
+        .into_iter()
+        .filter(|power| loudness(&[*power]) > threshold)
+        .collect();
+    loudness(&relative)
+}
+
+/// Apply the K-weighting filter of ITU-R BS.1770 to every channel.
+///
+/// The high shelf and high pass stages are designed for the sample rate with the bilinear
+/// transform, which matches the coefficients the standard lists for 48 kHz.
+fn k_weight(samples: &Samples) -> Vec<f64> {
+    let width = usize::from(samples.channels.max(1));
+    let rate = f64::from(samples.sample_rate.max(1));
+
+    let k = (std::f64::consts::PI * 1_681.974_450_955_533 / rate).tan();
+    let q = 0.707_175_236_955_419_6;
+    let high = 10_f64.powf(3.999_843_853_973_347 / 20.0);
+    let band = high.powf(0.499_666_774_154_541_6);
+    let norm = 1.0 + k / q + k * k;
+    let shelf = (
+        [
+            (high + band * k / q + k * k) / norm,
+            2.0 * (k * k - high) / norm,
+            (high - band * k / q + k * k) / norm,
+        ],
+        [2.0 * (k * k - 1.0) / norm, (1.0 - k / q + k * k) / norm],
+    );
+
+    let k = (std::f64::consts::PI * 38.135_470_876_024_44 / rate).tan();
+    let q = 0.500_327_037_323_877_3;
+    let norm = 1.0 + k / q + k * k;
+    let pass = (
+        [1.0, -2.0, 1.0],
+        [2.0 * (k * k - 1.0) / norm, (1.0 - k / q + k * k) / norm],
+    );
+
+    // Each channel keeps the last two inputs and outputs of both stages.
+    let mut states = vec![[[0.0_f64; 4]; 2]; width];
+    let mut output = Vec::with_capacity(samples.data.len());
+    for frame in samples.iter_frames() {
+        for (sample, stages) in frame.iter().zip(&mut states) {
+            let mut x = f64::from(*sample);
+            for ((b, a), [x1, x2, y1, y2]) in [shelf, pass].iter().zip(stages.iter_mut()) {
+                let y = b[0] * x + b[1] * *x1 + b[2] * *x2 - a[0] * *y1 - a[1] * *y2;
+                *x2 = *x1;
+                *x1 = x;
+                *y2 = *y1;
+                *y1 = y;
+                x = y;
+            }
+            output.push(x);
+        }
+    }
+    output
+}
+
+/// Group frames with a clipped sample in any channel into regions.
+///
+/// Samples count as clipped if their magnitude is at least `clip_threshold`. Clipped frames
+/// separated by fewer than `min_gap` unclipped frames belong to the same region, so a burst of
+/// overs yields one region instead of one per sample. Adjacent clipped frames always share a
+/// region, which spans from its first to past its last clipped frame.
+#[must_use]
+pub fn clip_regions(samples: &Samples, clip_threshold: f32, min_gap: usize) -> Vec<Selection> {
+    let mut regions: Vec<Selection> = Vec::new();
+
+    for (index, frame) in samples.iter_frames().enumerate() {
+        if !frame.iter().any(|sample| sample.abs() >= clip_threshold) {
+            continue;
+        }
+        match regions.last_mut() {
+            Some(region) if index - region.end < min_gap.max(1) => region.end = index + 1,
+            _ => regions.push(Selection::new(index, index + 1)),
+        }
+    }
+
+    regions
+}
+
+/// Find regions of sound separated by silences quieter than a threshold in decibels.
+///
+/// A frame is loud when any channel reaches the threshold. Loud frames separated by fewer than
+/// `hold_frames` quiet frames share a region, so brief dips within a sustained note do not split
+/// it. Regions span from their first to past their last loud frame, widened by `attack_frames`
+/// before and `release_frames` after to keep onsets and decays, without reaching past the
+/// samples or into neighboring regions. NaN thresholds find no regions.
+#[must_use]
+pub fn sound_regions(
+    samples: &Samples,
+    threshold_db: f32,
+    hold_frames: usize,
+    attack_frames: usize,
+    release_frames: usize,
+) -> Vec<Selection> {
+    if threshold_db.is_nan() {
+        return Vec::new();
+    }
+
+    let threshold = 10.0_f32.powf(threshold_db / 20.0);
+    let mut regions: Vec<Selection> = Vec::new();
+    for (index, frame) in samples.iter_frames().enumerate() {
+        if !frame.iter().any(|sample| sample.abs() >= threshold) {
+            continue;
+        }
+        match regions.last_mut() {
+            Some(region) if index - region.end < hold_frames.max(1) => region.end = index + 1,
+            _ => regions.push(Selection::new(index, index + 1)),
+        }
+    }
+
+    let frames = samples.frames();
+    let mut previous = 0;
+    (0..regions.len())
+        .map(|index| {
+            let next = regions.get(index + 1).map_or(frames, |region| region.start);
+            let region = regions[index];
+            let start = region.start.saturating_sub(attack_frames).max(previous);
+            let end = (region.end + release_frames).min(next);
+            previous = end;
+            Selection::new(start, end)
+        })
+        .collect()
+}
+
+/// Measure the DC offset of each channel of samples as the mean of its samples.
+///
+/// Channels without samples have no offset, so empty samples never produce NaN.
+#[must_use]
+pub fn dc_offset(samples: &Samples) -> Vec<f32> {
+    let width = usize::from(samples.channels.max(1));
+    let frames = samples.data.len() / width;
+
+    // Summing from positive zero keeps empty channels from reporting negative zero.
+    let mut sums = vec![0.0_f64; width];
+    for frame in samples.iter_frames() {
+        for (sum, sample) in sums.iter_mut().zip(frame) {
+            *sum += f64::from(*sample);
+        }
+    }
+    sums.into_iter()
+        .map(|sum| (sum / frames.max(1) as f64) as f32)
+        .collect()
+}
+
+/// Estimate the tempo of samples in beats per minute from the periodicity of their transients.
+///
+/// The onset detection function is the rise in energy of the first difference of the summed
+/// channels, which emphasizes high frequencies, over short steps. Its autocorrelation is searched
+/// for the strongest beat period between 60 and 200 beats per minute. Faster tempos are preferred
+/// over nearly as strong tempos at a whole fraction of them to avoid octave errors. Returns
+/// `None` if the samples are too short to hold two beats of the slowest tempo or have no periodic
+/// transients.
+#[must_use]
+pub fn estimate_bpm(samples: &Samples) -> Option<f32> {
+    let rate = f64::from(samples.sample_rate.max(1));
+    // Hop sizes are positive, so sign loss is not possible.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let hop = (rate * ONSET_HOP).round().max(1.0) as usize;
+
+    let mono: Vec<f32> = samples
+        .iter_frames()
+        .map(|frame| frame.iter().sum())
+        .collect();
+    let energies: Vec<f64> = mono
+        .windows(2)
+        .map(|pair| f64::from(pair[1] - pair[0]).powi(2))
+        .collect::<Vec<f64>>()
+        .chunks(hop)
+        .map(|chunk| chunk.iter().sum())
+        .collect();
+    let rises: Vec<f64> = energies
+        .windows(2)
+        .map(|pair| (pair[1] - pair[0]).max(0.0))
+        .collect();
+    // Smoothing widens onset peaks so that beat periods between two lags still line up.
+    let mut onsets: Vec<f64> = rises
+        .windows(ONSET_SMOOTHING.len())
+        .map(|window| window.iter().zip(ONSET_SMOOTHING).map(|(x, y)| x * y).sum())
+        .collect();
+    let mean = onsets.iter().sum::<f64>() / onsets.len().max(1) as f64;
+    onsets.iter_mut().for_each(|onset| *onset -= mean);
+
+    let lag = |bpm: f64| 60.0 * rate / hop as f64 / bpm;
+    // Lags are positive, so sign loss is not possible.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let (shortest, longest) = (
+        lag(TEMPO_RANGE.1).floor().max(1.0) as usize,
+        lag(TEMPO_RANGE.0).ceil() as usize,
+    );
+    if onsets.len() < 2 * longest {
+        return None;
+    }
+
+    let correlation: Vec<f64> = (0..=longest + 1)
+        .map(|lag| {
+            let sum: f64 = onsets.iter().zip(&onsets[lag..]).map(|(x, y)| x * y).sum();
+            sum / (onsets.len() - lag) as f64
+        })
+        .collect();
+    let strongest =
+        (shortest..=longest).max_by(|x, y| correlation[*x].total_cmp(&correlation[*y]))?;
+    if correlation[strongest] <= 0.0 {
+        return None;
+    }
+
+    // Beats also line up at whole multiples of their period, so the fastest strong tempo wins.
+    let threshold = TEMPO_MULTIPLE * correlation[strongest];
+    let best = (1..=strongest / shortest)
+        .rev()
+        .find_map(|multiple| {
+            // Lags are positive, so sign loss is not possible.
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let lag = (strongest as f64 / multiple as f64).round() as usize;
+            (lag.saturating_sub(1).max(shortest)..=lag + 1)
+                .max_by(|x, y| correlation[*x].total_cmp(&correlation[*y]))
+                .filter(|lag| correlation[*lag] >= threshold)
+        })
+        .unwrap_or(strongest);
+
+    // Refine the period between lags by fitting a parabola through the peak and its neighbors.
+    let (before, peak, after) = (
+        correlation[best - 1],
+        correlation[best],
+        correlation[best + 1],
+    );
+    let curvature = before - 2.0 * peak + after;
+    let offset = if curvature < 0.0 {
+        (0.5 * (before - after) / curvature).clamp(-0.5, 0.5)
+    } else {
+        0.0
+    };
+
+    let period = (best as f64 + offset) * hop as f64 / rate;
+    Some((60.0 / period) as f32)
+}
+
+/// Find the zero crossing nearest to a frame within a number of frames in either direction.
+///
+/// Channels of interleaved data are summed so that a crossing is shared by all channels. The
+/// frame of a sign change with the smaller magnitude is returned, preferring later frames when
+/// two crossings are equally near. Returns `None` if the start frame is out of bounds or there
+/// is no crossing within the search window.
+#[must_use]
+pub fn find_zero_crossing(
+    data: &[f32],
+    channels: u16,
+    start_frame: usize,
+    search_frames: usize,
+) -> Option<usize> {
+    find_zero_crossing_on(data, channels, None, start_frame, search_frames)
+}
+
+/// Find the zero crossing nearest to a frame like [`find_zero_crossing`], but only on the
+/// reference channel if one is given and it exists.
+#[must_use]
+pub fn find_zero_crossing_on(
+    data: &[f32],
+    channels: u16,
+    reference: Option<u16>,
+    start_frame: usize,
+    search_frames: usize,
+) -> Option<usize> {
+    let crossings = Crossings::new(data, channels, reference);
+    if start_frame >= crossings.frames {
+        return None;
+    }
+
+    (0..=search_frames).find_map(|distance| {
+        let forward = start_frame
+            .checked_add(distance)
+            .filter(|frame| *frame < crossings.frames && crossings.contains(*frame));
+        let backward = start_frame
+            .checked_sub(distance)
+            .filter(|frame| crossings.contains(*frame));
+        forward.or(backward)
+    })
+}
+
+/// Find the first zero crossing after or before a frame within a number of frames.
+///
+/// Crossings are chosen like in [`find_zero_crossing_on`]. The start frame itself is skipped, so
+/// repeated calls step from one crossing to the next. Returns `None` if the start frame is out of
+/// bounds or there is no crossing within the search window, such as for signals with a DC offset
+/// larger than their swing.
+#[must_use]
+pub fn step_zero_crossing(
+    data: &[f32],
+    channels: u16,
+    reference: Option<u16>,
+    start_frame: usize,
+    forward: bool,
+    search_frames: usize,
+) -> Option<usize> {
+    let crossings = Crossings::new(data, channels, reference);
+    if start_frame >= crossings.frames {
+        return None;
+    }
+
+    (1..=search_frames).find_map(|distance| {
+        let frame = if forward {
+            start_frame
+                .checked_add(distance)
+                .filter(|frame| *frame < crossings.frames)
+        } else {
+            start_frame.checked_sub(distance)
+        };
+        frame.filter(|frame| crossings.contains(*frame))
+    })
+}
+
+/// Sign changes of interleaved data on one channel or the sum of all channels.
+struct Crossings<'a> {
+    data: &'a [f32],
+    frames: usize,
+    reference: Option<usize>,
+    width: usize,
+}
+
+impl<'a> Crossings<'a> {
+    /// Create Crossings of a reference channel, or of the summed channels if it does not exist.
+    fn new(data: &'a [f32], channels: u16, reference: Option<u16>) -> Self {
+        let width = usize::from(channels.max(1));
+        Self {
+            data,
+            frames: data.len() / width,
+            reference: reference
+                .map(usize::from)
+                .filter(|channel| *channel < width),
+            width,
+        }
+    }
+
+    /// Get the value of a frame whose sign changes are searched.
+    fn value(&self, frame: usize) -> f32 {
+        match self.reference {
+            Some(channel) => self.data[frame * self.width + channel],
+            None => self.data[frame * self.width..(frame + 1) * self.width]
+                .iter()
+                .sum(),
+        }
+    }
+
+    /// Check whether a frame is zero or the smaller magnitude of a sign change with a neighbor.
+    fn contains(&self, frame: usize) -> bool {
+        let crosses = |neighbor: usize| {
+            let (sample, other) = (self.value(frame), self.value(neighbor));
+            (sample < 0.0) != (other < 0.0) && sample.abs() <= other.abs()
+        };
+
+        self.value(frame) == 0.0
+            || (frame + 1 < self.frames && crosses(frame + 1))
+            || (frame > 0 && crosses(frame - 1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create a sine tone with the same samples in every channel.
+    fn tone(frequency: f64, amplitude: f64, seconds: f64, channels: u16, rate: u32) -> Samples {
+        let frames = (seconds * f64::from(rate)) as usize;
+        let data = (0..frames)
+            .flat_map(|index| {
+                let phase = std::f64::consts::TAU * frequency * index as f64 / f64::from(rate);
+                vec![(amplitude * phase.sin()) as f32; usize::from(channels)]
+            })
+            .collect();
+        Samples::new(channels, rate, data)
+    }
+
+    #[test]
+    fn integrated_lufs_of_tones() {
+        // A full scale 997 Hz sine in one channel measures -3.01 LUFS by the definition of the
+        // standard, and K-weighting barely changes its level.
+        let mono = integrated_lufs(&tone(997.0, 1.0, 2.0, 1, 48_000));
+        assert!((mono + 3.01).abs() < 0.05, "{}", mono);
+
+        let stereo = integrated_lufs(&tone(997.0, 0.1, 2.0, 2, 44_100));
+        assert!((stereo + 20.0).abs() < 0.05, "{}", stereo);
+
+        // The LFE channel of 5.1 audio is left out and surround channels weigh 1.41.
+        let mut surround = tone(997.0, 0.1, 2.0, 6, 48_000);
+        for frame in surround.data.chunks_exact_mut(6) {
+            frame[..4].fill(0.0);
+        }
+        let expected = -23.01 + 10.0 * (2.0 * 1.41_f64).log10();
+        let actual = integrated_lufs(&surround);
+        assert!((actual - expected).abs() < 0.05, "{}", actual);
+
+        // Low frequencies are attenuated by the high pass stage.
+        assert!(integrated_lufs(&tone(20.0, 1.0, 2.0, 1, 48_000)) < -10.0);
+    }
+
+    #[test]
+    fn integrated_lufs_gates_quiet_blocks() {
+        let loud = tone(997.0, 0.1, 2.0, 2, 48_000);
+        let mut gated = loud.clone();
+        gated.data.extend(vec![0.0; 4 * 48_000]);
+        gated.data.extend(tone(997.0, 0.001, 2.0, 2, 48_000).data);
+
+        // Without gating the silence and the quiet tone would lower the loudness by about 6 LU, and
+        // only the blocks that partly overlap the loud tone still count.
+        let expected = integrated_lufs(&loud);
+        let actual = integrated_lufs(&gated);
+        assert!(
+            (actual - expected).abs() < 0.5,
+            "{} != {}",
+            actual,
+            expected
+        );
+
+        assert_eq!(
+            integrated_lufs(&Samples::new(2, 48_000, vec![0.0; 96_000])),
+            f64::NEG_INFINITY
+        );
+        assert_eq!(
+            integrated_lufs(&tone(997.0, 0.5, 0.2, 1, 48_000)),
+            f64::NEG_INFINITY
+        );
+    }
+
+    /// Generate decaying high pitched clicks on every beat of a tempo.
+    fn click_track(bpm: f64, sample_rate: u32, seconds: f64) -> Samples {
+        let rate = f64::from(sample_rate);
+        let beat = (60.0 / bpm * rate) as usize;
+        let length = (seconds * rate) as usize;
+        let data = (0..length)
+            .map(|frame| {
+                let offset = (frame % beat) as f64 / rate;
+                (2.0 * std::f64::consts::PI * 2000.0 * offset).sin() as f32
+                    * (-offset * 200.0).exp() as f32
+            })
+            .collect();
+        Samples::new(1, sample_rate, data)
+    }
+
+    #[test]
+    fn level_stats_of_full_scale_sine() {
+        let data = (0..8000)
+            .map(|index| (2.0 * std::f32::consts::PI * 100.0 * index as f32 / 8000.0).sin())
+            .collect();
+        let stats = level_stats(&Samples::new(1, 8000, data), 1.0);
+
+        let channel = &stats.channels[0];
+        assert!((channel.rms_db + 3.01).abs() < 0.01, "{}", channel.rms_db);
+        assert!(channel.peak_db.abs() < 0.01);
+        assert!((channel.crest_factor_db - 3.01).abs() < 0.01);
+        assert!(channel.dc_offset.abs() < 1e-4);
+    }
+
+    #[test]
+    fn level_stats_peak_and_clipping() {
+        let mut data = vec![0.0; 20];
+        data[6] = 0.5;
+        data[9] = -1.001;
+        data[15] = 1.2;
+        let stats = level_stats(&Samples::new(2, 8000, data), 1.0);
+
+        let expected_peak = 20.0 * 0.5_f32.log10();
+        assert!((stats.channels[0].peak_db - expected_peak).abs() < 1e-4);
+        assert_eq!(stats.channels[0].clipped, 0);
+        assert_eq!(stats.channels[1].clipped, 2);
+        assert_eq!(stats.clipped_frames, vec![4, 7]);
+
+        let lowered = level_stats(&Samples::new(2, 8000, vec![0.5, 0.0]), 0.5);
+        assert_eq!(lowered.clipped_frames, vec![0]);
+    }
+
+    #[test]
+    fn level_stats_correlation_of_channels() {
+        let sine: Vec<f32> = (0..8000)
+            .map(|index| (2.0 * std::f32::consts::PI * 100.0 * index as f32 / 8000.0).sin())
+            .collect();
+        let stereo = |right: &dyn Fn(f32) -> f32| {
+            let data = sine.iter().flat_map(|left| [*left, right(*left)]).collect();
+            level_stats(&Samples::new(2, 8000, data), 1.0).correlation
+        };
+
+        let same = stereo(&|left| 0.5 * left).unwrap();
+        assert!((same - 1.0).abs() < 1e-6);
+        let opposite = stereo(&|left| -left).unwrap();
+        assert!((opposite + 1.0).abs() < 1e-6);
+        assert_eq!(stereo(&|_left| 0.0), None);
+        assert_eq!(
+            level_stats(&Samples::new(1, 8000, sine), 1.0).correlation,
+            None
+        );
+    }
+
+    #[test]
+    fn level_stats_of_silence() {
+        let stats = level_stats(&Samples::new(2, 8000, Vec::new()), 1.0);
+        for channel in &stats.channels {
+            assert_eq!(channel.peak_db, f32::NEG_INFINITY);
+            assert_eq!(channel.crest_factor_db, 0.0);
+            assert!(!channel.dc_offset.is_nan());
+        }
+    }
+
+    #[test]
+    fn clip_regions_merge_within_gap() {
+        let mut data = vec![0.0; 80];
+        for (frame, channel) in [(2, 0), (3, 1), (6, 0), (20, 1), (21, 0), (35, 1)] {
+            data[2 * frame + channel] = if frame % 2 == 0 { 1.0 } else { -1.5 };
+        }
+        let samples = Samples::new(2, 8000, data);
+
+        assert_eq!(
+            clip_regions(&samples, 1.0, 2),
+            vec![
+                Selection::new(2, 4),
+                Selection::new(6, 7),
+                Selection::new(20, 22),
+                Selection::new(35, 36),
+            ]
+        );
+        assert_eq!(
+            clip_regions(&samples, 1.0, 3),
+            vec![
+                Selection::new(2, 7),
+                Selection::new(20, 22),
+                Selection::new(35, 36),
+            ]
+        );
+        assert_eq!(clip_regions(&samples, 1.2, 0).len(), 3);
+        assert_eq!(clip_regions(&samples, 2.0, 3), Vec::new());
+    }
+
+    #[test]
+    fn sound_regions_between_silences() {
+        let mut data = vec![0.0_f32; 1000];
+        for index in (100..200).chain(400..600).chain(800..900) {
+            data[index] = if index % 2 == 0 { 0.5 } else { -0.5 };
+        }
+        // A brief dip within the middle tone.
+        data[450..460].fill(0.0);
+        let samples = Samples::new(1, 1000, data.clone());
+
+        assert_eq!(
+            sound_regions(&samples, -20.0, 50, 5, 20),
+            vec![
+                Selection::new(95, 220),
+                Selection::new(395, 620),
+                Selection::new(795, 920),
+            ]
+        );
+        assert_eq!(
+            sound_regions(&samples, -20.0, 5, 5, 20),
+            vec![
+                Selection::new(95, 220),
+                Selection::new(395, 460),
+                Selection::new(460, 620),
+                Selection::new(795, 920),
+            ]
+        );
+        assert_eq!(
+            sound_regions(&samples, -20.0, 50, 200, 200),
+            vec![
+                Selection::new(0, 400),
+                Selection::new(400, 800),
+                Selection::new(800, 1000),
+            ]
+        );
+
+        // Sound in any channel counts, and quieter tones fall below higher thresholds.
+        let stereo = Samples::new(
+            2,
+            1000,
+            data.iter().flat_map(|sample| [0.0, *sample]).collect(),
+        );
+        assert_eq!(
+            sound_regions(&stereo, -20.0, 50, 0, 0)[0],
+            Selection::new(100, 200)
+        );
+        assert_eq!(sound_regions(&samples, 0.0, 50, 5, 20), Vec::new());
+        assert_eq!(sound_regions(&samples, f32::NAN, 50, 5, 20), Vec::new());
+    }
+
+    #[test]
+    fn dc_offset_of_channels() {
+        let samples = Samples::new(2, 8000, vec![0.5, -0.1, 0.3, -0.3, 0.1, -0.2]);
+        let offsets = dc_offset(&samples);
+        assert!((offsets[0] - 0.3).abs() < 1e-6);
+        assert!((offsets[1] + 0.2).abs() < 1e-6);
+
+        assert_eq!(
+            dc_offset(&Samples::new(2, 8000, Vec::new())),
+            vec![0.0, 0.0]
+        );
+        assert_eq!(dc_offset(&Samples::new(1, 8000, vec![0.0; 3])), vec![0.0]);
+    }
+
+    #[test]
+    fn estimate_bpm_click_track() {
+        let actual = estimate_bpm(&click_track(120.0, 22_050, 10.0)).unwrap();
+        assert!((actual - 120.0).abs() < 1.0, "bpm {}", actual);
+
+        for bpm in [72.0, 95.0, 174.0] {
+            let actual = estimate_bpm(&click_track(bpm, 22_050, 12.0)).unwrap();
+            assert!(
+                (f64::from(actual) - bpm).abs() < 1.0,
+                "bpm {} for {}",
+                actual,
+                bpm
+            );
+        }
+    }
+
+    #[test]
+    fn estimate_bpm_without_beats() {
+        assert_eq!(
+            estimate_bpm(&Samples::new(1, 8000, vec![0.0; 80_000])),
+            None
+        );
+        assert_eq!(estimate_bpm(&click_track(120.0, 8000, 1.0)), None);
+    }
+
+    #[test]
+    fn find_zero_crossing_nearest() {
+        let data = [0.5, 0.4, 0.2, -0.1, -0.3, -0.2, 0.3, 0.6];
+
+        assert_eq!(find_zero_crossing(&data, 1, 0, 8), Some(3));
+        assert_eq!(find_zero_crossing(&data, 1, 5, 8), Some(5));
+        assert_eq!(find_zero_crossing(&data, 1, 7, 1), None);
+        assert_eq!(find_zero_crossing(&data, 1, 7, 2), Some(5));
+        assert_eq!(find_zero_crossing(&data, 1, 8, 8), None);
+    }
+
+    #[test]
+    fn step_zero_crossing_of_sine_and_square() {
+        // A sine with a period of 100 frames crosses zero every 50 frames.
+        let sine: Vec<f32> = (0..400)
+            .map(|frame| (std::f32::consts::TAU * frame as f32 / 100.0).sin())
+            .collect();
+        assert_eq!(
+            step_zero_crossing(&sine, 1, None, 120, true, 100),
+            Some(150)
+        );
+        assert_eq!(
+            step_zero_crossing(&sine, 1, None, 150, true, 100),
+            Some(200)
+        );
+        assert_eq!(
+            step_zero_crossing(&sine, 1, None, 150, false, 100),
+            Some(100)
+        );
+        assert_eq!(step_zero_crossing(&sine, 1, None, 120, true, 20), None);
+        assert_eq!(step_zero_crossing(&sine, 1, None, 30, false, 100), Some(0));
+        assert_eq!(step_zero_crossing(&sine, 1, None, 400, true, 100), None);
+
+        let square: Vec<f32> = (0..40)
+            .map(|frame| if (frame / 10) % 2 == 0 { 0.5 } else { -0.5 })
+            .collect();
+        assert_eq!(step_zero_crossing(&square, 1, None, 0, true, 40), Some(9));
+        assert_eq!(step_zero_crossing(&square, 1, None, 9, true, 40), Some(10));
+        assert_eq!(
+            step_zero_crossing(&square, 1, None, 25, false, 40),
+            Some(20)
+        );
+    }
+
+    #[test]
+    fn step_zero_crossing_of_reference_channel() {
+        // The left channel crosses at frame 1 and the right channel at frame 2.
+        let data = [0.5, 0.5, 0.2, 0.4, -0.3, 0.1, -0.5, -0.2, -0.5, -0.5];
+
+        assert_eq!(step_zero_crossing(&data, 2, Some(0), 0, true, 4), Some(1));
+        assert_eq!(step_zero_crossing(&data, 2, Some(1), 0, true, 4), Some(2));
+        assert_eq!(step_zero_crossing(&data, 2, Some(1), 2, true, 2), None);
+        assert_eq!(step_zero_crossing(&data, 2, None, 0, true, 4), Some(2));
+        assert_eq!(step_zero_crossing(&data, 2, Some(5), 0, true, 4), Some(2));
+    }
+
+    #[test]
+    fn find_zero_crossing_on_reference_channel() {
+        let data = [0.5, 0.5, 0.2, 0.4, -0.3, 0.1, -0.5, -0.2, -0.5, -0.5];
+
+        assert_eq!(find_zero_crossing_on(&data, 2, Some(0), 4, 4), Some(1));
+        assert_eq!(find_zero_crossing_on(&data, 2, Some(1), 4, 4), Some(2));
+        assert_eq!(find_zero_crossing_on(&data, 2, Some(1), 4, 1), None);
+    }
+
+    #[test]
+    fn zero_crossing_of_offset_signal_is_none() {
+        let offset: Vec<f32> = (0..400)
+            .map(|frame| 0.5 + 0.3 * (std::f32::consts::TAU * frame as f32 / 100.0).sin())
+            .collect();
+
+        assert_eq!(find_zero_crossing(&offset, 1, 200, 200), None);
+        assert_eq!(step_zero_crossing(&offset, 1, None, 200, true, 200), None);
+        assert_eq!(step_zero_crossing(&offset, 1, None, 200, false, 200), None);
+    }
+
+    #[test]
+    fn find_zero_crossing_sums_channels() {
+        let data = [0.5, 0.5, 0.5, -0.1, -0.5, 0.6, -0.5, -0.5];
+
+        assert_eq!(find_zero_crossing(&data, 2, 0, 4), Some(2));
+        assert_eq!(find_zero_crossing(&data, 2, 0, 1), None);
+    }
+}
@@ -1,12 +1,105 @@
 //! Convenience structs for digital signal processing.
 
+use crate::dsp::{analysis, filters};
+use color_eyre::eyre;
 use rodio::buffer::SamplesBuffer;
+use std::convert::TryFrom;
+use std::slice::{ChunksExact, ChunksExactMut};
+
+/// Descriptive text of a recording, such as the fields of a WAV INFO list.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct AudioMetadata {
+    pub artist: Option<String>,
+    pub comment: Option<String>,
+    /// Name of the software that created the file.
+    pub software: Option<String>,
+    pub title: Option<String>,
+}
+
+impl AudioMetadata {
+    /// Check whether no field is set.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.artist.is_none()
+            && self.comment.is_none()
+            && self.software.is_none()
+            && self.title.is_none()
+    }
+}
+
+/// A named position in a signal, such as a WAV cue point.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Marker {
+    /// Frame index of the marker.
+    pub frame: usize,
+    /// Marker name, which is empty for unnamed markers.
+    pub label: String,
+}
+
+impl Marker {
+    /// Create a new Marker at a frame index.
+    #[must_use]
+    pub fn new(frame: usize, label: String) -> Self {
+        Self { frame, label }
+    }
+}
+
+/// A half open range of frames.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Selection {
+    /// First selected frame index.
+    pub start: usize,
+    /// Frame index after the last selected frame.
+    pub end: usize,
+}
+
+impl Selection {
+    /// Create a Selection between two frame boundaries given in either order.
+    #[must_use]
+    pub fn new(first: usize, second: usize) -> Self {
+        Self {
+            start: first.min(second),
+            end: first.max(second),
+        }
+    }
+
+    /// Check if the selection contains no frames.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Get the number of selected frames.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.end - self.start
+    }
+}
+
+/// Read access to interleaved multichannel audio, regardless of where it is stored.
+pub trait Signal {
+    /// Get the number of channels.
+    fn channels(&self) -> u16;
+    /// Get the number of frames, which are groups of one sample per channel.
+    fn frames(&self) -> usize;
+    /// Get the sample of a channel at a frame index.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the frame or channel is out of bounds.
+    fn sample(&self, frame: usize, channel: usize) -> f32;
+    /// Get the number of frames per second.
+    fn sample_rate(&self) -> u32;
+}
 
 /// A wrapper around Rodio's Samples to allow for repeated playback and additional processing.
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Samples {
     pub data: Vec<f32>,
     pub channels: u16,
+    pub markers: Vec<Marker>,
+    /// Descriptive text kept with the samples to write back with them.
+    pub metadata: AudioMetadata,
     pub sample_rate: u32,
 }
 
@@ -17,16 +110,455 @@ impl Samples {
         Self {
             data,
             channels,
+            markers: Vec::new(),
+            metadata: AudioMetadata::default(),
             sample_rate,
         }
     }
+
+    /// Create a new Samples by interleaving one signal per channel.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if there are no channels, more channels than fit a `u16`, or channels of
+    /// different lengths.
+    pub fn from_channels(channels_data: Vec<Vec<f32>>, sample_rate: u32) -> eyre::Result<Self> {
+        let channels = u16::try_from(channels_data.len())
+            .map_err(|_error| eyre::eyre!("{} channels are too many", channels_data.len()))?;
+        let frames = match channels_data.first() {
+            Some(first) => first.len(),
+            None => eyre::bail!("Samples need at least one channel"),
+        };
+        if let Some((index, channel)) = channels_data
+            .iter()
+            .enumerate()
+            .find(|(_index, channel)| channel.len() != frames)
+        {
+            eyre::bail!(
+                "Channel {} holds {} samples instead of {}",
+                index + 1,
+                channel.len(),
+                frames
+            );
+        }
+
+        let data = (0..frames)
+            .flat_map(|frame| channels_data.iter().map(move |channel| channel[frame]))
+            .collect();
+        Ok(Self::new(channels, sample_rate, data))
+    }
+
+    /// Get the number of frames, which are groups of one sample per channel.
+    #[must_use]
+    pub fn frames(&self) -> usize {
+        self.data.len() / usize::from(self.channels.max(1))
+    }
+
+    /// Iterate over frames as slices holding one sample per channel.
+    ///
+    /// Samples after the last whole frame are skipped.
+    #[must_use]
+    pub fn iter_frames(&self) -> FrameIter<'_> {
+        FrameIter {
+            chunks: self.data.chunks_exact(usize::from(self.channels.max(1))),
+        }
+    }
+
+    /// Iterate over frames as mutable slices holding one sample per channel.
+    ///
+    /// Samples after the last whole frame are skipped.
+    pub fn iter_frames_mut(&mut self) -> FrameIterMut<'_> {
+        FrameIterMut {
+            chunks: self
+                .data
+                .chunks_exact_mut(usize::from(self.channels.max(1))),
+        }
+    }
+
+    /// Copy the samples into one signal per channel.
+    ///
+    /// Samples after the last whole frame are skipped.
+    #[must_use]
+    pub fn split_channels(&self) -> Vec<Vec<f32>> {
+        let width = usize::from(self.channels.max(1));
+        let mut channels = vec![Vec::with_capacity(self.frames()); width];
+        for frame in self.iter_frames() {
+            for (channel, sample) in channels.iter_mut().zip(frame) {
+                channel.push(*sample);
+            }
+        }
+        channels
+    }
+
+    /// Join incoming samples onto the end, fading between them over a number of frames.
+    ///
+    /// See [`filters::append`] for how the samples are converted and joined.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the channels of the incoming samples cannot be converted.
+    pub fn append(&mut self, incoming: &Self, crossfade: usize) -> eyre::Result<()> {
+        filters::append(incoming, crossfade, self)
+    }
+
+    /// Negate the samples of one channel counted from zero, or of every channel if none is given.
+    ///
+    /// Inverting twice gives back the samples exactly.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `channel` is not one of the channels.
+    pub fn invert_phase(&mut self, channel: Option<u16>) -> eyre::Result<()> {
+        match channel {
+            Some(channel) if channel >= self.channels => eyre::bail!(
+                "Channel {} does not exist in samples with {} channels",
+                channel + 1,
+                self.channels
+            ),
+            Some(channel) => {
+                for frame in self.iter_frames_mut() {
+                    frame[usize::from(channel)] = -frame[usize::from(channel)];
+                }
+            }
+            None => self.data.iter_mut().for_each(|sample| *sample = -*sample),
+        }
+        Ok(())
+    }
+
+    /// Sum incoming samples scaled by a gain into these samples starting at a frame.
+    ///
+    /// See [`filters::mix`] for how channels and sample rates are converted.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the channels of the incoming samples cannot be converted.
+    pub fn mix(&mut self, incoming: &Self, gain: f32, frame: usize) -> eyre::Result<f32> {
+        filters::mix(incoming, gain, frame, self)
+    }
+
+    /// Quantize samples to a bit depth and hold each frame for a number of frames, for lo-fi sound.
+    ///
+    /// Either parameter can be left neutral, with a bit depth of zero or a downsample factor of
+    /// one. See [`filters::bitcrush`] for how samples are quantized.
+    pub fn bitcrush(&mut self, bits: u32, downsample_factor: u32) {
+        filters::bitcrush(self, bits, downsample_factor);
+    }
+
+    /// Reduce the level above a threshold in dBFS by a ratio with attack and release times in
+    /// milliseconds.
+    ///
+    /// Channels are linked, so the loudest channel of each frame sets the gain of all of them and
+    /// the stereo image does not shift. Infinite ratios limit peaks to the threshold. See
+    /// [`filters::compress`] for how the envelope is smoothed. Negative or NaN times count as zero.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `ratio` is below one or NaN or `threshold_dbfs` is NaN.
+    pub fn compress(
+        &mut self,
+        threshold_dbfs: f32,
+        ratio: f32,
+        attack_ms: f32,
+        release_ms: f32,
+    ) -> eyre::Result<()> {
+        let (attack, release) = (self.frames_in(attack_ms), self.frames_in(release_ms));
+        filters::compress(self, threshold_dbfs, ratio, attack, release)
+    }
+
+    /// Join other samples with the same channel count and sample rate onto the end, blending
+    /// them over an equal power crossfade of a number of frames.
+    ///
+    /// See [`filters::concatenate`] for how the seam is blended.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the channel counts or sample rates differ.
+    pub fn concatenate(&mut self, other: &Self, crossfade: usize) -> eyre::Result<()> {
+        filters::concatenate(other, crossfade, self)
+    }
+
+    /// Crossfade the end of a loop region into the frames before it over a number of frames, so
+    /// that the region loops without clicking.
+    ///
+    /// See [`filters::crossfade_loop`] for where the crossfade comes from. Returns the frames of
+    /// the looped region.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the region is empty or ends after the last frame.
+    pub fn crossfade_loop(
+        &mut self,
+        selection: Selection,
+        crossfade: usize,
+    ) -> eyre::Result<Selection> {
+        filters::crossfade_loop(selection, crossfade, self)
+    }
+
+    /// Find the zero crossing of the summed channels nearest to a frame within a number of frames
+    /// in either direction.
+    ///
+    /// See [`analysis::find_zero_crossing`] for how crossings are chosen. Returns `None` if the
+    /// frame is out of bounds or there is no crossing within the search window.
+    #[must_use]
+    pub fn nearest_zero_crossing(&self, frame: usize, max_search: usize) -> Option<usize> {
+        analysis::find_zero_crossing(&self.data, self.channels, frame, max_search)
+    }
+
+    /// Fade quiet regions toward silence with attack and release times in milliseconds and no
+    /// hold time.
+    ///
+    /// See [`filters::noise_gate`] for how the envelope and gain ramps behave. Negative or NaN
+    /// times count as zero.
+    pub fn noise_gate(&mut self, threshold_dbfs: f32, attack_ms: f32, release_ms: f32) {
+        let (attack, release) = (self.frames_in(attack_ms), self.frames_in(release_ms));
+        filters::noise_gate(self, threshold_dbfs, 0, attack, release);
+    }
+
+    /// Convert milliseconds to the nearest number of frames at the sample rate.
+    fn frames_in(&self, milliseconds: f32) -> usize {
+        // Casts saturate, so negative and NaN times become zero frames.
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let frames = (milliseconds * self.sample_rate as f32 / 1000.0).round() as usize;
+        frames
+    }
+
+    /// Sum other samples with a gain in decibels into these samples starting at a frame.
+    ///
+    /// Unlike [`Self::mix`], the samples are not converted, so both need the same channel count
+    /// and sample rate. These samples grow if the other samples run past their end. The returned
+    /// peak magnitude of the mixed frames exceeds one if they clip.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the channel counts or sample rates differ.
+    pub fn overlay(&mut self, other: &Self, at_frame: usize, gain_db: f32) -> eyre::Result<f32> {
+        if other.channels != self.channels {
+            eyre::bail!(
+                "Cannot overlay {} channels onto {} channels",
+                other.channels,
+                self.channels
+            );
+        }
+        if other.sample_rate != self.sample_rate {
+            eyre::bail!(
+                "Cannot overlay {} Hz samples onto {} Hz samples",
+                other.sample_rate,
+                self.sample_rate
+            );
+        }
+
+        let gain = 10.0_f32.powf(gain_db / 20.0);
+        filters::mix(other, gain, at_frame, self)
+    }
+
+    /// Transpose by semitones while keeping the number of frames.
+    ///
+    /// See [`filters::pitch_shift`] for the quality tradeoffs of the algorithm.
+    pub fn pitch_shift(&mut self, semitones: f32) {
+        filters::pitch_shift(semitones, self);
+    }
+
+    /// Change the length by a ratio while keeping the pitch.
+    ///
+    /// See [`filters::time_stretch`] for the accepted ratios.
+    pub fn time_stretch(&mut self, ratio: f32) {
+        filters::time_stretch(ratio, self);
+    }
+
+    /// Copy selected frames into new Samples with the same audio metadata.
+    ///
+    /// Markers inside the selection are kept relative to its start.
+    #[must_use]
+    pub fn slice(&self, selection: Selection) -> Self {
+        let channels = usize::from(self.channels);
+        let end = selection.end.min(self.frames());
+        let start = selection.start.min(end);
+
+        let markers = self
+            .markers
+            .iter()
+            .filter(|marker| (start..end).contains(&marker.frame))
+            .map(|marker| Marker::new(marker.frame - start, marker.label.clone()))
+            .collect();
+
+        Self {
+            data: self.data[start * channels..end * channels].to_vec(),
+            channels: self.channels,
+            markers,
+            metadata: self.metadata.clone(),
+            sample_rate: self.sample_rate,
+        }
+    }
+
+    /// Copy a short snippet of frames centered on a frame, fading it in and out so it starts and
+    /// stops without clicks.
+    ///
+    /// Snippets are cut short at the ends of the samples, and each fade lasts at most half the
+    /// snippet. Markers and audio metadata are not kept.
+    #[must_use]
+    pub fn snippet(&self, frame: usize, milliseconds: f32, fade_milliseconds: f32) -> Self {
+        let channels = usize::from(self.channels.max(1));
+        let (frames, total) = (self.frames_in(milliseconds), self.frames());
+        let start = frame.saturating_sub(frames / 2).min(total);
+        let end = start.saturating_add(frames).min(total);
+
+        let mut data = self.data[start * channels..end * channels].to_vec();
+        let fade = self.frames_in(fade_milliseconds).min((end - start) / 2);
+        let length = data.len();
+        for index in 0..fade {
+            let gain = index as f32 / fade as f32;
+            let (head, tail) = (index * channels, length - (index + 1) * channels);
+            for offset in 0..channels {
+                data[head + offset] *= gain;
+                data[tail + offset] *= gain;
+            }
+        }
+        Self::new(self.channels, self.sample_rate, data)
+    }
+
+    /// Replace selected frames with other samples, which may have a different length.
+    ///
+    /// Markers inside the selection are replaced by the markers of the other samples, placed
+    /// relative to the selection start, and later markers move by the change in length. Replacing
+    /// a selection with empty samples cuts it out.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the channel counts differ.
+    pub fn splice(&mut self, selection: Selection, part: Self) -> eyre::Result<()> {
+        if part.channels != self.channels {
+            eyre::bail!(
+                "Cannot put {} channels in place of {} channels",
+                part.channels,
+                self.channels
+            );
+        }
+
+        let width = usize::from(self.channels.max(1));
+        let end = selection.end.min(self.frames());
+        let start = selection.start.min(end);
+        let frames = part.frames();
+        self.data.splice(start * width..end * width, part.data);
+
+        self.markers
+            .retain(|marker| !(start..end).contains(&marker.frame));
+        for marker in &mut self.markers {
+            if marker.frame >= end {
+                marker.frame = marker.frame - end + start + frames;
+            }
+        }
+        self.markers.extend(
+            part.markers
+                .into_iter()
+                .map(|marker| Marker::new(marker.frame + start, marker.label)),
+        );
+        self.markers.sort_by_key(|marker| marker.frame);
+        Ok(())
+    }
+
+    /// Copy the frames between consecutive markers into separate Samples.
+    ///
+    /// The first segment starts at frame zero and the last ends after the last frame, so joining
+    /// the segments gives back the samples. Each segment keeps the marker it starts at. Markers at
+    /// either end or sharing a frame do not produce empty segments.
+    #[must_use]
+    pub fn split_at_markers(&self) -> Vec<Self> {
+        let frames = self.frames();
+        let mut bounds: Vec<usize> = self
+            .markers
+            .iter()
+            .map(|marker| marker.frame)
+            .filter(|frame| (1..frames).contains(frame))
+            .collect();
+        bounds.sort_unstable();
+        bounds.dedup();
+
+        let starts = std::iter::once(0).chain(bounds.iter().copied());
+        let ends = bounds.iter().copied().chain(std::iter::once(frames));
+        starts
+            .zip(ends)
+            .map(|(start, end)| self.slice(Selection::new(start, end)))
+            .collect()
+    }
+}
+
+impl Signal for Samples {
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn frames(&self) -> usize {
+        Self::frames(self)
+    }
+
+    fn sample(&self, frame: usize, channel: usize) -> f32 {
+        self.data[frame * usize::from(self.channels) + channel]
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
 }
 
+/// Iterator over the frames of samples, created by [`Samples::iter_frames`].
+#[derive(Clone, Debug)]
+pub struct FrameIter<'a> {
+    chunks: ChunksExact<'a, f32>,
+}
+
+impl<'a> Iterator for FrameIter<'a> {
+    type Item = &'a [f32];
+
+    fn next(&mut self) -> Option<&'a [f32]> {
+        self.chunks.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for FrameIter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.chunks.next_back()
+    }
+}
+
+impl ExactSizeIterator for FrameIter<'_> {}
+
+/// Iterator over the mutable frames of samples, created by [`Samples::iter_frames_mut`].
+#[derive(Debug)]
+pub struct FrameIterMut<'a> {
+    chunks: ChunksExactMut<'a, f32>,
+}
+
+impl<'a> Iterator for FrameIterMut<'a> {
+    type Item = &'a mut [f32];
+
+    fn next(&mut self) -> Option<&'a mut [f32]> {
+        self.chunks.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for FrameIterMut<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.chunks.next_back()
+    }
+}
+
+impl ExactSizeIterator for FrameIterMut<'_> {}
+
 impl Default for Samples {
     fn default() -> Self {
         Self {
             data: Vec::default(),
             channels: 1,
+            markers: Vec::default(),
+            metadata: AudioMetadata::default(),
             sample_rate: 1,
         }
     }
@@ -51,10 +583,10 @@ mod tests {
         }
 
         fn abs_diff_eq(&self, other: &Self, epsilon: f32) -> bool {
-            vec![
+            [
                 self.channels == other.channels,
                 self.sample_rate == other.sample_rate,
-                self.data.len() == self.data.len(),
+                self.data.len() == other.data.len(),
                 self.data
                     .iter()
                     .zip(other.data.iter())
@@ -64,4 +596,402 @@ mod tests {
             .all(|bool| *bool)
         }
     }
+
+    #[test]
+    fn selection_normalizes_order() {
+        let selection = Selection::new(10, 3);
+
+        assert_eq!(selection, Selection { start: 3, end: 10 });
+        assert_eq!(selection.len(), 7);
+        assert!(Selection::new(4, 4).is_empty());
+    }
+
+    #[test]
+    fn nearest_zero_crossing_of_sine() {
+        // A sine with a period of 100 frames crosses zero every 50 frames.
+        let data = (0..400)
+            .map(|frame| (std::f32::consts::TAU * frame as f32 / 100.0).sin())
+            .collect();
+        let samples = Samples::new(1, 8000, data);
+
+        assert_eq!(samples.nearest_zero_crossing(140, 20), Some(150));
+        assert_eq!(samples.nearest_zero_crossing(163, 20), Some(150));
+        assert_eq!(samples.nearest_zero_crossing(125, 10), None);
+    }
+
+    #[test]
+    fn concatenate_joins_end_to_end() {
+        let mut samples = Samples::new(2, 8000, vec![0.1, 0.2, 0.3, 0.4]);
+        let mut other = Samples::new(2, 8000, vec![0.5, 0.6, 0.7, 0.8, 0.9, 1.0]);
+        other.markers.push(Marker::new(1, String::from("second")));
+
+        samples.concatenate(&other, 0).unwrap();
+        assert_eq!(
+            samples.data,
+            vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9, 1.0]
+        );
+        assert_eq!(samples.frames(), 5);
+        assert_eq!(
+            samples.markers,
+            vec![Marker::new(3, String::from("second"))]
+        );
+    }
+
+    #[test]
+    fn concatenate_rejects_mismatches() {
+        let mut samples = Samples::new(2, 8000, vec![0.5; 4]);
+        assert!(samples
+            .concatenate(&Samples::new(1, 8000, vec![0.5; 2]), 0)
+            .is_err());
+        assert!(samples
+            .concatenate(&Samples::new(2, 48_000, vec![0.5; 4]), 0)
+            .is_err());
+        assert_eq!(samples.data, vec![0.5; 4]);
+    }
+
+    #[test]
+    fn concatenate_crossfades_seam() {
+        let mut samples = Samples::new(1, 8000, vec![1.0; 4]);
+        let other = Samples::new(1, 8000, vec![-1.0; 4]);
+
+        samples.concatenate(&other, 1).unwrap();
+        assert_eq!(samples.frames(), 7);
+        // The single overlapping frame is halfway through the fade with equal gains.
+        let gain = std::f32::consts::FRAC_PI_4.cos();
+        assert!((samples.data[3] - (gain - gain)).abs() < 1e-6);
+        assert_eq!(samples.data[..3], [1.0; 3]);
+        assert_eq!(samples.data[4..], [-1.0; 3]);
+
+        let mut samples = Samples::new(1, 8000, vec![1.0; 4]);
+        samples
+            .concatenate(&Samples::new(1, 8000, vec![1.0; 4]), 3)
+            .unwrap();
+        let seam = &samples.data[1..4];
+        assert!(seam
+            .iter()
+            .all(|sample| *sample > 1.0 && *sample <= 2.0_f32.sqrt()));
+    }
+
+    #[test]
+    fn overlay_at_start() {
+        let mut samples = Samples::new(2, 8000, vec![0.25, -0.25, 0.5, 0.0, 0.1, 0.1]);
+        let other = Samples::new(2, 8000, vec![0.5, 0.5, 0.25, 0.25]);
+
+        let peak = samples.overlay(&other, 0, 0.0).unwrap();
+        assert_eq!(samples.data, vec![0.75, 0.25, 0.75, 0.25, 0.1, 0.1]);
+        assert_eq!(peak, 0.75);
+    }
+
+    #[test]
+    fn overlay_past_end_extends() {
+        let mut samples = Samples::new(1, 8000, vec![0.5, 0.5]);
+        let other = Samples::new(1, 8000, vec![1.0, 1.0, 1.0]);
+
+        let peak = samples.overlay(&other, 1, -20.0 * 2.0_f32.log10()).unwrap();
+        assert_eq!(samples.data.len(), 4);
+        let expected = [0.5, 1.0, 0.5, 0.5];
+        for (actual, expected) in samples.data.iter().zip(expected) {
+            assert!((actual - expected).abs() < 1e-6, "{:?}", samples.data);
+        }
+        assert!((peak - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn overlay_rejects_mismatches() {
+        let mut samples = Samples::new(2, 8000, vec![0.5; 4]);
+        assert!(samples
+            .overlay(&Samples::new(1, 8000, vec![0.5; 2]), 0, 0.0)
+            .is_err());
+        assert!(samples
+            .overlay(&Samples::new(2, 44_100, vec![0.5; 4]), 0, 0.0)
+            .is_err());
+        assert_eq!(samples.data, vec![0.5; 4]);
+    }
+
+    #[test]
+    fn invert_phase_of_all_channels() {
+        let mut samples = Samples::new(2, 8000, vec![0.5, -0.25, 0.0, 1.0]);
+        samples.invert_phase(None).unwrap();
+        assert_eq!(samples.data, vec![-0.5, 0.25, -0.0, -1.0]);
+        samples.invert_phase(None).unwrap();
+        assert_eq!(samples.data, vec![0.5, -0.25, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn invert_phase_of_one_channel() {
+        let mut samples = Samples::new(3, 8000, vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6]);
+        samples.invert_phase(Some(1)).unwrap();
+        assert_eq!(samples.data, vec![0.1, -0.2, 0.3, 0.4, -0.5, 0.6]);
+
+        assert!(samples.invert_phase(Some(3)).is_err());
+        assert_eq!(samples.data, vec![0.1, -0.2, 0.3, 0.4, -0.5, 0.6]);
+    }
+
+    #[test]
+    fn compress_reduces_peaks_by_ratio() {
+        let data: Vec<f32> = (0..800)
+            .map(|index| if index % 2 == 0 { 0.5 } else { -0.5 })
+            .collect();
+        let peak = |samples: &Samples| {
+            let peak = samples
+                .data
+                .iter()
+                .fold(0.0_f32, |peak, sample| peak.max(sample.abs()));
+            20.0 * peak.log10()
+        };
+
+        let mut compressed = Samples::new(2, 8000, data.clone());
+        compressed.compress(-12.0, 2.0, 0.0, 50.0).unwrap();
+        let input = peak(&Samples::new(2, 8000, data.clone()));
+        let expected = -12.0 + (input + 12.0) / 2.0;
+        assert!((peak(&compressed) - expected).abs() < 0.01);
+
+        let mut limited = Samples::new(2, 8000, data.clone());
+        limited.compress(-12.0, f32::INFINITY, 0.0, 50.0).unwrap();
+        assert!((peak(&limited) + 12.0).abs() < 0.01);
+
+        let quiet: Vec<f32> = data.iter().map(|sample| sample * 0.1).collect();
+        let mut unchanged = Samples::new(2, 8000, quiet.clone());
+        unchanged.compress(-12.0, 4.0, 5.0, 50.0).unwrap();
+        assert_eq!(unchanged.data, quiet);
+
+        assert!(unchanged.compress(-12.0, 0.5, 5.0, 50.0).is_err());
+    }
+
+    #[test]
+    fn bitcrush_holds_runs_of_frames() {
+        let data: Vec<f32> = (0..3000)
+            .map(|index| ((index * 7919) % 2001) as f32 / 1000.0 - 1.0)
+            .collect();
+        let mut samples = Samples::new(3, 8000, data);
+        samples.bitcrush(8, 5);
+
+        let frames: Vec<&[f32]> = samples.data.chunks(3).collect();
+        for run in frames.chunks(5) {
+            assert!(run.iter().all(|frame| *frame == run[0]));
+        }
+        assert_ne!(frames[0], frames[5]);
+        let mut levels: Vec<u32> = samples.data.iter().map(|sample| sample.to_bits()).collect();
+        levels.sort_unstable();
+        levels.dedup();
+        assert!(levels.len() <= 256);
+    }
+
+    #[test]
+    fn noise_gate_ramps_over_configured_times() {
+        let data: Vec<f32> = (0..600)
+            .map(|index| {
+                if (100..500).contains(&index) {
+                    0.001
+                } else {
+                    0.5
+                }
+            })
+            .collect();
+        let mut samples = Samples::new(1, 1000, data.clone());
+
+        samples.noise_gate(-40.0, 10.0, 20.0);
+        let ramp = |range: std::ops::Range<usize>, level: f32| {
+            samples.data[range]
+                .iter()
+                .filter(|sample| **sample > 0.0 && **sample < level)
+                .count()
+        };
+        assert!((19..=20).contains(&ramp(100..500, 0.001)));
+        assert!((9..=10).contains(&ramp(500..600, 0.5)));
+        assert!(samples.data[400..500].iter().all(|sample| *sample == 0.0));
+        assert_eq!(samples.data[..100], data[..100]);
+        assert_eq!(samples.data[510..], data[510..]);
+
+        let mut instant = Samples::new(1, 1000, data.clone());
+        instant.noise_gate(-40.0, -5.0, f32::NAN);
+        assert_eq!(instant.data[100], 0.0);
+        assert_eq!(instant.data[500..], data[500..]);
+    }
+
+    #[test]
+    fn nearest_zero_crossing_of_silence_keeps_frame() {
+        let samples = Samples::new(2, 8000, vec![0.0; 200]);
+
+        assert_eq!(samples.nearest_zero_crossing(37, 16), Some(37));
+    }
+
+    #[test]
+    fn nearest_zero_crossing_near_edges() {
+        let samples = Samples::new(1, 8000, vec![0.5, -0.25, 0.5, 1.0, 1.0, 1.0, 0.75, -0.5]);
+
+        assert_eq!(samples.nearest_zero_crossing(0, 4), Some(1));
+        assert_eq!(samples.nearest_zero_crossing(7, 0), Some(7));
+        assert_eq!(samples.nearest_zero_crossing(6, 0), None);
+        assert_eq!(samples.nearest_zero_crossing(5, 8), Some(7));
+        assert_eq!(samples.nearest_zero_crossing(8, 8), None);
+    }
+
+    #[test]
+    fn iter_frames_yields_whole_frames() {
+        let mut samples = Samples::new(2, 8, vec![0.0, 0.1, 1.0, 1.1, 2.0, 2.1, 3.0]);
+
+        let frames: Vec<&[f32]> = samples.iter_frames().collect();
+        assert_eq!(frames, [[0.0, 0.1], [1.0, 1.1], [2.0, 2.1]]);
+        assert_eq!(samples.iter_frames().len(), samples.frames());
+        assert_eq!(samples.iter_frames().next_back(), Some(&[2.0, 2.1][..]));
+
+        for frame in samples.iter_frames_mut().rev().take(2) {
+            frame.swap(0, 1);
+        }
+        assert_eq!(samples.data, vec![0.0, 0.1, 1.1, 1.0, 2.1, 2.0, 3.0]);
+
+        let empty = Samples::new(0, 8, vec![0.5, 0.25]);
+        assert_eq!(empty.iter_frames().len(), 2);
+    }
+
+    #[test]
+    fn split_channels_round_trips() {
+        for samples in [
+            Samples::new(1, 8, vec![0.5, -0.25, 1.0]),
+            Samples::new(2, 44100, vec![0.0, 0.1, 1.0, 1.1, 2.0, 2.1]),
+            Samples::new(6, 48000, (0..24).map(|index| index as f32).collect()),
+            Samples::new(2, 8, Vec::new()),
+        ] {
+            let channels = samples.split_channels();
+            assert_eq!(channels.len(), usize::from(samples.channels));
+            assert!(channels
+                .iter()
+                .all(|channel| channel.len() == samples.frames()));
+            assert_eq!(
+                Samples::from_channels(channels, samples.sample_rate).unwrap(),
+                samples
+            );
+        }
+
+        let stereo = Samples::new(2, 8, vec![0.0, 0.1, 1.0, 1.1, 2.0]);
+        assert_eq!(stereo.split_channels(), [vec![0.0, 1.0], vec![0.1, 1.1]]);
+        let error = Samples::from_channels(vec![vec![0.0; 3], vec![0.0; 2]], 8).unwrap_err();
+        assert!(format!("{}", error).contains("Channel 2 holds 2 samples instead of 3"));
+        assert!(Samples::from_channels(Vec::new(), 8).is_err());
+    }
+
+    #[test]
+    fn slice_keeps_channels_aligned() {
+        let mut samples = Samples::new(2, 44100, vec![0.0, 0.1, 1.0, 1.1, 2.0, 2.1, 3.0, 3.1]);
+        samples.markers = vec![
+            Marker::new(0, String::from("start")),
+            Marker::new(2, String::from("middle")),
+        ];
+
+        let actual = samples.slice(Selection::new(3, 1));
+
+        assert_eq!(actual.data, vec![1.0, 1.1, 2.0, 2.1]);
+        assert_eq!(actual.channels, 2);
+        assert_eq!(actual.sample_rate, 44100);
+        assert_eq!(actual.markers, vec![Marker::new(1, String::from("middle"))]);
+        assert_eq!(samples.data.len(), 8);
+    }
+
+    #[test]
+    fn split_at_markers_joins_back() {
+        let mut samples = Samples::new(2, 10, (0..40).map(|index| index as f32).collect());
+        samples.markers = vec![
+            Marker::new(0, String::from("start")),
+            Marker::new(12, String::from("outro")),
+            Marker::new(5, String::from("verse")),
+            Marker::new(5, String::from("again")),
+            Marker::new(20, String::from("end")),
+        ];
+
+        let segments = samples.split_at_markers();
+        let lengths: Vec<usize> = segments.iter().map(Samples::frames).collect();
+        assert_eq!(lengths, vec![5, 7, 8]);
+        assert!(segments
+            .iter()
+            .all(|segment| segment.channels == 2 && segment.sample_rate == 10));
+        assert_eq!(
+            segments[1].markers[0],
+            Marker::new(0, String::from("verse"))
+        );
+
+        let joined: Vec<f32> = segments
+            .into_iter()
+            .flat_map(|segment| segment.data)
+            .collect();
+        assert_eq!(joined, samples.data);
+
+        samples.markers.clear();
+        assert_eq!(samples.split_at_markers(), vec![samples.clone()]);
+    }
+
+    #[test]
+    fn splice_cut_moves_markers() {
+        let mut samples = Samples::new(2, 10, (0..16).map(|index| index as f32).collect());
+        samples.markers = vec![
+            Marker::new(1, String::from("before")),
+            Marker::new(3, String::from("inside")),
+            Marker::new(5, String::from("end")),
+            Marker::new(7, String::from("after")),
+        ];
+
+        let empty = samples.slice(Selection::new(0, 0));
+        samples.splice(Selection::new(2, 5), empty).unwrap();
+        assert_eq!(
+            samples.data,
+            vec![0.0, 1.0, 2.0, 3.0, 10.0, 11.0, 12.0, 13.0, 14.0, 15.0]
+        );
+        assert_eq!(
+            samples.markers,
+            vec![
+                Marker::new(1, String::from("before")),
+                Marker::new(2, String::from("end")),
+                Marker::new(4, String::from("after")),
+            ]
+        );
+
+        let mut part = Samples::new(2, 10, vec![-1.0; 6]);
+        part.markers = vec![Marker::new(1, String::from("new"))];
+        samples.splice(Selection::new(1, 2), part).unwrap();
+        assert_eq!(samples.frames(), 7);
+        assert_eq!(
+            samples.markers,
+            vec![
+                Marker::new(2, String::from("new")),
+                Marker::new(4, String::from("end")),
+                Marker::new(6, String::from("after")),
+            ]
+        );
+        assert!(samples
+            .splice(Selection::new(0, 1), Samples::new(1, 10, vec![0.0]))
+            .is_err());
+    }
+
+    #[test]
+    fn snippet_fades_frames_around_center() {
+        let data = (0..40).flat_map(|_frame| [1.0, -0.5]).collect();
+        let samples = Samples::new(2, 1000, data);
+
+        let actual = samples.snippet(20, 8.0, 2.0);
+        assert_eq!(actual.channels, 2);
+        assert_eq!(actual.sample_rate, 1000);
+        assert_eq!(
+            actual.data,
+            vec![
+                0.0, -0.0, 0.5, -0.25, 1.0, -0.5, 1.0, -0.5, 1.0, -0.5, 1.0, -0.5, 0.5, -0.25, 0.0,
+                -0.0
+            ]
+        );
+
+        // Snippets near the ends are cut short and fade over at most half their length.
+        assert_eq!(samples.snippet(39, 8.0, 0.0).frames(), 5);
+        assert_eq!(
+            samples.snippet(1, 8.0, 10.0).data[..4],
+            [0.0, -0.0, 0.25, -0.125]
+        );
+        assert!(samples.snippet(60, 8.0, 2.0).data.is_empty());
+    }
+
+    #[test]
+    fn slice_clamps_to_length() {
+        let samples = Samples::new(1, 8, vec![0.0, 1.0, 2.0]);
+
+        assert_eq!(samples.slice(Selection::new(2, 10)).data, vec![2.0]);
+        assert!(samples.slice(Selection::new(5, 10)).data.is_empty());
+    }
 }
@@ -1,7 +1,17 @@
 //! Algorithms for filtering signals.
 
-use crate::dsp::buffer::Samples;
+use crate::dsp::analysis;
+use crate::dsp::buffer::{Marker, Samples, Selection};
+use crate::progress::Progress;
+use color_eyre::eyre;
 use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::f64::consts::PI;
+
+/// Number of samples in each frame of the phase vocoder, which is a power of two for the FFT.
+const VOCODER_SIZE: usize = 2048;
+/// Bounds of time stretch ratios, which keep output buffers reasonably sized.
+const STRETCH_RANGE: (f64, f64) = (1.0 / 16.0, 16.0);
 
 pub fn normalize(amplitude: f32, samples: &mut Samples) {
     let maximum = samples
@@ -16,10 +26,1470 @@ pub fn normalize(amplitude: f32, samples: &mut Samples) {
     }
 }
 
+/// Distort samples by clamping them to a threshold and amplifying the result back to full scale.
+///
+/// Thresholds above one are lowered to one, and non positive or NaN thresholds leave the samples
+/// unchanged.
+pub fn hard_clip(threshold: f32, samples: &mut Samples) {
+    if threshold.is_nan() || threshold <= 0.0 {
+        return;
+    }
+
+    let threshold = threshold.min(1.0);
+    for sample in &mut samples.data {
+        *sample = sample.clamp(-threshold, threshold) / threshold;
+    }
+}
+
+/// Distort samples by saturating them with a hyperbolic tangent curve.
+///
+/// Samples are multiplied by the drive before the curve is applied, so higher drives squash
+/// more of the signal, and the result is scaled so that full scale samples stay at full scale.
+/// Non positive or NaN drives leave the samples unchanged.
+pub fn soft_clip(drive: f32, samples: &mut Samples) {
+    if drive.is_nan() || drive <= 0.0 {
+        return;
+    }
+
+    let scale = drive.tanh();
+    for sample in &mut samples.data {
+        *sample = (*sample * drive).tanh() / scale;
+    }
+}
+
+/// Convert stereo samples from mid and side channels back to left and right channels.
+///
+/// Inverts [`encode_mid_side`] with L = M + S and R = M - S.
+///
+/// # Errors
+///
+/// Will return `Err` if the samples are not stereo.
+pub fn decode_mid_side(samples: &mut Samples) -> eyre::Result<()> {
+    if samples.channels != 2 {
+        eyre::bail!(
+            "Mid side decoding needs 2 channels but the samples have {}",
+            samples.channels
+        );
+    }
+
+    for frame in samples.iter_frames_mut() {
+        let (mid, side) = (frame[0], frame[1]);
+        frame[0] = mid + side;
+        frame[1] = mid - side;
+    }
+    Ok(())
+}
+
+/// Convert stereo samples from left and right channels to mid and side channels.
+///
+/// The first channel becomes the mid signal M = (L + R) / 2 and the second channel the side
+/// signal S = (L - R) / 2, so that widths can be edited by processing the side channel alone.
+///
+/// # Errors
+///
+/// Will return `Err` if the samples are not stereo.
+pub fn encode_mid_side(samples: &mut Samples) -> eyre::Result<()> {
+    if samples.channels != 2 {
+        eyre::bail!(
+            "Mid side encoding needs 2 channels but the samples have {}",
+            samples.channels
+        );
+    }
+
+    for frame in samples.iter_frames_mut() {
+        let (left, right) = (frame[0], frame[1]);
+        frame[0] = (left + right) / 2.0;
+        frame[1] = (left - right) / 2.0;
+    }
+    Ok(())
+}
+
+/// Widen or narrow the stereo image of samples by scaling their side channel.
+///
+/// Samples are encoded to mid and side channels, the side channel is multiplied by the width,
+/// and the result is decoded back to left and right. A width of 0.0 makes both channels the mono
+/// mid signal, 1.0 leaves the samples unchanged, and 2.0 doubles the side signal, which may clip.
+///
+/// # Errors
+///
+/// Will return `Err` if the samples are not stereo or `width` is negative or not finite.
+pub fn adjust_stereo_width(samples: &mut Samples, width: f32) -> eyre::Result<()> {
+    if !width.is_finite() || width < 0.0 {
+        eyre::bail!(
+            "Stereo width {} is not a finite value of zero or more",
+            width
+        );
+    }
+
+    encode_mid_side(samples)?;
+    for frame in samples.iter_frames_mut() {
+        frame[1] *= width;
+    }
+    decode_mid_side(samples)
+}
+
+/// Join incoming samples onto the end of samples, fading between them over a number of frames.
+///
+/// Incoming samples are converted like for [`mix`] and their markers are kept at their new
+/// positions. The fade overlaps the last frames of the samples with the first incoming frames,
+/// so the result is shorter than both combined by the length of the fade.
+///
+/// # Errors
+///
+/// Will return `Err` if the incoming samples have multiple channels but not as many as samples.
+pub fn append(incoming: &Samples, crossfade: usize, samples: &mut Samples) -> eyre::Result<()> {
+    let width = usize::from(samples.channels.max(1));
+    let (channels, step) = conform(incoming, samples)?;
+    let length = channels[0].len();
+    let frames = samples.frames();
+    let overlap = crossfade.min(frames).min(length);
+    let start = frames - overlap;
+
+    samples.data.resize((start + length) * width, 0.0);
+    let joined = samples.data[start * width..].chunks_exact_mut(width);
+    for (offset, values) in joined.enumerate() {
+        let fade = if offset < overlap {
+            (offset + 1) as f32 / (overlap + 1) as f32
+        } else {
+            1.0
+        };
+        for (channel, sample) in values.iter_mut().enumerate() {
+            *sample = sample.mul_add(1.0 - fade, channels[channel][offset] * fade);
+        }
+    }
+
+    for marker in &incoming.markers {
+        // Marker positions are non negative, so sign loss is not possible.
+        #[allow(clippy::cast_sign_loss)]
+        let frame = (marker.frame as f64 / step).round() as usize;
+        let frame = start + frame.min(length.saturating_sub(1));
+        samples
+            .markers
+            .push(Marker::new(frame, marker.label.clone()));
+    }
+    Ok(())
+}
+
+/// Join incoming samples with the same channel count and sample rate onto the end of samples.
+///
+/// Unlike [`append`], nothing is converted. The last frames of the samples and the first
+/// incoming frames overlap over a crossfade of up to `crossfade` frames with equal power
+/// cosine and sine gains, so the seam neither clicks nor dips in loudness for uncorrelated
+/// signals. Incoming markers are moved to their joined positions.
+///
+/// # Errors
+///
+/// Will return `Err` if the channel counts or sample rates differ.
+pub fn concatenate(
+    incoming: &Samples,
+    crossfade: usize,
+    samples: &mut Samples,
+) -> eyre::Result<()> {
+    if incoming.channels != samples.channels || incoming.sample_rate != samples.sample_rate {
+        eyre::bail!(
+            "Cannot join {} channels at {} Hz onto {} channels at {} Hz",
+            incoming.channels,
+            incoming.sample_rate,
+            samples.channels,
+            samples.sample_rate
+        );
+    }
+
+    let width = usize::from(samples.channels.max(1));
+    let overlap = crossfade.min(samples.frames()).min(incoming.frames());
+    let start = samples.frames() - overlap;
+
+    let seam = samples.data[start * width..].iter_mut();
+    for (index, (sample, other)) in seam.zip(&incoming.data).enumerate() {
+        let fade = (index / width + 1) as f64 / (overlap + 1) as f64 * PI / 2.0;
+        *sample = sample.mul_add(fade.cos() as f32, other * fade.sin() as f32);
+    }
+    samples
+        .data
+        .extend_from_slice(&incoming.data[overlap * width..]);
+
+    for marker in &incoming.markers {
+        samples
+            .markers
+            .push(Marker::new(start + marker.frame, marker.label.clone()));
+    }
+    Ok(())
+}
+
+/// Crossfade the end of a loop region into the frames before it, so that playing the region on
+/// repeat does not click at the seam.
+///
+/// The last frames of the region fade out over up to `crossfade` frames with equal power cosine
+/// and sine gains while as many frames before the region fade in, so the region ends on the
+/// frame just before its start. With fewer frames before the region than the crossfade, its own
+/// first frames, up to half the region, fade in instead and are cut, so the region starts where
+/// the crossfade ends. Each channel is blended separately.
+///
+/// Returns the frames of the looped region.
+///
+/// # Errors
+///
+/// Will return `Err` if the region is empty or ends after the last frame.
+pub fn crossfade_loop(
+    selection: Selection,
+    crossfade: usize,
+    samples: &mut Samples,
+) -> eyre::Result<Selection> {
+    let (start, end) = (selection.start, selection.end);
+    if selection.is_empty() || end > samples.frames() {
+        eyre::bail!(
+            "Loop region {}..{} must hold some of the {} frames",
+            start,
+            end,
+            samples.frames()
+        );
+    }
+
+    let before = crossfade.min(selection.len());
+    let (source, overlap) = if before <= start {
+        (start - before, before)
+    } else {
+        (start, crossfade.min(selection.len() / 2))
+    };
+
+    let width = usize::from(samples.channels.max(1));
+    let tail = end - overlap;
+    for offset in 0..overlap {
+        let fade = (offset + 1) as f64 / overlap as f64 * PI / 2.0;
+        let (fade_out, fade_in) = (fade.cos() as f32, fade.sin() as f32);
+        for channel in 0..width {
+            let other = samples.data[(source + offset) * width + channel];
+            let sample = &mut samples.data[(tail + offset) * width + channel];
+            *sample = sample.mul_add(fade_out, other * fade_in);
+        }
+    }
+
+    if source < start {
+        return Ok(selection);
+    }
+    let empty = samples.slice(Selection::new(0, 0));
+    samples.splice(Selection::new(start, start + overlap), empty)?;
+    Ok(Selection::new(start, end - overlap))
+}
+
+/// Convert incoming samples to the channel count and sample rate of samples.
+///
+/// Returns one signal per channel of samples and the resampling step over incoming frames.
+fn conform(incoming: &Samples, samples: &Samples) -> eyre::Result<(Vec<Vec<f32>>, f64)> {
+    let width = usize::from(samples.channels.max(1));
+    let incoming_width = usize::from(incoming.channels.max(1));
+    if incoming_width != 1 && incoming_width != width {
+        eyre::bail!(
+            "Cannot convert {} channels to {} channels",
+            incoming.channels,
+            samples.channels
+        );
+    }
+
+    let step = f64::from(incoming.sample_rate.max(1)) / f64::from(samples.sample_rate.max(1));
+    let incoming_frames = incoming.data.len() / incoming_width;
+    // Frame counts are non negative, so sign loss is not possible.
+    #[allow(clippy::cast_sign_loss)]
+    let length = (incoming_frames as f64 / step).round() as usize;
+    let channels = (0..width)
+        .map(|channel| {
+            let signal: Vec<f32> = incoming
+                .iter_frames()
+                .map(|frame| frame[channel.min(incoming_width - 1)])
+                .collect();
+            resample(&signal, step, length)
+        })
+        .collect();
+
+    Ok((channels, step))
+}
+
+/// Sum incoming samples scaled by a gain into samples starting at a frame.
+///
+/// Mono incoming samples are duplicated across channels and incoming samples with a different
+/// sample rate are resampled with linear interpolation. Samples grow if the incoming samples
+/// extend past their end. Sums are not limited, so the returned peak magnitude of the mixed
+/// frames exceeds one if they clip.
+///
+/// # Errors
+///
+/// Will return `Err` if the incoming samples have multiple channels but not as many as samples.
+pub fn mix(
+    incoming: &Samples,
+    gain: f32,
+    frame: usize,
+    samples: &mut Samples,
+) -> eyre::Result<f32> {
+    let width = usize::from(samples.channels.max(1));
+    let (channels, _step) = conform(incoming, samples)?;
+    let length = channels[0].len();
+
+    let end = (frame + length) * width;
+    if samples.data.len() < end {
+        samples.data.resize(end, 0.0);
+    }
+
+    let mut peak = 0.0_f32;
+    let mixed = samples.data[frame * width..end].chunks_exact_mut(width);
+    for (offset, values) in mixed.enumerate() {
+        for (channel, sample) in values.iter_mut().enumerate() {
+            *sample += channels[channel][offset] * gain;
+            peak = peak.max(sample.abs());
+        }
+    }
+    Ok(peak)
+}
+
+/// Move the stereo image of samples between the left and right channel with a constant power law.
+///
+/// Positions range from -1.0 for full left over 0.0 for center to 1.0 for full right. Channel
+/// gains follow a quarter cosine and sine and are scaled so that the center leaves the samples
+/// unchanged, which means the favored channel is raised by up to 3 dB and may clip.
+///
+/// # Errors
+///
+/// Will return `Err` if the samples are not stereo or `position` is out of range.
+pub fn pan(position: f32, samples: &mut Samples) -> eyre::Result<()> {
+    if samples.channels != 2 {
+        eyre::bail!(
+            "Panning needs 2 channels but the samples have {}",
+            samples.channels
+        );
+    }
+    if !(-1.0..=1.0).contains(&position) {
+        eyre::bail!("Pan position {} is not between -1.0 and 1.0", position);
+    }
+
+    let angle = (f64::from(position) + 1.0) * PI / 4.0;
+    let (left, right) = (
+        (2.0_f64.sqrt() * angle.cos()) as f32,
+        (2.0_f64.sqrt() * angle.sin()) as f32,
+    );
+    for frame in samples.iter_frames_mut() {
+        frame[0] *= left;
+        frame[1] *= right;
+    }
+    Ok(())
+}
+
+/// Scale samples by a gain in decibels, which may clip for positive gains.
+pub fn gain(decibels: f32, samples: &mut Samples) {
+    let scale = 10.0_f32.powf(decibels / 20.0);
+    samples.data.iter_mut().for_each(|sample| *sample *= scale);
+}
+
+/// Convert samples to a sample rate with linear interpolation and move markers to match.
+///
+/// # Errors
+///
+/// Will return `Err` if `sample_rate` is zero.
+pub fn change_sample_rate(sample_rate: u32, samples: &mut Samples) -> eyre::Result<()> {
+    if sample_rate == 0 {
+        eyre::bail!("Sample rate must be above zero");
+    }
+    if sample_rate == samples.sample_rate {
+        return Ok(());
+    }
+
+    let target = Samples::new(samples.channels, sample_rate, Vec::new());
+    let (channels, step) = conform(samples, &target)?;
+    let length = channels[0].len();
+    samples.data = (0..length)
+        .flat_map(|frame| channels.iter().map(move |channel| channel[frame]))
+        .collect();
+    samples.sample_rate = sample_rate;
+    for marker in &mut samples.markers {
+        // Marker positions are non negative, so sign loss is not possible.
+        #[allow(clippy::cast_sign_loss)]
+        let frame = (marker.frame as f64 / step).round() as usize;
+        marker.frame = frame.min(length.saturating_sub(1));
+    }
+    Ok(())
+}
+
+/// Mix all channels of samples down to a single channel by averaging each frame.
+pub fn to_mono(samples: &mut Samples) {
+    let width = usize::from(samples.channels.max(1));
+    samples.data = samples
+        .iter_frames()
+        .map(|frame| frame.iter().sum::<f32>() / width as f32)
+        .collect();
+    samples.channels = 1;
+}
+
+/// Remove leading and trailing frames whose channels are all quieter than a threshold in decibels.
+///
+/// Markers in the removed frames are dropped and the others are moved with the kept frames.
+/// Samples that are silent throughout become empty.
+pub fn trim_silence(threshold_db: f32, samples: &mut Samples) {
+    let width = usize::from(samples.channels.max(1));
+    let threshold = 10.0_f32.powf(threshold_db / 20.0);
+    let loud = |frame: &[f32]| frame.iter().any(|sample| sample.abs() >= threshold);
+
+    let frames: Vec<&[f32]> = samples.iter_frames().collect();
+    let start = frames.iter().position(|frame| loud(frame));
+    let end = frames.iter().rposition(|frame| loud(frame));
+    let (start, end) = match (start, end) {
+        (Some(start), Some(end)) => (start, end + 1),
+        _ => (0, 0),
+    };
+
+    samples.data = samples.data[start * width..end * width].to_vec();
+    samples
+        .markers
+        .retain(|marker| (start..end).contains(&marker.frame));
+    for marker in &mut samples.markers {
+        marker.frame -= start;
+    }
+}
+
+/// Reduce the level of samples above a threshold in decibels with a feedforward peak compressor.
+///
+/// The loudest channel of each frame drives an envelope that rises over `attack_frames` and
+/// falls over `release_frames` with exponential smoothing, so all channels get the same gain.
+/// Envelope levels above the threshold are lowered to the threshold plus their excess divided by
+/// the ratio. Ratios of 1000 or more act as a brick wall limiter when the attack is zero.
+///
+/// # Errors
+///
+/// Will return `Err` if `ratio` is below one or NaN or `threshold_db` is NaN.
+pub fn compress(
+    samples: &mut Samples,
+    threshold_db: f32,
+    ratio: f32,
+    attack_frames: usize,
+    release_frames: usize,
+) -> eyre::Result<()> {
+    if ratio.is_nan() || ratio < 1.0 {
+        eyre::bail!("Compression ratio {} must be at least 1", ratio);
+    }
+    if threshold_db.is_nan() {
+        eyre::bail!("Compression threshold must be a number");
+    }
+
+    // Smoothing coefficients reach about 63% of a level change within the given frames.
+    let coefficient = |frames: usize| {
+        if frames == 0 {
+            0.0
+        } else {
+            (-1.0 / frames as f32).exp()
+        }
+    };
+    let (attack, release) = (coefficient(attack_frames), coefficient(release_frames));
+    let mut envelope = 0.0_f32;
+
+    for frame in samples.iter_frames_mut() {
+        let level = frame
+            .iter()
+            .fold(0.0_f32, |peak, sample| peak.max(sample.abs()));
+        let smoothing = if level > envelope { attack } else { release };
+        envelope = envelope.mul_add(smoothing, level * (1.0 - smoothing));
+
+        let excess = 20.0 * envelope.log10() - threshold_db;
+        if excess > 0.0 {
+            let scale = 10.0_f32.powf((excess / ratio - excess) / 20.0);
+            frame.iter_mut().for_each(|sample| *sample *= scale);
+        }
+    }
+    Ok(())
+}
+
+/// Mix repeating echoes of samples back in, extending them until the echoes fade out.
+///
+/// Echoes repeat every `delay_frames` frames from a circular buffer and are scaled by `feedback`
+/// on each repeat. Output is the dry signal scaled by `1 - wet` plus the echoes scaled by `wet`,
+/// and samples grow by whole delays until the echoes are below -80 dBFS.
+///
+/// # Errors
+///
+/// Will return `Err` if `delay_frames` is zero, `feedback` is not at least zero and below one, or
+/// `wet` is not between zero and one.
+pub fn delay(
+    samples: &mut Samples,
+    delay_frames: usize,
+    feedback: f32,
+    wet: f32,
+) -> eyre::Result<()> {
+    if delay_frames == 0 {
+        eyre::bail!("Delay must be at least one frame");
+    }
+    if !(0.0..1.0).contains(&feedback) {
+        eyre::bail!(
+            "Delay feedback {} is not at least 0.0 and below 1.0",
+            feedback
+        );
+    }
+    if !(0.0..=1.0).contains(&wet) {
+        eyre::bail!("Delay wet level {} is not between 0.0 and 1.0", wet);
+    }
+
+    let width = usize::from(samples.channels.max(1));
+    let floor = 10.0_f32.powf(-80.0 / 20.0);
+    let mut line = vec![0.0_f32; delay_frames * width];
+    let mut position = 0;
+    let mut echo = |input: f32| {
+        let output = line[position];
+        line[position] = feedback.mul_add(output, input);
+        position = (position + 1) % line.len();
+        output
+    };
+
+    for sample in &mut samples.data {
+        *sample = (1.0 - wet).mul_add(*sample, wet * echo(*sample));
+    }
+    // Each pass over the delay line plays one more repeat, which only gets quieter after the
+    // first pass below the floor.
+    loop {
+        let tail: Vec<f32> = (0..delay_frames * width)
+            .map(|_index| wet * echo(0.0))
+            .collect();
+        if tail.iter().all(|sample| sample.abs() < floor) {
+            break;
+        }
+        samples.data.extend(tail);
+    }
+    Ok(())
+}
+
+/// Center every channel of samples on zero by subtracting its mean.
+///
+/// Offsets are measured with [`analysis::dc_offset`]. Use [`high_pass`] at a few hertz instead for
+/// offsets that drift over time.
+pub fn remove_dc_offset(samples: &mut Samples) {
+    let offsets = analysis::dc_offset(samples);
+    for frame in samples.iter_frames_mut() {
+        for (sample, offset) in frame.iter_mut().zip(&offsets) {
+            *sample -= offset;
+        }
+    }
+}
+
+/// Reduce the amplitude resolution of samples to a bit depth and hold each frame for a number of
+/// frames, for lo-fi sound with quantization noise and aliasing.
+///
+/// Samples are rounded to the nearest of `2^bits` evenly spaced levels from -1 up to one step
+/// below 1, like signed integer PCM, and louder samples are clamped to the outermost levels. Bit
+/// depths of zero or at least 24, the precision of `f32`, keep the amplitude resolution.
+/// Downsample factors of zero or one keep every frame.
+pub fn bitcrush(samples: &mut Samples, bits: u32, downsample_factor: u32) {
+    let width = usize::from(samples.channels.max(1));
+    let factor = usize::try_from(downsample_factor)
+        .unwrap_or(usize::MAX)
+        .max(1);
+    if factor > 1 {
+        for block in samples.data.chunks_mut(width * factor) {
+            let (held, rest) = block.split_at_mut(width.min(block.len()));
+            for frame in rest.chunks_mut(width) {
+                frame.copy_from_slice(&held[..frame.len()]);
+            }
+        }
+    }
+
+    if bits == 0 || bits >= 24 {
+        return;
+    }
+    let half = f64::from(1_u32 << (bits - 1));
+    for sample in &mut samples.data {
+        // Adding positive zero keeps quiet negative samples from becoming negative zero.
+        let level = (f64::from(*sample) * half).round().clamp(-half, half - 1.0) + 0.0;
+        // Levels are within full scale, so truncation is not possible.
+        #[allow(clippy::cast_possible_truncation)]
+        let quantized = (level / half) as f32;
+        *sample = quantized;
+    }
+}
+
+/// Multiply every channel of samples by a sine carrier in hertz for metallic sidebands.
+///
+/// The carrier phase is computed from the frame index, so it stays continuous across the whole
+/// buffer and is shared by all channels.
+///
+/// # Errors
+///
+/// Will return `Err` if `carrier_hz` is not above zero and below the Nyquist frequency.
+pub fn ring_modulate(samples: &mut Samples, carrier_hz: f32) -> eyre::Result<()> {
+    let nyquist = f64::from(samples.sample_rate) / 2.0;
+    let carrier = f64::from(carrier_hz);
+    if carrier.is_nan() || carrier <= 0.0 || carrier >= nyquist {
+        eyre::bail!(
+            "Carrier {} Hz must be above 0 Hz and below the Nyquist frequency of {} Hz",
+            carrier,
+            nyquist
+        );
+    }
+
+    let step = 2.0 * PI * carrier / f64::from(samples.sample_rate);
+    for (index, frame) in samples.iter_frames_mut().enumerate() {
+        let gain = (step * index as f64).sin() as f32;
+        frame.iter_mut().for_each(|sample| *sample *= gain);
+    }
+    Ok(())
+}
+
+/// Modulate the amplitude of samples with a sine at a rate in hertz and a depth.
+///
+/// Frames are scaled by `1 - depth * (0.5 - 0.5 * sin(2π * rate_hz * t))` for time `t` in
+/// seconds, so their gain swings between `1 - depth` and one. Depths are clamped to between zero
+/// and one, and all channels of a frame share the same gain to stay in sync.
+///
+/// # Errors
+///
+/// Will return `Err` if `rate_hz` is negative or not finite.
+pub fn tremolo(samples: &mut Samples, rate_hz: f32, depth: f32) -> eyre::Result<()> {
+    if !rate_hz.is_finite() || rate_hz < 0.0 {
+        eyre::bail!(
+            "Tremolo rate {} Hz is not a finite value of zero or more",
+            rate_hz
+        );
+    }
+
+    let depth = if depth.is_nan() {
+        0.0
+    } else {
+        depth.clamp(0.0, 1.0)
+    };
+    let step = 2.0 * PI * f64::from(rate_hz) / f64::from(samples.sample_rate.max(1));
+    for (index, frame) in samples.iter_frames_mut().enumerate() {
+        let modulator = 0.5_f64.mul_add(-(step * index as f64).sin(), 0.5) as f32;
+        let gain = depth.mul_add(-modulator, 1.0);
+        frame.iter_mut().for_each(|sample| *sample *= gain);
+    }
+    Ok(())
+}
+
+/// Fade samples toward silence once they stay quieter than a threshold in decibels for longer
+/// than a hold time.
+///
+/// The envelope of each channel follows its peaks immediately and falls over `release_frames`
+/// with exponential smoothing, so it does not drop between the peaks of a waveform. A frame is
+/// quiet when the envelopes of all channels are below the threshold, so loud channels keep the
+/// gate open for quiet ones and the stereo image does not shift. The first `hold_frames` frames of
+/// each quiet run are kept so that decays and short pauses between transients are not chopped.
+/// After that the gain ramps linearly to zero over `release_frames`, and back to one over
+/// `attack_frames` once a frame is loud again, so the gate does not click or chatter. Times of
+/// zero frames switch the gain at once. NaN thresholds leave the samples unchanged.
+pub fn noise_gate(
+    samples: &mut Samples,
+    threshold_db: f32,
+    hold_frames: usize,
+    attack_frames: usize,
+    release_frames: usize,
+) {
+    if threshold_db.is_nan() {
+        return;
+    }
+
+    let threshold = 10.0_f32.powf(threshold_db / 20.0);
+    let step = |frames: usize| {
+        if frames == 0 {
+            1.0
+        } else {
+            1.0 / frames as f32
+        }
+    };
+    let (attack, release) = (step(attack_frames), step(release_frames));
+    let decay = if release_frames == 0 {
+        0.0
+    } else {
+        (-1.0 / release_frames as f32).exp()
+    };
+
+    let width = usize::from(samples.channels.max(1));
+    let mut envelopes = vec![0.0_f32; width];
+    let mut gain = 1.0_f32;
+    let mut quiet = 0;
+    for frame in samples.iter_frames_mut() {
+        let mut loud = false;
+        for (sample, envelope) in frame.iter().zip(&mut envelopes) {
+            *envelope = sample.abs().max(*envelope * decay);
+            loud |= *envelope >= threshold;
+        }
+
+        if loud {
+            quiet = 0;
+            gain = (gain + attack).min(1.0);
+        } else {
+            quiet += 1;
+            if quiet > hold_frames {
+                gain = (gain - release).max(0.0);
+            }
+        }
+        frame.iter_mut().for_each(|sample| *sample *= gain);
+    }
+}
+
+/// Attenuate frequencies of samples above a cutoff in hertz with a second order Butterworth filter.
+///
+/// Coefficients are computed for the sample rate of the samples, and every channel is filtered
+/// with its own state, so channels do not bleed into each other.
+///
+/// # Errors
+///
+/// Will return `Err` if `cutoff` is not between zero and the Nyquist frequency.
+pub fn low_pass(cutoff: f32, samples: &mut Samples) -> eyre::Result<()> {
+    let coefficients = butterworth(cutoff, samples.sample_rate, false)?;
+    biquad(coefficients, samples);
+    Ok(())
+}
+
+/// Attenuate frequencies of samples below a cutoff in hertz with a second order Butterworth filter.
+///
+/// Channels are filtered independently like for [`low_pass`].
+///
+/// # Errors
+///
+/// Will return `Err` if `cutoff` is not between zero and the Nyquist frequency.
+pub fn high_pass(cutoff: f32, samples: &mut Samples) -> eyre::Result<()> {
+    let coefficients = butterworth(cutoff, samples.sample_rate, true)?;
+    biquad(coefficients, samples);
+    Ok(())
+}
+
+/// Compute normalized biquad coefficients `[b0, b1, b2, a1, a2]` of a Butterworth low or high
+/// pass filter with the bilinear transform.
+fn butterworth(cutoff: f32, sample_rate: u32, high: bool) -> eyre::Result<[f64; 5]> {
+    let nyquist = f64::from(sample_rate) / 2.0;
+    let cutoff = f64::from(cutoff);
+    if cutoff.is_nan() || cutoff <= 0.0 || cutoff >= nyquist {
+        eyre::bail!(
+            "Cutoff {} Hz must be above 0 Hz and below the Nyquist frequency of {} Hz",
+            cutoff,
+            nyquist
+        );
+    }
+
+    let omega = PI * cutoff / nyquist;
+    let alpha = omega.sin() / 2.0_f64.sqrt();
+    let cosine = omega.cos();
+    let a0 = 1.0 + alpha;
+    let (b0, b1) = if high {
+        ((1.0 + cosine) / 2.0, -(1.0 + cosine))
+    } else {
+        ((1.0 - cosine) / 2.0, 1.0 - cosine)
+    };
+    Ok([
+        b0 / a0,
+        b1 / a0,
+        b0 / a0,
+        -2.0 * cosine / a0,
+        (1.0 - alpha) / a0,
+    ])
+}
+
+/// Filter every channel of samples separately with biquad coefficients in direct form I.
+fn biquad(coefficients: [f64; 5], samples: &mut Samples) {
+    let [b0, b1, b2, a1, a2] = coefficients;
+    let width = usize::from(samples.channels.max(1));
+
+    // Each channel keeps its last two inputs and outputs.
+    let mut states = vec![[0.0_f64; 4]; width];
+    for frame in samples.iter_frames_mut() {
+        for (sample, [x1, x2, y1, y2]) in frame.iter_mut().zip(&mut states) {
+            let x0 = f64::from(*sample);
+            let y0 = b0 * x0 + b1 * *x1 + b2 * *x2 - a1 * *y1 - a2 * *y2;
+            *x2 = *x1;
+            *x1 = x0;
+            *y2 = *y1;
+            *y1 = y0;
+            *sample = y0 as f32;
+        }
+    }
+}
+
+/// Transpose samples by semitones while keeping their length.
+///
+/// Each channel is time stretched by the pitch ratio with a phase vocoder and then resampled back
+/// to its original length. The vocoder advances the phase of every frequency bin by its measured
+/// frequency, so steady tones keep their level without the echo of plain overlap-add, while
+/// transients are smeared slightly. Frames are taken from the same positions in every channel to
+/// keep multichannel signals coherent. A shift of zero semitones leaves the samples unchanged.
+pub fn pitch_shift(semitones: f32, samples: &mut Samples) {
+    pitch_shift_with_progress(semitones, samples, &mut |_fraction| {});
+}
+
+/// Transpose samples like [`pitch_shift`] while reporting the completed fraction to a callback.
+pub fn pitch_shift_with_progress(
+    semitones: f32,
+    samples: &mut Samples,
+    progress: &mut dyn FnMut(f32),
+) {
+    let mut progress = Progress::new(progress);
+    let frames = samples.frames();
+    if frames == 0 || semitones == 0.0 {
+        progress.finish();
+        return;
+    }
+
+    let ratio = 2.0_f64.powf(f64::from(semitones) / 12.0);
+    let channels = samples.split_channels();
+    let width = channels.len();
+    let shifted: Vec<Vec<f32>> = channels
+        .iter()
+        .enumerate()
+        .map(|(channel, signal)| {
+            let stretched = vocode(signal, ratio, &mut |fraction| {
+                progress.update((channel as f32 + fraction) / width as f32);
+            });
+            resample(&stretched, ratio, frames)
+        })
+        .collect();
+    interleave(samples, shifted);
+    progress.finish();
+}
+
+/// Change the length of samples by a ratio while keeping their pitch.
+///
+/// Ratios above one lengthen and ratios below one shorten the samples. Ratios are clamped to
+/// between a sixteenth and sixteen, and non positive or NaN ratios leave the samples unchanged.
+/// Channels are stretched with the same phase vocoder as [`pitch_shift`] and markers are moved to
+/// their stretched positions.
+pub fn time_stretch(ratio: f32, samples: &mut Samples) {
+    time_stretch_with_progress(ratio, samples, &mut |_fraction| {});
+}
+
+/// Stretch samples like [`time_stretch`] while reporting the completed fraction to a callback.
+pub fn time_stretch_with_progress(
+    ratio: f32,
+    samples: &mut Samples,
+    progress: &mut dyn FnMut(f32),
+) {
+    let mut progress = Progress::new(progress);
+    if samples.frames() == 0 || ratio.is_nan() || ratio <= 0.0 || ratio == 1.0 {
+        progress.finish();
+        return;
+    }
+
+    let ratio = f64::from(ratio).clamp(STRETCH_RANGE.0, STRETCH_RANGE.1);
+    let channels = samples.split_channels();
+    let width = channels.len();
+    let stretched: Vec<Vec<f32>> = channels
+        .iter()
+        .enumerate()
+        .map(|(channel, signal)| {
+            vocode(signal, ratio, &mut |fraction| {
+                progress.update((channel as f32 + fraction) / width as f32);
+            })
+        })
+        .collect();
+
+    interleave(samples, stretched);
+    let length = samples.frames();
+    for marker in &mut samples.markers {
+        // Marker positions are non negative, so sign loss is not possible.
+        #[allow(clippy::cast_sign_loss)]
+        let frame = (marker.frame as f64 * ratio).round() as usize;
+        marker.frame = frame.min(length.saturating_sub(1));
+    }
+    progress.finish();
+}
+
+/// Replace the samples with processed signals of each channel, which all have the same length.
+fn interleave(samples: &mut Samples, channels: Vec<Vec<f32>>) {
+    // Every channel is processed to the same length, so interleaving cannot fail.
+    if let Ok(processed) = Samples::from_channels(channels, samples.sample_rate) {
+        samples.data = processed.data;
+    }
+}
+
+/// Read a signal at evenly spaced positions with linear interpolation.
+fn resample(signal: &[f32], step: f64, length: usize) -> Vec<f32> {
+    if signal.is_empty() {
+        return vec![0.0; length];
+    }
+    let last = signal.len() - 1;
+
+    (0..length)
+        .map(|index| {
+            let position = index as f64 * step;
+            // Position is non negative, so sign loss is not possible.
+            #[allow(clippy::cast_sign_loss)]
+            let lower = (position.floor() as usize).min(last);
+            let upper = (lower + 1).min(last);
+            let fraction = (position - lower as f64).clamp(0.0, 1.0) as f32;
+            signal[lower].mul_add(1.0 - fraction, signal[upper] * fraction)
+        })
+        .collect()
+}
+
+/// Change the length of a signal by a factor without changing its pitch using a phase vocoder.
+///
+/// Frames centered every `hop / factor` samples are windowed and transformed, and each bin is
+/// resynthesized every `hop` samples with its phase advanced by the frequency measured from the
+/// phase change between analysis frames. Samples outside the signal are treated as silence, so
+/// every output sample is covered by the same number of frames. The fraction of written frames is
+/// reported after each frame.
+fn vocode(signal: &[f32], factor: f64, progress: &mut dyn FnMut(f32)) -> Vec<f32> {
+    let size = VOCODER_SIZE;
+    let (half, hop) = (size / 2, size / 4);
+    // Length and frame positions are non negative, so sign loss is not possible. Signals that
+    // shrink below half a sample keep one sample, so stretching never empties them.
+    #[allow(clippy::cast_sign_loss)]
+    let length =
+        ((signal.len() as f64 * factor).round() as usize).max(usize::from(!signal.is_empty()));
+
+    let window: Vec<f64> = (0..size)
+        .map(|index| ((index as f64 + 0.5) * PI / size as f64).sin().powi(2))
+        .collect();
+    let bin_step = 2.0 * PI / size as f64;
+    let mut analysis = vec![0.0_f64; half + 1];
+    let mut synthesis = vec![0.0_f64; half + 1];
+    let mut previous: Option<usize> = None;
+    let mut output = vec![0.0_f64; length + 2 * size];
+    let mut weights = vec![0.0_f64; length + 2 * size];
+    let mut buffer = vec![(0.0_f64, 0.0_f64); size];
+
+    for center in (0..length + half).step_by(hop) {
+        #[allow(clippy::cast_sign_loss)]
+        let source = (center as f64 / factor).round() as usize;
+        for (offset, (slot, weight)) in buffer.iter_mut().zip(&window).enumerate() {
+            let sample = (source + offset)
+                .checked_sub(half)
+                .and_then(|index| signal.get(index))
+                .map_or(0.0, |sample| f64::from(*sample));
+            *slot = (sample * weight, 0.0);
+        }
+        fft(&mut buffer, false);
+
+        for bin in 0..=half {
+            let (real, imaginary) = buffer[bin];
+            let magnitude = real.hypot(imaginary);
+            let phase = imaginary.atan2(real);
+
+            synthesis[bin] = match previous {
+                Some(last) if source > last => {
+                    let elapsed = (source - last) as f64;
+                    let expected = bin as f64 * bin_step * elapsed;
+                    let deviation = wrap_phase(phase - analysis[bin] - expected);
+                    let frequency = bin as f64 * bin_step + deviation / elapsed;
+                    synthesis[bin] + frequency * hop as f64
+                }
+                Some(_) => synthesis[bin] + bin as f64 * bin_step * hop as f64,
+                None => phase,
+            };
+            analysis[bin] = phase;
+
+            let value = (
+                magnitude * synthesis[bin].cos(),
+                magnitude * synthesis[bin].sin(),
+            );
+            buffer[bin] = value;
+            if bin > 0 && bin < half {
+                buffer[size - bin] = (value.0, -value.1);
+            }
+        }
+        previous = Some(source);
+        fft(&mut buffer, true);
+
+        // Output positions are offset by half a frame, which is removed when collecting samples.
+        for (offset, ((real, _imaginary), weight)) in buffer.iter().zip(&window).enumerate() {
+            output[center + offset] += real * weight;
+            weights[center + offset] += weight * weight;
+        }
+        progress(center as f32 / (length + half) as f32);
+    }
+
+    output[half..half + length]
+        .iter()
+        .zip(&weights[half..half + length])
+        .map(|(sample, weight)| (sample / weight.max(f64::EPSILON)) as f32)
+        .collect()
+}
+
+/// Wrap a phase in radians to between minus and plus pi.
+fn wrap_phase(phase: f64) -> f64 {
+    phase - 2.0 * PI * (phase / (2.0 * PI)).round()
+}
+
+/// Compute the discrete Fourier transform of complex values in place.
+///
+/// The length must be a power of two. The inverse transform is scaled by the length, so that it
+/// undoes the forward transform.
+fn fft(buffer: &mut [(f64, f64)], inverse: bool) {
+    let size = buffer.len();
+    let bits = size.trailing_zeros();
+
+    for index in 0..size {
+        let reversed = index.reverse_bits() >> (usize::BITS - bits);
+        if index < reversed {
+            buffer.swap(index, reversed);
+        }
+    }
+
+    let sign = if inverse { 1.0 } else { -1.0 };
+    let mut length = 2;
+    while length <= size {
+        let angle = sign * 2.0 * PI / length as f64;
+        for start in (0..size).step_by(length) {
+            for offset in 0..length / 2 {
+                let (sine, cosine) = (angle * offset as f64).sin_cos();
+                let (real, imaginary) = buffer[start + offset + length / 2];
+                let twiddled = (
+                    real * cosine - imaginary * sine,
+                    real * sine + imaginary * cosine,
+                );
+                let even = buffer[start + offset];
+                buffer[start + offset] = (even.0 + twiddled.0, even.1 + twiddled.1);
+                buffer[start + offset + length / 2] = (even.0 - twiddled.0, even.1 - twiddled.1);
+            }
+        }
+        length *= 2;
+    }
+
+    if inverse {
+        for value in buffer.iter_mut() {
+            *value = (value.0 / size as f64, value.1 / size as f64);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Count sign changes of the first channel.
+    fn zero_crossings(samples: &Samples) -> usize {
+        samples.split_channels()[0]
+            .windows(2)
+            .filter(|pair| (pair[0] < 0.0) != (pair[1] < 0.0))
+            .count()
+    }
+
+    fn sine(frequency: f32, sample_rate: u32, frames: usize) -> Vec<f32> {
+        (0..frames)
+            .map(|index| {
+                (2.0 * std::f32::consts::PI * frequency * index as f32 / sample_rate as f32).sin()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn pitch_shift_octave_up() {
+        let mut samples = Samples::new(1, 8000, sine(200.0, 8000, 8000));
+        let before = zero_crossings(&samples);
+
+        pitch_shift(12.0, &mut samples);
+        let after = zero_crossings(&samples);
+
+        assert_eq!(samples.data.len(), 8000);
+        let ratio = after as f64 / before as f64;
+        assert!((1.9..2.1).contains(&ratio), "ratio {}", ratio);
+    }
+
+    #[test]
+    fn pitch_shift_keeps_tone_level() {
+        let mut samples = Samples::new(1, 8000, sine(400.0, 8000, 8000));
+        pitch_shift(7.0, &mut samples);
+
+        // Skip the edges, where frames reach past the signal.
+        let steady = &samples.data[1000..7000];
+        let shifted = 400.0 * 2.0_f64.powf(7.0 / 12.0);
+        assert!((magnitude(steady, shifted, 8000) - 1.0).abs() < 0.1);
+        assert!(magnitude(steady, 400.0, 8000) < 0.05);
+
+        let mut silence = Samples::new(2, 8000, vec![0.0; 200]);
+        pitch_shift(-24.0, &mut silence);
+        assert_eq!(silence.data, vec![0.0; 200]);
+    }
+
+    #[test]
+    fn pitch_shift_zero_is_identity() {
+        let data = sine(250.0, 8000, 1000);
+        let mut samples = Samples::new(1, 8000, data.clone());
+        pitch_shift(0.0, &mut samples);
+        assert_eq!(samples.data, data);
+    }
+
+    #[test]
+    fn pitch_shift_single_frame() {
+        assert_eq!(resample(&[], 0.5, 3), vec![0.0; 3]);
+
+        for semitones in [-24.0, -13.0, 24.0] {
+            let mut samples = Samples::new(2, 44_100, vec![0.5, -0.5]);
+            pitch_shift(semitones, &mut samples);
+            assert_eq!(samples.frames(), 1);
+        }
+    }
+
+    #[test]
+    fn fft_round_trip() {
+        let signal: Vec<(f64, f64)> = (0..16).map(|index| ((index as f64).sin(), 0.0)).collect();
+        let mut buffer = signal.clone();
+
+        fft(&mut buffer, false);
+        let dc: f64 = signal.iter().map(|value| value.0).sum();
+        assert!((buffer[0].0 - dc).abs() < 1e-9 && buffer[0].1.abs() < 1e-9);
+
+        fft(&mut buffer, true);
+        assert!(buffer
+            .iter()
+            .zip(&signal)
+            .all(
+                |(actual, expected)| (actual.0 - expected.0).abs() < 1e-9 && actual.1.abs() < 1e-9
+            ));
+    }
+
+    #[test]
+    fn pitch_shift_keeps_channels_coherent() {
+        let left = sine(300.0, 8000, 4000);
+        let data = left.iter().flat_map(|sample| [*sample, -sample]).collect();
+        let mut samples = Samples::new(2, 8000, data);
+
+        pitch_shift(-5.0, &mut samples);
+
+        assert_eq!(samples.data.len(), 8000);
+        assert!(samples
+            .data
+            .chunks(2)
+            .all(|frame| (frame[0] + frame[1]).abs() < 1e-6));
+    }
+
+    /// Measure the amplitude of a frequency in a mono signal with the Goertzel algorithm.
+    fn magnitude(signal: &[f32], frequency: f64, sample_rate: u32) -> f64 {
+        let coefficient = 2.0 * (2.0 * PI * frequency / f64::from(sample_rate)).cos();
+        let (mut previous, mut before) = (0.0, 0.0);
+        for sample in signal {
+            let current = f64::from(*sample) + coefficient * previous - before;
+            before = previous;
+            previous = current;
+        }
+        let power = previous * previous + before * before - coefficient * previous * before;
+        2.0 * power.sqrt() / signal.len() as f64
+    }
+
+    #[test]
+    fn hard_clip_adds_odd_harmonics() {
+        let mut samples = Samples::new(1, 8000, sine(200.0, 8000, 8000));
+        assert!(magnitude(&samples.data, 600.0, 8000) < 1e-3);
+
+        hard_clip(0.5, &mut samples);
+
+        let peak = samples
+            .data
+            .iter()
+            .fold(0.0_f32, |peak, x| peak.max(x.abs()));
+        assert!((peak - 1.0).abs() < 1e-6);
+        assert!(magnitude(&samples.data, 600.0, 8000) > 0.05);
+        assert!(magnitude(&samples.data, 1000.0, 8000) > 0.01);
+        // Symmetric clipping keeps the waveform odd, so even harmonics stay absent.
+        assert!(magnitude(&samples.data, 400.0, 8000) < 1e-3);
+
+        let clipped = samples.data.clone();
+        hard_clip(0.0, &mut samples);
+        hard_clip(f32::NAN, &mut samples);
+        assert_eq!(samples.data, clipped);
+    }
+
+    #[test]
+    fn soft_clip_adds_harmonics_with_drive() {
+        let original = sine(200.0, 8000, 8000);
+        let third = |drive| {
+            let mut samples = Samples::new(1, 8000, original.clone());
+            soft_clip(drive, &mut samples);
+            assert!(samples.data.iter().all(|x| x.abs() <= 1.0 + 1e-6));
+            magnitude(&samples.data, 600.0, 8000)
+        };
+
+        let (gentle, heavy) = (third(0.5), third(8.0));
+        assert!(gentle > 1e-3);
+        assert!(heavy > 0.1);
+        assert!(heavy > gentle);
+
+        let mut samples = Samples::new(1, 8000, original.clone());
+        soft_clip(-1.0, &mut samples);
+        assert_eq!(samples.data, original);
+    }
+
+    #[test]
+    fn adjust_stereo_width_scales_side() {
+        let original = Samples::new(2, 8000, vec![0.5, 0.25, -0.5, 0.5]);
+        let widened = |width| {
+            let mut samples = original.clone();
+            adjust_stereo_width(&mut samples, width).unwrap();
+            samples.data
+        };
+
+        assert_eq!(widened(0.0), vec![0.375, 0.375, 0.0, 0.0]);
+        assert_eq!(widened(1.0), original.data);
+        assert_eq!(widened(2.0), vec![0.625, 0.125, -1.0, 1.0]);
+    }
+
+    #[test]
+    fn adjust_stereo_width_rejects_invalid_input() {
+        let mut mono = Samples::new(1, 8000, vec![0.5; 4]);
+        assert!(adjust_stereo_width(&mut mono, 1.5).is_err());
+        assert_eq!(mono.data, vec![0.5; 4]);
+
+        let mut stereo = Samples::new(2, 8000, vec![0.5, 0.25]);
+        assert!(adjust_stereo_width(&mut stereo, -0.5).is_err());
+        assert!(adjust_stereo_width(&mut stereo, f32::NAN).is_err());
+        assert_eq!(stereo.data, vec![0.5, 0.25]);
+    }
+
+    #[test]
+    fn mid_side_round_trip() {
+        let original = Samples::new(2, 8000, vec![0.5, 0.25, -1.0, 0.75, 0.1, -0.3, 0.0, 0.0]);
+        let mut samples = original.clone();
+
+        let close = |actual: &[f32], expected: &[f32]| {
+            actual
+                .iter()
+                .zip(expected)
+                .all(|(x, y)| (x - y).abs() <= f32::EPSILON)
+        };
+
+        encode_mid_side(&mut samples).unwrap();
+        let expected = [0.375, 0.125, -0.125, -0.875, -0.1, 0.2, 0.0, 0.0];
+        assert!(close(&samples.data, &expected), "{:?}", samples.data);
+
+        decode_mid_side(&mut samples).unwrap();
+        assert!(close(&samples.data, &original.data), "{:?}", samples.data);
+    }
+
+    #[test]
+    fn mid_side_rejects_mono() {
+        let mut mono = Samples::new(1, 8000, vec![0.5; 4]);
+
+        assert!(encode_mid_side(&mut mono).is_err());
+        assert!(decode_mid_side(&mut mono).is_err());
+        assert_eq!(mono.data, vec![0.5; 4]);
+    }
+
+    #[test]
+    fn pan_keeps_power_constant() {
+        let original = Samples::new(2, 8000, vec![0.5; 8]);
+        let panned = |position| {
+            let mut samples = original.clone();
+            pan(position, &mut samples).unwrap();
+            (samples.data[0], samples.data[1])
+        };
+
+        let (left, right) = panned(0.0);
+        assert!((left - 0.5).abs() < 1e-6 && (right - 0.5).abs() < 1e-6);
+        let (left, right) = panned(-1.0);
+        assert!((left - 0.5 * 2.0_f32.sqrt()).abs() < 1e-6 && right.abs() < 1e-6);
+        let (left, right) = panned(1.0);
+        assert!(left.abs() < 1e-6 && (right - 0.5 * 2.0_f32.sqrt()).abs() < 1e-6);
+
+        for position in [-0.75, -0.2, 0.3, 0.9] {
+            let (left, right) = panned(position);
+            assert!((left.powi(2) + right.powi(2) - 0.5).abs() < 1e-6);
+            assert_eq!(left > right, position < 0.0);
+        }
+    }
+
+    #[test]
+    fn pan_rejects_invalid_input() {
+        let mut mono = Samples::new(1, 8000, vec![0.5; 4]);
+        assert!(pan(0.5, &mut mono).is_err());
+        assert_eq!(mono.data, vec![0.5; 4]);
+
+        let mut stereo = Samples::new(2, 8000, vec![0.5; 4]);
+        assert!(pan(1.5, &mut stereo).is_err());
+        assert!(pan(f32::NAN, &mut stereo).is_err());
+        assert_eq!(stereo.data, vec![0.5; 4]);
+    }
+
+    #[test]
+    fn time_stretch_scales_length() {
+        for ratio in [0.5, 0.999, 1.001, 1.5, 3.0] {
+            let mut samples = Samples::new(2, 8000, vec![0.25; 8000]);
+
+            time_stretch(ratio, &mut samples);
+
+            let expected = 4000.0 * f64::from(ratio);
+            assert!(
+                (samples.frames() as f64 - expected).abs() <= 1.0,
+                "ratio {}",
+                ratio
+            );
+            assert_eq!(samples.data.len(), samples.frames() * 2);
+        }
+    }
+
+    #[test]
+    fn time_stretch_within_range_of_filter() {
+        for ratio in [0.25, 4.0] {
+            let mut samples = Samples::new(1, 8000, sine(200.0, 8000, 3000));
+
+            time_stretch(ratio, &mut samples);
+
+            let expected = 3000.0 * f64::from(ratio);
+            assert!((samples.frames() as f64 - expected).abs() <= 1.0);
+        }
+    }
+
+    #[test]
+    fn time_stretch_keeps_frequency() {
+        let mut samples = Samples::new(1, 8000, sine(200.0, 8000, 8000));
+        let before = zero_crossings(&samples) as f64 / 8000.0;
+
+        time_stretch(2.0, &mut samples);
+        let after = zero_crossings(&samples) as f64 / samples.frames() as f64;
+
+        assert_eq!(samples.frames(), 16000);
+        let ratio = after / before;
+        assert!((0.95..1.05).contains(&ratio), "ratio {}", ratio);
+    }
+
+    #[test]
+    fn time_stretch_extreme_ratios() {
+        let data = sine(200.0, 8000, 100);
+        let mut samples = Samples::new(1, 8000, data.clone());
+        time_stretch(1.0, &mut samples);
+        assert_eq!(samples.data, data);
+
+        time_stretch(f32::INFINITY, &mut samples);
+        assert_eq!(samples.frames(), 1600);
+        time_stretch(f32::NAN, &mut samples);
+        time_stretch(-2.0, &mut samples);
+        assert_eq!(samples.frames(), 1600);
+        time_stretch(1e-9, &mut samples);
+        assert_eq!(samples.frames(), 100);
+
+        let mut samples = Samples::new(2, 8000, vec![0.5, -0.5]);
+        time_stretch(0.25, &mut samples);
+        assert_eq!(samples.frames(), 1);
+        assert_eq!(vocode(&[0.5], 0.25, &mut |_fraction| ()).len(), 1);
+        assert!(vocode(&[], 2.0, &mut |_fraction| ()).is_empty());
+    }
+
+    #[test]
+    fn time_stretch_reports_progress() {
+        let mut samples = Samples::new(2, 8000, sine(200.0, 8000, 16000));
+        let mut reports = Vec::new();
+
+        time_stretch_with_progress(1.5, &mut samples, &mut |fraction| reports.push(fraction));
+
+        assert!(
+            reports.len() > 10 && reports.len() <= 101,
+            "{} reports",
+            reports.len()
+        );
+        assert!(reports.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(reports.last(), Some(&1.0));
+
+        reports.clear();
+        time_stretch_with_progress(1.0, &mut samples, &mut |fraction| reports.push(fraction));
+        assert_eq!(reports, vec![1.0]);
+    }
+
+    #[test]
+    fn append_crossfades_seam() {
+        let mut samples = Samples::new(1, 8000, vec![1.0; 4]);
+        let mut incoming = Samples::new(1, 8000, vec![0.0; 4]);
+        incoming.markers = vec![Marker::new(2, String::from("seam"))];
+
+        append(&incoming, 3, &mut samples).unwrap();
+
+        assert_eq!(samples.frames(), 5);
+        let expected = [1.0, 0.75, 0.5, 0.25, 0.0];
+        assert!(samples
+            .data
+            .iter()
+            .zip(expected)
+            .all(|(actual, expected)| (actual - expected).abs() < 1e-6));
+        assert_eq!(samples.markers, vec![Marker::new(3, String::from("seam"))]);
+    }
+
+    #[test]
+    fn crossfade_loop_joins_seam() {
+        let (first, second) = (sine(5.0, 8000, 16_000), sine(7.0, 8000, 16_000));
+        let data = first
+            .iter()
+            .zip(&second)
+            .flat_map(|(a, b)| [*a, *b])
+            .collect();
+        let original = Samples::new(2, 8000, data);
+        // Difference between the last and first frame of a region, which clicks when looped.
+        let seam = |samples: &Samples, selection: Selection| -> f32 {
+            (0..2)
+                .map(|channel| {
+                    let last = samples.data[(selection.end - 1) * 2 + channel];
+                    let first = samples.data[selection.start * 2 + channel];
+                    (last - first).abs()
+                })
+                .fold(0.0, f32::max)
+        };
+        let region = Selection::new(3000, 10_300);
+        assert!(seam(&original, region) > 0.5);
+
+        let mut samples = original.clone();
+        let looped = crossfade_loop(region, 400, &mut samples).unwrap();
+        assert_eq!(looped, region);
+        assert_eq!(samples.frames(), original.frames());
+        assert!(seam(&samples, looped) < 0.01);
+        assert_eq!(samples.data[..9800 * 2], original.data[..9800 * 2]);
+        assert_eq!(samples.data[10_300 * 2..], original.data[10_300 * 2..]);
+
+        // Regions without frames before them crossfade into their own start, which is cut.
+        let mut samples = original.clone();
+        let looped = crossfade_loop(Selection::new(0, 7300), 400, &mut samples).unwrap();
+        assert_eq!(looped, Selection::new(0, 6900));
+        assert_eq!(samples.frames(), original.frames() - 400);
+        assert!(seam(&samples, looped) < 0.01);
+
+        let mut samples = original.clone();
+        let looped = crossfade_loop(region, usize::MAX, &mut samples).unwrap();
+        assert_eq!(looped, Selection::new(3000, 6650));
+        assert!(seam(&samples, looped) < 0.01);
+
+        assert!(crossfade_loop(Selection::new(5, 5), 10, &mut samples).is_err());
+        assert!(crossfade_loop(Selection::new(0, 20_000), 10, &mut samples).is_err());
+    }
+
+    #[test]
+    fn append_converts_incoming() {
+        let mut samples = Samples::new(2, 8000, vec![0.5; 8]);
+        let incoming = Samples::new(1, 4000, vec![0.25; 3]);
+
+        append(&incoming, 0, &mut samples).unwrap();
+        assert_eq!(samples.frames(), 10);
+        assert_eq!(samples.data[8..], [0.25; 12]);
+
+        append(&incoming, 100, &mut samples).unwrap();
+        assert_eq!(samples.frames(), 10);
+
+        let error = append(&Samples::new(3, 8000, vec![0.0; 3]), 0, &mut samples);
+        assert!(error.is_err());
+    }
+
+    #[test]
+    fn mix_sums_sines() {
+        let low = sine(100.0, 8000, 800);
+        let high = sine(300.0, 8000, 800);
+        let data = low.iter().flat_map(|sample| [*sample, -sample]).collect();
+        let mut samples = Samples::new(2, 8000, data);
+
+        let peak = mix(&Samples::new(1, 8000, high.clone()), 0.5, 0, &mut samples).unwrap();
+
+        assert_eq!(samples.frames(), 800);
+        for (frame, pair) in samples.data.chunks(2).enumerate() {
+            assert!((pair[0] - (low[frame] + 0.5 * high[frame])).abs() < 1e-6);
+            assert!((pair[1] - (-low[frame] + 0.5 * high[frame])).abs() < 1e-6);
+        }
+        assert!(peak > 1.0);
+    }
+
+    #[test]
+    fn mix_past_end_grows() {
+        let mut samples = Samples::new(2, 8000, vec![0.25; 8]);
+        let incoming = Samples::new(2, 4000, vec![0.5, -0.5, 0.5, -0.5, 0.5, -0.5]);
+
+        let peak = mix(&incoming, 1.0, 2, &mut samples).unwrap();
+
+        assert_eq!(samples.frames(), 8);
+        assert_eq!(samples.data[4..6], [0.75, -0.25]);
+        assert_eq!(samples.data[14..16], [0.5, -0.5]);
+
+        mix(&incoming, 1.0, 10, &mut samples).unwrap();
+        assert_eq!(samples.frames(), 16);
+        assert_eq!(samples.data[16..20], [0.0; 4]);
+        assert!((peak - 0.75).abs() < 1e-6);
+
+        let error = mix(&Samples::new(3, 8000, vec![0.0; 3]), 1.0, 0, &mut samples);
+        assert!(error.is_err());
+    }
+
     #[test]
     fn process_default() {
         let mut actual = Samples::new(2, 20, vec![-0.5, -0.25, 0.25, 0.0]);
@@ -29,4 +1499,350 @@ mod tests {
         let expected = Samples::new(2, 20, vec![-1.0, -0.5, 0.5, 0.0]);
         assert_eq!(actual.data, expected.data);
     }
+
+    #[test]
+    fn gain_in_decibels() {
+        let mut samples = Samples::new(1, 8000, vec![0.5, -0.25, 0.0]);
+        gain(-20.0 * 2.0_f32.log10(), &mut samples);
+
+        let expected = [0.25, -0.125, 0.0];
+        for (actual, expected) in samples.data.iter().zip(expected) {
+            assert!((actual - expected).abs() < 1e-6, "{}", actual);
+        }
+    }
+
+    #[test]
+    fn change_sample_rate_halves_frames() {
+        let data = sine(100.0, 8000, 800)
+            .into_iter()
+            .flat_map(|sample| [sample, -sample])
+            .collect();
+        let mut samples = Samples::new(2, 8000, data);
+        samples
+            .markers
+            .push(Marker::new(400, String::from("middle")));
+
+        change_sample_rate(4000, &mut samples).unwrap();
+        assert_eq!(samples.sample_rate, 4000);
+        assert_eq!(samples.frames(), 400);
+        assert_eq!(samples.markers[0].frame, 200);
+        assert!(samples
+            .data
+            .chunks_exact(2)
+            .all(|frame| (frame[0] + frame[1]).abs() < 1e-6));
+
+        assert!(change_sample_rate(0, &mut samples).is_err());
+    }
+
+    #[test]
+    fn to_mono_averages_channels() {
+        let mut samples = Samples::new(2, 8000, vec![0.5, 0.25, -1.0, 1.0]);
+        to_mono(&mut samples);
+        assert_eq!(samples, Samples::new(1, 8000, vec![0.375, 0.0]));
+
+        to_mono(&mut samples);
+        assert_eq!(samples, Samples::new(1, 8000, vec![0.375, 0.0]));
+    }
+
+    #[test]
+    fn trim_silence_keeps_loud_frames() {
+        let data = vec![0.0, 0.0, 0.0001, 0.0, 0.5, 0.0, 0.0, -0.2, 0.0, 0.0];
+        let mut samples = Samples::new(2, 8000, data);
+        samples.markers.push(Marker::new(0, String::from("start")));
+        samples.markers.push(Marker::new(3, String::from("hit")));
+
+        trim_silence(-60.0, &mut samples);
+        assert_eq!(samples.data, vec![0.5, 0.0, 0.0, -0.2]);
+        assert_eq!(samples.markers, vec![Marker::new(1, String::from("hit"))]);
+
+        let mut silent = Samples::new(1, 8000, vec![0.0; 4]);
+        trim_silence(-60.0, &mut silent);
+        assert!(silent.data.is_empty());
+    }
+
+    #[test]
+    fn noise_gate_silences_quiet_sections() {
+        let mut data: Vec<f32> = [0.002, -0.002].iter().cycle().take(100).copied().collect();
+        data[10] = 0.8;
+        data[11] = -0.6;
+        data[60] = 0.003;
+        let mut samples = Samples::new(1, 8000, data.clone());
+
+        noise_gate(&mut samples, -40.0, 20, 0, 0);
+        assert_eq!(samples.data[..12], data[..12]);
+        assert_eq!(samples.data[12..32], data[12..32]);
+        assert!(samples.data[32..].iter().all(|sample| *sample == 0.0));
+
+        let mut stereo = Samples::new(2, 8000, vec![0.001, 0.5, 0.001, 0.001, 0.001, 0.001]);
+        noise_gate(&mut stereo, -40.0, 1, 0, 0);
+        assert_eq!(stereo.data, vec![0.001, 0.5, 0.001, 0.001, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn noise_gate_ramps_quiet_sections_down() {
+        let data: Vec<f32> = (0..6000)
+            .map(|index| {
+                if (2000..4000).contains(&index) {
+                    0.001
+                } else {
+                    0.5
+                }
+            })
+            .collect();
+        let mut samples = Samples::new(1, 8000, data.clone());
+
+        let mut held = Samples::new(1, 8000, data.clone());
+        noise_gate(&mut held, -40.0, 2000, 80, 200);
+        assert_eq!(held.data, data);
+
+        noise_gate(&mut samples, -40.0, 0, 80, 200);
+        assert_eq!(samples.data[..2000], data[..2000]);
+        assert!(samples.data[3500..4000].iter().all(|sample| *sample == 0.0));
+        assert_eq!(samples.data[4080..], data[4080..]);
+
+        let release = samples.data[2000..4000]
+            .iter()
+            .filter(|sample| **sample > 0.0 && **sample < 0.001)
+            .count();
+        let attack = samples.data[4000..]
+            .iter()
+            .filter(|sample| **sample > 0.0 && **sample < 0.5)
+            .count();
+        assert!((199..=200).contains(&release), "{} release frames", release);
+        assert!((79..=80).contains(&attack), "{} attack frames", attack);
+
+        let mut stereo = Samples::new(2, 8000, [0.5, 0.001].repeat(100));
+        noise_gate(&mut stereo, -40.0, 0, 0, 10);
+        assert_eq!(stereo.data, [0.5, 0.001].repeat(100));
+    }
+
+    #[test]
+    fn compress_limits_peaks() {
+        let data: Vec<f32> = sine(50.0, 8000, 4000)
+            .into_iter()
+            .enumerate()
+            .map(|(index, sample)| if index < 2000 { sample * 0.1 } else { sample })
+            .flat_map(|sample| [sample, sample * 0.5])
+            .collect();
+        let mut samples = Samples::new(2, 8000, data.clone());
+
+        compress(&mut samples, -6.0, f32::INFINITY, 0, 400).unwrap();
+        let limit = 10.0_f32.powf(-6.0 / 20.0);
+        assert!(samples
+            .data
+            .iter()
+            .all(|sample| sample.abs() <= limit + 1e-6));
+        // Quiet frames below the threshold pass unchanged.
+        assert_eq!(samples.data[..2000], data[..2000]);
+
+        let mut limited = Samples::new(2, 8000, data);
+        compress(&mut limited, -6.0, 1000.0, 0, 400).unwrap();
+        let peak = limited
+            .data
+            .iter()
+            .fold(0.0_f32, |peak, x| peak.max(x.abs()));
+        assert!(peak <= limit * 1.01, "peak {}", peak);
+    }
+
+    #[test]
+    fn compress_rejects_expanding_ratios() {
+        let mut samples = Samples::new(1, 8000, vec![0.9; 4]);
+        assert!(compress(&mut samples, -6.0, 0.5, 0, 0).is_err());
+        assert!(compress(&mut samples, f32::NAN, 2.0, 0, 0).is_err());
+        assert_eq!(samples.data, vec![0.9; 4]);
+    }
+
+    /// Measure the gain in decibels of a filter on a sine after its transient has settled.
+    fn filter_gain(
+        filter: fn(f32, &mut Samples) -> eyre::Result<()>,
+        cutoff: f32,
+        frequency: f32,
+    ) -> f64 {
+        let signal = sine(frequency, 8000, 8000);
+        let mut samples = Samples::new(1, 8000, signal.clone());
+        filter(cutoff, &mut samples).unwrap();
+
+        let before = magnitude(&signal[4000..], f64::from(frequency), 8000);
+        let after = magnitude(&samples.data[4000..], f64::from(frequency), 8000);
+        20.0 * (after / before).log10()
+    }
+
+    #[test]
+    fn low_pass_attenuates_above_cutoff() {
+        assert!(filter_gain(low_pass, 500.0, 3000.0) < -30.0);
+        assert!(filter_gain(low_pass, 500.0, 50.0).abs() < 0.1);
+        // Butterworth filters are 3 dB down at the cutoff.
+        assert!((filter_gain(low_pass, 500.0, 500.0) + 3.0).abs() < 0.2);
+    }
+
+    #[test]
+    fn high_pass_attenuates_below_cutoff() {
+        assert!(filter_gain(high_pass, 1000.0, 100.0) < -30.0);
+        assert!(filter_gain(high_pass, 1000.0, 3500.0).abs() < 0.1);
+        assert!((filter_gain(high_pass, 1000.0, 1000.0) + 3.0).abs() < 0.2);
+    }
+
+    #[test]
+    fn pass_filters_keep_channels_separate() {
+        let data = sine(2000.0, 8000, 400)
+            .into_iter()
+            .flat_map(|sample| [sample, 0.0])
+            .collect();
+        let mut samples = Samples::new(2, 8000, data);
+
+        low_pass(200.0, &mut samples).unwrap();
+        assert!(samples.data.iter().skip(1).step_by(2).all(|x| *x == 0.0));
+
+        for cutoff in [0.0, 4000.0, 9000.0, f32::NAN] {
+            assert!(high_pass(cutoff, &mut samples).is_err(), "{}", cutoff);
+        }
+    }
+
+    #[test]
+    fn delay_repeats_and_extends_tail() {
+        let mut samples = Samples::new(2, 8000, vec![1.0, -1.0, 0.0, 0.0, 0.0, 0.0]);
+        delay(&mut samples, 2, 0.5, 0.5).unwrap();
+
+        assert_eq!(samples.data[..6], [0.5, -0.5, 0.0, 0.0, 0.5, -0.5]);
+        assert_eq!(samples.data[8..10], [0.25, -0.25]);
+        assert_eq!(samples.data.len() % 4, 2);
+        let tail = &samples.data[samples.data.len() - 4..];
+        assert!(tail.iter().any(|sample| sample.abs() >= 1e-4));
+        assert!(tail.iter().all(|sample| sample.abs() < 2e-4));
+    }
+
+    #[test]
+    fn delay_rejects_invalid_settings() {
+        let mut samples = Samples::new(1, 8000, vec![0.5; 4]);
+        assert!(delay(&mut samples, 0, 0.5, 0.5).is_err());
+        assert!(delay(&mut samples, 2, 1.0, 0.5).is_err());
+        assert!(delay(&mut samples, 2, 0.5, 1.5).is_err());
+        assert_eq!(samples.data, vec![0.5; 4]);
+
+        delay(&mut samples, 2, 0.0, 0.0).unwrap();
+        assert_eq!(samples.data, vec![0.5; 4]);
+    }
+
+    #[test]
+    fn remove_dc_offset_centers_sine() {
+        let signal = sine(100.0, 8000, 8000);
+        let data = signal
+            .iter()
+            .flat_map(|sample| [sample + 0.1, -0.05])
+            .collect();
+        let mut samples = Samples::new(2, 8000, data);
+
+        remove_dc_offset(&mut samples);
+        let offsets = analysis::dc_offset(&samples);
+        assert!(
+            offsets.iter().all(|offset| offset.abs() < 1e-6),
+            "{:?}",
+            offsets
+        );
+        let left: Vec<f32> = samples.data.iter().step_by(2).copied().collect();
+        assert!(left
+            .iter()
+            .zip(&signal)
+            .all(|(actual, expected)| (actual - expected).abs() < 1e-5));
+
+        let mut empty = Samples::new(2, 8000, Vec::new());
+        remove_dc_offset(&mut empty);
+        assert!(empty.data.is_empty());
+    }
+
+    #[test]
+    fn bitcrush_limits_levels_and_holds_frames() {
+        let data: Vec<f32> = (0..20_000)
+            .map(|index| (index as f32 * 0.001).sin() * 1.5)
+            .collect();
+        let mut crushed = Samples::new(2, 8000, data.clone());
+        bitcrush(&mut crushed, 8, 1);
+
+        let mut levels: Vec<u32> = crushed.data.iter().map(|sample| sample.to_bits()).collect();
+        levels.sort_unstable();
+        levels.dedup();
+        assert_eq!(levels.len(), 256);
+        assert!(crushed
+            .data
+            .iter()
+            .all(|sample| (-1.0..1.0).contains(sample)));
+        assert!(crushed
+            .data
+            .iter()
+            .zip(&data)
+            .filter(|(_crushed, sample)| sample.abs() < 0.99)
+            .all(|(crushed, sample)| (crushed - sample).abs() <= 1.0 / 256.0));
+
+        let mut held = Samples::new(2, 8000, (0..14).map(|index| index as f32).collect());
+        bitcrush(&mut held, 0, 3);
+        assert_eq!(
+            held.data,
+            vec![0.0, 1.0, 0.0, 1.0, 0.0, 1.0, 6.0, 7.0, 6.0, 7.0, 6.0, 7.0, 12.0, 13.0]
+        );
+
+        let mut both = Samples::new(1, 8000, vec![0.3, 0.9, -0.6, 0.2]);
+        bitcrush(&mut both, 2, 2);
+        assert_eq!(both.data, vec![0.5, 0.5, -0.5, -0.5]);
+
+        let mut unchanged = Samples::new(1, 8000, vec![0.123, -0.456]);
+        bitcrush(&mut unchanged, 24, 0);
+        assert_eq!(unchanged.data, vec![0.123, -0.456]);
+    }
+
+    #[test]
+    fn ring_modulate_carrier_is_continuous_without_offset() {
+        let mut samples = Samples::new(2, 8000, vec![1.0; 16_000]);
+        ring_modulate(&mut samples, 100.0).unwrap();
+
+        let carrier: Vec<f32> = samples.data.iter().step_by(2).copied().collect();
+        assert_eq!(
+            carrier,
+            samples
+                .data
+                .iter()
+                .skip(1)
+                .step_by(2)
+                .copied()
+                .collect::<Vec<f32>>()
+        );
+        let mean = carrier.iter().map(|x| f64::from(*x)).sum::<f64>() / carrier.len() as f64;
+        assert!(mean.abs() < 1e-6, "mean {}", mean);
+
+        // Neighboring frames never jump by more than the carrier's largest slope per frame.
+        let limit = 2.0 * std::f32::consts::PI * 100.0 / 8000.0;
+        assert!(carrier
+            .windows(2)
+            .all(|pair| (pair[1] - pair[0]).abs() <= limit + 1e-6));
+    }
+
+    #[test]
+    fn ring_modulate_rejects_invalid_carriers() {
+        let mut samples = Samples::new(1, 8000, vec![0.5; 4]);
+        for carrier in [0.0, -10.0, 4000.0, f32::NAN] {
+            assert!(ring_modulate(&mut samples, carrier).is_err(), "{}", carrier);
+        }
+        assert_eq!(samples.data, vec![0.5; 4]);
+    }
+
+    #[test]
+    fn tremolo_swings_between_depth_and_unity() {
+        let mut samples = Samples::new(2, 8000, vec![1.0; 16_000]);
+        tremolo(&mut samples, 4.0, 0.6).unwrap();
+
+        let left: Vec<f32> = samples.data.iter().step_by(2).copied().collect();
+        let right: Vec<f32> = samples.data.iter().skip(1).step_by(2).copied().collect();
+        assert_eq!(left, right);
+        let (minimum, maximum) = left.iter().fold((f32::MAX, f32::MIN), |(low, high), x| {
+            (low.min(*x), high.max(*x))
+        });
+        assert!((minimum - 0.4).abs() < 1e-4, "{}", minimum);
+        assert!((maximum - 1.0).abs() < 1e-4, "{}", maximum);
+        // A quarter period in, the sine peaks and the gain is back at unity.
+        assert!((left[500] - 1.0).abs() < 1e-6);
+
+        let mut clamped = Samples::new(1, 8000, vec![1.0; 8000]);
+        tremolo(&mut clamped, 1.0, 3.0).unwrap();
+        assert!(clamped.data.iter().all(|x| (0.0..=1.0).contains(x)));
+        assert!(tremolo(&mut clamped, -1.0, 0.5).is_err());
+    }
 }
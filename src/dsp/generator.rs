@@ -0,0 +1,319 @@
+//! Synthesized test signals.
+
+use crate::dsp::buffer::Samples;
+use clap::ValueEnum;
+use color_eyre::eyre;
+use std::f64::consts::PI;
+
+/// Seed of the white noise generator, so that generated noise is reproducible.
+const NOISE_SEED: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// Frequency in hertz at which sweeps start.
+pub const SWEEP_START: f32 = 20.0;
+
+/// Shapes of generated signals.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+pub enum Waveform {
+    /// Rising ramp that falls back at the end of each period.
+    Sawtooth,
+    /// Zero samples.
+    Silence,
+    /// Pure tone.
+    Sine,
+    /// Alternation between the positive and negative amplitude with even halves.
+    Square,
+    /// Pure tone whose frequency rises linearly from [`SWEEP_START`] to the frequency.
+    Sweep,
+    /// Uniformly distributed samples independent for each channel.
+    WhiteNoise,
+}
+
+/// Generate samples of a waveform at a frequency and peak amplitude.
+///
+/// The samples last `duration` seconds rounded to whole frames, and every channel holds the same
+/// waveform except for white noise. Phases are computed from the frame index instead of being
+/// accumulated, so tones stay continuous without drifting over long durations. Sawtooth and square
+/// waves are band limited with polynomial steps at their discontinuities, which removes most
+/// aliasing but leaves some for frequencies close to the Nyquist frequency. White noise is seeded
+/// so that it is the same on every run, and the frequency is ignored for white noise and silence.
+///
+/// # Errors
+///
+/// Will return `Err` if `channels` or `sample_rate` is zero, `duration` or `amplitude` is
+/// negative or not finite, or a periodic waveform has a frequency that is not between zero and
+/// the Nyquist frequency.
+pub fn generate(
+    waveform: Waveform,
+    frequency: f32,
+    amplitude: f32,
+    duration: f32,
+    channels: u16,
+    sample_rate: u32,
+) -> eyre::Result<Samples> {
+    if channels == 0 || sample_rate == 0 {
+        eyre::bail!("Generated samples need at least one channel and a sample rate above zero");
+    }
+    if !duration.is_finite() || duration < 0.0 {
+        eyre::bail!(
+            "Duration {} must be a non-negative number of seconds",
+            duration
+        );
+    }
+    if !amplitude.is_finite() || amplitude < 0.0 {
+        eyre::bail!("Amplitude {} must be a non-negative number", amplitude);
+    }
+    let nyquist = f64::from(sample_rate) / 2.0;
+    let periodic = matches!(
+        waveform,
+        Waveform::Sawtooth | Waveform::Sine | Waveform::Square | Waveform::Sweep
+    );
+    if periodic && !(frequency > 0.0 && f64::from(frequency) < nyquist) {
+        eyre::bail!(
+            "Frequency {} must be above zero and below the Nyquist frequency of {}",
+            frequency,
+            nyquist
+        );
+    }
+
+    let width = usize::from(channels);
+    // The duration is checked to be non-negative and finite, so truncation is not possible.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let frames = (f64::from(duration) * f64::from(sample_rate)).round() as usize;
+    let step = f64::from(frequency) / f64::from(sample_rate);
+    let amplitude = f64::from(amplitude);
+
+    let data = match waveform {
+        Waveform::Silence => vec![0.0; frames * width],
+        Waveform::WhiteNoise => {
+            let mut state = NOISE_SEED;
+            (0..frames * width)
+                .map(|_| (amplitude * uniform(&mut state)) as f32)
+                .collect()
+        }
+        Waveform::Sweep => {
+            let rate = f64::from(sample_rate);
+            let start = f64::from(SWEEP_START);
+            // The phase integrates the linearly rising frequency over the whole duration.
+            let chirp = (f64::from(frequency) - start) / (frames.max(1) as f64 / rate);
+            (0..frames)
+                .flat_map(|frame| {
+                    let time = frame as f64 / rate;
+                    let phase = (start * time + chirp * time * time / 2.0).fract();
+                    std::iter::repeat_n((amplitude * (2.0 * PI * phase).sin()) as f32, width)
+                })
+                .collect()
+        }
+        Waveform::Sawtooth | Waveform::Sine | Waveform::Square => (0..frames)
+            .flat_map(|frame| {
+                let phase = (frame as f64 * step).fract();
+                let value = match waveform {
+                    Waveform::Sawtooth => 2.0 * phase - 1.0 - poly_blep(phase, step),
+                    Waveform::Square => {
+                        let level = if phase < 0.5 { 1.0 } else { -1.0 };
+                        level + poly_blep(phase, step) - poly_blep((phase + 0.5).fract(), step)
+                    }
+                    _ => (2.0 * PI * phase).sin(),
+                };
+                std::iter::repeat_n((amplitude * value) as f32, width)
+            })
+            .collect(),
+    };
+
+    Ok(Samples::new(channels, sample_rate, data))
+}
+
+/// Insert a number of silent frames before a frame, shifting later samples and markers.
+///
+/// Frames past the end append the silence instead.
+pub fn insert_silence(samples: &mut Samples, frame: usize, frames: usize) {
+    let width = usize::from(samples.channels);
+    let frame = frame.min(samples.frames());
+    let start = frame * width;
+    samples
+        .data
+        .splice(start..start, std::iter::repeat_n(0.0, frames * width));
+
+    for marker in &mut samples.markers {
+        if marker.frame >= frame {
+            marker.frame += frames;
+        }
+    }
+}
+
+/// Compute the polynomial correction that smooths a unit step at the start of each period.
+///
+/// The phase and the phase increment per frame are fractions of a period.
+fn poly_blep(phase: f64, step: f64) -> f64 {
+    if phase < step {
+        let offset = phase / step;
+        2.0f64.mul_add(offset, -offset * offset) - 1.0
+    } else if phase > 1.0 - step {
+        let offset = (phase - 1.0) / step;
+        offset.mul_add(offset, 2.0 * offset) + 1.0
+    } else {
+        0.0
+    }
+}
+
+/// Advance an xorshift generator and map its state to a number between minus one and one.
+fn uniform(state: &mut u64) -> f64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    // The top 53 bits fit the mantissa of a double exactly.
+    (*state >> 11) as f64 / (1_u64 << 52) as f64 - 1.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsp::Marker;
+
+    const WAVEFORMS: [Waveform; 6] = [
+        Waveform::Sawtooth,
+        Waveform::Silence,
+        Waveform::Sine,
+        Waveform::Square,
+        Waveform::Sweep,
+        Waveform::WhiteNoise,
+    ];
+
+    /// Count sign changes between consecutive samples of a mono signal.
+    fn crossings(data: &[f32]) -> usize {
+        data.windows(2)
+            .filter(|pair| (pair[0] < 0.0) != (pair[1] < 0.0))
+            .count()
+    }
+
+    #[test]
+    fn generate_length_matches_duration() {
+        for waveform in WAVEFORMS {
+            for (duration, channels, sample_rate) in
+                [(0.5, 2, 8000), (1.25, 3, 44_100), (0.0, 1, 8000)]
+            {
+                let samples =
+                    generate(waveform, 440.0, 0.5, duration, channels, sample_rate).unwrap();
+                let expected = (duration * sample_rate as f32) as usize * usize::from(channels);
+                assert_eq!(samples.data.len(), expected, "{:?}", waveform);
+                assert_eq!(samples.channels, channels);
+                assert_eq!(samples.sample_rate, sample_rate);
+            }
+        }
+    }
+
+    #[test]
+    fn generate_sine_peak_matches_amplitude() {
+        for amplitude in [0.25, 0.5, 1.0] {
+            let samples = generate(Waveform::Sine, 1000.0, amplitude, 1.0, 2, 48_000).unwrap();
+            let peak = samples
+                .data
+                .iter()
+                .fold(0.0_f32, |peak, x| peak.max(x.abs()));
+            assert!((peak - amplitude).abs() < 1e-4, "peak {}", peak);
+            assert!(samples
+                .data
+                .chunks_exact(2)
+                .all(|frame| frame[0] == frame[1]));
+        }
+    }
+
+    #[test]
+    fn generate_sine_frequency_from_crossings() {
+        for (frequency, sample_rate) in [(440.0, 44_100), (1000.0, 48_000), (123.4, 8000)] {
+            let samples = generate(Waveform::Sine, frequency, 0.5, 10.0, 1, sample_rate).unwrap();
+            // Every period crosses zero twice and a phase jump would add or drop crossings.
+            let measured = crossings(&samples.data) as f32 / 2.0 / 10.0;
+            assert!(
+                (measured - frequency).abs() <= 0.1,
+                "{} Hz measured {}",
+                frequency,
+                measured
+            );
+
+            let step = 2.0 * std::f32::consts::PI * frequency / sample_rate as f32;
+            let largest = samples.data.windows(2).fold(0.0_f32, |largest, pair| {
+                largest.max((pair[1] - pair[0]).abs())
+            });
+            assert!(largest <= 0.5 * step + 1e-4, "jump {}", largest);
+        }
+    }
+
+    #[test]
+    fn generate_sweep_rises_to_frequency() {
+        let samples = generate(Waveform::Sweep, 2000.0, 1.0, 2.0, 1, 16_000).unwrap();
+        let (first, last) = samples.data.split_at(1600);
+        let last = &last[last.len() - 1600..];
+
+        // The frequency rises by 99 Hz over each tenth of a second and crossings count its mean.
+        let early = crossings(first) as f32 / 2.0 / 0.1;
+        let late = crossings(last) as f32 / 2.0 / 0.1;
+        assert!((early - 69.5).abs() < 15.0, "early {}", early);
+        assert!((late - 1950.5).abs() < 15.0, "late {}", late);
+        assert!(samples.data.iter().all(|x| x.abs() <= 1.0));
+    }
+
+    #[test]
+    fn insert_silence_preserves_surrounding_samples() {
+        let data: Vec<f32> = (1..=8).map(|index| index as f32 / 8.0).collect();
+        let mut samples = Samples::new(2, 10, data.clone());
+        samples.markers = vec![
+            Marker::new(1, String::from("before")),
+            Marker::new(2, String::from("at")),
+        ];
+        insert_silence(&mut samples, 2, 3);
+
+        assert_eq!(samples.data[..4], data[..4]);
+        assert_eq!(samples.data[4..10], [0.0; 6]);
+        assert_eq!(samples.data[10..], data[4..]);
+        assert_eq!(
+            samples.markers,
+            vec![
+                Marker::new(1, String::from("before")),
+                Marker::new(5, String::from("at"))
+            ]
+        );
+
+        insert_silence(&mut samples, 100, 1);
+        assert_eq!(samples.frames(), 8);
+        assert_eq!(samples.data[14..], [0.0; 2]);
+    }
+
+    #[test]
+    fn generate_band_limited_and_noise_levels() {
+        for waveform in [Waveform::Sawtooth, Waveform::Square, Waveform::WhiteNoise] {
+            let samples = generate(waveform, 3000.0, 0.5, 1.0, 1, 44_100).unwrap();
+            let peak = samples
+                .data
+                .iter()
+                .fold(0.0_f32, |peak, x| peak.max(x.abs()));
+            let mean = samples.data.iter().map(|x| f64::from(*x)).sum::<f64>() / 44_100.0;
+            assert!(
+                peak <= 0.5 + 1e-6 && peak > 0.4,
+                "{:?} peak {}",
+                waveform,
+                peak
+            );
+            assert!(mean.abs() < 0.01, "{:?} mean {}", waveform, mean);
+        }
+
+        let noise = generate(Waveform::WhiteNoise, 0.0, 1.0, 0.01, 2, 8000).unwrap();
+        assert_ne!(noise.data[0], noise.data[1]);
+        assert_eq!(
+            noise,
+            generate(Waveform::WhiteNoise, 0.0, 1.0, 0.01, 2, 8000).unwrap()
+        );
+        let silence = generate(Waveform::Silence, 0.0, 1.0, 0.01, 2, 8000).unwrap();
+        assert!(silence.data.iter().all(|x| *x == 0.0));
+    }
+
+    #[test]
+    fn generate_rejects_invalid_parameters() {
+        assert!(generate(Waveform::Sine, 440.0, 1.0, 1.0, 0, 8000).is_err());
+        assert!(generate(Waveform::Sine, 440.0, 1.0, 1.0, 1, 0).is_err());
+        assert!(generate(Waveform::Sine, 440.0, 1.0, -1.0, 1, 8000).is_err());
+        assert!(generate(Waveform::Sine, 440.0, f32::NAN, 1.0, 1, 8000).is_err());
+        assert!(generate(Waveform::Square, 4000.0, 1.0, 1.0, 1, 8000).is_err());
+        assert!(generate(Waveform::Sawtooth, 0.0, 1.0, 1.0, 1, 8000).is_err());
+        assert!(generate(Waveform::WhiteNoise, 0.0, 1.0, 1.0, 1, 8000).is_ok());
+    }
+}
@@ -1,6 +1,20 @@
 //! Digital signal processing functions and user interface logic.
 
+pub mod analysis;
 pub mod buffer;
 pub mod filters;
+pub mod generator;
 
-pub use crate::dsp::buffer::Samples;
+pub use crate::dsp::analysis::{
+    clip_regions, dc_offset, estimate_bpm, find_zero_crossing, find_zero_crossing_on,
+    integrated_lufs, level_stats, sound_regions, step_zero_crossing, ChannelStats, Stats,
+};
+pub use crate::dsp::buffer::{
+    AudioMetadata, FrameIter, FrameIterMut, Marker, Samples, Selection, Signal,
+};
+pub use crate::dsp::filters::{
+    adjust_stereo_width, bitcrush, change_sample_rate, compress, decode_mid_side, delay,
+    encode_mid_side, gain, hard_clip, high_pass, low_pass, noise_gate, normalize, pan,
+    remove_dc_offset, ring_modulate, soft_clip, to_mono, tremolo, trim_silence,
+};
+pub use crate::dsp::generator::{generate, insert_silence, Waveform};
@@ -0,0 +1,237 @@
+//! Undo and redo of edits with snapshots swapped to disk.
+
+use crate::dsp::{AudioMetadata, Marker, Samples};
+use color_eyre::eyre;
+use memmap2::Mmap;
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+/// Default number of edits that can be undone.
+const LIMIT: usize = 32;
+
+/// Undo and redo stacks whose sample data is kept in temporary files instead of in memory.
+///
+/// Snapshot files are deleted when their entry is evicted or restored, and the temporary
+/// directory is deleted when the history is dropped.
+#[derive(Debug)]
+pub struct UndoHistory {
+    counter: usize,
+    folder: Option<TempDir>,
+    limit: usize,
+    redos: Vec<Snapshot>,
+    undos: VecDeque<Snapshot>,
+}
+
+impl UndoHistory {
+    /// Create an empty UndoHistory that remembers up to a number of edits.
+    #[must_use]
+    pub const fn new(limit: usize) -> Self {
+        Self {
+            counter: 0,
+            folder: None,
+            limit,
+            redos: Vec::new(),
+            undos: VecDeque::new(),
+        }
+    }
+
+    /// Check whether there is an edit to redo.
+    #[must_use]
+    pub fn can_redo(&self) -> bool {
+        !self.redos.is_empty()
+    }
+
+    /// Check whether there is an edit to undo.
+    #[must_use]
+    pub fn can_undo(&self) -> bool {
+        !self.undos.is_empty()
+    }
+
+    /// Record samples from before an edit so that the edit can be undone.
+    ///
+    /// Recording discards all redoable edits and evicts the oldest entry beyond the limit.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the snapshot cannot be written to a temporary file.
+    pub fn push(&mut self, samples: &Samples) -> eyre::Result<()> {
+        let snapshot = self.snapshot(samples)?;
+        self.redos.clear();
+        self.undos.push_back(snapshot);
+        while self.undos.len() > self.limit {
+            self.undos.pop_front();
+        }
+        Ok(())
+    }
+
+    /// Restore samples from before the most recent edit.
+    ///
+    /// The current samples are recorded so the edit can be redone. Returns `None` if there is
+    /// nothing to undo.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if a snapshot file cannot be written or read.
+    pub fn undo(&mut self, current: &Samples) -> eyre::Result<Option<Samples>> {
+        let restored = match self.undos.back() {
+            Some(snapshot) => snapshot.restore()?,
+            None => return Ok(None),
+        };
+
+        // Entries are only moved once both files are handled, so failures leave the history as is.
+        let redo = self.snapshot(current)?;
+        self.undos.pop_back();
+        self.redos.push(redo);
+        Ok(Some(restored))
+    }
+
+    /// Restore samples from after the most recently undone edit.
+    ///
+    /// Returns `None` if there is nothing to redo.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if a snapshot file cannot be written or read.
+    pub fn redo(&mut self, current: &Samples) -> eyre::Result<Option<Samples>> {
+        let restored = match self.redos.last() {
+            Some(snapshot) => snapshot.restore()?,
+            None => return Ok(None),
+        };
+
+        let undo = self.snapshot(current)?;
+        self.redos.pop();
+        self.undos.push_back(undo);
+        Ok(Some(restored))
+    }
+
+    /// Write samples to a new file in the temporary directory.
+    fn snapshot(&mut self, samples: &Samples) -> eyre::Result<Snapshot> {
+        let folder = match self.folder.take() {
+            Some(folder) => folder,
+            None => tempfile::Builder::new().prefix("sampitor").tempdir()?,
+        };
+        let folder = self.folder.insert(folder);
+        let path = folder.path().join(format!("{}.raw", self.counter));
+        self.counter += 1;
+
+        let mut writer = BufWriter::new(File::create(&path)?);
+        for sample in &samples.data {
+            writer.write_all(&sample.to_le_bytes())?;
+        }
+        writer.flush()?;
+
+        Ok(Snapshot {
+            channels: samples.channels,
+            markers: samples.markers.clone(),
+            metadata: samples.metadata.clone(),
+            path,
+            sample_rate: samples.sample_rate,
+        })
+    }
+}
+
+impl Default for UndoHistory {
+    fn default() -> Self {
+        Self::new(LIMIT)
+    }
+}
+
+/// Samples metadata kept in memory and sample data stored in a file.
+#[derive(Debug)]
+struct Snapshot {
+    channels: u16,
+    markers: Vec<Marker>,
+    metadata: AudioMetadata,
+    path: PathBuf,
+    sample_rate: u32,
+}
+
+impl Snapshot {
+    /// Read samples back from the snapshot file by memory mapping it.
+    fn restore(&self) -> eyre::Result<Samples> {
+        let file = File::open(&self.path)?;
+        // SAFETY: Snapshot files are private to this history and never modified after writing.
+        let map = unsafe { Mmap::map(&file)? };
+
+        let data = map
+            .chunks_exact(4)
+            .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            .collect();
+        let mut samples = Samples::new(self.channels, self.sample_rate, data);
+        samples.markers = self.markers.clone();
+        samples.metadata = self.metadata.clone();
+        Ok(samples)
+    }
+}
+
+impl Drop for Snapshot {
+    fn drop(&mut self) {
+        // The temporary directory removes any file left behind once the history is dropped.
+        let _result = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evicted_snapshots_are_deleted() {
+        let mut history = UndoHistory::new(2);
+        for value in [0.1, 0.2, 0.3] {
+            history.push(&Samples::new(1, 10, vec![value; 4])).unwrap();
+        }
+
+        let folder = history.folder.as_ref().unwrap().path().to_owned();
+        assert_eq!(fs::read_dir(&folder).unwrap().count(), 2);
+
+        let current = Samples::new(1, 10, vec![0.4; 4]);
+        let restored = history.undo(&current).unwrap().unwrap();
+        assert_eq!(restored.data, vec![0.3; 4]);
+        assert_eq!(fs::read_dir(&folder).unwrap().count(), 2);
+
+        drop(history);
+        assert!(!folder.exists());
+    }
+
+    #[test]
+    fn push_clears_redo() {
+        let mut history = UndoHistory::default();
+        let first = Samples::new(1, 10, vec![0.1; 4]);
+        let second = Samples::new(1, 10, vec![0.2; 4]);
+
+        history.push(&first).unwrap();
+        history.undo(&second).unwrap();
+        assert!(history.can_redo());
+
+        history.push(&first).unwrap();
+        assert!(!history.can_redo());
+        assert!(history.redo(&second).unwrap().is_none());
+    }
+
+    #[test]
+    fn failed_snapshot_keeps_entries() {
+        let mut history = UndoHistory::default();
+        let first = Samples::new(1, 10, vec![0.1; 4]);
+        let second = Samples::new(1, 10, vec![0.2; 4]);
+        history.push(&first).unwrap();
+
+        // Snapshots of the current samples fail once their folder is gone.
+        let folder = history
+            .folder
+            .replace(tempfile::tempdir().unwrap())
+            .unwrap();
+        fs::remove_dir(history.folder.as_ref().unwrap().path()).unwrap();
+        assert!(history.undo(&second).is_err());
+        assert!(history.can_undo());
+        assert!(!history.can_redo());
+
+        history.folder = Some(folder);
+        let restored = history.undo(&second).unwrap().unwrap();
+        assert_eq!(restored.data, first.data);
+        assert!(history.can_redo());
+    }
+}
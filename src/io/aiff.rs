@@ -0,0 +1,246 @@
+//! AIFF readers and writers for audio software and hardware that prefer it over WAV.
+
+use crate::dsp::Samples;
+use color_eyre::eyre;
+use std::convert::TryFrom;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// Check whether a file starts with the signature of an AIFF file.
+///
+/// # Errors
+///
+/// Will return `Err` if `path` cannot be read.
+pub fn is_aiff(path: &Path) -> eyre::Result<bool> {
+    let mut header = Vec::with_capacity(12);
+    File::open(path)?.take(12).read_to_end(&mut header)?;
+
+    Ok(header.len() == 12 && &header[..4] == b"FORM" && matches!(&header[8..], b"AIFF" | b"AIFC"))
+}
+
+/// Encode samples as an AIFF file with 16 or 24 bit signed integer samples.
+///
+/// Samples beyond full scale are clipped.
+///
+/// # Errors
+///
+/// Will return `Err` if `bits` is neither 16 nor 24 or the samples are too long for an AIFF file.
+pub fn encode_aiff(samples: &Samples, bits: u16) -> eyre::Result<Vec<u8>> {
+    if bits != 16 && bits != 24 {
+        eyre::bail!(
+            "AIFF files are written with 16 or 24 bits per sample, not {}",
+            bits
+        );
+    }
+    let width = usize::from(bits / 8);
+    let scale = f32::from(1u16 << 15) * if bits == 24 { 256.0 } else { 1.0 };
+
+    let mut comm = Vec::with_capacity(18);
+    comm.extend_from_slice(&i16::try_from(samples.channels)?.to_be_bytes());
+    comm.extend_from_slice(&u32::try_from(samples.frames())?.to_be_bytes());
+    comm.extend_from_slice(&bits.to_be_bytes());
+    comm.extend_from_slice(&encode_extended(samples.sample_rate));
+
+    let mut ssnd = Vec::with_capacity(8 + samples.data.len() * width);
+    ssnd.extend_from_slice(&[0; 8]);
+    for sample in &samples.data {
+        // Samples are clamped to the integer range first, so truncation is not possible.
+        #[allow(clippy::cast_possible_truncation)]
+        let value = (sample * scale).round().clamp(-scale, scale - 1.0) as i32;
+        ssnd.extend_from_slice(&value.to_be_bytes()[4 - width..]);
+    }
+
+    let mut form = b"AIFF".to_vec();
+    for (id, data) in [(b"COMM", comm), (b"SSND", ssnd)] {
+        form.extend_from_slice(id);
+        form.extend_from_slice(&u32::try_from(data.len())?.to_be_bytes());
+        form.extend_from_slice(&data);
+        if data.len() % 2 == 1 {
+            form.push(0);
+        }
+    }
+
+    let mut bytes = b"FORM".to_vec();
+    bytes.extend_from_slice(&u32::try_from(form.len())?.to_be_bytes());
+    bytes.extend_from_slice(&form);
+    Ok(bytes)
+}
+
+/// Decode samples from the contents of an uncompressed AIFF or AIFF-C file.
+///
+/// # Errors
+///
+/// Will return `Err` if `bytes` are not an AIFF file, are compressed, or lack a COMM or SSND
+/// chunk.
+pub fn decode_aiff(bytes: &[u8]) -> eyre::Result<Samples> {
+    let compressed = match bytes.get(..12) {
+        Some([b'F', b'O', b'R', b'M', _, _, _, _, b'A', b'I', b'F', b'F']) => false,
+        Some([b'F', b'O', b'R', b'M', _, _, _, _, b'A', b'I', b'F', b'C']) => true,
+        _ => eyre::bail!("Data is not an AIFF file"),
+    };
+
+    let mut format = None;
+    let mut sound = None;
+    let mut offset = 12;
+    while let Some(header) = bytes.get(offset..offset + 8) {
+        let size = u32::from_be_bytes([header[4], header[5], header[6], header[7]]) as usize;
+        let start = offset + 8;
+        let data = &bytes[start..(start + size).min(bytes.len())];
+        match &header[..4] {
+            b"COMM" => format = Some(data),
+            b"SSND" => sound = Some(data),
+            _ => (),
+        }
+        offset = start + size + size % 2;
+    }
+
+    let format = format
+        .filter(|format| format.len() >= 18)
+        .ok_or_else(|| eyre::eyre!("AIFF file has no COMM chunk"))?;
+    let sound = sound
+        .filter(|sound| sound.len() >= 8)
+        .ok_or_else(|| eyre::eyre!("AIFF file has no SSND chunk"))?;
+    if compressed && format.get(18..22) != Some(b"NONE") {
+        eyre::bail!("Compressed AIFF files are not supported");
+    }
+
+    let channels = u16::from_be_bytes([format[0], format[1]]);
+    let frames = u32::from_be_bytes([format[2], format[3], format[4], format[5]]) as usize;
+    let bits = u16::from_be_bytes([format[6], format[7]]);
+    let sample_rate = decode_extended(&format[8..18]);
+    if channels == 0 || sample_rate == 0 || !(1..=32).contains(&bits) {
+        eyre::bail!("AIFF file has an invalid COMM chunk");
+    }
+
+    // Samples are left justified in whole bytes, so narrower samples scale like wider ones.
+    let width = usize::from(bits.div_ceil(8));
+    let scale = 2f32.powi(i32::try_from(8 * width)? - 1);
+    let skip = u32::from_be_bytes([sound[0], sound[1], sound[2], sound[3]]) as usize;
+    let data = sound
+        .get(8 + skip..)
+        .unwrap_or_default()
+        .chunks_exact(width)
+        .take(frames * usize::from(channels))
+        .map(|chunk| {
+            let mut buffer = [0; 4];
+            buffer[..width].copy_from_slice(chunk);
+            // Shifting back down keeps the sign of the most significant byte.
+            (i32::from_be_bytes(buffer) >> (32 - 8 * width)) as f32 / scale
+        })
+        .collect();
+
+    Ok(Samples::new(channels, sample_rate, data))
+}
+
+/// Read samples from an uncompressed AIFF or AIFF-C file.
+///
+/// # Errors
+///
+/// Will return `Err` if `path` cannot be read or is not an uncompressed AIFF file.
+pub fn read_aiff(path: &Path) -> eyre::Result<Samples> {
+    decode_aiff(&std::fs::read(path)?)
+}
+
+/// Write samples to an AIFF file with 16 or 24 bit signed integer samples.
+///
+/// # Errors
+///
+/// Will return `Err` if `path` is unwritable or the samples cannot be encoded like in
+/// [`encode_aiff`].
+pub fn write_aiff(path: &Path, samples: &Samples, bits: u16) -> eyre::Result<()> {
+    std::fs::write(path, encode_aiff(samples, bits)?)?;
+    Ok(())
+}
+
+/// Encode a sample rate as the 80 bit extended precision float of COMM chunks.
+fn encode_extended(value: u32) -> [u8; 10] {
+    let mut bytes = [0; 10];
+    if value > 0 {
+        let exponent = 31 - value.leading_zeros();
+        // Exponents of 32 bit integers are below 32, so truncation is not possible.
+        #[allow(clippy::cast_possible_truncation)]
+        bytes[..2].copy_from_slice(&(16383 + exponent as u16).to_be_bytes());
+        bytes[2..].copy_from_slice(&(u64::from(value) << (63 - exponent)).to_be_bytes());
+    }
+    bytes
+}
+
+/// Decode the 80 bit extended precision float sample rate of COMM chunks.
+///
+/// Returns zero for negative or fractional rates below one and saturates at the largest rate.
+fn decode_extended(bytes: &[u8]) -> u32 {
+    let exponent = i32::from(u16::from_be_bytes([bytes[0], bytes[1]]) & 0x7FFF) - 16383;
+    let mut mantissa = [0; 8];
+    mantissa.copy_from_slice(&bytes[2..10]);
+    if bytes[0] & 0x80 != 0 || !(0..=63).contains(&exponent) {
+        return if bytes[0] & 0x80 == 0 && exponent > 63 {
+            u32::MAX
+        } else {
+            0
+        };
+    }
+
+    // The exponent is checked to be within 0 and 63, so the sign is not lost.
+    #[allow(clippy::cast_sign_loss)]
+    let value = u64::from_be_bytes(mantissa) >> (63 - exponent as u32);
+    u32::try_from(value).unwrap_or(u32::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extended_rates_round_trip() {
+        assert_eq!(
+            encode_extended(44_100),
+            [0x40, 0x0E, 0xAC, 0x44, 0, 0, 0, 0, 0, 0]
+        );
+        for rate in [
+            0,
+            1,
+            8000,
+            22_050,
+            44_100,
+            48_000,
+            96_000,
+            192_000,
+            u32::MAX,
+        ] {
+            assert_eq!(decode_extended(&encode_extended(rate)), rate);
+        }
+        assert_eq!(
+            decode_extended(&[0xC0, 0x0E, 0xAC, 0x44, 0, 0, 0, 0, 0, 0]),
+            0
+        );
+    }
+
+    #[test]
+    fn encode_and_decode_bit_depths() {
+        let samples = Samples::new(2, 48_000, vec![0.0, 0.5, -0.5, 1.0, -1.0, 0.25]);
+
+        for bits in [16, 24] {
+            let bytes = encode_aiff(&samples, bits).unwrap();
+            let actual = decode_aiff(&bytes).unwrap();
+            assert_eq!(actual.channels, 2);
+            assert_eq!(actual.sample_rate, 48_000);
+            assert_eq!(actual.data.len(), 6);
+            for (actual, expected) in actual.data.iter().zip(&samples.data) {
+                assert!(
+                    (actual - expected).abs() < 1e-4,
+                    "{} != {}",
+                    actual,
+                    expected
+                );
+            }
+        }
+
+        // Odd sound data lengths are padded to keep chunks aligned.
+        let bytes = encode_aiff(&Samples::new(1, 8000, vec![0.1]), 24).unwrap();
+        assert_eq!(bytes.len() % 2, 0);
+        assert_eq!(decode_aiff(&bytes).unwrap().data.len(), 1);
+        assert!(encode_aiff(&samples, 8).is_err());
+        assert!(decode_aiff(b"RIFF\0\0\0\0WAVE").is_err());
+    }
+}
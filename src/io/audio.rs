@@ -1,54 +1,581 @@
 //! Audio readers and writers.
 
 use crate::dsp::Samples;
+use crate::io::csv::{self, IndexColumn};
+use crate::io::{aiff, path, riff};
+use crate::progress::Progress;
 use color_eyre::eyre;
 use hound::{SampleFormat, WavSpec, WavWriter};
 use rodio::{Decoder, Source};
+use std::convert::TryFrom;
 use std::fs::File;
-use std::io::BufReader;
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom};
+use std::num::{NonZeroU32, NonZeroU8};
+use std::ops::Range;
 use std::path::Path;
+use std::str::FromStr;
+use std::sync::atomic::AtomicBool;
+use vorbis_rs::{VorbisBitrateManagementStrategy, VorbisEncoderBuilder};
+
+/// Number of frames passed to the Vorbis encoder at a time.
+const OGG_BLOCK_FRAMES: usize = 1024;
+
+/// Lowest quality level supported by the Vorbis encoder.
+const OGG_MIN_QUALITY: f32 = -0.2;
+
+/// Extensions of audio files that can be read but not written.
+const READ_ONLY_EXTENSIONS: [&str; 2] = ["flac", "mp3"];
+
+/// Sample encodings that can be read directly from the data chunk of a WAV file.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Encoding {
+    Float32,
+    Pcm16,
+    Pcm24,
+    Pcm32,
+    Pcm8,
+}
+
+impl Encoding {
+    /// Get the number of bytes used by one sample.
+    const fn width(self) -> usize {
+        match self {
+            Self::Float32 | Self::Pcm32 => 4,
+            Self::Pcm16 => 2,
+            Self::Pcm24 => 3,
+            Self::Pcm8 => 1,
+        }
+    }
+
+    /// Decode one little endian sample to the range -1.0 to 1.0.
+    fn decode(self, bytes: &[u8]) -> f32 {
+        match self {
+            Self::Float32 => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            Self::Pcm16 => f32::from(i16::from_le_bytes([bytes[0], bytes[1]])) / 32_768.0,
+            Self::Pcm24 => {
+                let value = i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) >> 8;
+                value as f32 / 8_388_608.0
+            }
+            Self::Pcm32 => {
+                let value = i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+                value as f32 / 2_147_483_648.0
+            }
+            Self::Pcm8 => (f32::from(bytes[0]) - 128.0) / 128.0,
+        }
+    }
+}
+
+/// Little endian sample formats of headerless PCM files.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RawFormat {
+    F32Le,
+    S16Le,
+    S24Le,
+    U8,
+}
+
+impl RawFormat {
+    /// Get the encoding that decodes samples of the format.
+    const fn encoding(self) -> Encoding {
+        match self {
+            Self::F32Le => Encoding::Float32,
+            Self::S16Le => Encoding::Pcm16,
+            Self::S24Le => Encoding::Pcm24,
+            Self::U8 => Encoding::Pcm8,
+        }
+    }
+}
+
+impl FromStr for RawFormat {
+    type Err = eyre::Report;
+
+    fn from_str(text: &str) -> eyre::Result<Self> {
+        match text.to_ascii_lowercase().as_str() {
+            "f32le" => Ok(Self::F32Le),
+            "s16le" => Ok(Self::S16Le),
+            "s24le" => Ok(Self::S24Le),
+            "u8" => Ok(Self::U8),
+            _ => eyre::bail!(
+                "Raw format {:?} is not one of f32le, s16le, s24le, or u8",
+                text
+            ),
+        }
+    }
+}
+
+/// Samples of a WAV file read in chunks on request, holding only the requested frames in memory.
+///
+/// Tabs view and play files too large to decode through a [`Window`](crate::io::window::Window)
+/// of these samples.
+#[derive(Debug)]
+pub struct StreamingSamples {
+    channels: u16,
+    data: Range<u64>,
+    encoding: Encoding,
+    file: File,
+    sample_rate: u32,
+}
+
+impl StreamingSamples {
+    /// Get the number of channels.
+    #[must_use]
+    pub const fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    /// Get the number of frames, which are groups of one sample per channel.
+    #[must_use]
+    pub fn frames(&self) -> usize {
+        let width = (self.encoding.width() * usize::from(self.channels)) as u64;
+        // Frame counts of files that do not fit in the address space cannot be used anyway.
+        #[allow(clippy::cast_possible_truncation)]
+        let frames = ((self.data.end - self.data.start) / width) as usize;
+        frames
+    }
+
+    /// Read a range of frames from the file, clamped to the number of frames.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the file cannot be read.
+    pub fn read_frames(&mut self, frames: Range<usize>) -> eyre::Result<Samples> {
+        let width = self.encoding.width();
+        let frame_width = width * usize::from(self.channels);
+        let end = frames.end.min(self.frames());
+        let start = frames.start.min(end);
+
+        let mut bytes = vec![0_u8; (end - start) * frame_width];
+        let offset = self.data.start + (start * frame_width) as u64;
+        self.file.seek(SeekFrom::Start(offset))?;
+        self.file.read_exact(&mut bytes)?;
+
+        let data = bytes
+            .chunks_exact(width)
+            .map(|bytes| self.encoding.decode(bytes))
+            .collect();
+        Ok(Samples::new(self.channels, self.sample_rate, data))
+    }
+
+    /// Get the number of frames per second.
+    #[must_use]
+    pub const fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+}
 
 /// Read audio metadata and samples from a file.
 ///
+/// Cue points in WAV files are read as markers. Uncompressed AIFF files, which the decoder does
+/// not support, are read with [`aiff::read_aiff`]. WAV files too large to decode into memory are
+/// opened with [`read_samples_streaming`] instead.
+///
 /// # Errors
 ///
 /// Will return `Err` if `path` cannot be opened or contains invalid audio data.
 pub fn read_samples(path: &Path) -> eyre::Result<Samples> {
-    let file = File::open(&path)?;
+    read_samples_with_progress(path, &mut |_fraction| {})
+}
+
+/// Read audio metadata and samples like [`read_samples`] while reporting the completed fraction to
+/// a callback.
+///
+/// The fraction is estimated from the duration in the file header, so formats without one only
+/// report completion.
+///
+/// # Errors
+///
+/// Will return `Err` if `path` cannot be opened or contains invalid audio data.
+pub fn read_samples_with_progress(
+    path: &Path,
+    progress: &mut dyn FnMut(f32),
+) -> eyre::Result<Samples> {
+    read(path, Progress::new(progress))
+}
+
+/// Read audio metadata and samples like [`read_samples_with_progress`] until a flag is set.
+///
+/// The flag is checked for every decoded sample, so a read that is no longer needed stops
+/// shortly after it is cancelled.
+///
+/// # Errors
+///
+/// Will return `Err` if `path` cannot be opened, contains invalid audio data, or the read was
+/// cancelled.
+pub fn read_samples_cancellable(
+    path: &Path,
+    progress: &mut dyn FnMut(f32),
+    cancel: &AtomicBool,
+) -> eyre::Result<Samples> {
+    read(path, Progress::new(progress).with_cancel(cancel))
+}
+
+/// Read audio metadata and samples from a file while reporting to a progress.
+fn read(path: &Path, mut progress: Progress) -> eyre::Result<Samples> {
+    let mut samples = if aiff::is_aiff(path)? {
+        aiff::read_aiff(path)?
+    } else {
+        decode_samples(path, &mut progress)?
+    };
+    if progress.is_cancelled() {
+        eyre::bail!("Reading {:?} was cancelled", path);
+    }
+
+    // Files that are not WAV files have no RIFF chunks to read markers and metadata from.
+    if let Ok(chunks) = riff::read_chunks(path) {
+        samples.markers = riff::decode_markers(&chunks);
+        samples.metadata = riff::decode_metadata(&chunks);
+    }
+
+    progress.finish();
+    Ok(samples)
+}
+
+/// Open a WAV file for reading ranges of samples on request.
+///
+/// # Errors
+///
+/// Will return `Err` if `path` cannot be opened, is not a WAV file, or uses a compressed sample
+/// encoding.
+pub fn read_samples_streaming(path: &Path) -> eyre::Result<StreamingSamples> {
+    let format = riff::read_chunks(path)?
+        .into_iter()
+        .find(|chunk| &chunk.id == b"fmt ")
+        .ok_or_else(|| eyre::eyre!("WAV file is missing a \"fmt\" chunk"))?;
+    let (encoding, channels, sample_rate) = wav_format(&format.data)?;
+    let data = riff::data_range(path)?;
+
+    // Ignore a trailing partial frame.
+    let frame_width = (encoding.width() * usize::from(channels)) as u64;
+    let data = data.start..data.end - (data.end - data.start) % frame_width;
+
+    Ok(StreamingSamples {
+        channels,
+        data,
+        encoding,
+        file: File::open(path)?,
+        sample_rate,
+    })
+}
+
+/// Parse the sample encoding, channel count, and sample rate of a WAV format chunk.
+fn wav_format(format: &[u8]) -> eyre::Result<(Encoding, u16, u32)> {
+    if format.len() < 16 {
+        eyre::bail!("WAV format chunk is truncated");
+    }
+    let mut tag = u16::from_le_bytes([format[0], format[1]]);
+    let channels = u16::from_le_bytes([format[2], format[3]]);
+    let sample_rate = u32::from_le_bytes([format[4], format[5], format[6], format[7]]);
+    let bits = u16::from_le_bytes([format[14], format[15]]);
+
+    // WAVE_FORMAT_EXTENSIBLE stores the actual format tag at the start of the subformat GUID.
+    if tag == 0xFFFE && format.len() >= 26 {
+        tag = u16::from_le_bytes([format[24], format[25]]);
+    }
+
+    let encoding = match (tag, bits) {
+        (1, 8) => Encoding::Pcm8,
+        (1, 16) => Encoding::Pcm16,
+        (1, 24) => Encoding::Pcm24,
+        (1, 32) => Encoding::Pcm32,
+        (3, 32) => Encoding::Float32,
+        _ => eyre::bail!(
+            "WAV encoding with format tag {} and {} bits cannot be read directly",
+            tag,
+            bits
+        ),
+    };
+    if channels == 0 {
+        eyre::bail!("WAV file has no channels");
+    }
+
+    Ok((encoding, channels, sample_rate))
+}
+
+/// Decode headerless interleaved PCM bytes into samples.
+///
+/// Returns the samples and the number of trailing bytes that were dropped because they do not
+/// fill a whole frame.
+///
+/// # Errors
+///
+/// Will return `Err` if `channels` or `sample_rate` is zero.
+pub fn decode_raw(
+    bytes: &[u8],
+    format: RawFormat,
+    channels: u16,
+    sample_rate: u32,
+) -> eyre::Result<(Samples, usize)> {
+    if channels == 0 {
+        eyre::bail!("Raw PCM needs at least one channel");
+    }
+    if sample_rate == 0 {
+        eyre::bail!("Raw PCM needs a sample rate above zero");
+    }
+
+    let encoding = format.encoding();
+    let frame_width = encoding.width() * usize::from(channels);
+    let dropped = bytes.len() % frame_width;
+    let data = bytes[..bytes.len() - dropped]
+        .chunks_exact(encoding.width())
+        .map(|sample| encoding.decode(sample))
+        .collect();
+
+    Ok((Samples::new(channels, sample_rate, data), dropped))
+}
+
+/// Check whether a file starts with the header of an audio format that the decoder recognizes.
+///
+/// WAV, AIFF, Ogg, FLAC, and MP3 files are recognized, so files without one of their signatures are
+/// likely headerless PCM.
+///
+/// # Errors
+///
+/// Will return `Err` if `path` cannot be read.
+pub fn has_header(path: &Path) -> eyre::Result<bool> {
+    let mut signature = Vec::with_capacity(4);
+    File::open(path)?.take(4).read_to_end(&mut signature)?;
+
+    Ok(match signature.as_slice() {
+        [b'R', b'I', b'F', b'F'] | [b'O', b'g', b'g', b'S'] | [b'f', b'L', b'a', b'C'] => true,
+        [b'F', b'O', b'R', b'M'] | [b'I', b'D', b'3', _] => true,
+        // MPEG audio frames start with eleven set sync bits.
+        [0xFF, second, _, _] => second & 0xE0 == 0xE0,
+        _ => false,
+    })
+}
+
+/// Read a headerless PCM file like [`decode_raw`].
+///
+/// # Errors
+///
+/// Will return `Err` if `path` cannot be read, or `channels` or `sample_rate` is zero.
+pub fn read_raw(
+    path: &Path,
+    format: RawFormat,
+    channels: u16,
+    sample_rate: u32,
+) -> eyre::Result<(Samples, usize)> {
+    decode_raw(&std::fs::read(path)?, format, channels, sample_rate)
+}
+
+/// Decode all samples of a file into memory while reporting the fraction of its duration read.
+fn decode_samples(path: &Path, progress: &mut Progress) -> eyre::Result<Samples> {
+    let file = File::open(path)?;
     let reader = BufReader::new(file);
     let source = Decoder::new(reader)?;
 
     let channels = source.channels();
     let sample_rate = source.sample_rate();
-    let samples: Vec<f32> = source.convert_samples().buffered().collect();
+    let total = source.total_duration().map_or(0.0, |duration| {
+        duration.as_secs_f32() * sample_rate as f32 * f32::from(channels)
+    });
+    let samples: Vec<f32> = source
+        .convert_samples()
+        .buffered()
+        .enumerate()
+        .map_while(|(index, sample)| {
+            if total > 0.0 {
+                progress.update(index as f32 / total);
+            }
+            (!progress.is_cancelled()).then_some(sample)
+        })
+        .collect();
     Ok(Samples::new(channels, sample_rate, samples))
 }
 
+/// Write audio samples to a compressed Ogg Vorbis file.
+///
+/// Quality follows the libvorbis scale from -1.0 to 1.0 and levels below the encoder's lowest
+/// supported quality of -0.2 are raised to it. Markers are not written.
+///
+/// # Errors
+///
+/// Will return `Err` if `quality` is out of range, `samples` has no channels, has more than 255
+/// channels, or `path` is unwritable.
+pub fn write_ogg(path: &Path, samples: &Samples, quality: f32) -> eyre::Result<()> {
+    if !(-1.0..=1.0).contains(&quality) {
+        eyre::bail!("OGG quality {} is not between -1.0 and 1.0", quality);
+    }
+    let channels = u8::try_from(samples.channels)
+        .ok()
+        .and_then(NonZeroU8::new)
+        .ok_or_else(|| eyre::eyre!("OGG files need 1 to 255 channels"))?;
+    let sample_rate = NonZeroU32::new(samples.sample_rate)
+        .ok_or_else(|| eyre::eyre!("OGG files need a positive sample rate"))?;
+
+    let writer = BufWriter::new(File::create(path)?);
+    let mut encoder = VorbisEncoderBuilder::new(sample_rate, channels, writer)?
+        .bitrate_management_strategy(VorbisBitrateManagementStrategy::QualityVbr {
+            target_quality: quality.max(OGG_MIN_QUALITY),
+        })
+        .build()?;
+
+    let width = usize::from(samples.channels);
+    for block in samples.data.chunks(OGG_BLOCK_FRAMES * width) {
+        let planar: Vec<Vec<f32>> = (0..width)
+            .map(|channel| block.iter().skip(channel).step_by(width).copied().collect())
+            .collect();
+        encoder.encode_audio_block(&planar)?;
+    }
+    encoder.finish()?;
+
+    Ok(())
+}
+
+/// Settings of the formats written by [`write_by_extension`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WriteOptions {
+    /// Bits per sample of AIFF files, 16 or 24.
+    pub aiff_bits: u16,
+    /// Leading column of CSV files.
+    pub csv_index: IndexColumn,
+    /// Quality level of OGG files from -1.0 to 1.0.
+    pub ogg_quality: f32,
+}
+
+impl Default for WriteOptions {
+    fn default() -> Self {
+        Self {
+            aiff_bits: 16,
+            csv_index: IndexColumn::default(),
+            ogg_quality: 0.5,
+        }
+    }
+}
+
+/// Write samples to a file in the format of its extension and return whether it is a WAV file.
+///
+/// AIFF, CSV, and OGG files are written with the options, and files with any other extension
+/// are written as WAV like in [`write_samples`]. Markers are not written to a marker file.
+///
+/// # Errors
+///
+/// Will return `Err` if the extension belongs to a format that can only be read, such as FLAC
+/// or MP3, the samples cannot be encoded with the options, or `path` is unwritable.
+pub fn write_by_extension(
+    path: &Path,
+    samples: &Samples,
+    options: &WriteOptions,
+) -> eyre::Result<bool> {
+    if let Some(extension) = READ_ONLY_EXTENSIONS
+        .iter()
+        .find(|extension| path::has_extension(path, extension))
+    {
+        eyre::bail!(
+            "Cannot write {} files, so save {} as WAV, AIFF, OGG, or CSV instead",
+            extension.to_uppercase(),
+            path::name(path)?
+        );
+    }
+
+    if path::has_extension(path, "aif") || path::has_extension(path, "aiff") {
+        aiff::write_aiff(path, samples, options.aiff_bits)?;
+        Ok(false)
+    } else if path::has_extension(path, "csv") {
+        csv::write_csv(path, samples, options.csv_index)?;
+        Ok(false)
+    } else if path::has_extension(path, "ogg") {
+        write_ogg(path, samples, options.ogg_quality)?;
+        Ok(false)
+    } else {
+        write_samples(path, samples)?;
+        Ok(true)
+    }
+}
+
 /// Write audio metdata and samples to a file.
 ///
+/// Markers are written as WAV cue points and metadata as an INFO list.
+///
 /// # Errors
 ///
 /// Will return `Err` if `path` is unwritable.
 pub fn write_samples(path: &Path, samples: &Samples) -> eyre::Result<()> {
+    write_samples_with_progress(path, samples, &mut |_fraction| {})
+}
+
+/// Write audio metadata and samples like [`write_samples`] while reporting the completed fraction
+/// to a callback.
+///
+/// # Errors
+///
+/// Will return `Err` if `path` is unwritable.
+pub fn write_samples_with_progress(
+    path: &Path,
+    samples: &Samples,
+    progress: &mut dyn FnMut(f32),
+) -> eyre::Result<()> {
+    write_wav_with_progress(path, samples, 32, progress)
+}
+
+/// Write audio samples to a WAV file with 16 or 24 bit integer or 32 bit float samples.
+///
+/// Integer samples beyond full scale are clipped. Markers are written as WAV cue points and
+/// metadata as an INFO list.
+///
+/// # Errors
+///
+/// Will return `Err` if `bits` is not 16, 24, or 32, or `path` is unwritable.
+pub fn write_wav(path: &Path, samples: &Samples, bits: u16) -> eyre::Result<()> {
+    write_wav_with_progress(path, samples, bits, &mut |_fraction| {})
+}
+
+/// Write audio samples to a WAV file like [`write_wav`] while reporting to a progress callback.
+fn write_wav_with_progress(
+    path: &Path,
+    samples: &Samples,
+    bits: u16,
+    progress: &mut dyn FnMut(f32),
+) -> eyre::Result<()> {
+    let sample_format = match bits {
+        16 | 24 => SampleFormat::Int,
+        32 => SampleFormat::Float,
+        _ => eyre::bail!(
+            "WAV files are written with 16, 24, or 32 bits per sample, not {}",
+            bits
+        ),
+    };
+    let mut progress = Progress::new(progress);
     let spec = WavSpec {
         channels: samples.channels,
         sample_rate: samples.sample_rate,
-        bits_per_sample: 32,
-        sample_format: SampleFormat::Float,
+        bits_per_sample: bits,
+        sample_format,
     };
 
     let mut writer = WavWriter::create(path, spec)?;
 
-    for sample in &samples.data {
-        writer.write_sample(*sample)?;
+    let scale = 2f32.powi(i32::from(bits) - 1);
+    let total = samples.data.len();
+    for (index, sample) in samples.data.iter().enumerate() {
+        if sample_format == SampleFormat::Float {
+            writer.write_sample(*sample)?;
+        } else {
+            // Samples are clamped to the integer range first, so truncation is not possible.
+            #[allow(clippy::cast_possible_truncation)]
+            let value = (sample * scale).round().clamp(-scale, scale - 1.0) as i32;
+            writer.write_sample(value)?;
+        }
+        progress.update(index as f32 / total as f32);
     }
+    writer.finalize()?;
 
+    let mut chunks = riff::encode_metadata(&samples.metadata)?;
+    if !samples.markers.is_empty() {
+        chunks.extend(riff::encode_markers(&samples.markers)?);
+    }
+    if !chunks.is_empty() {
+        riff::append_chunks(path, &chunks)?;
+    }
+
+    progress.finish();
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::dsp::{AudioMetadata, Marker, Selection};
     use crate::util;
     use approx::assert_abs_diff_eq;
 
@@ -60,4 +587,243 @@ mod tests {
         let actual = read_samples(&path).unwrap();
         assert_abs_diff_eq!(actual, expected, epsilon = 0.0001);
     }
+
+    #[test]
+    fn write_and_read_slice() {
+        let samples = Samples::new(2, 32, vec![0.0, 0.5, -0.25, 0.25, 1.0, -1.0]);
+        let expected = samples.slice(Selection::new(1, 3));
+        let path = util::test::temp_wave_file(&expected).unwrap();
+
+        let actual = read_samples(&path).unwrap();
+        assert_abs_diff_eq!(actual, expected, epsilon = 0.0001);
+        assert_eq!(actual.data.len(), 4);
+    }
+
+    #[test]
+    fn write_and_read_report_progress() {
+        let folder = tempfile::tempdir().unwrap();
+        let path = folder.path().join("progress.wav");
+        let samples = Samples::new(2, 8000, vec![0.25; 20_000]);
+        let increasing = |reports: &[f32]| {
+            reports.len() > 10
+                && reports.windows(2).all(|pair| pair[0] <= pair[1])
+                && reports.last() == Some(&1.0)
+        };
+
+        let mut reports = Vec::new();
+        write_samples_with_progress(&path, &samples, &mut |fraction| reports.push(fraction))
+            .unwrap();
+        assert!(increasing(&reports), "{:?}", reports);
+
+        reports.clear();
+        let actual =
+            read_samples_with_progress(&path, &mut |fraction| reports.push(fraction)).unwrap();
+        assert!(increasing(&reports), "{:?}", reports);
+        assert_eq!(actual.frames(), 10_000);
+    }
+
+    #[test]
+    fn read_stops_once_cancelled() {
+        let samples = Samples::new(2, 8000, vec![0.25; 20_000]);
+        let path = util::test::temp_wave_file(&samples).unwrap();
+        let cancel = AtomicBool::new(false);
+
+        let mut reports = Vec::new();
+        let result = read_samples_cancellable(
+            &path,
+            &mut |fraction| {
+                reports.push(fraction);
+                if fraction >= 0.5 {
+                    cancel.store(true, std::sync::atomic::Ordering::Relaxed);
+                }
+            },
+            &cancel,
+        );
+        assert!(result.is_err());
+        assert!(
+            reports.iter().all(|fraction| *fraction < 0.6),
+            "{:?}",
+            reports
+        );
+
+        let cancel = AtomicBool::new(false);
+        let actual = read_samples_cancellable(&path, &mut |_fraction| {}, &cancel).unwrap();
+        assert_eq!(actual.frames(), 10_000);
+    }
+
+    #[test]
+    fn write_wav_bit_depths() {
+        let folder = tempfile::tempdir().unwrap();
+        let path = folder.path().join("depth.wav");
+        let expected = Samples::new(2, 8000, vec![0.0, 0.5, -0.5, 1.0, -1.0, 0.25]);
+
+        for bits in [16, 24, 32] {
+            write_wav(&path, &expected, bits).unwrap();
+            assert_eq!(
+                hound::WavReader::open(&path)
+                    .unwrap()
+                    .spec()
+                    .bits_per_sample,
+                bits
+            );
+            assert_abs_diff_eq!(read_samples(&path).unwrap(), expected, epsilon = 0.0001);
+        }
+        assert!(write_wav(&path, &expected, 8).is_err());
+    }
+
+    #[test]
+    fn streaming_pcm_encodings() {
+        let folder = tempfile::tempdir().unwrap();
+        let path = folder.path().join("pcm.wav");
+        let spec = WavSpec {
+            channels: 1,
+            sample_rate: 8,
+            bits_per_sample: 16,
+            sample_format: SampleFormat::Int,
+        };
+        let mut writer = WavWriter::create(&path, spec).unwrap();
+        for sample in [0_i16, 16_384, -32_768] {
+            writer.write_sample(sample).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let streamed = read_samples_streaming(&path)
+            .unwrap()
+            .read_frames(0..3)
+            .unwrap();
+        let decoded = decode_samples(&path, &mut Progress::new(&mut |_fraction| {})).unwrap();
+        assert_abs_diff_eq!(streamed, decoded, epsilon = 0.0001);
+        assert_abs_diff_eq!(streamed.data[1], 0.5);
+    }
+
+    #[test]
+    fn write_and_read_markers() {
+        let mut expected = Samples::new(1, 32, vec![0.0f32, -0.25f32, 0.25f32, 1.0f32]);
+        expected.markers = vec![
+            Marker::new(1, String::from("attack")),
+            Marker::new(3, String::new()),
+        ];
+        let path = util::test::temp_wave_file(&expected).unwrap();
+
+        let actual = read_samples(&path).unwrap();
+        assert_abs_diff_eq!(actual, expected, epsilon = 0.0001);
+        assert_eq!(actual.markers, expected.markers);
+    }
+
+    #[test]
+    fn write_and_read_metadata() {
+        let mut expected = Samples::new(1, 32, vec![0.0, 0.5, -0.5, 0.25]);
+        expected.markers = vec![Marker::new(2, String::from("chorus"))];
+        expected.metadata = AudioMetadata {
+            artist: Some(String::from("Band")),
+            comment: None,
+            software: Some(String::from("Sampitor")),
+            title: Some(String::from("Song")),
+        };
+        let path = util::test::temp_wave_file(&expected).unwrap();
+
+        let actual = read_samples(&path).unwrap();
+        assert_eq!(actual.metadata, expected.metadata);
+        assert_eq!(actual.markers, expected.markers);
+        assert_eq!(
+            crate::io::metadata::read_metadata(&path).unwrap().tags[0],
+            (String::from("Title"), String::from("Song"))
+        );
+
+        expected.metadata = AudioMetadata::default();
+        write_wav(&path, &expected, 16).unwrap();
+        assert!(read_samples(&path).unwrap().metadata.is_empty());
+    }
+
+    #[test]
+    fn read_markers_of_integer_wave() {
+        let folder = tempfile::tempdir().unwrap();
+        let path = folder.path().join("library.wav");
+        let spec = WavSpec {
+            channels: 2,
+            sample_rate: 8000,
+            bits_per_sample: 16,
+            sample_format: SampleFormat::Int,
+        };
+        let mut writer = WavWriter::create(&path, spec).unwrap();
+        for index in 0..200_i16 {
+            writer.write_sample(index * 100).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let markers = vec![
+            Marker::new(10, String::from("loop start")),
+            Marker::new(90, String::from("loop end")),
+        ];
+        riff::append_chunks(&path, &riff::encode_markers(&markers).unwrap()).unwrap();
+
+        let actual = read_samples(&path).unwrap();
+        assert_eq!(actual.frames(), 100);
+        assert_eq!(actual.markers, markers);
+    }
+
+    #[test]
+    fn decode_raw_formats() {
+        let cases: [(RawFormat, Vec<u8>, Vec<f32>); 4] = [
+            (
+                RawFormat::F32Le,
+                [0.5_f32, -0.25]
+                    .iter()
+                    .flat_map(|x| x.to_le_bytes())
+                    .collect(),
+                vec![0.5, -0.25],
+            ),
+            (
+                RawFormat::S16Le,
+                vec![0x00, 0x40, 0x00, 0x80],
+                vec![0.5, -1.0],
+            ),
+            (
+                RawFormat::S24Le,
+                vec![0x00, 0x00, 0x40, 0x00, 0x00, 0xE0],
+                vec![0.5, -0.25],
+            ),
+            (RawFormat::U8, vec![0x80, 0xC0], vec![0.0, 0.5]),
+        ];
+
+        for (format, bytes, expected) in cases {
+            let (actual, dropped) = decode_raw(&bytes, format, 2, 8000).unwrap();
+            assert_eq!(actual, Samples::new(2, 8000, expected), "{:?}", format);
+            assert_eq!(dropped, 0);
+        }
+    }
+
+    #[test]
+    fn decode_raw_drops_partial_frame() {
+        let bytes = [0x00, 0x40, 0x00, 0xC0, 0x00, 0x40, 0x00];
+
+        let (actual, dropped) = decode_raw(&bytes, RawFormat::S16Le, 2, 8000).unwrap();
+        assert_eq!(actual.data, vec![0.5, -0.5]);
+        assert_eq!(dropped, 3);
+
+        assert!(decode_raw(&bytes, RawFormat::S16Le, 0, 8000).is_err());
+        assert!(decode_raw(&bytes, RawFormat::S16Le, 1, 0).is_err());
+    }
+
+    #[test]
+    fn parse_raw_format() {
+        assert_eq!("S24LE".parse::<RawFormat>().unwrap(), RawFormat::S24Le);
+        assert_eq!("u8".parse::<RawFormat>().unwrap(), RawFormat::U8);
+        assert!("s16be".parse::<RawFormat>().is_err());
+    }
+
+    #[test]
+    fn has_header_detects_signatures() {
+        let folder = tempfile::tempdir().unwrap();
+        let wave = util::test::temp_wave_file(&Samples::new(1, 8, vec![0.0; 4])).unwrap();
+        let raw = folder.path().join("capture.raw");
+        std::fs::write(&raw, [0x00, 0x40, 0x00, 0xC0]).unwrap();
+        let short = folder.path().join("short.pcm");
+        std::fs::write(&short, [0x80]).unwrap();
+
+        assert!(has_header(&wave).unwrap());
+        assert!(!has_header(&raw).unwrap());
+        assert!(!has_header(&short).unwrap());
+        assert!(has_header(&folder.path().join("missing.raw")).is_err());
+    }
 }
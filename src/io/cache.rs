@@ -0,0 +1,263 @@
+//! In memory cache of decoded audio files.
+
+use crate::dsp::Samples;
+use color_eyre::eyre;
+use std::fs;
+use std::io;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::SystemTime;
+
+/// Bytes of decoded samples kept by the shared cache, if not configured otherwise.
+pub const DEFAULT_BUDGET: usize = 256 * 1024 * 1024;
+
+/// Cache shared by every read of audio files in the application.
+static SHARED: Mutex<Cache> = Mutex::new(Cache::new(DEFAULT_BUDGET));
+
+/// Identity of a file on disk, which changes when the file is rewritten.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Key {
+    modified: SystemTime,
+    path: PathBuf,
+    size: u64,
+}
+
+impl Key {
+    /// Get the key of a file from its canonical path, modification time, and size.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `path` does not exist or its metadata is unreadable.
+    pub fn of(path: &Path) -> io::Result<Self> {
+        let path = fs::canonicalize(path)?;
+        let metadata = fs::metadata(&path)?;
+
+        Ok(Self {
+            modified: metadata.modified()?,
+            path,
+            size: metadata.len(),
+        })
+    }
+}
+
+/// Decoded samples of recently read files, evicting the least recently used files once their
+/// samples exceed a byte budget.
+#[derive(Debug, Default)]
+pub struct Cache {
+    budget: usize,
+    /// Cached files ordered from least to most recently used.
+    entries: Vec<(Key, Samples)>,
+}
+
+impl Cache {
+    /// Create an empty Cache that keeps at most a number of bytes of samples.
+    #[must_use]
+    pub const fn new(budget: usize) -> Self {
+        Self {
+            budget,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Get the number of bytes of samples in the cache.
+    #[must_use]
+    pub fn bytes(&self) -> usize {
+        self.entries
+            .iter()
+            .map(|(_key, samples)| size(samples))
+            .sum()
+    }
+
+    /// Get a copy of the samples cached for a file key and mark them as recently used.
+    ///
+    /// Entries for older versions of the same file are dropped, since they can no longer match.
+    pub fn get(&mut self, key: &Key) -> Option<Samples> {
+        self.entries
+            .retain(|(cached, _samples)| cached == key || cached.path != key.path);
+        let index = self
+            .entries
+            .iter()
+            .position(|(cached, _samples)| cached == key)?;
+
+        let entry = self.entries.remove(index);
+        let samples = entry.1.clone();
+        self.entries.push(entry);
+        Some(samples)
+    }
+
+    /// Cache a copy of the samples decoded from a file, evicting the least recently used files
+    /// until they fit the budget.
+    ///
+    /// Samples larger than the whole budget are not cached.
+    pub fn insert(&mut self, key: Key, samples: &Samples) {
+        self.entries
+            .retain(|(cached, _samples)| cached.path != key.path);
+        if size(samples) > self.budget {
+            return;
+        }
+
+        self.entries.push((key, samples.clone()));
+        self.evict();
+    }
+
+    /// Read samples through the cache, calling a reader only if the file is not cached or has
+    /// changed since it was cached.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the reader fails.
+    pub fn read(
+        &mut self,
+        path: &Path,
+        read: impl FnOnce(&Path) -> eyre::Result<Samples>,
+    ) -> eyre::Result<Samples> {
+        // Files without readable metadata are left for the reader to report.
+        let key = match Key::of(path) {
+            Ok(key) => key,
+            Err(_error) => return read(path),
+        };
+        if let Some(samples) = self.get(&key) {
+            return Ok(samples);
+        }
+
+        let samples = read(path)?;
+        self.insert(key, &samples);
+        Ok(samples)
+    }
+
+    /// Change the byte budget, evicting the least recently used files if they no longer fit.
+    pub fn set_budget(&mut self, budget: usize) {
+        self.budget = budget;
+        self.evict();
+    }
+
+    /// Drop the least recently used files until the cached samples fit the budget.
+    fn evict(&mut self) {
+        let mut bytes = self.bytes();
+        while bytes > self.budget && !self.entries.is_empty() {
+            let (_key, samples) = self.entries.remove(0);
+            bytes -= size(&samples);
+        }
+    }
+}
+
+/// Lock the cache shared by the application.
+pub fn shared() -> MutexGuard<'static, Cache> {
+    SHARED.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Read samples through the shared cache like [`Cache::read`].
+///
+/// The cache is only locked to look up and store samples, so slow reads do not block others.
+///
+/// # Errors
+///
+/// Will return `Err` if `path` does not exist or the reader fails.
+pub fn read_shared(
+    path: &Path,
+    read: impl FnOnce(&Path) -> eyre::Result<Samples>,
+) -> eyre::Result<Samples> {
+    let key = match Key::of(path) {
+        Ok(key) => key,
+        Err(_error) => return read(path),
+    };
+    if let Some(samples) = shared().get(&key) {
+        return Ok(samples);
+    }
+
+    let samples = read(path)?;
+    shared().insert(key, &samples);
+    Ok(samples)
+}
+
+/// Get the bytes that samples take in memory.
+fn size(samples: &Samples) -> usize {
+    samples.data.len() * mem::size_of::<f32>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::audio;
+    use std::cell::Cell;
+    use std::time::Duration;
+
+    #[test]
+    fn second_read_hits_until_file_changes() {
+        let folder = tempfile::tempdir().unwrap();
+        let path = folder.path().join("take.wav");
+        audio::write_samples(&path, &Samples::new(1, 8000, vec![0.0, 0.5])).unwrap();
+
+        let decodes = Cell::new(0);
+        let read = |path: &Path| {
+            decodes.set(decodes.get() + 1);
+            audio::read_samples(path)
+        };
+        let mut cache = Cache::new(DEFAULT_BUDGET);
+
+        let first = cache.read(&path, read).unwrap();
+        let second = cache
+            .read(&folder.path().join(".").join("take.wav"), read)
+            .unwrap();
+        assert_eq!(decodes.get(), 1);
+        assert_eq!(first, second);
+        assert_eq!(cache.bytes(), 8);
+
+        let file = fs::File::options().write(true).open(&path).unwrap();
+        let modified = file.metadata().unwrap().modified().unwrap();
+        file.set_modified(modified + Duration::from_secs(5))
+            .unwrap();
+        drop(file);
+
+        cache.read(&path, read).unwrap();
+        assert_eq!(decodes.get(), 2);
+        assert_eq!(cache.entries.len(), 1);
+        assert!(cache
+            .read(&folder.path().join("missing.wav"), read)
+            .is_err());
+        assert_eq!(decodes.get(), 3);
+        assert_eq!(cache.entries.len(), 1);
+    }
+
+    #[test]
+    fn budget_evicts_least_recently_used() {
+        let folder = tempfile::tempdir().unwrap();
+        let paths: Vec<PathBuf> = ["a.wav", "b.wav", "c.wav", "big.wav"]
+            .iter()
+            .map(|name| folder.path().join(name))
+            .collect();
+        for path in &paths[..3] {
+            audio::write_samples(path, &Samples::new(1, 8000, vec![0.25; 4])).unwrap();
+        }
+        audio::write_samples(&paths[3], &Samples::new(1, 8000, vec![0.25; 16])).unwrap();
+
+        let decodes = Cell::new(0);
+        let read = |path: &Path| {
+            decodes.set(decodes.get() + 1);
+            audio::read_samples(path)
+        };
+        let mut cache = Cache::new(40);
+
+        cache.read(&paths[0], read).unwrap();
+        cache.read(&paths[1], read).unwrap();
+        cache.read(&paths[0], read).unwrap();
+        cache.read(&paths[2], read).unwrap();
+        assert_eq!(cache.bytes(), 32);
+        cache.read(&paths[0], read).unwrap();
+        assert_eq!(decodes.get(), 3);
+        cache.read(&paths[1], read).unwrap();
+        assert_eq!(decodes.get(), 4);
+
+        cache.read(&paths[3], read).unwrap();
+        assert_eq!(decodes.get(), 5);
+        assert_eq!(cache.bytes(), 32);
+
+        cache.set_budget(20);
+        assert_eq!(cache.bytes(), 16);
+        cache.read(&paths[1], read).unwrap();
+        assert_eq!(decodes.get(), 5);
+        cache.read(&paths[0], read).unwrap();
+        assert_eq!(decodes.get(), 6);
+    }
+}
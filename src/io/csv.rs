@@ -0,0 +1,191 @@
+//! Comma separated text readers and writers for inspecting sample values.
+
+use crate::dsp::Samples;
+use color_eyre::eyre;
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+/// Leading column written before the channel columns of each row.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum IndexColumn {
+    /// Frame index starting at zero.
+    Frame,
+    #[default]
+    None,
+    /// Frame start time in seconds.
+    Time,
+}
+
+impl IndexColumn {
+    /// Get the header of the column, if it is written.
+    const fn header(self) -> Option<&'static str> {
+        match self {
+            Self::Frame => Some("frame"),
+            Self::None => None,
+            Self::Time => Some("time"),
+        }
+    }
+}
+
+/// Write samples as comma separated text with a header row and one row per frame.
+///
+/// Each channel has a column after the optional index column. Values are formatted with Rust's
+/// locale independent float formatting, so decimals are always separated by a period and read
+/// back exactly. Rows are streamed to the file instead of being collected in memory.
+///
+/// # Errors
+///
+/// Will return `Err` if `path` is unwritable.
+pub fn write_csv(path: &Path, samples: &Samples, index: IndexColumn) -> eyre::Result<()> {
+    let width = usize::from(samples.channels.max(1));
+    let rate = f64::from(samples.sample_rate.max(1));
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    let mut headers: Vec<String> = index.header().map(String::from).into_iter().collect();
+    headers.extend((1..=width).map(|channel| format!("channel {}", channel)));
+    writeln!(writer, "{}", headers.join(","))?;
+
+    for (frame, values) in samples.data.chunks_exact(width).enumerate() {
+        match index {
+            IndexColumn::Frame => write!(writer, "{},", frame)?,
+            IndexColumn::None => (),
+            IndexColumn::Time => write!(writer, "{},", frame as f64 / rate)?,
+        }
+        for (channel, value) in values.iter().enumerate() {
+            if channel > 0 {
+                write!(writer, ",")?;
+            }
+            write!(writer, "{}", value)?;
+        }
+        writeln!(writer)?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Read comma separated sample values like those of [`write_csv`] at a sample rate.
+///
+/// A first row that does not hold numbers is a header, and a header starting with a frame or
+/// time column marks that column as an index to skip. Every other column is a channel. Blank
+/// lines are ignored.
+///
+/// # Errors
+///
+/// Will return `Err` if `path` is unreadable, `sample_rate` is zero, rows have differing numbers
+/// of values, a value is not a number, or the file has no channels.
+pub fn read_csv(path: &Path, sample_rate: u32) -> eyre::Result<Samples> {
+    if sample_rate == 0 {
+        eyre::bail!("CSV samples need a sample rate above zero");
+    }
+
+    let reader = BufReader::new(File::open(path)?);
+    let mut columns: Option<(usize, usize)> = None;
+    let mut data = Vec::new();
+
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+
+        let (skip, width) = match columns {
+            Some(columns) => columns,
+            None => {
+                let header = fields.iter().any(|field| field.parse::<f32>().is_err());
+                let skip = usize::from(
+                    header && ["frame", "time"].contains(&fields[0].to_lowercase().as_str()),
+                );
+                columns = Some((skip, fields.len() - skip));
+                if header {
+                    continue;
+                }
+                (skip, fields.len() - skip)
+            }
+        };
+
+        if fields.len() != skip + width {
+            eyre::bail!(
+                "Line {} has {} values but {} were expected",
+                number + 1,
+                fields.len(),
+                skip + width
+            );
+        }
+        for field in &fields[skip..] {
+            let value = field.parse::<f32>().map_err(|_error| {
+                eyre::eyre!("Line {} has invalid value {:?}", number + 1, field)
+            })?;
+            data.push(value);
+        }
+    }
+
+    let width = columns.map_or(0, |(_skip, width)| width);
+    if width == 0 {
+        eyre::bail!("CSV file {:?} has no channel columns", path);
+    }
+    Ok(Samples::new(u16::try_from(width)?, sample_rate, data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn write_and_read_stereo() {
+        let folder = tempfile::tempdir().unwrap();
+        let expected = Samples::new(2, 4, vec![0.0, -0.25, 0.1, 1.0, -0.333_333_34, 0.5]);
+
+        for (index, header) in [
+            (IndexColumn::Frame, "frame,channel 1,channel 2"),
+            (IndexColumn::None, "channel 1,channel 2"),
+            (IndexColumn::Time, "time,channel 1,channel 2"),
+        ] {
+            let path = folder.path().join("samples.csv");
+            write_csv(&path, &expected, index).unwrap();
+
+            let text = fs::read_to_string(&path).unwrap();
+            let lines: Vec<&str> = text.lines().collect();
+            assert_eq!(lines.len(), 4);
+            assert_eq!(lines[0], header);
+            assert!(!text.contains(';'));
+
+            assert_eq!(read_csv(&path, 4).unwrap(), expected, "{:?}", index);
+        }
+
+        let path = folder.path().join("samples.csv");
+        write_csv(&path, &expected, IndexColumn::Time).unwrap();
+        let text = fs::read_to_string(&path).unwrap();
+        assert_eq!(text.lines().nth(2), Some("0.25,0.1,1"));
+    }
+
+    #[test]
+    fn read_without_header() {
+        let folder = tempfile::tempdir().unwrap();
+        let path = folder.path().join("values.csv");
+        fs::write(&path, "0.5, -0.5\n\n1,0\n").unwrap();
+
+        let actual = read_csv(&path, 8000).unwrap();
+        assert_eq!(actual, Samples::new(2, 8000, vec![0.5, -0.5, 1.0, 0.0]));
+    }
+
+    #[test]
+    fn read_rejects_malformed_rows() {
+        let folder = tempfile::tempdir().unwrap();
+        let path = folder.path().join("bad.csv");
+
+        for text in ["a,b\n0.5\n", "0.5,0.25\n0.5,x\n", "\n", "frame\n0\n"] {
+            fs::write(&path, text).unwrap();
+            assert!(read_csv(&path, 8000).is_err(), "{:?}", text);
+        }
+
+        fs::write(&path, "0.5\n").unwrap();
+        assert!(read_csv(&path, 0).is_err());
+    }
+}
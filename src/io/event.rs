@@ -1,18 +1,19 @@
-//! Keyboard input event handlers.
+//! Keyboard and mouse input event handlers.
 
-use crossterm::event::{self, Event, KeyEvent};
+use crossterm::event::{self, Event};
 use std::sync::mpsc::Sender;
 use std::thread::{self, JoinHandle};
 
-/// Spawn a thread to offload polling for keyboard events.
+/// Spawn a thread to offload polling for keyboard and mouse events.
 ///
 /// # Panics
 ///
 /// Will return `Err` if `sender` fails or events are unreadable.
-pub fn handler(sender: Sender<Option<KeyEvent>>) -> JoinHandle<()> {
+pub fn handler(sender: Sender<Event>) -> JoinHandle<()> {
     thread::spawn(move || loop {
-        if let Event::Key(key) = event::read().unwrap() {
-            sender.send(Some(key)).unwrap();
+        let event = event::read().unwrap();
+        if matches!(event, Event::Key(_) | Event::Mouse(_)) {
+            sender.send(event).unwrap();
         }
     })
 }
@@ -0,0 +1,176 @@
+//! Audacity label track readers and writers for keeping markers next to audio files.
+
+use crate::dsp::{Marker, Samples};
+use color_eyre::eyre;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Suffix appended to an audio file name to get the file its markers are kept in.
+pub const SIDECAR_SUFFIX: &str = ".markers.txt";
+
+/// Parse markers from Audacity label track text.
+///
+/// Each line holds a start time, an end time, and a label separated by tabs, with times in
+/// seconds. Labels of regions become markers at the region start, and lines with the frequency
+/// range of spectral labels, which start with a backslash, are skipped.
+///
+/// # Errors
+///
+/// Will return `Err` if a line does not start with two non-negative times.
+pub fn parse_labels(text: &str, sample_rate: u32) -> eyre::Result<Vec<Marker>> {
+    let mut markers = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        if line.trim().is_empty() || line.starts_with('\\') {
+            continue;
+        }
+
+        let mut fields = line.splitn(3, '\t');
+        let mut time = || {
+            fields
+                .next()
+                .and_then(|field| field.trim().parse::<f64>().ok())
+                .filter(|seconds| seconds.is_finite() && *seconds >= 0.0)
+                .ok_or_else(|| eyre::eyre!("Label line {} needs a start and end time", index + 1))
+        };
+        let start = time()?;
+        let _end = time()?;
+        let label = fields.next().unwrap_or_default().trim_end_matches('\r');
+
+        // Times are checked to be non-negative and finite, so truncation is not possible.
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let frame = (start * f64::from(sample_rate)).round() as usize;
+        markers.push(Marker::new(frame, label.to_owned()));
+    }
+
+    markers.sort_by_key(|marker| marker.frame);
+    Ok(markers)
+}
+
+/// Format markers as Audacity label track text with a point label per marker.
+#[must_use]
+pub fn format_labels(markers: &[Marker], sample_rate: u32) -> String {
+    let rate = f64::from(sample_rate.max(1));
+    markers
+        .iter()
+        .map(|marker| {
+            let seconds = marker.frame as f64 / rate;
+            format!("{:.6}\t{:.6}\t{}\n", seconds, seconds, marker.label)
+        })
+        .collect()
+}
+
+/// Read markers from an Audacity label track file.
+///
+/// # Errors
+///
+/// Will return `Err` if `path` cannot be read or is not a label track.
+pub fn read_labels(path: &Path, sample_rate: u32) -> eyre::Result<Vec<Marker>> {
+    parse_labels(&fs::read_to_string(path)?, sample_rate)
+}
+
+/// Write markers to an Audacity label track file.
+///
+/// # Errors
+///
+/// Will return `Err` if `path` is unwritable.
+pub fn write_labels(path: &Path, markers: &[Marker], sample_rate: u32) -> eyre::Result<()> {
+    fs::write(path, format_labels(markers, sample_rate))?;
+    Ok(())
+}
+
+/// Get the path of the file that keeps the markers of an audio file.
+#[must_use]
+pub fn sidecar(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(SIDECAR_SUFFIX);
+    PathBuf::from(name)
+}
+
+/// Read the markers kept next to an audio file, if it has a marker file.
+///
+/// # Errors
+///
+/// Will return `Err` if the marker file exists but cannot be read or is not a label track.
+pub fn read_sidecar(path: &Path, sample_rate: u32) -> eyre::Result<Option<Vec<Marker>>> {
+    let sidecar = sidecar(path);
+    if sidecar.is_file() {
+        read_labels(&sidecar, sample_rate).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+/// Keep the markers of samples written to a path in a marker file next to it.
+///
+/// A marker file left from earlier markers is removed if the samples have none, so deleted
+/// markers do not come back when the file is reopened.
+///
+/// # Errors
+///
+/// Will return `Err` if the marker file cannot be written or removed.
+pub fn write_sidecar(path: &Path, samples: &Samples) -> eyre::Result<()> {
+    let sidecar = sidecar(path);
+    if !samples.markers.is_empty() {
+        write_labels(&sidecar, &samples.markers, samples.sample_rate)
+    } else if sidecar.is_file() {
+        fs::remove_file(&sidecar)?;
+        Ok(())
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labels_round_trip() {
+        let folder = tempfile::tempdir().unwrap();
+        let path = folder.path().join("song.txt");
+        let markers = vec![
+            Marker::new(0, String::from("start")),
+            Marker::new(44_100, String::from("verse")),
+            Marker::new(4_057_200, String::from("bad click at 1:32")),
+            Marker::new(4_057_201, String::new()),
+        ];
+
+        write_labels(&path, &markers, 44_100).unwrap();
+        assert_eq!(read_labels(&path, 44_100).unwrap(), markers);
+    }
+
+    #[test]
+    fn parse_audacity_regions_and_spectral_lines() {
+        let text = "1.500000\t2.000000\tchorus\n\\\t100.0\t2000.0\n0.25\t0.25\tintro\r\n\n";
+        let markers = parse_labels(text, 8000).unwrap();
+
+        assert_eq!(
+            markers,
+            vec![
+                Marker::new(2000, String::from("intro")),
+                Marker::new(12_000, String::from("chorus")),
+            ]
+        );
+        assert!(parse_labels("verse\t1.0\tlabel", 8000).is_err());
+        assert!(parse_labels("-1.0\t1.0\tlabel", 8000).is_err());
+    }
+
+    #[test]
+    fn sidecar_follows_markers() {
+        let folder = tempfile::tempdir().unwrap();
+        let path = folder.path().join("song.wav");
+        let mut samples = Samples::new(1, 100, vec![0.0; 200]);
+
+        assert_eq!(sidecar(&path), folder.path().join("song.wav.markers.txt"));
+        assert_eq!(read_sidecar(&path, 100).unwrap(), None);
+
+        samples.markers = vec![Marker::new(150, String::from("drop"))];
+        write_sidecar(&path, &samples).unwrap();
+        assert_eq!(read_sidecar(&path, 100).unwrap(), Some(samples.markers));
+
+        samples.markers = Vec::new();
+        write_sidecar(&path, &samples).unwrap();
+        assert!(!sidecar(&path).exists());
+        write_sidecar(&path, &samples).unwrap();
+    }
+}
@@ -0,0 +1,504 @@
+//! Read only probes of tags and technical details embedded in audio files.
+
+use crate::io::riff;
+use color_eyre::eyre;
+use lewton::inside_ogg::OggStreamReader;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Number of bytes after an ID3 tag that are searched for the first MPEG audio frame.
+const MPEG_SEARCH: usize = 64 * 1024;
+
+/// Bitrates in kilobits per second of MPEG-1 Layer III frames by bitrate index.
+const MPEG1_BITRATES: [u32; 15] = [
+    0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320,
+];
+
+/// Bitrates in kilobits per second of MPEG-2 and MPEG-2.5 Layer III frames by bitrate index.
+const MPEG2_BITRATES: [u32; 15] = [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160];
+
+/// Descriptive tags and technical details of an audio file.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Metadata {
+    /// Labeled technical details such as the format and bitrate.
+    pub details: Vec<(String, String)>,
+    /// Labeled tags such as the title, artist, and album in file order.
+    pub tags: Vec<(String, String)>,
+}
+
+impl Metadata {
+    /// Check whether no details or tags are known.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.details.is_empty() && self.tags.is_empty()
+    }
+
+    /// Format details followed by tags as `label: value` lines, noting when there are no tags.
+    #[must_use]
+    pub fn lines(&self) -> Vec<String> {
+        if self.is_empty() {
+            return vec![String::from("No metadata")];
+        }
+
+        let mut lines: Vec<String> = self
+            .details
+            .iter()
+            .map(|(label, value)| format!("{}: {}", label, value))
+            .collect();
+        lines.push(String::new());
+        if self.tags.is_empty() {
+            lines.push(String::from("No metadata tags"));
+        }
+        lines.extend(
+            self.tags
+                .iter()
+                .map(|(label, value)| format!("{}: {}", label, value)),
+        );
+        lines
+    }
+}
+
+/// Probe the tags and technical details of a FLAC, Ogg Vorbis, MP3, or WAV file.
+///
+/// The format is recognized by its signature and only headers and tags are read, not the
+/// samples. Files of other formats have no metadata.
+///
+/// # Errors
+///
+/// Will return `Err` if `path` is unreadable or its headers are malformed.
+pub fn read_metadata(path: &Path) -> eyre::Result<Metadata> {
+    let mut signature = Vec::with_capacity(4);
+    File::open(path)?.take(4).read_to_end(&mut signature)?;
+
+    match signature.as_slice() {
+        [b'f', b'L', b'a', b'C'] => read_flac(path),
+        [b'O', b'g', b'g', b'S'] => read_ogg(path),
+        [b'R', b'I', b'F', b'F'] => read_wave(path),
+        [b'I', b'D', b'3', _] => read_mp3(path),
+        [0xFF, second, _, _] if second & 0xE0 == 0xE0 => read_mp3(path),
+        _ => Ok(Metadata::default()),
+    }
+}
+
+/// Read stream information and Vorbis comments of a FLAC file.
+fn read_flac(path: &Path) -> eyre::Result<Metadata> {
+    let reader = claxon::FlacReader::open(path)?;
+    let info = reader.streaminfo();
+
+    let mut details = vec![
+        (String::from("Format"), String::from("FLAC")),
+        (
+            String::from("Bit depth"),
+            format!("{} bits", info.bits_per_sample),
+        ),
+    ];
+    if let Some(vendor) = reader.vendor() {
+        details.push((String::from("Encoder"), vendor.to_owned()));
+    }
+    let tags = reader
+        .tags()
+        .map(|(key, value)| (tag_label(key), value.to_owned()))
+        .collect();
+
+    Ok(Metadata { details, tags })
+}
+
+/// Read identification and comment headers of an Ogg Vorbis file.
+fn read_ogg(path: &Path) -> eyre::Result<Metadata> {
+    let reader = OggStreamReader::new(BufReader::new(File::open(path)?))
+        .map_err(|error| eyre::eyre!("Ogg Vorbis headers are invalid: {}", error))?;
+
+    let mut details = vec![(String::from("Format"), String::from("Ogg Vorbis"))];
+    if reader.ident_hdr.bitrate_nominal > 0 {
+        details.push((
+            String::from("Bitrate"),
+            format!("{} kbps", reader.ident_hdr.bitrate_nominal / 1000),
+        ));
+    }
+    details.push((String::from("Encoder"), reader.comment_hdr.vendor.clone()));
+    let tags = reader
+        .comment_hdr
+        .comment_list
+        .iter()
+        .map(|(key, value)| (tag_label(key), value.clone()))
+        .collect();
+
+    Ok(Metadata { details, tags })
+}
+
+/// Read ID3 tags and the first frame header of an MP3 file.
+///
+/// ID3v2.3 and ID3v2.4 text frames are read, falling back to an ID3v1 tag at the end of the file.
+fn read_mp3(path: &Path) -> eyre::Result<Metadata> {
+    let mut file = File::open(path)?;
+    let mut header = [0_u8; 10];
+    let read = file.read(&mut header)?;
+
+    let (mut tags, audio_start) = if read == 10 && header.starts_with(b"ID3") {
+        let size = syncsafe(&header[6..10]) as usize;
+        let mut body = Vec::with_capacity(size);
+        (&mut file).take(size as u64).read_to_end(&mut body)?;
+        // A footer repeats the header after the tag.
+        let footer = if header[5] & 0x10 == 0 { 0 } else { 10 };
+        (id3v2_tags(header[3], header[5], &body), 10 + size + footer)
+    } else {
+        (Vec::new(), 0)
+    };
+    if tags.is_empty() {
+        tags = id3v1_tags(&mut file)?;
+    }
+
+    file.seek(SeekFrom::Start(audio_start as u64))?;
+    let mut audio = Vec::with_capacity(MPEG_SEARCH);
+    (&mut file)
+        .take(MPEG_SEARCH as u64)
+        .read_to_end(&mut audio)?;
+
+    let mut details = vec![(String::from("Format"), String::from("MP3"))];
+    if let Some((bitrate, sample_rate)) = mpeg_frame(&audio) {
+        details.push((String::from("Bitrate"), format!("{} kbps", bitrate)));
+        details.push((String::from("Sample rate"), format!("{} Hz", sample_rate)));
+    }
+    Ok(Metadata { details, tags })
+}
+
+/// Read the format chunk and INFO list of a WAV file.
+fn read_wave(path: &Path) -> eyre::Result<Metadata> {
+    let chunks = riff::read_chunks(path)?;
+
+    let mut details = vec![(String::from("Format"), String::from("WAV"))];
+    if let Some(format) = chunks.iter().find(|chunk| &chunk.id == b"fmt ") {
+        if format.data.len() >= 16 {
+            let tag = u16::from_le_bytes([format.data[0], format.data[1]]);
+            let rate = u32::from_le_bytes([
+                format.data[8],
+                format.data[9],
+                format.data[10],
+                format.data[11],
+            ]);
+            let bits = u16::from_le_bytes([format.data[14], format.data[15]]);
+            details.push((
+                String::from("Encoding"),
+                match tag {
+                    1 => format!("{} bit integer PCM", bits),
+                    3 => format!("{} bit float PCM", bits),
+                    0xFFFE => format!("{} bit extensible", bits),
+                    _ => format!("Format tag {} with {} bits", tag, bits),
+                },
+            ));
+            details.push((
+                String::from("Bitrate"),
+                format!("{} kbps", u64::from(rate) * 8 / 1000),
+            ));
+        }
+    }
+
+    let tags = riff::decode_info(&chunks)
+        .into_iter()
+        .map(|(id, text)| {
+            let label = match &id {
+                b"IART" => String::from("Artist"),
+                b"ICMT" => String::from("Comment"),
+                b"ICRD" => String::from("Date"),
+                b"IGNR" => String::from("Genre"),
+                b"INAM" => String::from("Title"),
+                b"IPRD" => String::from("Album"),
+                b"ISFT" => String::from("Encoder"),
+                b"ITRK" => String::from("Track"),
+                _ => String::from_utf8_lossy(&id).into_owned(),
+            };
+            (label, text)
+        })
+        .collect();
+
+    Ok(Metadata { details, tags })
+}
+
+/// Get the display label of a Vorbis comment or ID3 frame name.
+fn tag_label(key: &str) -> String {
+    let label = match key.to_uppercase().as_str() {
+        "ALBUM" | "TALB" => "Album",
+        "ARTIST" | "TPE1" => "Artist",
+        "COMMENT" | "DESCRIPTION" => "Comment",
+        "DATE" | "TDRC" | "TYER" => "Date",
+        "ENCODER" | "TSSE" => "Encoder",
+        "ENCODED-BY" | "TENC" => "Encoded by",
+        "GENRE" | "TCON" => "Genre",
+        "TITLE" | "TIT2" => "Title",
+        "TRACKNUMBER" | "TRCK" => "Track",
+        _ => key,
+    };
+    label.to_owned()
+}
+
+/// Decode a 28 bit integer stored in the low seven bits of four bytes.
+fn syncsafe(bytes: &[u8]) -> u32 {
+    bytes
+        .iter()
+        .fold(0, |value, byte| (value << 7) | u32::from(byte & 0x7F))
+}
+
+/// Parse text frames of an ID3v2.3 or ID3v2.4 tag body.
+///
+/// Other versions and unsynchronised tags are skipped since their frames are laid out
+/// differently.
+fn id3v2_tags(version: u8, flags: u8, mut body: &[u8]) -> Vec<(String, String)> {
+    if !(3..=4).contains(&version) || flags & 0x80 != 0 {
+        return Vec::new();
+    }
+    if flags & 0x40 != 0 && body.len() >= 4 {
+        let size = if version == 4 {
+            syncsafe(&body[0..4]) as usize
+        } else {
+            u32::from_be_bytes([body[0], body[1], body[2], body[3]]) as usize + 4
+        };
+        body = &body[size.min(body.len())..];
+    }
+
+    let mut tags = Vec::new();
+    while body.len() >= 10 && body[0] != 0 {
+        let id = String::from_utf8_lossy(&body[0..4]).into_owned();
+        let size = if version == 4 {
+            syncsafe(&body[4..8]) as usize
+        } else {
+            u32::from_be_bytes([body[4], body[5], body[6], body[7]]) as usize
+        };
+        let end = match 10_usize.checked_add(size) {
+            Some(end) if end <= body.len() => end,
+            _ => break,
+        };
+
+        if id.starts_with('T') && id != "TXXX" {
+            let text = id3_text(&body[10..end]);
+            if !text.is_empty() {
+                tags.push((tag_label(&id), text));
+            }
+        }
+        body = &body[end..];
+    }
+    tags
+}
+
+/// Decode the text of an ID3v2 text frame, joining multiple values with semicolons.
+fn id3_text(frame: &[u8]) -> String {
+    let (encoding, bytes) = match frame.split_first() {
+        Some(parts) => parts,
+        None => return String::new(),
+    };
+
+    let text = match encoding {
+        0 => bytes.iter().map(|byte| char::from(*byte)).collect(),
+        1 | 2 => {
+            let mut units: Vec<u16> = bytes
+                .chunks_exact(2)
+                .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                .collect();
+            // Byte order marks flip little endian text, which then starts with a swapped mark.
+            if *encoding == 1 && units.first() == Some(&0xFFFE) {
+                units.iter_mut().for_each(|unit| *unit = unit.swap_bytes());
+            }
+            if units.first() == Some(&0xFEFF) {
+                units.remove(0);
+            }
+            String::from_utf16_lossy(&units)
+        }
+        _ => String::from_utf8_lossy(bytes).into_owned(),
+    };
+
+    text.split('\0')
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .collect::<Vec<&str>>()
+        .join("; ")
+}
+
+/// Read the title, artist, and album of an ID3v1 tag in the last 128 bytes of a file.
+fn id3v1_tags(file: &mut File) -> eyre::Result<Vec<(String, String)>> {
+    if file.metadata()?.len() < 128 {
+        return Ok(Vec::new());
+    }
+    let mut tag = [0_u8; 128];
+    file.seek(SeekFrom::End(-128))?;
+    file.read_exact(&mut tag)?;
+    if !tag.starts_with(b"TAG") {
+        return Ok(Vec::new());
+    }
+
+    let fields = [
+        ("Title", &tag[3..33]),
+        ("Artist", &tag[33..63]),
+        ("Album", &tag[63..93]),
+        ("Date", &tag[93..97]),
+    ];
+    Ok(fields
+        .iter()
+        .map(|(label, bytes)| {
+            let text: String = bytes
+                .iter()
+                .take_while(|byte| **byte != 0)
+                .map(|byte| char::from(*byte))
+                .collect();
+            (String::from(*label), text.trim().to_owned())
+        })
+        .filter(|(_label, text)| !text.is_empty())
+        .collect())
+}
+
+/// Find the first MPEG Layer III frame header and get its bitrate in kilobits per second and
+/// its sample rate.
+fn mpeg_frame(bytes: &[u8]) -> Option<(u32, u32)> {
+    bytes.windows(4).find_map(|header| {
+        if header[0] != 0xFF || header[1] & 0xE0 != 0xE0 {
+            return None;
+        }
+        let version = (header[1] >> 3) & 0x03;
+        let layer = (header[1] >> 1) & 0x03;
+        let bitrate = usize::from(header[2] >> 4);
+        let rate = usize::from((header[2] >> 2) & 0x03);
+        if version == 1 || layer != 1 || bitrate == 0 || bitrate == 15 || rate == 3 {
+            return None;
+        }
+
+        let (bitrates, divisor) = match version {
+            3 => (&MPEG1_BITRATES, 1),
+            2 => (&MPEG2_BITRATES, 2),
+            _ => (&MPEG2_BITRATES, 4),
+        };
+        Some((bitrates[bitrate], [44_100, 48_000, 32_000][rate] / divisor))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsp::Samples;
+    use crate::io::audio;
+    use std::fs;
+
+    /// Encode an ID3v2.3 text frame with Latin-1 text.
+    fn text_frame(id: &[u8; 4], text: &str) -> Vec<u8> {
+        let mut frame = id.to_vec();
+        frame.extend(&(text.len() as u32 + 1).to_be_bytes());
+        frame.extend([0, 0, 0]);
+        frame.extend(text.as_bytes());
+        frame
+    }
+
+    #[test]
+    fn read_mp3_tags_and_bitrate() {
+        let folder = tempfile::tempdir().unwrap();
+        let path = folder.path().join("song.mp3");
+
+        let mut frames = text_frame(b"TIT2", "Night Drive");
+        frames.extend(text_frame(b"TPE1", "The Band"));
+        frames.extend(text_frame(b"TSSE", "LAME 3.100"));
+        frames.extend([0; 16]);
+        let mut bytes = b"ID3\x03\x00\x00".to_vec();
+        bytes.extend([0, 0, 0, frames.len() as u8]);
+        bytes.extend(frames);
+        // MPEG-1 Layer III at 128 kbps and 44.1 kHz.
+        bytes.extend([0xFF, 0xFB, 0x90, 0x64]);
+        bytes.extend([0; 412]);
+        fs::write(&path, bytes).unwrap();
+
+        let metadata = read_metadata(&path).unwrap();
+        assert_eq!(
+            metadata.tags,
+            vec![
+                (String::from("Title"), String::from("Night Drive")),
+                (String::from("Artist"), String::from("The Band")),
+                (String::from("Encoder"), String::from("LAME 3.100")),
+            ]
+        );
+        assert!(metadata
+            .details
+            .contains(&(String::from("Bitrate"), String::from("128 kbps"))));
+        assert!(metadata
+            .details
+            .contains(&(String::from("Sample rate"), String::from("44100 Hz"))));
+    }
+
+    #[test]
+    fn read_mp3_id3v1_fallback() {
+        let folder = tempfile::tempdir().unwrap();
+        let path = folder.path().join("old.mp3");
+
+        let mut bytes = vec![0xFF, 0xF3, 0x80, 0x64];
+        bytes.extend([0; 200]);
+        let mut tag = [0_u8; 128];
+        tag[..3].copy_from_slice(b"TAG");
+        tag[3..8].copy_from_slice(b"Hello");
+        tag[63..68].copy_from_slice(b"Album");
+        bytes.extend(tag);
+        fs::write(&path, bytes).unwrap();
+
+        let metadata = read_metadata(&path).unwrap();
+        assert_eq!(
+            metadata.tags,
+            vec![
+                (String::from("Title"), String::from("Hello")),
+                (String::from("Album"), String::from("Album")),
+            ]
+        );
+        // MPEG-2 Layer III at 64 kbps and 22.05 kHz.
+        assert!(metadata
+            .details
+            .contains(&(String::from("Bitrate"), String::from("64 kbps"))));
+    }
+
+    #[test]
+    fn id3_text_encodings() {
+        assert_eq!(id3_text(b"\x00caf\xe9"), "café");
+        assert_eq!(id3_text(b"\x01\xff\xfeH\x00i\x00"), "Hi");
+        assert_eq!(id3_text(b"\x02\x00H\x00i"), "Hi");
+        assert_eq!(id3_text(b"\x03Rock\x00Pop\x00"), "Rock; Pop");
+        assert_eq!(id3_text(b""), "");
+    }
+
+    #[test]
+    fn read_wave_details_and_info() {
+        let folder = tempfile::tempdir().unwrap();
+        let path = folder.path().join("take.wav");
+        audio::write_samples(&path, &Samples::new(2, 8000, vec![0.0; 8])).unwrap();
+        let info = b"INFOINAM\x05\x00\x00\x00Take\x00\x00".to_vec();
+        riff::append_chunks(&path, &[riff::Chunk::new(*b"LIST", info)]).unwrap();
+
+        let metadata = read_metadata(&path).unwrap();
+        assert_eq!(
+            metadata.tags,
+            vec![(String::from("Title"), String::from("Take"))]
+        );
+        assert_eq!(
+            metadata.details[0],
+            (String::from("Format"), String::from("WAV"))
+        );
+        assert!(metadata.details.len() > 1);
+    }
+
+    #[test]
+    fn read_ogg_comments() {
+        let folder = tempfile::tempdir().unwrap();
+        let path = folder.path().join("tone.ogg");
+        audio::write_ogg(&path, &Samples::new(1, 8000, vec![0.0; 800]), 0.5).unwrap();
+
+        let metadata = read_metadata(&path).unwrap();
+        assert_eq!(
+            metadata.details[0],
+            (String::from("Format"), String::from("Ogg Vorbis"))
+        );
+        assert!(metadata.lines().contains(&String::from("No metadata tags")));
+    }
+
+    #[test]
+    fn unknown_formats_have_no_metadata() {
+        let folder = tempfile::tempdir().unwrap();
+        let path = folder.path().join("capture.raw");
+        fs::write(&path, [0x00, 0x40, 0x00, 0xC0]).unwrap();
+
+        let metadata = read_metadata(&path).unwrap();
+        assert!(metadata.is_empty());
+        assert_eq!(metadata.lines(), vec![String::from("No metadata")]);
+        assert!(read_metadata(&folder.path().join("missing.wav")).is_err());
+    }
+}
@@ -1,6 +1,15 @@
 //! Input and output handlers.
 
+pub mod aiff;
 pub mod audio;
+pub mod cache;
+pub mod csv;
 pub mod event;
+pub mod labels;
+pub mod metadata;
 pub mod path;
+pub mod png;
+pub mod riff;
 pub mod terminal;
+pub mod watch;
+pub mod window;
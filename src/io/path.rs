@@ -2,7 +2,260 @@
 
 use color_eyre::eyre;
 use std::cmp::Ordering;
-use std::path::Path;
+use std::env;
+use std::path::{Component, Path, PathBuf, MAIN_SEPARATOR};
+
+/// Separator drawn between breadcrumb segments.
+pub const BREADCRUMB_SEPARATOR: &str = " > ";
+
+/// Expand a leading tilde and environment variables in a typed path.
+///
+/// Variables may be written as `$NAME` or `${NAME}`.
+///
+/// # Errors
+///
+/// Will return `Err` if the home directory is unknown or a variable is undefined.
+pub fn expand(text: &str) -> eyre::Result<PathBuf> {
+    let mut expanded = String::with_capacity(text.len());
+    let mut rest = text;
+
+    if let Some(tail) = text.strip_prefix('~') {
+        if tail.is_empty() || tail.starts_with(MAIN_SEPARATOR) {
+            let home = dirs::home_dir().ok_or_else(|| eyre::eyre!("Home directory is unknown"))?;
+            expanded
+                .push_str(home.to_str().ok_or_else(|| {
+                    eyre::eyre!("Home directory {:?} is not valid Unicode", home)
+                })?);
+            rest = tail;
+        }
+    }
+
+    let mut chars = rest.chars().peekable();
+    while let Some(char) = chars.next() {
+        if char != '$' {
+            expanded.push(char);
+            continue;
+        }
+
+        let braced = chars.peek() == Some(&'{');
+        if braced {
+            chars.next();
+        }
+
+        let mut name = String::new();
+        while let Some(&next) = chars.peek() {
+            if next.is_alphanumeric() || next == '_' {
+                name.push(next);
+                chars.next();
+            } else {
+                break;
+            }
+        }
+
+        if braced && chars.next() != Some('}') {
+            eyre::bail!("Variable ${{{}}} is missing a closing brace", name);
+        }
+
+        if name.is_empty() {
+            expanded.push('$');
+        } else {
+            let value =
+                env::var(&name).map_err(|_| eyre::eyre!("Variable ${} is not defined", name))?;
+            expanded.push_str(&value);
+        }
+    }
+
+    Ok(PathBuf::from(expanded))
+}
+
+/// Split a path into breadcrumb segments that fit within a display width.
+///
+/// Leading segments are replaced by an ellipsis when the segments joined by
+/// [`BREADCRUMB_SEPARATOR`] would be wider than `width`. The final segment is always kept.
+#[must_use]
+pub fn breadcrumbs(path: &Path, width: usize) -> Vec<String> {
+    let segments: Vec<String> = path
+        .components()
+        .filter_map(|component| match component {
+            Component::Prefix(prefix) => Some(prefix.as_os_str().to_string_lossy().into_owned()),
+            Component::RootDir => Some(MAIN_SEPARATOR.to_string()),
+            Component::Normal(name) => Some(name.to_string_lossy().into_owned()),
+            Component::CurDir | Component::ParentDir => None,
+        })
+        .collect();
+
+    let separator = BREADCRUMB_SEPARATOR.chars().count();
+    let length = |segments: &[String]| -> usize {
+        segments
+            .iter()
+            .map(|segment| segment.chars().count())
+            .sum::<usize>()
+            + separator * segments.len().saturating_sub(1)
+    };
+
+    if length(&segments) <= width {
+        return segments;
+    }
+
+    let mut start = segments.len().saturating_sub(1);
+    while start > 0 && 1 + separator + length(&segments[start - 1..]) <= width {
+        start -= 1;
+    }
+
+    if start == 0 {
+        return segments;
+    }
+
+    std::iter::once(String::from("…"))
+        .chain(segments[start..].iter().cloned())
+        .collect()
+}
+
+/// Complete the final component of a typed path against the file system.
+///
+/// Returns the typed text extended by the longest prefix shared by all matching entries, with a
+/// trailing separator appended when the only match is a directory.
+#[must_use]
+pub fn complete(cwd: &Path, text: &str) -> Option<String> {
+    let split = text.rfind(MAIN_SEPARATOR).map_or(0, |index| index + 1);
+    let (head, prefix) = text.split_at(split);
+
+    let directory = if head.is_empty() {
+        cwd.to_owned()
+    } else {
+        cwd.join(expand(head).ok()?)
+    };
+
+    let matches: Vec<(String, bool)> = sorted_names(&directory, prefix.starts_with('.'))
+        .ok()?
+        .into_iter()
+        .filter(|(name, _is_dir)| name.starts_with(prefix))
+        .collect();
+
+    let mut common = matches.first()?.0.clone();
+    for (name, _is_dir) in &matches {
+        while !name.starts_with(&common) {
+            common.pop();
+        }
+    }
+
+    let mut completion = format!("{}{}", head, common);
+    if let [(_name, true)] = matches.as_slice() {
+        completion.push(MAIN_SEPARATOR);
+    }
+    Some(completion)
+}
+
+/// Find the directory to browse for a path and the file name to highlight within it.
+///
+/// # Errors
+///
+/// Will return `Err` if `path` does not exist.
+pub fn locate(path: &Path) -> eyre::Result<(PathBuf, Option<String>)> {
+    // Rebuilding the path from its components strips trailing separators.
+    let path: PathBuf = path.components().collect();
+
+    if path.is_dir() {
+        Ok((path, None))
+    } else if path.is_file() {
+        let parent = path
+            .parent()
+            .ok_or_else(|| eyre::eyre!("File path {:?} does not have a parent", path))?;
+        Ok((parent.to_owned(), Some(name(&path)?.to_string())))
+    } else {
+        Err(eyre::eyre!("Path {:?} does not exist", path))
+    }
+}
+
+/// Resolve a typed file name to write audio to, relative to a working directory.
+///
+/// Typed names are expanded like [`expand`] and may be absolute. The format is chosen by the
+/// extension, which must be `wav`, `wave`, `ogg`, or `csv`.
+///
+/// # Errors
+///
+/// Will return `Err` if the name is empty, does not end with a file name, lies in a directory that
+/// does not exist, or has an unsupported extension.
+pub fn save_target(cwd: &Path, text: &str) -> eyre::Result<PathBuf> {
+    if text.trim().is_empty() {
+        eyre::bail!("Type a file name to write to");
+    }
+    if text.ends_with(MAIN_SEPARATOR) || text.ends_with('/') {
+        eyre::bail!("{} is a directory, not a file name", text);
+    }
+
+    let path = cwd.join(expand(text)?);
+    let parent = path.parent().unwrap_or(cwd);
+    if path.file_name().is_none() || !parent.is_dir() {
+        eyre::bail!("Directory {:?} does not exist", parent);
+    }
+
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some(extension)
+            if ["aif", "aiff", "csv", "ogg", "wav", "wave"]
+                .contains(&extension.to_lowercase().as_str()) =>
+        {
+            Ok(path)
+        }
+        _ => eyre::bail!("{} needs a .wav, .aiff, .ogg, or .csv extension", text),
+    }
+}
+
+/// Expand a path whose file name holds `*` or `?` wildcards into the files matching it, sorted by
+/// name.
+///
+/// Paths without wildcards are returned unchanged whether or not they exist, so reading them
+/// reports missing files. Wildcards in directory names are not expanded.
+///
+/// # Errors
+///
+/// Will return `Err` if the directory of a pattern cannot be read or no file matches the pattern.
+pub fn glob(pattern: &Path) -> eyre::Result<Vec<PathBuf>> {
+    let wanted = match pattern.file_name().and_then(|name| name.to_str()) {
+        Some(name) if name.contains(['*', '?']) => name.chars().collect::<Vec<char>>(),
+        _ => return Ok(vec![pattern.to_owned()]),
+    };
+    let directory = match pattern.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    let mut paths = Vec::new();
+    for inode in directory.read_dir()? {
+        let path = inode?.path();
+        let matched = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| wildcard(&wanted, &name.chars().collect::<Vec<char>>()));
+        if matched && path.is_file() {
+            paths.push(pattern.with_file_name(path.file_name().unwrap_or_default()));
+        }
+    }
+
+    if paths.is_empty() {
+        eyre::bail!("No files match {:?}", pattern);
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+/// Check whether a name matches a pattern where `*` stands for any text and `?` for any character.
+fn wildcard(pattern: &[char], name: &[char]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some(('*', rest)) => (0..=name.len()).any(|skip| wildcard(rest, &name[skip..])),
+        Some((wanted, rest)) => name.split_first().is_some_and(|(first, remaining)| {
+            (*wanted == '?' || wanted == first) && wildcard(rest, remaining)
+        }),
+    }
+}
+
+/// Check whether a path ends with an extension, ignoring case.
+#[must_use]
+pub fn has_extension(path: &Path, extension: &str) -> bool {
+    path.extension()
+        .is_some_and(|actual| actual.eq_ignore_ascii_case(extension))
+}
 
 /// Get path file name or descriptive error.
 ///
@@ -18,18 +271,21 @@ pub fn name(path: &Path) -> eyre::Result<&str> {
 
 /// Read inodes from a directory and sort them with subdirectories first
 ///
+/// Inodes whose names start with a period are skipped unless `hidden` is true.
+///
 /// # Errors
 ///
 /// Will return `Err` if `directory` does not exist or contains files whose metadata is unparseable.
-pub fn sorted_names(directory: &Path) -> eyre::Result<Vec<(String, bool)>> {
+pub fn sorted_names(directory: &Path, hidden: bool) -> eyre::Result<Vec<(String, bool)>> {
     let mut files: Vec<(String, bool)> = vec![];
 
     for inode in directory.read_dir()? {
         let inode = inode?;
-        files.push((
-            name(&inode.path())?.to_string(),
-            inode.file_type()?.is_dir(),
-        ));
+        let name = name(&inode.path())?.to_string();
+
+        if hidden || !name.starts_with('.') {
+            files.push((name, inode.file_type()?.is_dir()));
+        }
     }
 
     files.sort_by(|left, right| {
@@ -49,6 +305,129 @@ pub mod tests {
     use super::*;
     use std::fs::{self, File};
 
+    #[test]
+    fn save_target_validates_names() {
+        let folder = tempfile::tempdir().unwrap();
+        let cwd = folder.path();
+        fs::create_dir(cwd.join("takes")).unwrap();
+
+        assert_eq!(save_target(cwd, "a.wav").unwrap(), cwd.join("a.wav"));
+        assert_eq!(
+            save_target(cwd, "takes/b.OGG").unwrap(),
+            cwd.join("takes").join("b.OGG")
+        );
+        assert_eq!(save_target(cwd, "d.csv").unwrap(), cwd.join("d.csv"));
+        assert_eq!(save_target(cwd, "e.aif").unwrap(), cwd.join("e.aif"));
+        let absolute = cwd.join("takes").join("c.wave");
+        assert_eq!(
+            save_target(Path::new("/"), absolute.to_str().unwrap()).unwrap(),
+            absolute
+        );
+
+        for invalid in ["", "  ", "takes/", "missing/a.wav", "a.mp3", "a"] {
+            assert!(save_target(cwd, invalid).is_err(), "{:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn glob_matches_wildcards() {
+        let folder = tempfile::tempdir().unwrap();
+        for name in ["kick_01.wav", "kick_02.wav", "kick_10.flac", "snare.wav"] {
+            File::create(folder.path().join(name)).unwrap();
+        }
+        fs::create_dir(folder.path().join("kick_03.wav")).unwrap();
+
+        assert_eq!(
+            glob(&folder.path().join("kick_0?.wav")).unwrap(),
+            vec![
+                folder.path().join("kick_01.wav"),
+                folder.path().join("kick_02.wav")
+            ]
+        );
+        assert_eq!(glob(&folder.path().join("*.flac")).unwrap().len(), 1);
+        assert_eq!(glob(&folder.path().join("*")).unwrap().len(), 4);
+        assert_eq!(
+            glob(Path::new("missing.wav")).unwrap(),
+            vec![PathBuf::from("missing.wav")]
+        );
+        assert!(glob(&folder.path().join("*.ogg")).is_err());
+    }
+
+    #[test]
+    fn breadcrumbs_nested() {
+        let path = Path::new("/home/user/samples");
+
+        assert_eq!(breadcrumbs(path, 80), vec!["/", "home", "user", "samples"]);
+    }
+
+    #[test]
+    fn breadcrumbs_root() {
+        assert_eq!(breadcrumbs(Path::new("/"), 80), vec!["/"]);
+        assert_eq!(breadcrumbs(Path::new("/"), 0), vec!["/"]);
+    }
+
+    #[test]
+    fn breadcrumbs_truncate() {
+        let path = Path::new("/home/user/samples");
+
+        assert_eq!(breadcrumbs(path, 18), vec!["…", "user", "samples"]);
+        assert_eq!(breadcrumbs(path, 3), vec!["…", "samples"]);
+    }
+
+    #[test]
+    fn complete_shared_prefix() {
+        let folder = tempfile::tempdir().unwrap();
+        File::create(folder.path().join("kick_01.wav")).unwrap();
+        File::create(folder.path().join("kick_02.wav")).unwrap();
+        fs::create_dir(folder.path().join("snares")).unwrap();
+
+        assert_eq!(complete(folder.path(), "ki"), Some(String::from("kick_0")));
+        assert_eq!(
+            complete(folder.path(), "sn"),
+            Some(format!("snares{}", MAIN_SEPARATOR))
+        );
+        assert_eq!(complete(folder.path(), "hat"), None);
+    }
+
+    #[test]
+    fn expand_tilde() {
+        let home = dirs::home_dir().unwrap();
+
+        assert_eq!(expand("~").unwrap(), home);
+        assert_eq!(expand("~/samples/").unwrap(), home.join("samples"));
+        assert_eq!(expand("/tmp/~").unwrap(), PathBuf::from("/tmp/~"));
+    }
+
+    #[test]
+    fn expand_variables() {
+        env::set_var("SAMPITOR_TEST_EXPAND", "samples");
+
+        assert_eq!(
+            expand("/$SAMPITOR_TEST_EXPAND/${SAMPITOR_TEST_EXPAND}").unwrap(),
+            PathBuf::from("/samples/samples")
+        );
+        assert!(expand("$SAMPITOR_TEST_UNDEFINED").is_err());
+    }
+
+    #[test]
+    fn locate_directory_and_file() {
+        let folder = tempfile::tempdir().unwrap();
+        let folder = folder.path();
+        fs::create_dir(folder.join("loops")).unwrap();
+        File::create(folder.join("loops").join("beat.wav")).unwrap();
+
+        let trailing = format!("{}{}", folder.join("loops").display(), MAIN_SEPARATOR);
+        assert_eq!(
+            locate(Path::new(&trailing)).unwrap(),
+            (folder.join("loops"), None)
+        );
+        assert_eq!(
+            locate(&folder.join("loops").join("beat.wav")).unwrap(),
+            (folder.join("loops"), Some(String::from("beat.wav")))
+        );
+        assert!(locate(&folder.join("missing")).is_err());
+    }
+
     #[test]
     fn sort_folders_before_files() {
         let folder = tempfile::tempdir().unwrap().path().to_owned();
@@ -65,7 +444,26 @@ pub mod tests {
             (String::from("a"), false),
             (String::from("d"), false),
         ];
-        let actual = sorted_names(&folder).unwrap();
+        let actual = sorted_names(&folder, false).unwrap();
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn filter_hidden_names() {
+        let folder = tempfile::tempdir().unwrap();
+        File::create(folder.path().join(".hidden")).unwrap();
+        File::create(folder.path().join("visible")).unwrap();
+
+        assert_eq!(
+            sorted_names(folder.path(), false).unwrap(),
+            vec![(String::from("visible"), false)]
+        );
+        assert_eq!(
+            sorted_names(folder.path(), true).unwrap(),
+            vec![
+                (String::from(".hidden"), false),
+                (String::from("visible"), false)
+            ]
+        );
+    }
 }
@@ -0,0 +1,103 @@
+//! PNG writers for images drawn from audio, such as waveforms.
+
+use color_eyre::eyre;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use std::convert::TryFrom;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+/// Bytes that start every PNG file.
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+/// Encode 8 bit RGB pixels, stored row by row from the top left, as a PNG file.
+///
+/// # Errors
+///
+/// Will return `Err` if either dimension is zero or does not fit a PNG file, or the number of
+/// pixel bytes does not match the dimensions.
+pub fn encode_rgb(width: usize, height: usize, pixels: &[u8]) -> eyre::Result<Vec<u8>> {
+    if width == 0 || height == 0 {
+        eyre::bail!("Images need a width and height of at least one pixel");
+    }
+    if pixels.len() != width * height * 3 {
+        eyre::bail!(
+            "{} bytes of pixels do not fill a {}x{} RGB image",
+            pixels.len(),
+            width,
+            height
+        );
+    }
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&u32::try_from(width)?.to_be_bytes());
+    header.extend_from_slice(&u32::try_from(height)?.to_be_bytes());
+    // Bit depth 8, color type RGB, and the only compression, filter, and interlace methods.
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+    // Each row starts with a byte choosing no filter.
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    for row in pixels.chunks_exact(width * 3) {
+        encoder.write_all(&[0])?;
+        encoder.write_all(row)?;
+    }
+    let data = encoder.finish()?;
+
+    let mut bytes = SIGNATURE.to_vec();
+    chunk(&mut bytes, b"IHDR", &header)?;
+    chunk(&mut bytes, b"IDAT", &data)?;
+    chunk(&mut bytes, b"IEND", &[])?;
+    Ok(bytes)
+}
+
+/// Write 8 bit RGB pixels, stored row by row from the top left, to a PNG file.
+///
+/// # Errors
+///
+/// Will return `Err` if the pixels cannot be encoded as in [`encode_rgb`] or `path` is unwritable.
+pub fn write_rgb(path: &Path, width: usize, height: usize, pixels: &[u8]) -> eyre::Result<()> {
+    fs::write(path, encode_rgb(width, height, pixels)?)?;
+    Ok(())
+}
+
+/// Append a chunk with its length and checksum to a PNG file.
+fn chunk(bytes: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) -> eyre::Result<()> {
+    bytes.extend_from_slice(&u32::try_from(data.len())?.to_be_bytes());
+    let start = bytes.len();
+    bytes.extend_from_slice(kind);
+    bytes.extend_from_slice(data);
+    let checksum = crc32fast::hash(&bytes[start..]);
+    bytes.extend_from_slice(&checksum.to_be_bytes());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::ZlibDecoder;
+    use std::io::Read;
+
+    #[test]
+    fn encode_rgb_writes_checked_chunks() {
+        let pixels = [255, 0, 0, 0, 255, 0, 0, 0, 255, 255, 255, 255];
+        let bytes = encode_rgb(2, 2, &pixels).unwrap();
+
+        assert_eq!(bytes[..8], SIGNATURE);
+        assert_eq!(bytes[8..16], [0, 0, 0, 13, b'I', b'H', b'D', b'R']);
+        assert_eq!(bytes[16..29], [0, 0, 0, 2, 0, 0, 0, 2, 8, 2, 0, 0, 0]);
+        assert_eq!(bytes[29..33], crc32fast::hash(&bytes[12..29]).to_be_bytes());
+        assert_eq!(bytes[bytes.len() - 12..bytes.len() - 4], *b"\0\0\0\0IEND");
+
+        let length = u32::from_be_bytes([bytes[33], bytes[34], bytes[35], bytes[36]]) as usize;
+        assert_eq!(bytes[37..41], *b"IDAT");
+        let mut rows = Vec::new();
+        ZlibDecoder::new(&bytes[41..41 + length])
+            .read_to_end(&mut rows)
+            .unwrap();
+        assert_eq!(rows, [0, 255, 0, 0, 0, 255, 0, 0, 0, 0, 255, 255, 255, 255]);
+
+        assert!(encode_rgb(0, 2, &[]).is_err());
+        assert!(encode_rgb(2, 2, &pixels[..9]).is_err());
+    }
+}
@@ -0,0 +1,416 @@
+//! RIFF chunk readers and writers for WAV metadata that Hound does not handle.
+
+use crate::dsp::{AudioMetadata, Marker};
+use color_eyre::eyre;
+use std::convert::TryFrom;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::Path;
+
+/// A top level RIFF chunk.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Chunk {
+    pub id: [u8; 4],
+    pub data: Vec<u8>,
+}
+
+impl Chunk {
+    /// Create a new Chunk from an identifier and contents.
+    #[must_use]
+    pub fn new(id: [u8; 4], data: Vec<u8>) -> Self {
+        Self { id, data }
+    }
+
+    /// Encode chunk header, contents, and padding byte.
+    fn encode(&self) -> eyre::Result<Vec<u8>> {
+        let size = u32::try_from(self.data.len())?;
+        let mut bytes = Vec::with_capacity(self.data.len() + 9);
+
+        bytes.extend_from_slice(&self.id);
+        bytes.extend_from_slice(&size.to_le_bytes());
+        bytes.extend_from_slice(&self.data);
+        if self.data.len() % 2 == 1 {
+            bytes.push(0);
+        }
+
+        Ok(bytes)
+    }
+}
+
+/// Append chunks to the end of a RIFF file and update its header size.
+///
+/// # Errors
+///
+/// Will return `Err` if `path` is not a writable RIFF file.
+pub fn append_chunks(path: &Path, chunks: &[Chunk]) -> eyre::Result<()> {
+    let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+    read_header(&mut file)?;
+
+    let mut end = file.seek(SeekFrom::End(0))?;
+    // Chunks must start on even offsets.
+    if end % 2 == 1 {
+        file.write_all(&[0])?;
+        end += 1;
+    }
+
+    for chunk in chunks {
+        let bytes = chunk.encode()?;
+        file.write_all(&bytes)?;
+        end += bytes.len() as u64;
+    }
+
+    file.seek(SeekFrom::Start(4))?;
+    file.write_all(&u32::try_from(end - 8)?.to_le_bytes())?;
+    Ok(())
+}
+
+/// Encode markers as a cue chunk and an associated data list chunk of labels.
+///
+/// # Errors
+///
+/// Will return `Err` if a marker frame does not fit in a 32 bit cue point.
+pub fn encode_markers(markers: &[Marker]) -> eyre::Result<Vec<Chunk>> {
+    let mut cue = Vec::with_capacity(4 + 24 * markers.len());
+    cue.extend_from_slice(&u32::try_from(markers.len())?.to_le_bytes());
+
+    let mut list = b"adtl".to_vec();
+
+    for (index, marker) in markers.iter().enumerate() {
+        let id = u32::try_from(index + 1)?.to_le_bytes();
+        let frame = u32::try_from(marker.frame)
+            .map_err(|_| eyre::eyre!("Marker frame {} is too large for WAV", marker.frame))?
+            .to_le_bytes();
+
+        cue.extend_from_slice(&id);
+        cue.extend_from_slice(&frame);
+        cue.extend_from_slice(b"data");
+        cue.extend_from_slice(&0_u32.to_le_bytes());
+        cue.extend_from_slice(&0_u32.to_le_bytes());
+        cue.extend_from_slice(&frame);
+
+        if !marker.label.is_empty() {
+            let mut label = id.to_vec();
+            label.extend_from_slice(marker.label.as_bytes());
+            label.push(0);
+            list.extend(Chunk::new(*b"labl", label).encode()?);
+        }
+    }
+
+    let mut chunks = vec![Chunk::new(*b"cue ", cue)];
+    if list.len() > 4 {
+        chunks.push(Chunk::new(*b"LIST", list));
+    }
+    Ok(chunks)
+}
+
+/// Parse markers from cue and associated data list chunks.
+///
+/// Cue points are named by their `labl` entries, or by their `note` entries if they have no
+/// label, since some editors keep marker names as notes. Malformed cue points and labels are
+/// skipped.
+#[must_use]
+pub fn decode_markers(chunks: &[Chunk]) -> Vec<Marker> {
+    let mut points: Vec<(u32, Marker)> = Vec::new();
+
+    for chunk in chunks.iter().filter(|chunk| &chunk.id == b"cue ") {
+        for point in chunk.data.get(4..).unwrap_or_default().chunks_exact(24) {
+            let id = le_u32(&point[0..4]);
+            let frame = le_u32(&point[20..24]) as usize;
+            points.push((id, Marker::new(frame, String::new())));
+        }
+    }
+
+    let entries: Vec<Chunk> = chunks
+        .iter()
+        .filter(|chunk| &chunk.id == b"LIST" && chunk.data.starts_with(b"adtl"))
+        .flat_map(|list| sub_chunks(&list.data[4..]))
+        .filter(|sub| sub.data.len() >= 4)
+        .collect();
+    // Notes are read first so that labels of the same cue point replace them.
+    for kind in [b"note", b"labl"] {
+        for sub in entries.iter().filter(|sub| &sub.id == kind) {
+            let id = le_u32(&sub.data[0..4]);
+            let text = sub.data[4..]
+                .split(|byte| *byte == 0)
+                .next()
+                .unwrap_or_default();
+            if let Some((_id, marker)) = points.iter_mut().find(|(point, _marker)| *point == id) {
+                marker.label = String::from_utf8_lossy(text).into_owned();
+            }
+        }
+    }
+
+    let mut markers: Vec<Marker> = points.into_iter().map(|(_id, marker)| marker).collect();
+    markers.sort_by_key(|marker| marker.frame);
+    markers
+}
+
+/// Parse text entries such as `INAM` for the title from INFO list chunks.
+///
+/// Entries are returned in file order with their terminating null bytes removed.
+#[must_use]
+pub fn decode_info(chunks: &[Chunk]) -> Vec<([u8; 4], String)> {
+    chunks
+        .iter()
+        .filter(|chunk| &chunk.id == b"LIST" && chunk.data.starts_with(b"INFO"))
+        .flat_map(|list| sub_chunks(&list.data[4..]))
+        .map(|sub| {
+            let text = sub.data.split(|byte| *byte == 0).next().unwrap_or_default();
+            (sub.id, String::from_utf8_lossy(text).trim().to_owned())
+        })
+        .filter(|(_id, text)| !text.is_empty())
+        .collect()
+}
+
+/// Collect the title, artist, comment, and software entries of INFO list chunks.
+///
+/// Later entries replace earlier ones with the same identifier.
+#[must_use]
+pub fn decode_metadata(chunks: &[Chunk]) -> AudioMetadata {
+    let mut metadata = AudioMetadata::default();
+    for (id, text) in decode_info(chunks) {
+        let field = match &id {
+            b"IART" => &mut metadata.artist,
+            b"ICMT" => &mut metadata.comment,
+            b"INAM" => &mut metadata.title,
+            b"ISFT" => &mut metadata.software,
+            _ => continue,
+        };
+        *field = Some(text);
+    }
+    metadata
+}
+
+/// Encode metadata as an INFO list chunk, or no chunks if no field is set.
+///
+/// # Errors
+///
+/// Will return `Err` if a field is too long for a RIFF chunk.
+pub fn encode_metadata(metadata: &AudioMetadata) -> eyre::Result<Vec<Chunk>> {
+    let fields = [
+        (b"INAM", &metadata.title),
+        (b"IART", &metadata.artist),
+        (b"ICMT", &metadata.comment),
+        (b"ISFT", &metadata.software),
+    ];
+
+    let mut list = b"INFO".to_vec();
+    for (id, text) in fields {
+        if let Some(text) = text.as_deref().filter(|text| !text.is_empty()) {
+            let mut data = text.as_bytes().to_vec();
+            data.push(0);
+            list.extend(Chunk::new(*id, data).encode()?);
+        }
+    }
+
+    if list.len() > 4 {
+        Ok(vec![Chunk::new(*b"LIST", list)])
+    } else {
+        Ok(Vec::new())
+    }
+}
+
+/// Read all top level chunks of a RIFF WAVE file except for the sample data.
+///
+/// # Errors
+///
+/// Will return `Err` if `path` is unreadable or is not a RIFF WAVE file.
+pub fn read_chunks(path: &Path) -> eyre::Result<Vec<Chunk>> {
+    let mut file = File::open(path)?;
+    let length = read_header(&mut file)?;
+    let mut chunks = Vec::new();
+    let mut header = [0_u8; 8];
+
+    while file.stream_position()? + 8 <= length {
+        file.read_exact(&mut header)?;
+        let id = [header[0], header[1], header[2], header[3]];
+        let size = u64::from(le_u32(&header[4..8]));
+        let padded = size + size % 2;
+
+        if &id == b"data" {
+            file.seek(SeekFrom::Current(i64::try_from(padded)?))?;
+            continue;
+        }
+
+        let mut data = Vec::new();
+        (&mut file).take(size).read_to_end(&mut data)?;
+        if data.len() as u64 != size {
+            eyre::bail!("RIFF chunk {:?} is truncated", String::from_utf8_lossy(&id));
+        }
+        file.seek(SeekFrom::Current(i64::try_from(padded - size)?))?;
+        chunks.push(Chunk::new(id, data));
+    }
+
+    Ok(chunks)
+}
+
+/// Find the byte range of the sample data chunk in a RIFF WAVE file without reading it.
+///
+/// # Errors
+///
+/// Will return `Err` if `path` is unreadable, is not a RIFF WAVE file, or has no complete data
+/// chunk.
+pub fn data_range(path: &Path) -> eyre::Result<Range<u64>> {
+    let mut file = File::open(path)?;
+    let length = read_header(&mut file)?.min(file.metadata()?.len());
+    let mut header = [0_u8; 8];
+
+    while file.stream_position()? + 8 <= length {
+        file.read_exact(&mut header)?;
+        let size = u64::from(le_u32(&header[4..8]));
+        let start = file.stream_position()?;
+
+        if &header[0..4] == b"data" {
+            if start + size > length {
+                eyre::bail!("RIFF chunk \"data\" is truncated");
+            }
+            return Ok(start..start + size);
+        }
+        file.seek(SeekFrom::Current(i64::try_from(size + size % 2)?))?;
+    }
+
+    eyre::bail!("WAV file is missing a \"data\" chunk")
+}
+
+/// Check for a RIFF WAVE header and return the file length it declares.
+fn read_header(file: &mut File) -> eyre::Result<u64> {
+    let mut header = [0_u8; 12];
+    file.read_exact(&mut header)
+        .map_err(|_| eyre::eyre!("File is too short to be a RIFF file"))?;
+
+    if &header[0..4] != b"RIFF" || &header[8..12] != b"WAVE" {
+        eyre::bail!("File is not a RIFF WAVE file");
+    }
+
+    Ok(u64::from(le_u32(&header[4..8])) + 8)
+}
+
+/// Decode a little endian 32 bit integer from the start of a slice.
+fn le_u32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+/// Split the body of a list chunk into its sub chunks.
+fn sub_chunks(mut bytes: &[u8]) -> Vec<Chunk> {
+    let mut chunks = Vec::new();
+
+    while bytes.len() >= 8 {
+        let id = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        let size = le_u32(&bytes[4..8]) as usize;
+        let end = match 8_usize.checked_add(size) {
+            Some(end) if end <= bytes.len() => end,
+            _ => break,
+        };
+
+        chunks.push(Chunk::new(id, bytes[8..end].to_vec()));
+        bytes = &bytes[(end + size % 2).min(bytes.len())..];
+    }
+
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markers_round_trip() {
+        let markers = vec![
+            Marker::new(4, String::from("verse")),
+            Marker::new(10, String::new()),
+            Marker::new(12, String::from("odd")),
+        ];
+
+        let chunks = encode_markers(&markers).unwrap();
+        assert_eq!(decode_markers(&chunks), markers);
+    }
+
+    #[test]
+    fn decode_markers_written_by_other_editors() {
+        let point = |id: u32, frame: u32| {
+            let mut bytes = id.to_le_bytes().to_vec();
+            bytes.extend(0_u32.to_le_bytes());
+            bytes.extend(b"data");
+            bytes.extend([0; 8]);
+            bytes.extend(frame.to_le_bytes());
+            bytes
+        };
+        let entry = |id: &[u8; 4], cue: u32, text: &[u8]| {
+            let mut data = cue.to_le_bytes().to_vec();
+            data.extend(text);
+            Chunk::new(*id, data).encode().unwrap()
+        };
+
+        // Cue point identifiers need not be sequential or sorted by frame.
+        let mut cue = 3_u32.to_le_bytes().to_vec();
+        cue.extend(point(7, 900));
+        cue.extend(point(2, 100));
+        cue.extend(point(5, 400));
+        let mut list = b"adtl".to_vec();
+        list.extend(entry(b"note", 7, b"loop end "));
+        list.extend(entry(b"labl", 2, b"odd "));
+        list.extend(entry(b"note", 2, b"replaced by label "));
+        list.extend(entry(b"ltxt", 5, b"    "));
+        let chunks = vec![Chunk::new(*b"cue ", cue), Chunk::new(*b"LIST", list)];
+
+        assert_eq!(
+            decode_markers(&chunks),
+            vec![
+                Marker::new(100, String::from("odd")),
+                Marker::new(400, String::new()),
+                Marker::new(900, String::from("loop end")),
+            ]
+        );
+    }
+
+    #[test]
+    fn decode_info_entries() {
+        let mut list = b"INFOINAM\x05\0\0\0Song\0\0IART\x04\0\0\0Band".to_vec();
+        list.extend(b"ICMT\x01\0\0\0\0\0");
+        let chunks = vec![
+            Chunk::new(*b"LIST", list),
+            Chunk::new(*b"LIST", b"adtlINAM\x04\0\0\0Skip".to_vec()),
+        ];
+
+        assert_eq!(
+            decode_info(&chunks),
+            vec![
+                (*b"INAM", String::from("Song")),
+                (*b"IART", String::from("Band"))
+            ]
+        );
+    }
+
+    #[test]
+    fn metadata_round_trip() {
+        let metadata = AudioMetadata {
+            artist: Some(String::from("Band")),
+            comment: Some(String::from("Second take")),
+            software: None,
+            title: Some(String::from("Song")),
+        };
+        let chunks = encode_metadata(&metadata).unwrap();
+
+        assert_eq!(chunks.len(), 1);
+        assert!(chunks[0]
+            .data
+            .starts_with(b"INFOINAM\x05\0\0\0Song\0\0IART"));
+        assert_eq!(decode_metadata(&chunks), metadata);
+        assert!(encode_metadata(&AudioMetadata::default())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn sub_chunk_padding() {
+        let mut bytes = Chunk::new(*b"labl", vec![1, 0, 0, 0, b'a', 0, 7])
+            .encode()
+            .unwrap();
+        bytes.extend(Chunk::new(*b"note", vec![2]).encode().unwrap());
+
+        let actual = sub_chunks(&bytes);
+        assert_eq!(actual.len(), 2);
+        assert_eq!(actual[1], Chunk::new(*b"note", vec![2]));
+    }
+}
@@ -1,36 +1,125 @@
 //! Terminal oriented functions.
 
+use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
 use crossterm::execute;
 use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
 use std::io;
 use std::io::Stdout;
+use std::ops::{Deref, DerefMut};
+use std::panic;
+use std::thread;
 use tui::backend::CrosstermBackend;
 use tui::Terminal;
 
 pub type CrossTerm = Terminal<CrosstermBackend<Stdout>>;
 
+/// Terminal window taken over as an alternate screen, which is returned once dropped.
+///
+/// Dropping the screen restores the terminal even if the application returns early with an
+/// error, so [`Screen::leave`] is only needed to report errors restoring it.
+pub struct Screen {
+    /// Whether control of the terminal was already returned.
+    left: bool,
+    terminal: CrossTerm,
+}
+
+impl Screen {
+    /// Return control of the terminal window from the alternate screen.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if terminal control cannot be returned.
+    pub fn leave(mut self) -> eyre::Result<()> {
+        self.left = true;
+        leave(&mut self.terminal)
+    }
+}
+
+impl Deref for Screen {
+    type Target = CrossTerm;
+
+    fn deref(&self) -> &CrossTerm {
+        &self.terminal
+    }
+}
+
+impl DerefMut for Screen {
+    fn deref_mut(&mut self) -> &mut CrossTerm {
+        &mut self.terminal
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        // Errors cannot be reported while dropping, and the terminal is returned where possible.
+        if !self.left {
+            let _result = leave(&mut self.terminal);
+        }
+    }
+}
+
 /// Return control of current terminal window from alternate screen.
 ///
 /// # Errors
 ///
 /// Will return `Err` if `terminal` control cannot be returned.
 pub fn leave(terminal: &mut CrossTerm) -> eyre::Result<()> {
-    execute!(terminal.backend_mut(), LeaveAlternateScreen,)?;
+    execute!(
+        terminal.backend_mut(),
+        DisableMouseCapture,
+        LeaveAlternateScreen,
+    )?;
+    terminal::disable_raw_mode()?;
 
     Ok(())
 }
 
-/// Take control of current terminal window as an alternate screen.
+/// Restore the terminal before reporting a panic so it does not keep capturing the mouse.
+///
+/// Only panics of the main thread end the application. Panics of worker threads, such as those
+/// reading files, are reported to their tab while the main loop keeps drawing, so the terminal
+/// stays in the alternate screen for them.
+fn restore_on_panic() {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if thread::current().name() == Some("main") {
+            release();
+        }
+        hook(info);
+    }));
+}
+
+/// Return the terminal from the alternate screen and raw mode without a terminal handle.
+///
+/// This runs while an error or panic is already being reported, so restoration errors are
+/// ignored.
+fn release() {
+    let _result = execute!(io::stdout(), DisableMouseCapture, LeaveAlternateScreen);
+    let _result = terminal::disable_raw_mode();
+}
+
+/// Take control of current terminal window as an alternate screen until the returned screen is
+/// left or dropped.
 ///
 /// # Errors
 ///
 /// Will return `Err` if unable to take control of the current terminal.
-pub fn take() -> eyre::Result<CrossTerm> {
+pub fn take() -> eyre::Result<Screen> {
+    restore_on_panic();
     terminal::enable_raw_mode()?;
     let mut screen = io::stdout();
-    execute!(screen, EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(screen);
-    let terminal = Terminal::new(backend)?;
+    let entered = execute!(screen, EnterAlternateScreen, EnableMouseCapture)
+        .and_then(|()| Terminal::new(CrosstermBackend::new(screen)));
 
-    Ok(terminal)
+    match entered {
+        Ok(terminal) => Ok(Screen {
+            left: false,
+            terminal,
+        }),
+        // Raw mode is already on, so the terminal is returned before reporting the error.
+        Err(error) => {
+            release();
+            Err(error.into())
+        }
+    }
 }
@@ -0,0 +1,104 @@
+//! Watchers that notice inodes created in or removed from directories.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Time a directory must stay unchanged before a watcher reports its changes.
+pub const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Watcher of one directory, which notices created, removed, and renamed inodes by polling the
+/// modification time of the directory.
+///
+/// Changes are reported once the directory has stayed unchanged for the debounce time, so rapid
+/// creation of many files refreshes listings once instead of on every file.
+#[derive(Clone, Debug)]
+pub struct Watcher {
+    /// Time the directory was first seen changed since changes were last reported.
+    changed: Option<Instant>,
+    debounce: Duration,
+    directory: PathBuf,
+    /// Modification time of the directory when it was last polled, if it was readable.
+    modified: Option<SystemTime>,
+}
+
+impl Watcher {
+    /// Start watching a directory, reporting changes after a debounce time.
+    #[must_use]
+    pub fn new(directory: PathBuf, debounce: Duration) -> Self {
+        Self {
+            changed: None,
+            debounce,
+            modified: modified(&directory),
+            directory,
+        }
+    }
+
+    /// Get the watched directory.
+    #[must_use]
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Check the directory for changes at a point in time.
+    ///
+    /// Returns `true` once if the directory changed and then stayed unchanged for the debounce
+    /// time. A directory that becomes unreadable also counts as changed.
+    pub fn poll(&mut self, now: Instant) -> bool {
+        let modified = modified(&self.directory);
+        if modified != self.modified {
+            self.modified = modified;
+            self.changed = Some(now);
+        }
+
+        match self.changed {
+            Some(changed) if now.saturating_duration_since(changed) >= self.debounce => {
+                self.changed = None;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Get the modification time of a directory, if it is readable.
+fn modified(directory: &Path) -> Option<SystemTime> {
+    fs::metadata(directory)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn poll_reports_changes_after_debounce() {
+        let folder = tempfile::tempdir().unwrap();
+        let mut watcher = Watcher::new(folder.path().to_owned(), DEBOUNCE);
+        let start = Instant::now();
+        assert!(!watcher.poll(start + DEBOUNCE * 2));
+
+        // Directory times are set explicitly, since file system timestamps may be coarse.
+        let directory = fs::File::open(folder.path()).unwrap();
+        let modified = directory.metadata().unwrap().modified().unwrap();
+        fs::write(folder.path().join("take.wav"), b"").unwrap();
+        directory
+            .set_modified(modified + Duration::from_secs(1))
+            .unwrap();
+        assert!(!watcher.poll(start));
+        assert!(!watcher.poll(start + DEBOUNCE / 2));
+
+        directory
+            .set_modified(modified + Duration::from_secs(2))
+            .unwrap();
+        assert!(!watcher.poll(start + DEBOUNCE));
+        assert!(watcher.poll(start + DEBOUNCE * 2));
+        assert!(!watcher.poll(start + DEBOUNCE * 3));
+
+        fs::remove_dir_all(folder.path()).unwrap();
+        assert!(!watcher.poll(start + DEBOUNCE * 4));
+        assert!(watcher.poll(start + DEBOUNCE * 5));
+        assert_eq!(watcher.directory(), folder.path());
+    }
+}
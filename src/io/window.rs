@@ -0,0 +1,348 @@
+//! Viewing and playback of WAV files too large to decode into memory.
+
+use crate::dsp::{Samples, Signal};
+use crate::io::audio::{self, StreamingSamples};
+use crate::progress::Progress;
+use crate::ui::peaks::Peaks;
+use color_eyre::eyre;
+use rodio::Source;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+
+/// File size in bytes above which WAV files are opened windowed instead of decoded into memory.
+pub const STREAM_THRESHOLD: u64 = 2 * 1024 * 1024 * 1024;
+
+/// Level of the finest peaks of windowed files, whose blocks hold 1024 frames.
+pub const PEAK_LEVEL: usize = 6;
+
+/// Most frames held in memory by the window of a windowed file.
+///
+/// The chart reads frames directly once fewer than half its points fit in the visible frames at
+/// [`PEAK_LEVEL`], so this is enough for any range it reads directly.
+pub const WINDOW_FRAMES: usize = 2048 * Peaks::block_size(PEAK_LEVEL);
+
+/// Frames read from the file at a time during playback.
+const PLAYBACK_FRAMES: usize = 4096;
+
+/// Frames of a file held in memory around the range last requested.
+///
+/// Frames outside the window read as silence, so the window implements [`Signal`] over the whole
+/// file and can be plotted like decoded samples.
+#[derive(Debug)]
+pub struct Window {
+    capacity: usize,
+    /// Window samples and the frame of the file they start at.
+    loaded: (usize, Samples),
+    reader: StreamingSamples,
+    /// Number of times frames were read from the file.
+    reads: usize,
+}
+
+impl Window {
+    /// Create an empty Window over a file that holds at most a number of frames.
+    #[must_use]
+    pub fn new(reader: StreamingSamples, capacity: usize) -> Self {
+        let empty = Samples::new(reader.channels(), reader.sample_rate(), Vec::new());
+        Self {
+            capacity: capacity.max(1),
+            loaded: (0, empty),
+            reader,
+            reads: 0,
+        }
+    }
+
+    /// Get the frames of the file held in memory.
+    #[must_use]
+    pub fn loaded(&self) -> Range<usize> {
+        let (start, samples) = &self.loaded;
+        *start..start + samples.frames()
+    }
+
+    /// Make sure a range of frames is in memory, reading it from the file if it is not.
+    ///
+    /// Ranges are clamped to the frames of the file and cut to the capacity of the window. Reads
+    /// fill the whole capacity centered on the range, so small scrolls do not read again.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the file cannot be read.
+    pub fn fetch(&mut self, frames: Range<usize>) -> eyre::Result<()> {
+        let total = self.reader.frames();
+        let start = frames.start.min(total);
+        let end = frames.end.clamp(start, total).min(start + self.capacity);
+        let loaded = self.loaded();
+        if loaded.start <= start && end <= loaded.end {
+            return Ok(());
+        }
+
+        let margin = (self.capacity - (end - start)) / 2;
+        let first = start
+            .saturating_sub(margin)
+            .min(total.saturating_sub(self.capacity));
+        let samples = self.reader.read_frames(first..first + self.capacity)?;
+        self.loaded = (first, samples);
+        self.reads += 1;
+        Ok(())
+    }
+}
+
+impl Signal for Window {
+    fn channels(&self) -> u16 {
+        self.reader.channels()
+    }
+
+    fn frames(&self) -> usize {
+        self.reader.frames()
+    }
+
+    fn sample(&self, frame: usize, channel: usize) -> f32 {
+        assert!(frame < self.frames() && channel < usize::from(self.channels()));
+
+        let (start, samples) = &self.loaded;
+        match frame.checked_sub(*start) {
+            Some(offset) if offset < samples.frames() => {
+                samples.data[offset * usize::from(samples.channels) + channel]
+            }
+            _ => 0.0,
+        }
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.reader.sample_rate()
+    }
+}
+
+/// A WAV file open for viewing and playback without decoding it into memory.
+#[derive(Debug)]
+pub struct Windowed {
+    path: PathBuf,
+    /// Peaks of the whole file from [`PEAK_LEVEL`] up, for plotting zoomed out views.
+    pub peaks: Peaks,
+    pub window: Window,
+}
+
+impl Windowed {
+    /// Open a WAV file windowed, summarizing its peaks in one pass that reports progress to a
+    /// callback until a flag is set.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the file is not a WAV file with an uncompressed encoding, cannot be
+    /// read, or the read was cancelled.
+    pub fn open(
+        path: &Path,
+        progress: &mut dyn FnMut(f32),
+        cancel: &AtomicBool,
+    ) -> eyre::Result<Self> {
+        let mut reader = audio::read_samples_streaming(path)?;
+        let mut progress = Progress::new(progress).with_cancel(cancel);
+        let peaks = Peaks::streamed(&mut reader, PEAK_LEVEL, &mut progress)?;
+
+        Ok(Self {
+            path: path.to_owned(),
+            peaks,
+            window: Window::new(reader, WINDOW_FRAMES),
+        })
+    }
+
+    /// Make sure the frames within visible chart bounds are in memory if the chart reads them
+    /// directly instead of from the peaks.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the file cannot be read.
+    pub fn fetch_visible(&mut self, bounds: (f64, f64)) -> eyre::Result<()> {
+        // Bounds are clamped to be non negative, so sign loss is not possible.
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let (start, end) = (
+            bounds.0.max(0.0) as usize,
+            bounds.1.max(0.0).ceil() as usize,
+        );
+        if end.saturating_sub(start) <= WINDOW_FRAMES {
+            self.window.fetch(start..end)?;
+        }
+        Ok(())
+    }
+
+    /// Create a playback source that streams the file from a frame.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the file can no longer be opened.
+    pub fn source(&self, frame: usize) -> eyre::Result<StreamSource> {
+        Ok(StreamSource::new(
+            audio::read_samples_streaming(&self.path)?,
+            frame,
+        ))
+    }
+}
+
+/// Playback source that reads blocks of frames from a file as they are played.
+///
+/// Playback ends early if the file can no longer be read.
+#[derive(Debug)]
+pub struct StreamSource {
+    block: Vec<f32>,
+    /// Frame of the file after the current block.
+    next: usize,
+    position: usize,
+    reader: StreamingSamples,
+}
+
+impl StreamSource {
+    /// Create a StreamSource that starts playing a file at a frame.
+    #[must_use]
+    pub fn new(reader: StreamingSamples, frame: usize) -> Self {
+        Self {
+            block: Vec::new(),
+            next: frame,
+            position: 0,
+            reader,
+        }
+    }
+}
+
+impl Iterator for StreamSource {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.position == self.block.len() {
+            if self.next >= self.reader.frames() {
+                return None;
+            }
+            let samples = self
+                .reader
+                .read_frames(self.next..self.next + PLAYBACK_FRAMES)
+                .ok()?;
+            self.next += samples.frames();
+            self.block = samples.data;
+            self.position = 0;
+        }
+
+        let sample = self.block.get(self.position).copied();
+        self.position += 1;
+        sample
+    }
+}
+
+impl Source for StreamSource {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        self.reader.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.reader.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        let frames = self.reader.frames().saturating_sub(self.next) as f64;
+        let rest = (self.block.len() - self.position) / usize::from(self.channels().max(1));
+        Some(Duration::from_secs_f64(
+            (frames + rest as f64) / f64::from(self.sample_rate().max(1)),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write a stereo WAV file whose samples differ from frame to frame.
+    fn long_file(folder: &Path, frames: usize) -> (PathBuf, Samples) {
+        let path = folder.join("long.wav");
+        let data = (0..frames * 2)
+            .map(|index| ((index * 7919) % 201) as f32 / 100.0 - 1.0)
+            .collect();
+        let samples = Samples::new(2, 8000, data);
+        audio::write_samples(&path, &samples).unwrap();
+        (path, samples)
+    }
+
+    #[test]
+    fn fetch_reads_only_missing_frames() {
+        let folder = tempfile::tempdir().unwrap();
+        let (path, samples) = long_file(folder.path(), 250_000);
+        let reader = audio::read_samples_streaming(&path).unwrap();
+        let mut window = Window::new(reader, 10_000);
+
+        assert_eq!(window.sample(100, 0), 0.0);
+        window.fetch(100_000..102_000).unwrap();
+        assert_eq!(window.loaded(), 96_000..106_000);
+        assert_eq!(window.reads, 1);
+        for frame in [96_000, 100_000, 105_999] {
+            for channel in 0..2 {
+                assert_eq!(
+                    window.sample(frame, channel),
+                    samples.sample(frame, channel)
+                );
+            }
+        }
+        assert_eq!(window.sample(106_000, 0), 0.0);
+
+        window.fetch(104_000..106_000).unwrap();
+        assert_eq!(window.reads, 1);
+        window.fetch(105_000..107_000).unwrap();
+        assert_eq!(window.reads, 2);
+        assert_eq!(window.loaded(), 101_000..111_000);
+
+        window.fetch(0..50_000).unwrap();
+        assert_eq!(window.loaded(), 0..10_000);
+        window.fetch(249_990..300_000).unwrap();
+        assert_eq!(window.loaded(), 240_000..250_000);
+        assert_eq!(window.sample(249_999, 1), samples.sample(249_999, 1));
+    }
+
+    #[test]
+    fn windowed_plots_from_peaks_and_window() {
+        let folder = tempfile::tempdir().unwrap();
+        let (path, samples) = long_file(folder.path(), 300_000);
+        let mut fractions = Vec::new();
+        let mut windowed = Windowed::open(
+            &path,
+            &mut |fraction| fractions.push(fraction),
+            &AtomicBool::new(false),
+        )
+        .unwrap();
+
+        assert!(windowed.peaks.fits(&windowed.window));
+        assert_eq!(windowed.peaks.peak(1), Peaks::new(&samples).peak(1));
+        assert_eq!(fractions.last(), Some(&1.0));
+        assert!(windowed
+            .peaks
+            .level(Peaks::block_size(PEAK_LEVEL) - 1)
+            .is_none());
+
+        windowed.fetch_visible((0.0, 300_000.0)).unwrap();
+        assert_eq!(windowed.window.reads, 1);
+        windowed.fetch_visible((1000.0, 2000.5)).unwrap();
+        assert!(windowed.window.loaded().contains(&2000));
+        assert_eq!(windowed.window.sample(2000, 0), samples.sample(2000, 0));
+        assert_eq!(windowed.window.reads, 1);
+
+        let cancelled = Windowed::open(&path, &mut |_fraction| (), &AtomicBool::new(true));
+        assert!(cancelled.is_err());
+    }
+
+    #[test]
+    fn stream_source_plays_from_frame() {
+        let folder = tempfile::tempdir().unwrap();
+        let (path, samples) = long_file(folder.path(), 10_000);
+        let reader = audio::read_samples_streaming(&path).unwrap();
+        let source = StreamSource::new(reader, 1234);
+
+        assert_eq!(source.channels(), 2);
+        assert_eq!(source.sample_rate(), 8000);
+        assert_eq!(
+            source.total_duration(),
+            Some(Duration::from_secs_f64(8766.0 / 8000.0))
+        );
+        let played: Vec<f32> = source.collect();
+        assert_eq!(played, samples.data[1234 * 2..]);
+    }
+}
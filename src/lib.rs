@@ -1,8 +1,16 @@
 //! Digital audio editor for the terminal.
 
+pub mod action;
 pub mod app;
+pub mod cli;
+pub mod config;
 pub mod dsp;
+pub mod history;
 pub mod io;
+mod progress;
+pub mod recorder;
+pub mod session;
+pub mod tab;
 pub mod ui;
 mod util;
 pub mod view;
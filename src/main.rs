@@ -1,56 +1,164 @@
-//! Application entrypoint and command line parsers.
+//! Application entrypoint.
 
-use clap::{AppSettings, Parser};
+use clap::Parser;
 use color_eyre::eyre;
 use rodio::{OutputStream, Sink};
-use sampitor::dsp::Samples;
-use sampitor::io::{self, audio};
-use sampitor::view::filter::{Filter, Normalize};
-use sampitor::view::{Chart, File, Filters, View};
-use sampitor::App;
-use std::convert::TryInto;
+use sampitor::action::Keymap;
+use sampitor::app::{self, App};
+use sampitor::cli::{Command, Options};
+use sampitor::config::Config;
+use sampitor::io;
+use sampitor::session::Session;
+use sampitor::tab::{RegionDetection, Tab};
+use sampitor::ui::meter::Ballistics;
+use sampitor::ui::theme::{self, Palette};
+use sampitor::view::filter::{
+    BitCrusher, Compressor, Delay, Filter, HardClip, HighPass, LowPass, NoiseGate, Normalize, Pan,
+    PitchShift, RingModulator, SoftClip, StereoWidth, TimeStretch, Tremolo,
+};
+use sampitor::view::{chart, Chart, File, Filters, View};
 use std::env;
 use std::io::Stdout;
-use std::path::PathBuf;
 use tui::backend::CrosstermBackend;
 
-#[derive(Parser)]
-#[clap(
-    about = env!("CARGO_PKG_DESCRIPTION"),
-    global_setting = AppSettings::ColorAuto,
-    global_setting = AppSettings::ColoredHelp,
-    version = env!("CARGO_PKG_VERSION"),
-)]
-struct Options {
-    /// Audio file search directory
-    #[clap(short, long)]
-    dir: Option<PathBuf>,
-    /// Audio sample file path
-    #[clap(short, long)]
-    file: Option<PathBuf>,
-}
-
 fn main() -> eyre::Result<()> {
     color_eyre::install()?;
     let options = Options::parse();
+    // Headless runs exit before the audio output or terminal are touched, so they work in scripts
+    // without either.
+    if let Some(batch) = options.headless() {
+        return batch.run();
+    }
+    match &options.command {
+        Some(Command::Batch(batch)) => return batch.run(),
+        Some(Command::Convert(convert)) => return convert.run(),
+        Some(Command::Generate(generate)) => return generate.run(),
+        None => (),
+    }
+
+    // Invalid paths should be reported before the terminal enters raw mode.
+    let startup = options.startup(&env::current_dir()?)?;
+    // Sessions whose file is gone or whose format is unreadable are ignored to start fresh.
+    let session_path = Session::path();
+    let session = session_path
+        .as_deref()
+        .and_then(|path| Session::read(path).ok().flatten());
+    // Without a file to open, the file of the last session is reopened.
+    let opened = startup
+        .file
+        .clone()
+        .or_else(|| session.as_ref().and_then(|session| session.file.clone()));
+
+    // The startup file is read on a background thread so that the interface opens immediately,
+    // and errors decoding it are reported in the status area.
+    let mut tab = match &opened {
+        Some(file_path) => Tab::load(file_path.clone()),
+        None => Tab::default(),
+    };
+    let session = session.filter(|session| {
+        opened
+            .as_deref()
+            .is_some_and(|file_path| session.matches(file_path))
+    });
+    if let Some(session) = &session {
+        tab.restore_session(session);
+    }
 
     let (_stream, handle) = OutputStream::try_default()?;
     let sink = Sink::try_new(&handle)?;
+    sink.set_volume(startup.volume);
 
-    let samples = match options.file {
-        Some(file_path) => audio::read_samples(&file_path)?,
-        None => Samples::default(),
+    // A broken configuration file should not prevent startup, so defaults are used and the error
+    // is shown once the interface is running. Changes are not persisted to avoid overwriting it.
+    let (config, config_path, config_error) = match Config::path() {
+        Some(path) => match Config::read(&path) {
+            Ok(config) => (config, Some(path), None),
+            Err(error) => (Config::default(), None, Some(error)),
+        },
+        None => (Config::default(), None, None),
     };
-    let channels: usize = samples.channels.try_into()?;
 
-    let mut chart = Chart::new(String::new(), channels, samples.data.len() / channels);
-    let mut file = match options.dir {
-        Some(directory) => File::try_new(directory)?,
-        None => File::try_new(env::current_dir()?)?,
+    let (keymap, keymap_error) = match Keymap::try_new(&config.keys) {
+        Ok(keymap) => (keymap, None),
+        Err(error) => (Keymap::default(), Some(error)),
     };
 
+    let name = startup.theme.unwrap_or(config.theme);
+    let (theme, theme_error) =
+        match Palette::try_new(name, &config.colors, theme::supports_truecolor()) {
+            Ok(palette) => (palette, None),
+            Err(error) => (Palette::default(), Some(error)),
+        };
+
+    if let Some(megabytes) = config.cache_megabytes {
+        io::cache::shared().set_budget(megabytes.saturating_mul(1024 * 1024));
+    }
+    let clip_threshold = config.clip_threshold.unwrap_or(app::CLIP_THRESHOLD);
+    let csv_index = config.csv_index;
+    let decibel_floor = config.decibel_floor.unwrap_or(chart::DECIBEL_FLOOR);
+    let defaults = Ballistics::default();
+    let ballistics = Ballistics {
+        decay: config.meter_decay.unwrap_or(defaults.decay),
+        hold: config.meter_hold.unwrap_or(defaults.hold),
+    };
+    let (record_channels, record_sample_rate) = (config.record_channels, config.record_sample_rate);
+    let detection = RegionDetection::default();
+    let region_detection = RegionDetection {
+        attack: config
+            .region_attack_milliseconds
+            .unwrap_or(detection.attack),
+        hold: config.region_hold_milliseconds.unwrap_or(detection.hold),
+        release: config
+            .region_release_milliseconds
+            .unwrap_or(detection.release),
+        threshold_db: config.region_threshold_db.unwrap_or(detection.threshold_db),
+    };
+    let scrub = config.scrub;
+    let scrub_milliseconds = config.scrub_milliseconds.unwrap_or(app::SCRUB_MILLISECONDS);
+    let snap_channel = config.snap_channel;
+    let snap_search_frames = config.snap_search_frames.unwrap_or(app::SNAP_SEARCH_FRAMES);
+    let snap_to_zero_crossing = config.snap_to_zero_crossing;
+
+    let mut chart = Chart::new(String::new(), 0, 0)
+        .with_clip_threshold(clip_threshold)
+        .with_decibel_floor(decibel_floor);
+    let mut file = File::try_new(startup.directory)?.with_config(config, config_path);
+    if let Some(file_path) = &startup.file {
+        file.remember(file_path.clone());
+    }
+
+    let mut bit_crusher = BitCrusher::default();
+    let mut compressor = Compressor::default();
+    let mut delay = Delay::default();
+    let mut hard_clip = HardClip::default();
+    let mut high_pass = HighPass::default();
+    let mut low_pass = LowPass::default();
+    let mut noise_gate = NoiseGate::default();
     let mut normalize = Normalize::default();
-    let mut pairs: Vec<(&str, &mut dyn Filter)> = vec![("Normalize", &mut normalize)];
+    let mut pan = Pan::default();
+    let mut pitch_shift = PitchShift::default();
+    let mut ring_modulator = RingModulator::default();
+    let mut soft_clip = SoftClip::default();
+    let mut stereo_width = StereoWidth::default();
+    let mut time_stretch = TimeStretch::default();
+    let mut tremolo = Tremolo::default();
+    let mut pairs: Vec<(&str, &mut dyn Filter)> = vec![
+        ("Normalize", &mut normalize),
+        ("Hard clip", &mut hard_clip),
+        ("Soft clip", &mut soft_clip),
+        ("Pan", &mut pan),
+        ("Stereo width", &mut stereo_width),
+        ("Noise gate", &mut noise_gate),
+        ("Compressor", &mut compressor),
+        ("Low pass", &mut low_pass),
+        ("High pass", &mut high_pass),
+        ("Delay", &mut delay),
+        ("Ring modulator", &mut ring_modulator),
+        ("Bit crusher", &mut bit_crusher),
+        ("Tremolo", &mut tremolo),
+        ("Pitch shift", &mut pitch_shift),
+        ("Time stretch", &mut time_stretch),
+    ];
     let mut filters = Filters::new(&mut pairs);
 
     let mut views: Vec<(&str, &mut dyn View<CrosstermBackend<Stdout>>)> = vec![
@@ -59,12 +167,41 @@ fn main() -> eyre::Result<()> {
         ("Filters", &mut filters),
     ];
 
-    let mut app = App::new(&mut views, samples);
+    let mut app = App::new(&mut views, tab)
+        .with_keymap(keymap)
+        .with_clip_threshold(clip_threshold)
+        .with_csv_index(csv_index)
+        .with_meter(ballistics)
+        .with_recording(record_channels, record_sample_rate)
+        .with_region_detection(region_detection)
+        .with_scrub(scrub, scrub_milliseconds)
+        .with_snap_channel(snap_channel)
+        .with_theme(theme)
+        .with_zero_crossing_snap(snap_to_zero_crossing, snap_search_frames);
+    if let Some(view) = startup.view {
+        app.select(view.label());
+    } else if let Some(label) = session.as_ref().and_then(|session| session.view.as_deref()) {
+        app.select(label);
+    }
+    // Every startup error is shown together, since dismissing one should not hide the others.
+    let errors: Vec<String> = [config_error, keymap_error, theme_error]
+        .iter()
+        .flatten()
+        .map(|error| format!("{}", error))
+        .collect();
+    if !errors.is_empty() {
+        app.report(eyre::eyre!(errors.join("\n\n")));
+    }
 
-    // Control of the terminal needs to be returned even if the application encounters an error.
+    // Control of the terminal is returned even if the application encounters an error, since
+    // the screen restores the terminal once dropped.
     let mut terminal = io::terminal::take()?;
     let result = app.run(&mut terminal, &sink);
-    io::terminal::leave(&mut terminal)?;
+    terminal.leave()?;
 
+    // Failed runs keep the previous session, since their state may be what went wrong.
+    if let (Ok(()), Some(path), Some(session)) = (&result, &session_path, app.session()) {
+        session.write(path)?;
+    }
     result
 }
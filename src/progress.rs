@@ -0,0 +1,86 @@
+//! Coarse reporting of how far long running operations have come.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Smallest increase of the completed fraction that is passed on to the callback.
+const STEP: f32 = 0.01;
+
+/// Forwards the completed fraction of an operation to a callback only when it grew noticeably.
+pub struct Progress<'a> {
+    callback: &'a mut dyn FnMut(f32),
+    /// Flag set by another thread once the operation is no longer needed.
+    cancel: Option<&'a AtomicBool>,
+    reported: f32,
+}
+
+impl<'a> Progress<'a> {
+    /// Create a new Progress that reports to a callback.
+    pub fn new(callback: &'a mut dyn FnMut(f32)) -> Self {
+        Self {
+            callback,
+            cancel: None,
+            reported: f32::NEG_INFINITY,
+        }
+    }
+
+    /// Let the operation be stopped early by setting a flag.
+    #[must_use]
+    pub const fn with_cancel(mut self, cancel: &'a AtomicBool) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    /// Check whether the operation was asked to stop.
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel
+            .is_some_and(|cancel| cancel.load(Ordering::Relaxed))
+    }
+
+    /// Report completion unless it was already reported.
+    pub fn finish(&mut self) {
+        self.update(1.0);
+    }
+
+    /// Report a completed fraction, clamped between zero and one, if it advanced by at least a
+    /// step since the last report or reached completion.
+    ///
+    /// Fractions smaller than the last report are ignored, so reports never decrease.
+    pub fn update(&mut self, fraction: f32) {
+        let fraction = fraction.clamp(0.0, 1.0);
+        if fraction - self.reported >= STEP || (fraction >= 1.0 && self.reported < 1.0) {
+            self.reported = fraction;
+            (self.callback)(fraction);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn update_reports_coarse_increases() {
+        let mut reports = Vec::new();
+        let mut callback = |fraction| reports.push(fraction);
+        let mut progress = Progress::new(&mut callback);
+
+        for fraction in [0.0, 0.001, 0.5, 0.2, 0.505, 0.999, 2.0] {
+            progress.update(fraction);
+        }
+        progress.finish();
+
+        assert_eq!(reports, vec![0.0, 0.5, 0.999, 1.0]);
+    }
+
+    #[test]
+    fn is_cancelled_follows_flag() {
+        let cancel = AtomicBool::new(false);
+        let mut callback = |_fraction| {};
+        let progress = Progress::new(&mut callback).with_cancel(&cancel);
+
+        assert!(!progress.is_cancelled());
+        cancel.store(true, Ordering::Relaxed);
+        assert!(progress.is_cancelled());
+    }
+}
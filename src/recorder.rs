@@ -0,0 +1,303 @@
+//! Capture of audio from input devices into samples.
+
+use crate::dsp::Samples;
+use crate::ui::meter;
+use color_eyre::eyre;
+use rodio::cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use rodio::cpal::{self, SampleFormat, SampleRate, Stream, StreamConfig, SupportedStreamConfig};
+use std::mem;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+
+/// Samples captured so far and how many of them were metered.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Capture {
+    /// Number of samples whose levels were read.
+    metered: usize,
+    pub samples: Samples,
+}
+
+impl Capture {
+    /// Create an empty Capture for a channel count and sample rate.
+    #[must_use]
+    pub fn new(channels: u16, sample_rate: u32) -> Self {
+        Self {
+            metered: 0,
+            samples: Samples::new(channels, sample_rate, Vec::new()),
+        }
+    }
+
+    /// Append interleaved frames delivered by an input callback.
+    ///
+    /// Samples of a partial frame at the end of the data are dropped, since callbacks deliver
+    /// whole frames and keeping them would shift the channels of later frames.
+    pub fn push<T: cpal::Sample>(&mut self, data: &[T]) {
+        let width = usize::from(self.samples.channels.max(1));
+        for frame in data.chunks_exact(width) {
+            self.samples
+                .data
+                .extend(frame.iter().map(cpal::Sample::to_f32));
+        }
+    }
+
+    /// Append all blocks waiting in a channel from an input callback.
+    ///
+    /// Returns `false` once the channel is empty and its sender is gone, so no more blocks can
+    /// arrive.
+    pub fn drain(&mut self, blocks: &Receiver<Vec<f32>>) -> bool {
+        loop {
+            match blocks.try_recv() {
+                Ok(block) => self.push(&block),
+                Err(TryRecvError::Empty) => return true,
+                Err(TryRecvError::Disconnected) => return false,
+            }
+        }
+    }
+
+    /// Get the samples captured since the last call, for metering.
+    pub fn unmetered(&mut self) -> &[f32] {
+        let start = mem::replace(&mut self.metered, self.samples.data.len());
+        &self.samples.data[start..]
+    }
+}
+
+/// Input device and stream format to record with, where `None` picks the device default.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Settings {
+    pub channels: Option<u16>,
+    /// Name of the input device as listed by [`input_devices`].
+    pub device: Option<String>,
+    pub sample_rate: Option<u32>,
+}
+
+/// Get the names of the available input devices.
+///
+/// # Errors
+///
+/// Will return `Err` if the audio host cannot list its devices.
+pub fn input_devices() -> eyre::Result<Vec<String>> {
+    let devices = cpal::default_host()
+        .input_devices()
+        .map_err(|error| eyre::eyre!("Input devices could not be listed: {}", error))?;
+    Ok(devices.filter_map(|device| device.name().ok()).collect())
+}
+
+/// A running recording from an input device.
+///
+/// The input callback converts samples to `f32` and sends them over a channel without locking,
+/// so redraws never hold up the audio thread. The channel is drained on the UI thread.
+pub struct Recorder {
+    /// Blocks of interleaved samples sent by the input callback.
+    blocks: Receiver<Vec<f32>>,
+    capture: Capture,
+    /// Errors reported by the input stream.
+    errors: Receiver<String>,
+    stream: Stream,
+}
+
+impl Recorder {
+    /// Start recording from an input device at a channel count and sample rate.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the input device does not exist, does not support the requested
+    /// channel count and sample rate, or the stream cannot be started.
+    pub fn start(settings: &Settings) -> eyre::Result<Self> {
+        let device = find_device(settings.device.as_deref())?;
+        let supported = choose_config(&device, settings)?;
+        let format = supported.sample_format();
+        let config: StreamConfig = supported.into();
+        if config.channels == 0 || config.sample_rate.0 == 0 {
+            eyre::bail!("Input device reports no channels or a sample rate of zero");
+        }
+
+        let (sender, blocks) = mpsc::channel();
+        let (error_sender, errors) = mpsc::channel();
+        let stream = match format {
+            SampleFormat::F32 => build::<f32>(&device, &config, sender, error_sender),
+            SampleFormat::I16 => build::<i16>(&device, &config, sender, error_sender),
+            SampleFormat::U16 => build::<u16>(&device, &config, sender, error_sender),
+        }?;
+        stream
+            .play()
+            .map_err(|error| eyre::eyre!("Recording could not start: {}", error))?;
+
+        Ok(Self {
+            blocks,
+            capture: Capture::new(config.channels, config.sample_rate.0),
+            errors,
+            stream,
+        })
+    }
+
+    /// Append the samples received since the last call and check the input stream for errors.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the input stream failed, for example because its device was
+    /// disconnected. The samples captured until then are kept.
+    pub fn drain(&mut self) -> eyre::Result<()> {
+        self.capture.drain(&self.blocks);
+        match self.errors.try_recv() {
+            Ok(error) => Err(eyre::eyre!("Recording failed: {}", error)),
+            Err(_error) => Ok(()),
+        }
+    }
+
+    /// Get the number of frames recorded so far.
+    #[must_use]
+    pub fn frames(&self) -> usize {
+        self.capture.samples.frames()
+    }
+
+    /// Get the sample rate of the recording.
+    #[must_use]
+    pub const fn sample_rate(&self) -> u32 {
+        self.capture.samples.sample_rate
+    }
+
+    /// Get the peak and RMS level of each channel since the last call, for drawing level meters.
+    pub fn take_levels(&mut self) -> (Vec<f32>, Vec<f32>) {
+        let channels = self.capture.samples.channels;
+        let block = self.capture.unmetered();
+        (
+            meter::block_peaks(block, channels),
+            meter::block_rms(block, channels),
+        )
+    }
+
+    /// Stop recording and get the captured samples, including the ones still in the channel.
+    #[must_use]
+    pub fn stop(self) -> Samples {
+        let Self {
+            blocks,
+            mut capture,
+            stream,
+            ..
+        } = self;
+        // Dropping the stream closes it even if its device is gone and pausing would fail.
+        drop(stream);
+        capture.drain(&blocks);
+        capture.samples
+    }
+}
+
+/// Find an input device by name, or the default input device if no name is given.
+fn find_device(name: Option<&str>) -> eyre::Result<cpal::Device> {
+    let host = cpal::default_host();
+    match name {
+        Some(name) => host
+            .input_devices()
+            .map_err(|error| eyre::eyre!("Input devices could not be listed: {}", error))?
+            .find(|device| device.name().is_ok_and(|device| device == name))
+            .ok_or_else(|| eyre::eyre!("Input device {} is not available", name)),
+        None => host
+            .default_input_device()
+            .ok_or_else(|| eyre::eyre!("No audio input device is available for recording")),
+    }
+}
+
+/// Choose a stream format of a device with the requested channel count and sample rate, using
+/// the device defaults for those not requested.
+fn choose_config(
+    device: &cpal::Device,
+    settings: &Settings,
+) -> eyre::Result<SupportedStreamConfig> {
+    let default = device
+        .default_input_config()
+        .map_err(|error| eyre::eyre!("Input device cannot record: {}", error))?;
+    if settings.channels.is_none() && settings.sample_rate.is_none() {
+        return Ok(default);
+    }
+
+    let channels = settings.channels.unwrap_or_else(|| default.channels());
+    let rate = SampleRate(settings.sample_rate.unwrap_or(default.sample_rate().0));
+    device
+        .supported_input_configs()
+        .map_err(|error| eyre::eyre!("Input device cannot record: {}", error))?
+        .filter(|range| {
+            range.channels() == channels
+                && range.min_sample_rate() <= rate
+                && rate <= range.max_sample_rate()
+        })
+        .max_by(|first, second| first.cmp_default_heuristics(second))
+        .map(|range| range.with_sample_rate(rate))
+        .ok_or_else(|| {
+            eyre::eyre!(
+                "Input device cannot record {} channels at {} Hz",
+                channels,
+                rate.0
+            )
+        })
+}
+
+/// Build an input stream that sends samples of a format converted to `f32` over a channel.
+fn build<T: cpal::Sample>(
+    device: &cpal::Device,
+    config: &StreamConfig,
+    blocks: Sender<Vec<f32>>,
+    errors: Sender<String>,
+) -> eyre::Result<Stream> {
+    device
+        .build_input_stream(
+            config,
+            move |data: &[T], _info| {
+                // The receiver only goes away once recording stops, so late blocks can be dropped.
+                let _result = blocks.send(data.iter().map(cpal::Sample::to_f32).collect());
+            },
+            move |report| {
+                let _result = errors.send(format!("{}", report));
+            },
+        )
+        .map_err(|error| eyre::eyre!("Input stream could not be opened: {}", error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_appends_frames_and_peaks() {
+        let mut capture = Capture::new(2, 8000);
+        capture.push(&[0.5_f32, -0.25, -0.75, 0.125]);
+        capture.push(&[i16::MAX, 0, 16_384]);
+
+        assert_eq!(capture.samples.frames(), 3);
+        assert_eq!(capture.samples.data[..4], [0.5, -0.25, -0.75, 0.125]);
+        assert!((capture.samples.data[4] - 1.0).abs() < 1e-4);
+        assert_eq!(meter::block_peaks(capture.unmetered(), 2)[1], 0.25);
+        assert_eq!(meter::block_peaks(capture.unmetered(), 2), vec![0.0, 0.0]);
+
+        capture.push(&[u16::MAX / 2 + 1, 0]);
+        assert_eq!(capture.samples.data[6..], [0.0, -1.0]);
+        assert_eq!(meter::block_peaks(capture.unmetered(), 2), vec![0.0, 1.0]);
+    }
+
+    #[test]
+    fn drain_keeps_blocks_sent_before_disconnect() {
+        let (sender, blocks) = mpsc::channel();
+        let mut capture = Capture::new(1, 8000);
+        sender.send(vec![0.5, -0.25]).unwrap();
+        sender.send(vec![0.75]).unwrap();
+
+        assert!(capture.drain(&blocks));
+        assert_eq!(capture.samples.data, vec![0.5, -0.25, 0.75]);
+        sender.send(vec![-1.0]).unwrap();
+        drop(sender);
+        assert!(!capture.drain(&blocks));
+        assert_eq!(capture.samples.frames(), 4);
+        assert_eq!(meter::block_peaks(capture.unmetered(), 1), vec![1.0]);
+    }
+
+    #[test]
+    #[cfg(feature = "device-tests")]
+    fn record_from_default_device() {
+        assert!(!input_devices().unwrap().is_empty());
+        let mut recorder = Recorder::start(&Settings::default()).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(500));
+        recorder.drain().unwrap();
+
+        let samples = recorder.stop();
+        assert!(samples.frames() > 0);
+        assert!(samples.sample_rate > 0);
+    }
+}
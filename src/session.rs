@@ -0,0 +1,178 @@
+//! Cursor, selection, and chart bounds of the last opened file, kept between runs.
+
+use crate::tab::Tab;
+use color_eyre::eyre;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// Version of the session format written by this build.
+///
+/// Fields added later are filled with defaults when reading older sessions, so the version is
+/// only raised when existing fields change meaning.
+pub const VERSION: u32 = 1;
+
+/// State of the active tab stored as TOML when the application exits.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct Session {
+    /// Visible chart bounds in frames, if the file was shown in the chart.
+    pub bounds: Option<(f64, f64)>,
+    /// Audio file of the active tab.
+    pub file: Option<PathBuf>,
+    /// Cursor frame index where playback starts.
+    pub playhead: usize,
+    /// Boundaries of the selected frames, if any.
+    pub selection: Option<(usize, usize)>,
+    /// Format version the session was written with.
+    pub version: u32,
+    /// Menu label of the displayed view.
+    pub view: Option<String>,
+}
+
+impl Session {
+    /// Get the default session file location, if the platform has one.
+    #[must_use]
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|directory| directory.join("sampitor").join("session.toml"))
+    }
+
+    /// Capture the state of a tab shown in a view, if the tab holds a file that has loaded.
+    #[must_use]
+    pub fn capture(tab: &Tab, view: Option<&str>) -> Option<Self> {
+        if tab.is_loading() {
+            return None;
+        }
+        let file = tab.path.as_ref()?;
+
+        Some(Self {
+            bounds: tab.zoom.as_ref().map(|axes| axes.bounds()),
+            file: Some(fs::canonicalize(file).unwrap_or_else(|_error| file.clone())),
+            playhead: tab.playhead,
+            selection: tab
+                .selection
+                .map(|selection| (selection.start, selection.end)),
+            version: VERSION,
+            view: view.map(str::to_owned),
+        })
+    }
+
+    /// Check whether the session belongs to an audio file.
+    #[must_use]
+    pub fn matches(&self, file: &Path) -> bool {
+        let canonical = fs::canonicalize(file).unwrap_or_else(|_error| file.to_owned());
+        self.file.as_ref() == Some(&canonical)
+    }
+
+    /// Read a session file.
+    ///
+    /// Returns `None` if the session file does not exist, was written by a newer version, or
+    /// its audio file no longer exists, so that the application starts fresh.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `path` is unreadable or contains invalid TOML.
+    pub fn read(path: &Path) -> eyre::Result<Option<Self>> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error.into()),
+        };
+        let session: Self = toml::from_str(&text)
+            .map_err(|error| eyre::eyre!("Session file {:?} is invalid: {}", path, error))?;
+
+        let exists = session.file.as_ref().is_some_and(|file| file.is_file());
+        Ok((exists && session.version <= VERSION).then_some(session))
+    }
+
+    /// Write the session to a file, creating its parent directories if necessary.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `path` is unwritable.
+    pub fn write(&self, path: &Path) -> eyre::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, toml::to_string(self)?)?;
+        Ok(())
+    }
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self {
+            bounds: None,
+            file: None,
+            playhead: 0,
+            selection: None,
+            version: VERSION,
+            view: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsp::{Samples, Selection};
+    use crate::ui::axes::Axes;
+
+    #[test]
+    fn write_and_read_round_trip() {
+        let folder = tempfile::tempdir().unwrap();
+        let file = folder.path().join("take.wav");
+        fs::write(&file, b"").unwrap();
+        let path = folder.path().join("state").join("session.toml");
+
+        let mut tab = Tab::new(Samples::new(1, 8, vec![0.0; 100]), Some(file.clone()));
+        tab.playhead = 42;
+        tab.selection = Some(Selection::new(10, 30));
+        tab.zoom = Some(Axes::new([5.0, 55.5], [-1.0, 1.0], 1.0));
+        let session = Session::capture(&tab, Some("Chart")).unwrap();
+        assert_eq!(
+            session,
+            Session {
+                bounds: Some((5.0, 55.5)),
+                file: Some(fs::canonicalize(&file).unwrap()),
+                playhead: 42,
+                selection: Some((10, 30)),
+                version: VERSION,
+                view: Some(String::from("Chart")),
+            }
+        );
+        assert!(session.matches(&folder.path().join(".").join("take.wav")));
+
+        session.write(&path).unwrap();
+        assert_eq!(Session::read(&path).unwrap(), Some(session));
+        assert!(Session::capture(&Tab::default(), None).is_none());
+    }
+
+    #[test]
+    fn read_starts_fresh_without_file() {
+        let folder = tempfile::tempdir().unwrap();
+        let path = folder.path().join("session.toml");
+        assert_eq!(Session::read(&path).unwrap(), None);
+
+        let file = folder.path().join("gone.wav");
+        fs::write(&path, format!("file = {:?}\nplayhead = 12\n", file)).unwrap();
+        assert_eq!(Session::read(&path).unwrap(), None);
+
+        // Sessions without newer fields read with defaults, while newer versions are ignored.
+        fs::write(&file, b"").unwrap();
+        let session = Session::read(&path).unwrap().unwrap();
+        assert_eq!(session.playhead, 12);
+        assert_eq!(session.version, VERSION);
+        fs::write(
+            &path,
+            format!("file = {:?}\nversion = {}\n", file, VERSION + 1),
+        )
+        .unwrap();
+        assert_eq!(Session::read(&path).unwrap(), None);
+
+        fs::write(&path, "playhead = [").unwrap();
+        assert!(Session::read(&path).is_err());
+    }
+}
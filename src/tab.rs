@@ -0,0 +1,1496 @@
+//! Open audio buffers and their editing state.
+
+use crate::dsp::{self, Marker, Samples, Selection, Signal, Stats};
+use crate::history::UndoHistory;
+use crate::io::audio::WriteOptions;
+use crate::io::metadata::{self, Metadata};
+use crate::io::window::{self, Windowed};
+use crate::io::{audio, cache, labels, path, riff};
+use crate::session::Session;
+use crate::ui::axes::Axes;
+use crate::ui::peaks::Peaks;
+use color_eyre::eyre;
+use std::fs;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+
+/// Identifier for the next created tab.
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Level and times that tell regions of sound apart from the silences between them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RegionDetection {
+    /// Milliseconds kept before the first loud frame of each region.
+    pub attack: f32,
+    /// Milliseconds of the shortest silence that separates two regions.
+    pub hold: f32,
+    /// Milliseconds kept after the last loud frame of each region.
+    pub release: f32,
+    /// Level in decibels below which frames count as silent.
+    pub threshold_db: f32,
+}
+
+impl Default for RegionDetection {
+    fn default() -> Self {
+        Self {
+            attack: 10.0,
+            hold: 250.0,
+            release: 100.0,
+            threshold_db: -50.0,
+        }
+    }
+}
+
+/// An open audio buffer and the state needed to edit it.
+#[derive(Debug)]
+pub struct Tab {
+    /// Edits that can be undone or redone.
+    pub history: UndoHistory,
+    /// Edit running on a background thread that will replace the samples once finished.
+    editing: Option<Editing>,
+    id: usize,
+    /// Background read that will replace the samples once finished.
+    loading: Option<Loading>,
+    /// Tags and format details embedded in the file the samples were read from.
+    pub metadata: Metadata,
+    /// Whether samples were edited since they were last read or saved.
+    pub modified: bool,
+    /// Plot ranges of the current samples, once the chart has drawn them decimated.
+    pub peaks: Option<Peaks>,
+    /// File the samples were read from or last written to.
+    pub path: Option<PathBuf>,
+    /// Copy of the samples from before the last edit, kept for comparison until committed.
+    pub preview: Option<Samples>,
+    /// Whether the chart hides the preview instead of overlaying it on the current samples.
+    pub preview_hidden: bool,
+    /// Cursor frame index where playback starts.
+    pub playhead: usize,
+    pub samples: Samples,
+    /// Frames chosen for editing, if any.
+    pub selection: Option<Selection>,
+    /// Level statistics and the selection they were measured over, once requested.
+    pub stats: Option<(Option<Selection>, Stats)>,
+    /// Estimated beats per minute of the current samples, once requested.
+    pub tempo: Option<f32>,
+    /// File opened for viewing and playback only because it is too large to decode into memory,
+    /// in which case the samples hold no frames.
+    pub windowed: Option<Windowed>,
+    /// Visible chart bounds, once the tab has been shown in the chart.
+    pub zoom: Option<Axes>,
+}
+
+impl Tab {
+    /// Create a new Tab from samples and the file they belong to.
+    #[must_use]
+    pub fn new(samples: Samples, path: Option<PathBuf>) -> Self {
+        Self {
+            history: UndoHistory::default(),
+            editing: None,
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            loading: None,
+            metadata: Metadata::default(),
+            modified: false,
+            path,
+            peaks: None,
+            playhead: 0,
+            preview: None,
+            preview_hidden: false,
+            samples,
+            selection: None,
+            stats: None,
+            tempo: None,
+            windowed: None,
+            zoom: None,
+        }
+    }
+
+    /// Create an empty Tab that reads samples from a file on a background thread.
+    ///
+    /// The read is cancelled once the tab is dropped or replaced, so the thread stops shortly
+    /// after its result is no longer needed. WAV files larger than [`window::STREAM_THRESHOLD`]
+    /// are opened windowed instead of decoded.
+    #[must_use]
+    pub fn load(path: PathBuf) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let (progress_sender, progress) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let thread_cancel = Arc::clone(&cancel);
+        let thread_path = path.clone();
+        thread::spawn(move || {
+            // The receivers are gone if the tab was closed, so the results are no longer needed.
+            let mut progress = |fraction| {
+                let _result = progress_sender.send(fraction);
+            };
+            let large = fs::metadata(&thread_path)
+                .is_ok_and(|metadata| metadata.len() > window::STREAM_THRESHOLD);
+            let result = if large && audio::read_samples_streaming(&thread_path).is_ok() {
+                Windowed::open(&thread_path, &mut progress, &thread_cancel).map(|windowed| {
+                    let mut samples = Samples::new(
+                        windowed.window.channels(),
+                        windowed.window.sample_rate(),
+                        Vec::new(),
+                    );
+                    if let Ok(chunks) = riff::read_chunks(&thread_path) {
+                        samples.markers = riff::decode_markers(&chunks);
+                        samples.metadata = riff::decode_metadata(&chunks);
+                    }
+                    (samples, Some(windowed))
+                })
+            } else {
+                // Files read before are copied from the cache unless they changed on disk since.
+                cache::read_shared(&thread_path, |path| {
+                    audio::read_samples_cancellable(path, &mut progress, &thread_cancel)
+                })
+                .map(|samples| (samples, None))
+            };
+            // Tags are informational, so files whose headers cannot be probed still open.
+            let result = result.and_then(|(mut samples, windowed)| {
+                // Marker files are written on every save, so they are newer than cue points.
+                if let Some(markers) = labels::read_sidecar(&thread_path, samples.sample_rate)? {
+                    samples.markers = markers;
+                }
+                let metadata = metadata::read_metadata(&thread_path).unwrap_or_default();
+                Ok((samples, metadata, windowed))
+            });
+            let _result = sender.send(result);
+        });
+
+        Self::loading(Loading {
+            cancel,
+            fraction: 0.0,
+            path,
+            progress,
+            receiver,
+            session: None,
+        })
+    }
+
+    /// Create an empty Tab that waits for the result of a background read.
+    fn loading(loading: Loading) -> Self {
+        Self {
+            loading: Some(loading),
+            ..Self::default()
+        }
+    }
+
+    /// Get the identifier that is unique to each created tab.
+    #[must_use]
+    pub const fn id(&self) -> usize {
+        self.id
+    }
+
+    /// Check whether the tab is empty, unmodified, and not tied to a file.
+    #[must_use]
+    pub fn is_blank(&self) -> bool {
+        self.path.is_none() && !self.is_loading() && !self.modified && self.samples.data.is_empty()
+    }
+
+    /// Check whether samples are still being read in the background.
+    #[must_use]
+    pub const fn is_loading(&self) -> bool {
+        self.loading.is_some()
+    }
+
+    /// Get the description of the background edit and its fraction that was completed as of the
+    /// last poll, if an edit is running.
+    #[must_use]
+    pub fn edit_progress(&self) -> Option<(&str, f32)> {
+        self.editing
+            .as_ref()
+            .map(|editing| (editing.label.as_str(), editing.fraction))
+    }
+
+    /// Get the fraction of the background read that was completed as of the last poll.
+    #[must_use]
+    pub fn load_progress(&self) -> Option<f32> {
+        self.loading.as_ref().map(|loading| loading.fraction)
+    }
+
+    /// Get the number of frames, including those of windowed files that are not in memory.
+    #[must_use]
+    pub fn frames(&self) -> usize {
+        self.windowed.as_ref().map_or_else(
+            || self.samples.frames(),
+            |windowed| windowed.window.frames(),
+        )
+    }
+
+    /// Move the playhead, selection, and chart bounds to those of a session, once the tab has
+    /// finished loading.
+    ///
+    /// Frames past the end of the samples are clamped to it, and empty bounds are ignored.
+    pub fn restore_session(&mut self, session: &Session) {
+        if let Some(loading) = &mut self.loading {
+            loading.session = Some(session.clone());
+            return;
+        }
+
+        let frames = self.frames();
+        self.playhead = session.playhead.min(frames);
+        self.selection = session
+            .selection
+            .map(|(start, end)| Selection::new(start.min(frames), end.min(frames)))
+            .filter(|selection| !selection.is_empty());
+        self.zoom = session
+            .bounds
+            .filter(|(start, end)| start.is_finite() && end.is_finite() && start < end)
+            .map(|(start, end)| Axes::new([start, end], [-1.0, 1.0], 1.0));
+    }
+
+    /// Check that the samples can be edited or saved, which windowed files and tabs with a
+    /// running background edit cannot.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the tab holds a windowed file or runs a background edit.
+    pub fn ensure_editable(&self) -> eyre::Result<()> {
+        self.ensure_idle()?;
+        if self.windowed.is_some() {
+            eyre::bail!(
+                "{} is too large to edit and is open for viewing and playback only",
+                self.name()
+            );
+        }
+        Ok(())
+    }
+
+    /// Check that no background edit is running, whose result would replace later changes.
+    fn ensure_idle(&self) -> eyre::Result<()> {
+        if let Some(editing) = &self.editing {
+            eyre::bail!(
+                "Wait for {} {} to finish before changing it",
+                editing.label.to_lowercase(),
+                self.name()
+            );
+        }
+        Ok(())
+    }
+
+    /// Get display name for the tab.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        self.path
+            .as_deref()
+            .or_else(|| self.loading.as_ref().map(|loading| loading.path.as_path()))
+            .and_then(|path| path::name(path).ok())
+            .unwrap_or("untitled")
+    }
+
+    /// Discard the preview and keep the last edit.
+    pub fn commit_preview(&mut self) {
+        self.preview = None;
+    }
+
+    /// Undo the last edit if it still has a preview.
+    ///
+    /// Returns whether there was a preview to revert.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the undo history cannot be read or written.
+    pub fn revert_preview(&mut self) -> eyre::Result<bool> {
+        if self.preview.is_none() {
+            return Ok(false);
+        }
+        self.undo()
+    }
+
+    /// Mix incoming samples scaled by a gain into the samples at the playhead as an undoable edit.
+    ///
+    /// Returns the peak magnitude of the mixed frames, which exceeds one if they clip.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the tab is loading, the channels cannot be mixed, or the undo history
+    /// cannot be written.
+    pub fn mix(&mut self, incoming: &Samples, gain: f32) -> eyre::Result<f32> {
+        if self.is_loading() {
+            eyre::bail!(
+                "Wait for {} to finish loading before mixing into it",
+                self.name()
+            );
+        }
+
+        let mut mixed = self.samples.clone();
+        let peak = mixed.mix(incoming, gain, self.playhead)?;
+        self.edit(mixed)?;
+        Ok(peak)
+    }
+
+    /// Insert silence lasting a number of milliseconds at the playhead as an undoable edit.
+    ///
+    /// Later frames, markers, and selection boundaries move back by the inserted length.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the tab is loading or empty, the length is not a positive
+    /// number, or the undo history cannot be written.
+    pub fn insert_silence(&mut self, milliseconds: f32) -> eyre::Result<()> {
+        if self.is_loading() {
+            eyre::bail!(
+                "Wait for {} to finish loading before editing it",
+                self.name()
+            );
+        }
+        // Blank tabs have no meaningful sample rate to measure the length with.
+        if self.samples.data.is_empty() {
+            eyre::bail!("Open or generate audio before inserting silence");
+        }
+        if !milliseconds.is_finite() || milliseconds <= 0.0 {
+            eyre::bail!(
+                "Silence length {} must be a positive number of milliseconds",
+                milliseconds
+            );
+        }
+
+        // The length is checked to be positive and finite, so truncation is not possible.
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let frames = (f64::from(milliseconds) / 1000.0 * f64::from(self.samples.sample_rate))
+            .round() as usize;
+        let mut inserted = self.samples.clone();
+        dsp::insert_silence(&mut inserted, self.playhead, frames);
+        self.edit(inserted)?;
+
+        let playhead = self.playhead;
+        let shift = |frame: usize| {
+            if frame > playhead {
+                frame + frames
+            } else {
+                frame
+            }
+        };
+        self.selection = self
+            .selection
+            .map(|selection| Selection::new(shift(selection.start), shift(selection.end)));
+        Ok(())
+    }
+
+    /// Remove the selected frames as an undoable edit and move the playhead to where they were.
+    ///
+    /// Markers inside the selection are removed and later markers move back with their frames.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the tab is loading, nothing is selected, or the undo history cannot be
+    /// written.
+    pub fn delete_selection(&mut self) -> eyre::Result<()> {
+        if self.is_loading() {
+            eyre::bail!(
+                "Wait for {} to finish loading before editing it",
+                self.name()
+            );
+        }
+        let selection = self
+            .selection
+            .ok_or_else(|| eyre::eyre!("Select frames in the chart before deleting them"))?;
+
+        let mut cut = self.samples.clone();
+        let empty = cut.slice(Selection::new(0, 0));
+        cut.splice(selection, empty)?;
+        self.edit(cut)?;
+        self.playhead = selection.start.min(self.samples.frames());
+        self.selection = None;
+        Ok(())
+    }
+
+    /// Crossfade the end of the selection into the frames before it as an undoable edit, so that
+    /// the selection loops without clicking.
+    ///
+    /// The crossfade is clamped to the frames available, and the selection moves to the looped
+    /// frames if its own start had to be crossfaded and cut. See [`Samples::crossfade_loop`].
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the tab is loading, nothing is selected, the length is not a positive
+    /// number, or the undo history cannot be written.
+    pub fn crossfade_loop(&mut self, milliseconds: f32) -> eyre::Result<()> {
+        if self.is_loading() {
+            eyre::bail!(
+                "Wait for {} to finish loading before editing it",
+                self.name()
+            );
+        }
+        let selection = self.selection.ok_or_else(|| {
+            eyre::eyre!("Select the frames to loop in the chart before crossfading their seam")
+        })?;
+        if !milliseconds.is_finite() || milliseconds <= 0.0 {
+            eyre::bail!(
+                "Crossfade length {} must be a positive number of milliseconds",
+                milliseconds
+            );
+        }
+
+        // The length is checked to be positive and finite, so truncation is not possible.
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let frames = (f64::from(milliseconds) / 1000.0 * f64::from(self.samples.sample_rate))
+            .round() as usize;
+        let mut looped = self.samples.clone();
+        let selection = looped.crossfade_loop(selection, frames)?;
+        self.edit(looped)?;
+        self.selection = Some(selection);
+        self.playhead = self.playhead.min(self.samples.frames());
+        Ok(())
+    }
+
+    /// Flip the polarity of one channel counted from zero, or of all channels if `None`, in the
+    /// selected frames or the whole tab if nothing is selected, as an undoable edit.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the tab is loading, `channel` is not one of its channels, or the undo
+    /// history cannot be written.
+    pub fn invert_polarity(&mut self, channel: Option<u16>) -> eyre::Result<()> {
+        if self.is_loading() {
+            eyre::bail!(
+                "Wait for {} to finish loading before editing it",
+                self.name()
+            );
+        }
+
+        let mut inverted = self.samples.clone();
+        match self.selection {
+            Some(selection) => {
+                let mut part = inverted.slice(selection);
+                part.invert_phase(channel)?;
+                inverted.splice(selection, part)?;
+            }
+            None => inverted.invert_phase(channel)?,
+        }
+        self.edit(inverted)
+    }
+
+    /// Get the index of the marker at the playhead, if there is one.
+    #[must_use]
+    pub fn marker_at_playhead(&self) -> Option<usize> {
+        self.samples
+            .markers
+            .iter()
+            .position(|marker| marker.frame == self.playhead)
+    }
+
+    /// Add a named marker at the playhead, keeping markers sorted by frame.
+    ///
+    /// Marker changes are saved to the undo history but do not replace the preview.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the tab is loading, holds a windowed file, runs a background edit, or
+    /// the undo history cannot be written.
+    pub fn add_marker(&mut self, label: String) -> eyre::Result<()> {
+        if self.is_loading() {
+            eyre::bail!(
+                "Wait for {} to finish loading before marking it",
+                self.name()
+            );
+        }
+        self.ensure_editable()?;
+
+        self.history.push(&self.samples)?;
+        let frame = self.playhead.min(self.samples.frames());
+        let index = self
+            .samples
+            .markers
+            .partition_point(|marker| marker.frame <= frame);
+        self.samples
+            .markers
+            .insert(index, Marker::new(frame, label));
+        self.modified = true;
+        Ok(())
+    }
+
+    /// Rename the marker at the playhead, or delete it if the label is empty.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the tab is loading, holds a windowed file, runs a background edit,
+    /// there is no marker at the playhead, or the undo history cannot be written.
+    pub fn rename_marker(&mut self, label: String) -> eyre::Result<()> {
+        if self.is_loading() {
+            eyre::bail!(
+                "Wait for {} to finish loading before renaming its markers",
+                self.name()
+            );
+        }
+        self.ensure_editable()?;
+        let index = self.marker_at_playhead().ok_or_else(|| {
+            eyre::eyre!("Move the playhead onto a marker with ]m or [m to rename it")
+        })?;
+
+        self.history.push(&self.samples)?;
+        if label.is_empty() {
+            self.samples.markers.remove(index);
+        } else {
+            self.samples.markers[index].label = label;
+        }
+        self.modified = true;
+        Ok(())
+    }
+
+    /// Join incoming samples onto the end of the samples as an undoable edit.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the tab is loading, the channels cannot be converted, or the undo
+    /// history cannot be written.
+    pub fn append(&mut self, incoming: &Samples, crossfade: usize) -> eyre::Result<()> {
+        if self.is_loading() {
+            eyre::bail!(
+                "Wait for {} to finish loading before appending to it",
+                self.name()
+            );
+        }
+
+        let mut joined = self.samples.clone();
+        joined.append(incoming, crossfade)?;
+        self.edit(joined)
+    }
+
+    /// Run a filter on a copy of the samples and keep the result as an undoable edit.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the tab is loading, the filter fails, or the undo history cannot be
+    /// written.
+    pub fn apply(&mut self, filter: fn(&mut Samples) -> eyre::Result<()>) -> eyre::Result<()> {
+        if self.is_loading() {
+            eyre::bail!(
+                "Wait for {} to finish loading before editing it",
+                self.name()
+            );
+        }
+
+        let mut filtered = self.samples.clone();
+        filter(&mut filtered)?;
+        self.edit(filtered)
+    }
+
+    /// Run a slow edit of the selected frames, or of the whole tab if nothing is selected, on a
+    /// background thread and keep its result as an undoable edit once [`Self::poll`] finds it
+    /// finished.
+    ///
+    /// The edit reports its completed fraction to a callback, which is shown with its label until
+    /// it finishes. Other changes are refused meanwhile, and a selection is resized to the length
+    /// of the edited frames.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the tab is loading, holds a windowed file, or already runs an edit.
+    pub fn edit_in_background<F>(&mut self, label: &str, edit: F) -> eyre::Result<()>
+    where
+        F: FnOnce(&mut Samples, &mut dyn FnMut(f32)) + Send + 'static,
+    {
+        if self.is_loading() {
+            eyre::bail!(
+                "Wait for {} to finish loading before editing it",
+                self.name()
+            );
+        }
+        self.ensure_editable()?;
+
+        let mut part = match self.selection {
+            Some(selection) => self.samples.slice(selection),
+            None => self.samples.clone(),
+        };
+        let (sender, receiver) = mpsc::channel();
+        let (progress_sender, progress) = mpsc::channel();
+        thread::spawn(move || {
+            // The receivers are gone if the tab was closed, so the result is no longer needed.
+            edit(&mut part, &mut |fraction| {
+                let _result = progress_sender.send(fraction);
+            });
+            let _result = sender.send(part);
+        });
+
+        self.editing = Some(Editing {
+            fraction: 0.0,
+            label: label.to_owned(),
+            progress,
+            receiver,
+            selection: self.selection,
+        });
+        Ok(())
+    }
+
+    /// Replace samples with an edited version, keeping the previous samples to undo and preview.
+    fn edit(&mut self, edited: Samples) -> eyre::Result<()> {
+        self.ensure_editable()?;
+        self.history.push(&self.samples)?;
+        self.preview = Some(mem::replace(&mut self.samples, edited));
+        self.modified = true;
+        self.peaks = None;
+        self.stats = None;
+        self.tempo = None;
+        Ok(())
+    }
+
+    /// Write samples to the file they belong to in the format of its extension and mark them as
+    /// unmodified.
+    ///
+    /// Formats are written with the default options of [`audio::write_by_extension`], such as 16
+    /// bits per sample for AIFF files.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the tab has no file, holds a windowed file, the file is in a format
+    /// that can only be read, or the file is unwritable.
+    pub fn save(&mut self) -> eyre::Result<()> {
+        self.ensure_editable()?;
+        let path = self
+            .path
+            .as_ref()
+            .ok_or_else(|| eyre::eyre!("Write {} to a file before saving it", self.name()))?;
+        audio::write_by_extension(path, &self.samples, &WriteOptions::default())?;
+        labels::write_sidecar(path, &self.samples)?;
+        self.modified = false;
+        Ok(())
+    }
+
+    /// Write the segments between markers as numbered WAV files to a folder next to the file.
+    ///
+    /// Samples of song.wav are written to song-segments/song-01.wav and onward, replacing files
+    /// from an earlier split. Returns the paths of the written files.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the tab has no file or markers, or the folder or a segment cannot be
+    /// written.
+    pub fn split_at_markers(&self) -> eyre::Result<Vec<PathBuf>> {
+        self.ensure_editable()?;
+        let path = self
+            .path
+            .as_ref()
+            .ok_or_else(|| eyre::eyre!("Write {} to a file before splitting it", self.name()))?;
+        if self.samples.markers.is_empty() {
+            eyre::bail!("Add markers to {} to split it at", self.name());
+        }
+
+        write_parts(path, "segments", &self.samples.split_at_markers())
+    }
+
+    /// Find the regions of sound between silences in the samples.
+    ///
+    /// See [`dsp::sound_regions`] for how frames are grouped into regions.
+    #[must_use]
+    pub fn sound_regions(&self, detection: &RegionDetection) -> Vec<Selection> {
+        // Negative times are clamped to zero, so sign loss is not possible.
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let frames = |milliseconds: f32| {
+            (f64::from(milliseconds.max(0.0)) / 1000.0 * f64::from(self.samples.sample_rate))
+                .round() as usize
+        };
+        dsp::sound_regions(
+            &self.samples,
+            detection.threshold_db,
+            frames(detection.hold),
+            frames(detection.attack),
+            frames(detection.release),
+        )
+    }
+
+    /// Write the regions of sound between silences as numbered WAV files to a folder next to the
+    /// file.
+    ///
+    /// Regions of song.wav are written to song-regions/song-01.wav and onward, replacing files
+    /// from an earlier export. Returns the paths of the written files.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the tab has no file or no sound above the threshold, or the folder or
+    /// a region cannot be written.
+    pub fn export_regions(&self, detection: &RegionDetection) -> eyre::Result<Vec<PathBuf>> {
+        self.ensure_editable()?;
+        let path = self.path.as_ref().ok_or_else(|| {
+            eyre::eyre!(
+                "Write {} to a file before exporting its regions",
+                self.name()
+            )
+        })?;
+        let regions = self.sound_regions(detection);
+        if regions.is_empty() {
+            eyre::bail!(
+                "{} has no sound above {} dB to export",
+                self.name(),
+                detection.threshold_db
+            );
+        }
+
+        let parts: Vec<Samples> = regions
+            .into_iter()
+            .map(|region| self.samples.slice(region))
+            .collect();
+        write_parts(path, "regions", &parts)
+    }
+
+    /// Replace samples with their state before the most recent edit.
+    ///
+    /// Returns whether there was an edit to undo.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the undo history cannot be read or written.
+    pub fn undo(&mut self) -> eyre::Result<bool> {
+        self.ensure_idle()?;
+        let samples = self.history.undo(&self.samples)?;
+        Ok(self.restore(samples))
+    }
+
+    /// Replace samples with their state after the most recently undone edit.
+    ///
+    /// Returns whether there was an edit to redo.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the undo history cannot be read or written.
+    pub fn redo(&mut self) -> eyre::Result<bool> {
+        self.ensure_idle()?;
+        let samples = self.history.redo(&self.samples)?;
+        Ok(self.restore(samples))
+    }
+
+    /// Replace samples restored from the history and keep the cursor within them.
+    fn restore(&mut self, samples: Option<Samples>) -> bool {
+        match samples {
+            Some(samples) => {
+                self.modified = true;
+                self.preview = None;
+                self.peaks = None;
+                self.samples = samples;
+                self.stats = None;
+                self.tempo = None;
+                let frames = self.samples.frames();
+                self.playhead = self.playhead.min(frames);
+                self.selection = self
+                    .selection
+                    .map(|selection| {
+                        Selection::new(selection.start.min(frames), selection.end.min(frames))
+                    })
+                    .filter(|selection| !selection.is_empty());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Replace samples with the result of a finished background read or edit.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the background read or edit failed.
+    pub fn poll(&mut self) -> eyre::Result<()> {
+        let result = match &mut self.loading {
+            Some(loading) => match loading.receiver.try_recv() {
+                Ok(result) => result,
+                Err(TryRecvError::Empty) => {
+                    if let Some(fraction) = loading.progress.try_iter().last() {
+                        loading.fraction = fraction;
+                    }
+                    return Ok(());
+                }
+                Err(TryRecvError::Disconnected) => Err(eyre::eyre!(
+                    "Reading {:?} stopped unexpectedly",
+                    loading.path
+                )),
+            },
+            None => return self.poll_editing(),
+        };
+
+        if let Some(loading) = self.loading.take() {
+            let (samples, metadata, windowed) = result?;
+            *self = Self::new(samples, Some(loading.path.clone()));
+            self.metadata = metadata;
+            self.windowed = windowed;
+            if let Some(session) = &loading.session {
+                self.restore_session(session);
+            }
+        }
+        Ok(())
+    }
+
+    /// Keep the result of a finished background edit as an undoable edit.
+    fn poll_editing(&mut self) -> eyre::Result<()> {
+        let part = match &mut self.editing {
+            Some(editing) => match editing.receiver.try_recv() {
+                Ok(part) => part,
+                Err(TryRecvError::Empty) => {
+                    if let Some(fraction) = editing.progress.try_iter().last() {
+                        editing.fraction = fraction;
+                    }
+                    return Ok(());
+                }
+                Err(TryRecvError::Disconnected) => {
+                    let label = editing.label.clone();
+                    self.editing = None;
+                    eyre::bail!("{} {} stopped unexpectedly", label, self.name());
+                }
+            },
+            None => return Ok(()),
+        };
+
+        let selection = self.editing.take().and_then(|editing| editing.selection);
+        let mut edited = self.samples.clone();
+        match selection {
+            Some(selection) => {
+                let start = selection.start.min(edited.frames());
+                let frames = part.frames();
+                edited.splice(selection, part)?;
+                self.selection = Some(Selection::new(start, start + frames))
+                    .filter(|selection| !selection.is_empty());
+            }
+            None => edited = part,
+        }
+        self.playhead = self.playhead.min(edited.frames());
+        self.edit(edited)
+    }
+}
+
+/// Write parts of a file as numbered WAV files to a folder next to it, named after the file and a
+/// suffix, and return their paths.
+fn write_parts(path: &Path, suffix: &str, parts: &[Samples]) -> eyre::Result<Vec<PathBuf>> {
+    let stem = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .ok_or_else(|| eyre::eyre!("File name {:?} is not valid Unicode", path))?;
+    let folder = path.with_file_name(format!("{}-{}", stem, suffix));
+    fs::create_dir_all(&folder)?;
+
+    let width = parts.len().to_string().len().max(2);
+    parts
+        .iter()
+        .enumerate()
+        .map(|(index, part)| {
+            let file = folder.join(format!("{}-{:0width$}.wav", stem, index + 1));
+            audio::write_samples(&file, part)?;
+            Ok(file)
+        })
+        .collect()
+}
+
+impl Default for Tab {
+    fn default() -> Self {
+        Self::new(Samples::default(), None)
+    }
+}
+
+/// A file being read on a background thread.
+#[derive(Debug)]
+struct Loading {
+    /// Flag that tells the reading thread to stop.
+    cancel: Arc<AtomicBool>,
+    /// Completed fraction of the read as of the last poll.
+    fraction: f32,
+    path: PathBuf,
+    /// Completed fractions reported by the reading thread.
+    progress: Receiver<f32>,
+    receiver: Receiver<eyre::Result<(Samples, Metadata, Option<Windowed>)>>,
+    /// Session to restore once the read finishes.
+    session: Option<Session>,
+}
+
+/// An edit running on a background thread.
+#[derive(Debug)]
+struct Editing {
+    /// Completed fraction of the edit as of the last poll.
+    fraction: f32,
+    /// Description of the edit shown while it runs.
+    label: String,
+    /// Completed fractions reported by the editing thread.
+    progress: Receiver<f32>,
+    /// Edited frames sent once the edit finishes.
+    receiver: Receiver<Samples>,
+    /// Frames the edit started from, or `None` for the whole tab.
+    selection: Option<Selection>,
+}
+
+impl Drop for Loading {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::aiff;
+    use crate::util;
+    use approx::assert_abs_diff_eq;
+    use std::time::{Duration, Instant};
+
+    fn wait(tab: &mut Tab) -> eyre::Result<()> {
+        let start = Instant::now();
+        while tab.is_loading() && start.elapsed() < Duration::from_secs(10) {
+            tab.poll()?;
+            thread::sleep(Duration::from_millis(1));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn load_in_background() {
+        let samples = Samples::new(2, 100, vec![0.25, -0.5, 0.75, 1.0]);
+        let path = util::test::temp_wave_file(&samples).unwrap();
+
+        let mut tab = Tab::load(path.clone());
+        assert!(tab.is_loading());
+        wait(&mut tab).unwrap();
+
+        assert!(!tab.is_loading());
+        assert_eq!(tab.load_progress(), None);
+        assert_eq!(tab.path, Some(path));
+        assert_abs_diff_eq!(tab.samples, samples, epsilon = 0.0001);
+    }
+
+    #[test]
+    fn poll_moves_from_loading_to_loaded() {
+        let (sender, receiver) = mpsc::channel();
+        let (progress_sender, progress) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let mut tab = Tab::loading(Loading {
+            cancel: Arc::clone(&cancel),
+            fraction: 0.0,
+            path: PathBuf::from("song.wav"),
+            progress,
+            receiver,
+            session: None,
+        });
+
+        tab.poll().unwrap();
+        assert_eq!(tab.load_progress(), Some(0.0));
+        progress_sender.send(0.25).unwrap();
+        progress_sender.send(0.5).unwrap();
+        tab.poll().unwrap();
+        assert!(tab.is_loading());
+        assert_eq!(tab.load_progress(), Some(0.5));
+
+        let samples = Samples::new(1, 10, vec![0.5; 4]);
+        sender
+            .send(Ok((samples.clone(), Metadata::default(), None)))
+            .unwrap();
+        tab.poll().unwrap();
+        assert!(!tab.is_loading());
+        assert_eq!(tab.samples, samples);
+        assert_eq!(tab.path, Some(PathBuf::from("song.wav")));
+        // Finished reads are not cancelled, but their flag is set once the state is dropped.
+        assert!(cancel.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn restore_session_waits_for_loading_and_clamps() {
+        let (sender, receiver) = mpsc::channel();
+        let (_progress_sender, progress) = mpsc::channel();
+        let mut tab = Tab::loading(Loading {
+            cancel: Arc::default(),
+            fraction: 0.0,
+            path: PathBuf::from("song.wav"),
+            progress,
+            receiver,
+            session: None,
+        });
+        let session = Session {
+            bounds: Some((2.0, 6.0)),
+            playhead: 5,
+            selection: Some((3, 20)),
+            ..Session::default()
+        };
+
+        tab.restore_session(&session);
+        assert_eq!(tab.playhead, 0);
+        sender
+            .send(Ok((
+                Samples::new(1, 10, vec![0.5; 8]),
+                Metadata::default(),
+                None,
+            )))
+            .unwrap();
+        tab.poll().unwrap();
+        assert_eq!(tab.playhead, 5);
+        assert_eq!(tab.selection, Some(Selection::new(3, 8)));
+        assert_eq!(tab.zoom.as_ref().map(Axes::bounds), Some((2.0, 6.0)));
+
+        tab.restore_session(&Session {
+            bounds: Some((4.0, 4.0)),
+            playhead: 30,
+            ..Session::default()
+        });
+        assert_eq!((tab.playhead, tab.selection, tab.zoom), (8, None, None));
+    }
+
+    #[test]
+    fn poll_reports_failed_read() {
+        let (sender, receiver) = mpsc::channel();
+        let (_progress_sender, progress) = mpsc::channel();
+        let mut tab = Tab::loading(Loading {
+            cancel: Arc::default(),
+            fraction: 0.0,
+            path: PathBuf::from("song.wav"),
+            progress,
+            receiver,
+            session: None,
+        });
+
+        sender.send(Err(eyre::eyre!("Broken header"))).unwrap();
+        assert!(tab.poll().is_err());
+        assert!(!tab.is_loading());
+
+        let (sender, receiver) = mpsc::channel();
+        let (_progress_sender, progress) = mpsc::channel();
+        let mut tab = Tab::loading(Loading {
+            cancel: Arc::default(),
+            fraction: 0.0,
+            path: PathBuf::from("song.wav"),
+            progress,
+            receiver,
+            session: None,
+        });
+        drop(sender);
+        assert!(tab.poll().is_err());
+    }
+
+    #[test]
+    fn replacing_loading_tab_cancels_read() {
+        let (_sender, receiver) = mpsc::channel();
+        let (_progress_sender, progress) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let mut tab = Tab::loading(Loading {
+            cancel: Arc::clone(&cancel),
+            fraction: 0.0,
+            path: PathBuf::from("song.wav"),
+            progress,
+            receiver,
+            session: None,
+        });
+
+        assert!(tab.is_loading());
+        assert!(!cancel.load(Ordering::Relaxed));
+        tab = Tab::default();
+        assert!(cancel.load(Ordering::Relaxed));
+        assert!(!tab.is_loading());
+    }
+
+    #[test]
+    fn load_missing_file_error() {
+        let folder = tempfile::tempdir().unwrap();
+        let mut tab = Tab::load(folder.path().join("missing.wav"));
+
+        assert_eq!(tab.name(), "missing.wav");
+        assert!(wait(&mut tab).is_err());
+        assert!(!tab.is_loading());
+    }
+
+    #[test]
+    fn insert_silence_at_playhead() {
+        let mut tab = Tab::new(Samples::new(1, 1000, vec![0.5, -0.5, 0.25, -0.25]), None);
+        tab.playhead = 1;
+        tab.selection = Some(Selection::new(0, 3));
+
+        tab.insert_silence(2.0).unwrap();
+        assert_eq!(tab.samples.data, vec![0.5, 0.0, 0.0, -0.5, 0.25, -0.25]);
+        assert_eq!(tab.selection, Some(Selection::new(0, 5)));
+        assert_eq!(tab.playhead, 1);
+        assert!(tab.modified);
+
+        assert!(tab.insert_silence(0.0).is_err());
+        assert!(tab.insert_silence(f32::NAN).is_err());
+        assert!(Tab::default().insert_silence(10.0).is_err());
+        assert!(tab.undo().unwrap());
+        assert_eq!(tab.samples.data, vec![0.5, -0.5, 0.25, -0.25]);
+    }
+
+    #[test]
+    fn edit_in_background_resizes_selection() {
+        let mut tab = Tab::new(Samples::new(2, 8, vec![0.5; 20]), None);
+        tab.selection = Some(Selection::new(2, 6));
+        tab.playhead = 9;
+        let (sender, receiver) = mpsc::channel::<()>();
+        tab.edit_in_background("Doubling", move |samples, progress| {
+            let _result = receiver.recv();
+            progress(0.5);
+            samples.data = samples
+                .data
+                .iter()
+                .flat_map(|sample| [*sample; 2])
+                .collect();
+        })
+        .unwrap();
+
+        tab.poll().unwrap();
+        assert_eq!(tab.edit_progress(), Some(("Doubling", 0.0)));
+        let error = tab.undo().unwrap_err();
+        assert!(format!("{}", error).contains("Wait for doubling untitled"));
+        assert!(tab.invert_polarity(None).is_err());
+        assert!(tab
+            .edit_in_background("Again", |_samples, _progress| ())
+            .is_err());
+
+        sender.send(()).unwrap();
+        let start = Instant::now();
+        while tab.edit_progress().is_some() && start.elapsed() < Duration::from_secs(10) {
+            tab.poll().unwrap();
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(tab.samples.frames(), 14);
+        assert_eq!(tab.selection, Some(Selection::new(2, 10)));
+        assert_eq!(tab.playhead, 9);
+        assert!(tab.modified);
+        assert!(tab.undo().unwrap());
+        assert_eq!(tab.samples.frames(), 10);
+    }
+
+    #[test]
+    fn crossfade_loop_previews_edit() {
+        let data = (0..8000)
+            .map(|frame| (frame % 100) as f32 / 100.0)
+            .collect();
+        let mut tab = Tab::new(Samples::new(1, 1000, data), None);
+        assert!(tab.crossfade_loop(10.0).is_err());
+
+        tab.selection = Some(Selection::new(0, 1050));
+        tab.playhead = 7990;
+        assert!(tab.crossfade_loop(f32::NAN).is_err());
+        tab.crossfade_loop(10.0).unwrap();
+        assert_eq!(tab.selection, Some(Selection::new(0, 1040)));
+        assert_eq!(tab.samples.frames(), 7990);
+        assert_eq!(tab.playhead, 7990);
+        assert!(tab.preview.is_some());
+
+        assert!(tab.revert_preview().unwrap());
+        assert_eq!(tab.samples.frames(), 8000);
+    }
+
+    #[test]
+    fn invert_polarity_of_selected_channel() {
+        let data = vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6];
+        let mut tab = Tab::new(Samples::new(2, 10, data.clone()), None);
+
+        tab.selection = Some(Selection::new(1, 3));
+        tab.invert_polarity(Some(0)).unwrap();
+        assert_eq!(tab.samples.data, vec![0.1, 0.2, -0.3, 0.4, -0.5, 0.6]);
+        assert!(tab.modified);
+        assert!(tab.invert_polarity(Some(2)).is_err());
+
+        tab.selection = None;
+        tab.invert_polarity(None).unwrap();
+        assert_eq!(tab.samples.data, vec![-0.1, -0.2, 0.3, -0.4, 0.5, -0.6]);
+        assert!(tab.undo().unwrap());
+        assert!(tab.undo().unwrap());
+        assert_eq!(tab.samples.data, data);
+    }
+
+    #[test]
+    fn delete_selection_cuts_markers() {
+        let mut tab = Tab::new(
+            Samples::new(1, 10, vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6]),
+            None,
+        );
+        tab.samples.markers = vec![
+            Marker::new(1, String::from("kept")),
+            Marker::new(3, String::from("cut")),
+            Marker::new(5, String::from("moved")),
+        ];
+        assert!(tab.delete_selection().is_err());
+
+        tab.selection = Some(Selection::new(2, 4));
+        tab.delete_selection().unwrap();
+        assert_eq!(tab.samples.data, vec![0.1, 0.2, 0.5, 0.6]);
+        assert_eq!(
+            tab.samples.markers,
+            vec![
+                Marker::new(1, String::from("kept")),
+                Marker::new(3, String::from("moved")),
+            ]
+        );
+        assert_eq!(tab.playhead, 2);
+        assert_eq!(tab.selection, None);
+
+        assert!(tab.undo().unwrap());
+        assert_eq!(tab.samples.markers.len(), 3);
+    }
+
+    #[test]
+    fn add_rename_and_delete_markers() {
+        let mut tab = Tab::new(Samples::new(1, 10, vec![0.0; 8]), None);
+        tab.playhead = 5;
+        tab.add_marker(String::from("late")).unwrap();
+        tab.playhead = 2;
+        assert!(tab.rename_marker(String::from("missing")).is_err());
+        tab.add_marker(String::from("early")).unwrap();
+        assert_eq!(
+            tab.samples.markers,
+            vec![
+                Marker::new(2, String::from("early")),
+                Marker::new(5, String::from("late")),
+            ]
+        );
+        assert!(tab.modified);
+
+        tab.rename_marker(String::from("verse")).unwrap();
+        assert_eq!(tab.samples.markers[0].label, "verse");
+        tab.rename_marker(String::new()).unwrap();
+        assert_eq!(
+            tab.samples.markers,
+            vec![Marker::new(5, String::from("late"))]
+        );
+
+        assert!(tab.undo().unwrap());
+        assert_eq!(tab.samples.markers[0].label, "verse");
+    }
+
+    #[test]
+    fn markers_wait_for_edits_and_windowed_files() {
+        let samples = Samples::new(1, 10, vec![0.0; 8]);
+        let mut tab = Tab::new(samples.clone(), None);
+        tab.add_marker(String::from("verse")).unwrap();
+        let (sender, receiver) = mpsc::channel::<()>();
+        tab.edit_in_background("Waiting", move |_samples, _progress| {
+            let _result = receiver.recv();
+        })
+        .unwrap();
+
+        assert!(tab.add_marker(String::from("chorus")).is_err());
+        assert!(tab.rename_marker(String::from("intro")).is_err());
+        sender.send(()).unwrap();
+        while tab.edit_progress().is_some() {
+            tab.poll().unwrap();
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(
+            tab.samples.markers,
+            vec![Marker::new(0, String::from("verse"))]
+        );
+
+        let path = util::test::temp_wave_file(&samples).unwrap();
+        let mut tab = Tab::new(Samples::new(1, 10, Vec::new()), Some(path.clone()));
+        tab.windowed =
+            Some(Windowed::open(&path, &mut |_fraction| (), &AtomicBool::new(false)).unwrap());
+        assert!(tab.add_marker(String::from("verse")).is_err());
+        assert!(tab.samples.markers.is_empty());
+        assert!(!tab.modified);
+    }
+
+    #[test]
+    fn save_and_load_keep_markers() {
+        let folder = tempfile::tempdir().unwrap();
+        let path = folder.path().join("marked.wav");
+        let mut samples = Samples::new(1, 8000, vec![0.25; 800]);
+        samples.markers = vec![Marker::new(400, String::from("bad click"))];
+
+        let mut tab = Tab::new(samples, Some(path.clone()));
+        tab.save().unwrap();
+        assert!(labels::sidecar(&path).is_file());
+        // Markers in the marker file replace the cue points of the WAV file.
+        labels::write_labels(
+            &labels::sidecar(&path),
+            &[Marker::new(200, String::from("verse"))],
+            8000,
+        )
+        .unwrap();
+
+        let mut tab = Tab::load(path);
+        wait(&mut tab).unwrap();
+        assert_eq!(
+            tab.samples.markers,
+            vec![Marker::new(200, String::from("verse"))]
+        );
+    }
+
+    #[test]
+    fn split_at_markers_writes_segments() {
+        let folder = tempfile::tempdir().unwrap();
+        let path = folder.path().join("take.wav");
+        let mut samples = Samples::new(
+            2,
+            8000,
+            (0..200).map(|index| index as f32 / 200.0).collect(),
+        );
+        samples.markers = vec![
+            Marker::new(30, String::from("verse")),
+            Marker::new(70, String::from("chorus")),
+        ];
+
+        let mut tab = Tab::new(samples, None);
+        assert!(tab.split_at_markers().is_err());
+        tab.path = Some(path);
+        let paths = tab.split_at_markers().unwrap();
+
+        let segments = folder.path().join("take-segments");
+        assert_eq!(
+            paths,
+            vec![
+                segments.join("take-01.wav"),
+                segments.join("take-02.wav"),
+                segments.join("take-03.wav"),
+            ]
+        );
+        let mut joined = Vec::new();
+        for (path, frames) in paths.iter().zip([30, 40, 30]) {
+            let segment = audio::read_samples(path).unwrap();
+            assert_eq!((segment.channels, segment.sample_rate), (2, 8000));
+            assert_eq!(segment.frames(), frames);
+            joined.extend(segment.data);
+        }
+        // Reading quantizes samples to 16 bits, so the joined segments match within a tolerance.
+        assert_eq!(joined.len(), tab.samples.data.len());
+        assert!(joined
+            .iter()
+            .zip(&tab.samples.data)
+            .all(|(actual, expected)| (actual - expected).abs() < 1e-4));
+
+        tab.samples.markers.clear();
+        assert!(tab.split_at_markers().is_err());
+    }
+
+    #[test]
+    fn export_regions_writes_sounds() {
+        let folder = tempfile::tempdir().unwrap();
+        let path = folder.path().join("kit.wav");
+        let mut data = vec![0.0_f32; 2000];
+        for (start, end) in [(100, 300), (1000, 1200)] {
+            for (frame, sample) in data.iter_mut().enumerate().take(end).skip(start) {
+                *sample = (frame % 50) as f32 / 100.0 + 0.25;
+            }
+        }
+        let detection = RegionDetection {
+            attack: 10.0,
+            hold: 100.0,
+            release: 50.0,
+            threshold_db: -20.0,
+        };
+
+        let mut tab = Tab::new(Samples::new(1, 1000, data), None);
+        assert!(tab.export_regions(&detection).is_err());
+        tab.path = Some(path);
+        assert_eq!(
+            tab.sound_regions(&detection),
+            vec![Selection::new(90, 350), Selection::new(990, 1250)]
+        );
+        let paths = tab.export_regions(&detection).unwrap();
+
+        let regions = folder.path().join("kit-regions");
+        assert_eq!(
+            paths,
+            vec![regions.join("kit-01.wav"), regions.join("kit-02.wav")]
+        );
+        for (path, start) in paths.iter().zip([90, 990]) {
+            let region = audio::read_samples(path).unwrap();
+            assert_eq!(region.frames(), 260);
+            let expected = &tab.samples.data[start..start + 260];
+            assert!(region
+                .data
+                .iter()
+                .zip(expected)
+                .all(|(actual, expected)| (actual - expected).abs() < 1e-4));
+        }
+
+        tab.samples
+            .data
+            .iter_mut()
+            .for_each(|sample| *sample *= 0.01);
+        let error = tab.export_regions(&detection).unwrap_err();
+        assert!(format!("{}", error).contains("no sound above -20 dB"));
+    }
+
+    #[test]
+    fn undo_and_redo_edit() {
+        let mut tab = Tab::new(Samples::new(1, 10, vec![0.5; 8]), None);
+        tab.selection = Some(Selection::new(2, 8));
+
+        tab.history.push(&tab.samples).unwrap();
+        tab.samples = Samples::new(1, 10, vec![0.25; 4]);
+        assert!(tab.undo().unwrap());
+        assert_eq!(tab.samples.data, vec![0.5; 8]);
+
+        tab.playhead = 6;
+        assert!(tab.redo().unwrap());
+        assert_eq!(tab.samples.data, vec![0.25; 4]);
+        assert_eq!(tab.playhead, 4);
+        assert_eq!(tab.selection, Some(Selection::new(2, 4)));
+        assert!(!tab.redo().unwrap());
+    }
+
+    #[test]
+    fn revert_preview_undoes_edit() {
+        let mut tab = Tab::new(Samples::new(1, 10, vec![0.5; 4]), None);
+        assert!(!tab.revert_preview().unwrap());
+
+        tab.history.push(&tab.samples).unwrap();
+        tab.preview = Some(tab.samples.clone());
+        tab.samples.data = vec![1.0; 4];
+
+        assert!(tab.revert_preview().unwrap());
+        assert_eq!(tab.samples.data, vec![0.5; 4]);
+        assert!(tab.preview.is_none());
+        assert!(tab.history.can_redo());
+    }
+
+    #[test]
+    fn mix_at_playhead_is_undoable() {
+        let mut tab = Tab::new(Samples::new(1, 10, vec![0.5; 4]), None);
+        tab.playhead = 2;
+
+        let peak = tab.mix(&Samples::new(1, 10, vec![0.75; 4]), 1.0).unwrap();
+        assert_eq!(tab.samples.data, vec![0.5, 0.5, 1.25, 1.25, 0.75, 0.75]);
+        assert!((peak - 1.25).abs() < f32::EPSILON);
+        assert!(tab.modified);
+
+        assert!(tab.undo().unwrap());
+        assert_eq!(tab.samples.data, vec![0.5; 4]);
+    }
+
+    #[test]
+    fn save_clears_modified() {
+        let folder = tempfile::tempdir().unwrap();
+        let mut tab = Tab::new(Samples::new(1, 10, vec![0.5; 4]), None);
+        tab.modified = true;
+
+        assert!(tab.save().is_err());
+        assert!(tab.modified);
+
+        tab.path = Some(folder.path().join("saved.wav"));
+        tab.save().unwrap();
+        assert!(!tab.modified);
+        assert_eq!(
+            audio::read_samples(&folder.path().join("saved.wav"))
+                .unwrap()
+                .data
+                .len(),
+            4
+        );
+    }
+
+    #[test]
+    fn save_keeps_format_of_file() {
+        let folder = tempfile::tempdir().unwrap();
+        let path = folder.path().join("loop.aif");
+        let mut tab = Tab::new(Samples::new(2, 8000, vec![0.5, -0.5, 0.25, 0.0]), None);
+        tab.path = Some(path.clone());
+        tab.save().unwrap();
+
+        let saved = aiff::read_aiff(&path).unwrap();
+        assert_eq!((saved.channels, saved.sample_rate), (2, 8000));
+        assert!(saved
+            .data
+            .iter()
+            .zip(&tab.samples.data)
+            .all(|(actual, expected)| (actual - expected).abs() < 1e-4));
+
+        // Formats that can only be read are left untouched.
+        let path = folder.path().join("loop.flac");
+        fs::write(&path, b"fLaC").unwrap();
+        tab.path = Some(path.clone());
+        tab.modified = true;
+        assert!(tab.save().is_err());
+        assert!(tab.modified);
+        assert_eq!(fs::read(&path).unwrap(), b"fLaC");
+    }
+
+    #[test]
+    fn name_from_path() {
+        let tab = Tab::new(Samples::default(), Some(PathBuf::from("/samples/kick.wav")));
+
+        assert_eq!(tab.name(), "kick.wav");
+        assert_eq!(Tab::default().name(), "untitled");
+    }
+}
@@ -1,12 +1,16 @@
 //! Structs for drawing plot axes.
 
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::convert::TryFrom;
+use tui::layout::Rect;
 use tui::text::Span;
 use tui::widgets::Axis;
 
 /// Chart axes with shift and zoom features.
 #[derive(Clone, Debug, PartialEq)]
 pub struct Axes {
+    /// Level in decibels at the bottom of the unzoomed vertical axis, if it shows decibels.
+    decibel_floor: Option<f64>,
     speed: f64,
     x: [f64; 2],
     y: [f64; 2],
@@ -16,21 +20,21 @@ impl Axes {
     /// Create an Axes from viewport dimensions.
     #[must_use]
     pub const fn new(x: [f64; 2], y: [f64; 2], speed: f64) -> Self {
-        Self { speed, x, y }
+        Self {
+            decibel_floor: None,
+            speed,
+            x,
+            y,
+        }
     }
 
     /// Generate a TUI Axis pair.
     #[must_use]
-    pub fn axes(&self) -> (Axis, Axis) {
+    pub fn axes(&self) -> (Axis<'_>, Axis<'_>) {
+        let (x_labels, y_labels) = self.labels();
         let labels: (Vec<Span>, Vec<Span>) = (
-            self.x
-                .iter()
-                .map(|num| Span::from(format!("{:.2}", num)))
-                .collect(),
-            self.y
-                .iter()
-                .map(|num| Span::from(format!("{:.2}", num)))
-                .collect(),
+            x_labels.into_iter().map(Span::from).collect(),
+            y_labels.into_iter().map(Span::from).collect(),
         );
 
         (
@@ -39,6 +43,115 @@ impl Axes {
         )
     }
 
+    /// Get the visible horizontal bounds.
+    #[must_use]
+    pub const fn bounds(&self) -> (f64, f64) {
+        (self.x[0], self.x[1])
+    }
+
+    /// Label vertical positions from -1 to 1 as decibels from a floor to full scale, or as plain
+    /// numbers if there is no floor.
+    pub fn set_decibel_floor(&mut self, floor: Option<f64>) {
+        self.decibel_floor = floor;
+    }
+
+    /// Pan horizontally to center on a position.
+    pub fn center(&mut self, position: f64) {
+        let radius = (self.x[1] - self.x[0]) / 2.0;
+        self.x = [position - radius, position + radius];
+    }
+
+    /// Pan horizontally to center on a position if it is outside of the visible bounds.
+    pub fn reveal(&mut self, position: f64) {
+        if position < self.x[0] || position > self.x[1] {
+            self.center(position);
+        }
+    }
+
+    /// Show horizontal positions from zero to an end.
+    pub fn fit(&mut self, end: f64) {
+        self.x = [0.0, end];
+    }
+
+    /// Map a horizontal position to the column that draws it in a plot of the given width.
+    ///
+    /// Returns `None` if the position is outside of the visible bounds.
+    #[must_use]
+    pub fn column(&self, position: f64, width: u16) -> Option<u16> {
+        let span = self.x[1] - self.x[0];
+        let offset = (position - self.x[0]) / span * f64::from(width);
+
+        // Offset is checked to be within the plot width, so truncation is not possible.
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        (span > 0.0 && offset >= 0.0 && offset < f64::from(width)).then_some(offset as u16)
+    }
+
+    /// Compute the area where a chart with these axes draws its data inside an unbordered area.
+    ///
+    /// Mirrors the layout of TUI charts, which reserve rows and columns for labels and axis lines.
+    #[must_use]
+    pub fn graph_area(&self, area: Rect) -> Rect {
+        let (x_labels, y_labels) = self.labels();
+        let y_width = y_labels.iter().map(String::len).max().unwrap_or_default();
+        // The first horizontal label ends below the vertical axis line.
+        let x_width = x_labels
+            .first()
+            .map_or(0, |label| label.len().saturating_sub(1));
+        let label_width = u16::try_from(y_width.max(x_width))
+            .unwrap_or(u16::MAX)
+            .min(area.width / 3);
+
+        let left = label_width.saturating_add(1).min(area.width);
+        let bottom = 2.min(area.height);
+        Rect::new(
+            area.x + left,
+            area.y,
+            area.width - left,
+            area.height - bottom,
+        )
+    }
+
+    /// Map a column of a plot with the given width to the horizontal position at its center.
+    #[must_use]
+    pub fn position(&self, column: u16, width: u16) -> f64 {
+        let span = self.x[1] - self.x[0];
+        (f64::from(column) + 0.5).mul_add(span / f64::from(width.max(1)), self.x[0])
+    }
+
+    /// Convert a terminal column to the nearest frame boundary of a plot drawn in a graph area.
+    ///
+    /// Columns beyond the graph area map to the edges of the visible bounds. The result is clamped
+    /// to the range 0 to `frame_count`.
+    #[must_use]
+    pub fn frame_at(&self, graph: Rect, column: u16, frame_count: usize) -> usize {
+        let position = if column < graph.left() {
+            self.x[0]
+        } else if column >= graph.right() {
+            self.x[1]
+        } else {
+            self.position(column - graph.left(), graph.width).round()
+        };
+
+        // Position is clamped to be non negative, so sign loss is not possible.
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let frame = position.max(0.0) as usize;
+        frame.min(frame_count)
+    }
+
+    /// Squeeze or expand axes bounds by one step, as if zooming with the arrow keys.
+    pub fn magnify(&mut self, inward: bool) {
+        self.zoom(if inward { KeyCode::Up } else { KeyCode::Down });
+    }
+
+    /// Move axes bounds horizontally by one step, as if panning with the arrow keys.
+    pub fn scroll(&mut self, forward: bool) {
+        self.shift(if forward {
+            KeyCode::Right
+        } else {
+            KeyCode::Left
+        });
+    }
+
     /// Update axes state based on keyboard input.
     pub fn key_event(&mut self, event: KeyEvent) {
         match event.modifiers {
@@ -53,6 +166,21 @@ impl Axes {
         }
     }
 
+    /// Format bounds of both axes as labels.
+    fn labels(&self) -> (Vec<String>, Vec<String>) {
+        (
+            self.x.iter().map(|num| format!("{:.2}", num)).collect(),
+            self.y
+                .iter()
+                .map(|num| match self.decibel_floor {
+                    // Adding zero turns the negative zero at full scale into a positive zero.
+                    Some(floor) => format!("{:.1} dB", floor * (1.0 - num) / 2.0 + 0.0),
+                    None => format!("{:.2}", num),
+                })
+                .collect(),
+        )
+    }
+
     /// Move axes bounds horizontally or vertically.
     fn shift(&mut self, code: KeyCode) {
         let direction = match code {
@@ -97,6 +225,69 @@ impl Axes {
 mod tests {
     use super::*;
 
+    #[test]
+    fn frame_at_columns() {
+        let axes = Axes::new([0.0, 100.0], [-1.0, 1.0], 1.0);
+        let graph = Rect::new(10, 0, 50, 10);
+
+        assert_eq!(axes.frame_at(graph, 10, 100), 1);
+        assert_eq!(axes.frame_at(graph, 34, 100), 49);
+        assert_eq!(axes.frame_at(graph, 59, 100), 99);
+        assert_eq!(axes.frame_at(graph, 2, 100), 0);
+        assert_eq!(axes.frame_at(graph, 70, 100), 100);
+        assert_eq!(axes.frame_at(graph, 70, 40), 40);
+    }
+
+    #[test]
+    fn column_position_inverse() {
+        let cases = [
+            Axes::new([0.0, 100.0], [-1.0, 1.0], 1.0),
+            Axes::new([25.0, 50.0], [-1.0, 1.0], 1.0),
+            Axes::new([-40.0, 360.0], [-1.0, 1.0], 1.0),
+        ];
+
+        for axes in cases {
+            for column in 0..40 {
+                let position = axes.position(column, 40);
+                assert_eq!(axes.column(position, 40), Some(column));
+            }
+        }
+
+        let axes = Axes::new([25.0, 50.0], [-1.0, 1.0], 1.0);
+        assert_eq!(axes.position(0, 25), 25.5);
+        assert_eq!(axes.column(10.0, 25), None);
+        assert_eq!(axes.column(50.0, 25), None);
+    }
+
+    #[test]
+    fn graph_area_skips_labels() {
+        let axes = Axes::new([0.0, 1000.0], [-1.0, 1.0], 1.0);
+        let actual = axes.graph_area(Rect::new(1, 1, 60, 20));
+
+        // Label "-1.00" is five columns wide and the axis line takes one more.
+        assert_eq!(actual, Rect::new(7, 1, 54, 18));
+    }
+
+    #[test]
+    fn graph_area_skips_decibel_labels() {
+        let mut axes = Axes::new([0.0, 1000.0], [-1.0, 1.0], 1.0);
+        axes.set_decibel_floor(Some(-60.0));
+        assert_eq!(axes.labels().1, vec!["-60.0 dB", "0.0 dB"]);
+
+        let actual = axes.graph_area(Rect::new(1, 1, 60, 20));
+        assert_eq!(actual, Rect::new(10, 1, 51, 18));
+    }
+
+    #[test]
+    fn reveal_centers_hidden_positions() {
+        let mut axes = Axes::new([0.0, 100.0], [-1.0, 1.0], 1.0);
+        axes.reveal(40.0);
+        assert_eq!(axes.bounds(), (0.0, 100.0));
+
+        axes.reveal(500.0);
+        assert_eq!(axes.bounds(), (450.0, 550.0));
+    }
+
     #[test]
     fn shift_left() {
         let mut axes = Axes::new([5.0, 10.0], [-1.0, 1.0], 1.0);
@@ -0,0 +1,455 @@
+//! Peak and RMS level meters of playback and recording with peak ballistics.
+
+use rodio::Source;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Running measurements of one channel, stored as bits of floats so they need no lock.
+#[derive(Debug, Default)]
+struct Accumulator {
+    count: AtomicU32,
+    peak: AtomicU32,
+    squares: AtomicU32,
+}
+
+/// Peaks and mean squares of each channel written by the audio thread and read by the draw loop.
+#[derive(Clone, Debug, Default)]
+pub struct Levels(Arc<Vec<Accumulator>>);
+
+impl Levels {
+    /// Create silent Levels for a number of channels.
+    #[must_use]
+    pub fn new(channels: u16) -> Self {
+        Self(Arc::new(
+            (0..channels).map(|_| Accumulator::default()).collect(),
+        ))
+    }
+
+    /// Raise the peak of a channel to the magnitude of a sample and add it to the mean square.
+    pub fn raise(&self, channel: usize, sample: f32) {
+        // Bits of non negative floats order like their values, so the maximum needs no lock.
+        if let Some(accumulator) = self.0.get(channel) {
+            accumulator
+                .peak
+                .fetch_max(sample.abs().to_bits(), Ordering::Relaxed);
+            // Only the audio thread adds to the sum, so the update never has to retry.
+            let _result =
+                accumulator
+                    .squares
+                    .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| {
+                        Some((f32::from_bits(bits) + sample * sample).to_bits())
+                    });
+            accumulator.count.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Get the peak of each channel since the last call and start new ones.
+    #[must_use]
+    pub fn take(&self) -> Vec<f32> {
+        self.0
+            .iter()
+            .map(|accumulator| f32::from_bits(accumulator.peak.swap(0, Ordering::Relaxed)))
+            .collect()
+    }
+
+    /// Get the RMS level of each channel since the last call and start new ones.
+    #[must_use]
+    pub fn take_rms(&self) -> Vec<f32> {
+        self.0
+            .iter()
+            .map(|accumulator| {
+                let squares = f32::from_bits(accumulator.squares.swap(0, Ordering::Relaxed));
+                let count = accumulator.count.swap(0, Ordering::Relaxed);
+                // Counts of samples between redraws are small, so precision loss is not possible.
+                #[allow(clippy::cast_precision_loss)]
+                let mean = squares / count.max(1) as f32;
+                mean.sqrt()
+            })
+            .collect()
+    }
+}
+
+/// Source adapter that passes samples through unchanged while recording their peaks.
+pub struct Tap<S> {
+    channel: usize,
+    inner: S,
+    levels: Levels,
+}
+
+impl<S: Source<Item = f32>> Tap<S> {
+    /// Create a Tap that records peaks of a source into levels.
+    pub fn new(inner: S, levels: Levels) -> Self {
+        Self {
+            channel: 0,
+            inner,
+            levels,
+        }
+    }
+}
+
+impl<S: Source<Item = f32>> Iterator for Tap<S> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let sample = self.inner.next()?;
+        self.levels.raise(self.channel, sample);
+        self.channel = (self.channel + 1) % usize::from(self.inner.channels().max(1));
+        Some(sample)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<S: Source<Item = f32>> Source for Tap<S> {
+    fn current_frame_len(&self) -> Option<usize> {
+        self.inner.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.inner.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+}
+
+/// How fast meters fall after peaks and how long peak holds stay.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ballistics {
+    /// Fall of levels and expired peak holds in decibels per second.
+    pub decay: f32,
+    /// Seconds that a peak hold stays before falling.
+    pub hold: f32,
+}
+
+impl Default for Ballistics {
+    fn default() -> Self {
+        Self {
+            decay: 20.0,
+            hold: 1.5,
+        }
+    }
+}
+
+/// Meter state of one channel.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ChannelMeter {
+    /// Seconds since the peak hold was last raised.
+    age: f32,
+    /// Whether a sample reached the clipping threshold since the meter was last reset.
+    pub clipped: bool,
+    /// Magnitude of the peak hold tick.
+    pub hold: f32,
+    /// Magnitude of the decaying peak level.
+    pub level: f32,
+    /// Magnitude of the decaying RMS level.
+    pub rms: f32,
+}
+
+/// Level meters of all channels with peak decay, peak holds, and latching clip indicators.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Meter {
+    ballistics: Ballistics,
+    channels: Vec<ChannelMeter>,
+}
+
+impl Meter {
+    /// Create a Meter without channels that moves with ballistics.
+    #[must_use]
+    pub const fn new(ballistics: Ballistics) -> Self {
+        Self {
+            ballistics,
+            channels: Vec::new(),
+        }
+    }
+
+    /// Get the state of each channel.
+    #[must_use]
+    pub fn channels(&self) -> &[ChannelMeter] {
+        &self.channels
+    }
+
+    /// Advance the meters by a number of seconds in which the channels reached peaks and RMS
+    /// levels.
+    ///
+    /// Levels fall at the decay rate unless new ones are higher, and peak holds move as in
+    /// [`update_hold`]. Peaks at or above the clip threshold latch the clip indicator of their
+    /// channel.
+    pub fn update(&mut self, peaks: &[f32], rms: &[f32], seconds: f32, clip_threshold: f32) {
+        self.channels.resize(peaks.len(), ChannelMeter::default());
+        let fall = fall(self.ballistics.decay, seconds);
+
+        for (channel, (meter, peak)) in self.channels.iter_mut().zip(peaks).enumerate() {
+            let peak = peak.abs();
+            meter.level = (meter.level * fall).max(peak);
+            meter.rms = (meter.rms * fall).max(rms.get(channel).copied().unwrap_or_default());
+            let (hold, age) = update_hold(
+                (meter.hold, meter.age),
+                peak,
+                meter.level,
+                seconds,
+                self.ballistics,
+            );
+            meter.hold = hold;
+            meter.age = age;
+            meter.clipped |= peak >= clip_threshold;
+        }
+    }
+
+    /// Show fixed peaks without ballistics or RMS levels, such as those of samples that are not
+    /// playing.
+    ///
+    /// Clip indicators stay latched.
+    pub fn show(&mut self, peaks: &[f32]) {
+        self.channels.resize(peaks.len(), ChannelMeter::default());
+        for (meter, peak) in self.channels.iter_mut().zip(peaks) {
+            meter.age = 0.0;
+            meter.hold = peak.abs();
+            meter.level = peak.abs();
+            meter.rms = 0.0;
+        }
+    }
+
+    /// Clear peak holds and clip indicators.
+    pub fn reset(&mut self) {
+        for meter in &mut self.channels {
+            meter.age = 0.0;
+            meter.clipped = false;
+            meter.hold = meter.level;
+        }
+    }
+}
+
+/// Get the largest magnitude of each channel in a block of interleaved samples.
+#[must_use]
+pub fn block_peaks(block: &[f32], channels: u16) -> Vec<f32> {
+    let width = usize::from(channels.max(1));
+    let mut peaks = vec![0.0_f32; width];
+    for (index, sample) in block.iter().enumerate() {
+        peaks[index % width] = peaks[index % width].max(sample.abs());
+    }
+    peaks
+}
+
+/// Get the root mean square of each channel in a block of interleaved samples.
+///
+/// Channels without samples in the block have an RMS level of zero.
+#[must_use]
+pub fn block_rms(block: &[f32], channels: u16) -> Vec<f32> {
+    let width = usize::from(channels.max(1));
+    let mut squares = vec![0.0_f64; width];
+    let mut counts = vec![0_usize; width];
+    for (index, sample) in block.iter().enumerate() {
+        squares[index % width] += f64::from(*sample) * f64::from(*sample);
+        counts[index % width] += 1;
+    }
+
+    squares
+        .iter()
+        .zip(counts)
+        // Means of squares of samples are at most as large as samples, so truncation is not
+        // possible.
+        .map(|(squares, count)| (squares / count.max(1) as f64).sqrt() as f32)
+        .collect()
+}
+
+/// Convert a magnitude to decibels relative to full scale.
+#[must_use]
+pub fn decibels(magnitude: f32) -> f32 {
+    20.0 * magnitude.abs().log10()
+}
+
+/// Advance a peak hold and its age in seconds by a number of seconds in which its channel reached
+/// a peak and had a decaying level.
+///
+/// Holds jump to higher peaks and stay for the hold time, after which they fall at the decay rate
+/// but never below the level.
+#[must_use]
+pub fn update_hold(
+    (hold, age): (f32, f32),
+    peak: f32,
+    level: f32,
+    seconds: f32,
+    ballistics: Ballistics,
+) -> (f32, f32) {
+    let peak = peak.abs();
+    if peak >= hold {
+        return (peak, 0.0);
+    }
+
+    let age = age + seconds.max(0.0);
+    if age > ballistics.hold {
+        ((hold * fall(ballistics.decay, seconds)).max(level), age)
+    } else {
+        (hold, age)
+    }
+}
+
+/// Get the factor by which levels fall in a number of seconds at a decay rate in decibels per
+/// second.
+fn fall(decay: f32, seconds: f32) -> f32 {
+    10_f32.powf(-decay * seconds.max(0.0) / 20.0)
+}
+
+/// Draw a channel meter as a text gauge of cells filled linearly in decibels above a floor, with
+/// solid cells up to the RMS level, hashes up to the peak level, and the peak hold as a tick.
+#[must_use]
+pub fn gauge(meter: &ChannelMeter, floor: f32, width: usize) -> String {
+    let cells = |magnitude: f32| {
+        let decibels = decibels(magnitude);
+        let fraction = if decibels > floor {
+            (1.0 - decibels / floor).min(1.0)
+        } else {
+            0.0
+        };
+        // Fraction is clamped between zero and one, so truncation is not possible.
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let cells = (fraction * width as f32).round() as usize;
+        cells
+    };
+    let rms = cells(meter.rms);
+    let filled = cells(meter.level);
+    let hold = cells(meter.hold);
+
+    let bar: String = (1..=width)
+        .map(|cell| {
+            if cell <= rms {
+                '█'
+            } else if cell <= filled {
+                '#'
+            } else if cell == hold {
+                '|'
+            } else {
+                ' '
+            }
+        })
+        .collect();
+    format!("[{}]", bar)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rodio::buffer::SamplesBuffer;
+
+    #[test]
+    fn levels_decay_and_holds_fall_after_hold_time() {
+        let ballistics = Ballistics {
+            decay: 20.0,
+            hold: 1.0,
+        };
+        let mut meter = Meter::new(ballistics);
+
+        meter.update(&[0.5, 0.1], &[], 0.0, 1.0);
+        assert_eq!(meter.channels()[0].level, 0.5);
+
+        // Meters fall by 20 dB per second, a factor of ten, over ten updates of simulated frames.
+        for _ in 0..10 {
+            meter.update(&[0.0, 0.1], &[], 0.1, 1.0);
+        }
+        let channel = &meter.channels()[0];
+        assert!((channel.level - 0.05).abs() < 1e-4, "{}", channel.level);
+        assert!(channel.hold < 0.5 && channel.hold > channel.level);
+        assert_eq!(meter.channels()[1].level, 0.1);
+        assert_eq!(meter.channels()[1].hold, 0.1);
+
+        meter.update(&[0.0, 0.0], &[], 0.5, 1.0);
+        let held = meter.channels()[1].clone();
+        assert!(held.level < 0.1);
+        assert_eq!(held.hold, 0.1);
+        meter.update(&[0.0, 0.0], &[], 0.6, 1.0);
+        assert!(meter.channels()[1].hold < 0.1);
+    }
+
+    #[test]
+    fn clip_indicator_latches_until_reset() {
+        let mut meter = Meter::new(Ballistics::default());
+        meter.update(&[0.95], &[], 0.1, 0.9);
+        for _ in 0..50 {
+            meter.update(&[0.1], &[], 0.1, 0.9);
+        }
+        assert!(meter.channels()[0].clipped);
+
+        meter.show(&[0.25]);
+        assert!(meter.channels()[0].clipped);
+        assert_eq!(meter.channels()[0].level, 0.25);
+
+        meter.reset();
+        assert!(!meter.channels()[0].clipped);
+        assert_eq!(meter.channels()[0].hold, 0.25);
+    }
+
+    #[test]
+    fn block_peak_and_rms_of_known_block() {
+        let block = [0.5, -1.0, -0.5, 0.0, 0.5, 0.0, -0.5, 0.0];
+
+        assert_eq!(block_peaks(&block, 2), vec![0.5, 1.0]);
+        let rms = block_rms(&block, 2);
+        assert_eq!(rms[0], 0.5);
+        assert_eq!(rms[1], 0.5);
+        assert!((decibels(rms[0]) + 6.0206).abs() < 1e-3);
+        assert_eq!(decibels(block_peaks(&block, 2)[1]), 0.0);
+        assert_eq!(block_rms(&[], 2), vec![0.0, 0.0]);
+    }
+
+    #[test]
+    fn hold_stays_then_decays_by_elapsed_time() {
+        let ballistics = Ballistics {
+            decay: 20.0,
+            hold: 1.0,
+        };
+
+        let (hold, age) = update_hold((0.25, 3.0), 0.5, 0.5, 0.1, ballistics);
+        assert_eq!((hold, age), (0.5, 0.0));
+        let (hold, age) = update_hold((hold, age), 0.1, 0.1, 0.9, ballistics);
+        assert_eq!(hold, 0.5);
+        assert!((age - 0.9).abs() < 1e-6);
+
+        // Past the hold time, half a second at 20 dB per second falls by 10 dB.
+        let (hold, age) = update_hold((hold, age), 0.0, 0.0, 0.5, ballistics);
+        assert!((hold - 0.5 * 10_f32.powf(-0.5)).abs() < 1e-6, "{}", hold);
+        assert!((age - 1.4).abs() < 1e-6);
+        let (hold, _age) = update_hold((hold, age), 0.0, 0.2, 1.0, ballistics);
+        assert_eq!(hold, 0.2);
+    }
+
+    #[test]
+    fn tap_records_channel_peaks() {
+        let levels = Levels::new(2);
+        let source = SamplesBuffer::new(2, 8000, vec![0.5, -0.75, -0.25, 0.5]);
+        let samples: Vec<f32> = Tap::new(source, levels.clone()).collect();
+
+        assert_eq!(samples, vec![0.5, -0.75, -0.25, 0.5]);
+        assert_eq!(levels.take(), vec![0.5, 0.75]);
+        assert_eq!(levels.take(), vec![0.0, 0.0]);
+        let rms = levels.take_rms();
+        assert!((rms[0] - 0.156_25_f32.sqrt()).abs() < 1e-6);
+        assert!((rms[1] - 0.406_25_f32.sqrt()).abs() < 1e-6);
+        assert_eq!(levels.take_rms(), vec![0.0, 0.0]);
+    }
+
+    #[test]
+    fn gauge_draws_level_and_hold() {
+        let meter = ChannelMeter {
+            level: 0.031_622_8,
+            hold: 1.0,
+            ..ChannelMeter::default()
+        };
+        assert_eq!(gauge(&meter, -60.0, 6), "[###  |]");
+        assert_eq!(gauge(&ChannelMeter::default(), -60.0, 3), "[   ]");
+        let meter = ChannelMeter {
+            level: 1.0,
+            rms: 0.031_622_8,
+            ..ChannelMeter::default()
+        };
+        assert_eq!(gauge(&meter, -60.0, 6), "[███###]");
+    }
+}
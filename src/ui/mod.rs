@@ -1,4 +1,9 @@
 //! Logic for rendering aspects of visual components.
 
 pub mod axes;
+pub mod meter;
+pub mod peaks;
+pub mod ruler;
+pub mod theme;
+pub mod unsaved;
 pub mod util;
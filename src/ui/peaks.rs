@@ -0,0 +1,260 @@
+//! Precomputed sample ranges for plotting long signals quickly.
+
+use crate::dsp::Signal;
+use crate::io::audio::StreamingSamples;
+use crate::progress::Progress;
+use color_eyre::eyre;
+
+/// Number of frames summarized by each block of the finest level.
+pub const BLOCK: usize = 16;
+
+/// Frames read from a file at a time while streaming peaks.
+const STREAM_CHUNK: usize = 1 << 16;
+
+/// Minimum and maximum samples of blocks of frames, at block sizes doubling from level to level.
+///
+/// The finest level is computed from the samples and each coarser level from pairs of blocks of
+/// the previous one, so all levels together take about a quarter of the memory of the samples. The
+/// last block of each level may hold fewer frames than the others.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Peaks {
+    /// Level of the finest blocks, above zero for peaks that skip fine levels to save memory.
+    base: usize,
+    channels: usize,
+    frames: usize,
+    /// Minimum and maximum sample of every block per level from the base level, interleaved by
+    /// channel.
+    levels: Vec<Vec<(f32, f32)>>,
+}
+
+impl Peaks {
+    /// Create Peaks by reading every sample of a signal once.
+    #[must_use]
+    pub fn new(signal: &dyn Signal) -> Self {
+        let channels = usize::from(signal.channels());
+        let frames = signal.frames();
+
+        let mut finest = Vec::with_capacity(frames.div_ceil(BLOCK) * channels);
+        scan(signal, BLOCK, &mut finest);
+        Self::coarsen(0, channels, frames, finest)
+    }
+
+    /// Create Peaks starting at a coarser base level by reading a WAV file once in chunks.
+    ///
+    /// Only one chunk of samples is in memory at a time, so files larger than memory can be
+    /// summarized. Blocks finer than the base level are skipped, which divides the memory of the
+    /// peaks by two for every level.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the file cannot be read or the progress was cancelled.
+    pub(crate) fn streamed(
+        reader: &mut StreamingSamples,
+        base: usize,
+        progress: &mut Progress,
+    ) -> eyre::Result<Self> {
+        let channels = usize::from(reader.channels());
+        let frames = reader.frames();
+        let size = Self::block_size(base);
+        // Chunks of whole blocks keep blocks from straddling two reads.
+        let chunk = size * (STREAM_CHUNK / size).max(1);
+
+        let mut finest = Vec::with_capacity(frames.div_ceil(size) * channels);
+        for start in (0..frames).step_by(chunk) {
+            if progress.is_cancelled() {
+                eyre::bail!("Summarizing peaks was cancelled");
+            }
+            let samples = reader.read_frames(start..start + chunk)?;
+            scan(&samples, size, &mut finest);
+            progress.update(start as f32 / frames as f32);
+        }
+        progress.finish();
+        Ok(Self::coarsen(base, channels, frames, finest))
+    }
+
+    /// Combine pairs of blocks from the finest level up until one block covers all frames.
+    fn coarsen(base: usize, channels: usize, frames: usize, finest: Vec<(f32, f32)>) -> Self {
+        let mut levels = vec![finest];
+        while levels[levels.len() - 1].len() > channels {
+            let previous = &levels[levels.len() - 1];
+            let coarser = previous
+                .chunks(2 * channels)
+                .flat_map(|pair| {
+                    (0..channels).map(move |channel| {
+                        pair.iter()
+                            .skip(channel)
+                            .step_by(channels)
+                            .fold((f32::MAX, f32::MIN), |(low, high), block| {
+                                (low.min(block.0), high.max(block.1))
+                            })
+                    })
+                })
+                .collect();
+            levels.push(coarser);
+        }
+
+        Self {
+            base,
+            channels,
+            frames,
+            levels,
+        }
+    }
+
+    /// Get the number of frames in each block of a level.
+    #[must_use]
+    pub const fn block_size(level: usize) -> usize {
+        BLOCK << level
+    }
+
+    /// Get the minimum and maximum sample of a channel within a block of a level.
+    ///
+    /// Returns `None` if the level, block, or channel is out of bounds.
+    #[must_use]
+    pub fn block(&self, level: usize, index: usize, channel: usize) -> Option<(f32, f32)> {
+        if channel >= self.channels {
+            return None;
+        }
+        self.levels
+            .get(level.checked_sub(self.base)?)?
+            .get(index * self.channels + channel)
+            .copied()
+    }
+
+    /// Get the largest sample magnitude of a channel over the whole signal.
+    ///
+    /// Returns `None` if the channel is out of bounds or the signal is empty.
+    #[must_use]
+    pub fn peak(&self, channel: usize) -> Option<f32> {
+        let coarsest = self.base + self.levels.len().checked_sub(1)?;
+        let (low, high) = self.block(coarsest, 0, channel)?;
+        Some(low.abs().max(high.abs()))
+    }
+
+    /// Check whether the peaks were computed from a signal with the same shape.
+    #[must_use]
+    pub fn fits(&self, signal: &dyn Signal) -> bool {
+        self.channels == usize::from(signal.channels()) && self.frames == signal.frames()
+    }
+
+    /// Find the finest level whose blocks hold at least a number of frames.
+    ///
+    /// Returns `None` if blocks of the base level are already larger, where the samples should be
+    /// read directly.
+    #[must_use]
+    pub fn level(&self, frames: usize) -> Option<usize> {
+        if frames < Self::block_size(self.base) {
+            return None;
+        }
+        let levels = self.base..self.base + self.levels.len();
+        let coarsest = levels.end.saturating_sub(1);
+        let level = levels
+            .into_iter()
+            .find(|level| Self::block_size(*level) >= frames)
+            .unwrap_or(coarsest);
+        Some(level)
+    }
+}
+
+/// Append the minimum and maximum sample of every channel in each block of frames of a signal.
+fn scan(signal: &dyn Signal, size: usize, blocks: &mut Vec<(f32, f32)>) {
+    let frames = signal.frames();
+    for start in (0..frames).step_by(size) {
+        for channel in 0..usize::from(signal.channels()) {
+            blocks.push((start..(start + size).min(frames)).fold(
+                (f32::MAX, f32::MIN),
+                |(low, high), index| {
+                    let sample = signal.sample(index, channel);
+                    (low.min(sample), high.max(sample))
+                },
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsp::Samples;
+    use crate::io::audio;
+
+    #[test]
+    fn blocks_match_direct_scan() {
+        let frames: usize = 1000;
+        let data = (0..frames * 2)
+            .map(|index| ((index * 7919) % 201) as f32 / 100.0 - 1.0)
+            .collect();
+        let samples = Samples::new(2, 8000, data);
+        let peaks = Peaks::new(&samples);
+
+        for level in 0..4 {
+            let size = Peaks::block_size(level);
+            for index in 0..frames.div_ceil(size) {
+                for channel in 0..2 {
+                    let expected = (index * size..((index + 1) * size).min(frames))
+                        .map(|frame| samples.sample(frame, channel))
+                        .fold((f32::MAX, f32::MIN), |(low, high), sample| {
+                            (low.min(sample), high.max(sample))
+                        });
+                    assert_eq!(peaks.block(level, index, channel), Some(expected));
+                }
+            }
+            assert_eq!(peaks.block(level, frames.div_ceil(size), 0), None);
+        }
+        assert_eq!(peaks.block(0, 0, 2), None);
+        let loudest = (0..frames).fold(0.0_f32, |peak, frame| {
+            peak.max(samples.sample(frame, 1).abs())
+        });
+        assert_eq!(peaks.peak(1), Some(loudest));
+        assert_eq!(peaks.peak(2), None);
+        assert!(peaks.fits(&samples));
+        assert!(!peaks.fits(&samples.slice(crate::dsp::Selection::new(0, 10))));
+    }
+
+    #[test]
+    fn streamed_peaks_match_decoded_peaks() {
+        let folder = tempfile::tempdir().unwrap();
+        let path = folder.path().join("long.wav");
+        let frames: usize = 300_001;
+        let data = (0..frames * 2)
+            .map(|index| ((index * 7919) % 201) as f32 / 100.0 - 1.0)
+            .collect();
+        let samples = Samples::new(2, 8000, data);
+        audio::write_samples(&path, &samples).unwrap();
+
+        let mut reader = audio::read_samples_streaming(&path).unwrap();
+        let mut fractions = Vec::new();
+        let mut callback = |fraction| fractions.push(fraction);
+        let streamed = Peaks::streamed(&mut reader, 3, &mut Progress::new(&mut callback)).unwrap();
+        let decoded = Peaks::new(&samples);
+
+        assert!(streamed.fits(&samples));
+        assert_eq!(streamed.block(2, 0, 0), None);
+        for level in 3..decoded.levels.len() {
+            for index in 0..frames.div_ceil(Peaks::block_size(level)) {
+                for channel in 0..2 {
+                    assert_eq!(
+                        streamed.block(level, index, channel),
+                        decoded.block(level, index, channel)
+                    );
+                }
+            }
+        }
+        assert_eq!(streamed.peak(0), decoded.peak(0));
+        assert_eq!(streamed.level(Peaks::block_size(3) - 1), None);
+        assert_eq!(streamed.level(Peaks::block_size(3)), Some(3));
+        assert_eq!(streamed.level(usize::MAX), decoded.level(usize::MAX));
+        assert_eq!(fractions.last(), Some(&1.0));
+    }
+
+    #[test]
+    fn level_fits_frames() {
+        let peaks = Peaks::new(&Samples::new(1, 8000, vec![0.0; 1024]));
+
+        assert_eq!(peaks.level(BLOCK - 1), None);
+        assert_eq!(peaks.level(BLOCK), Some(0));
+        assert_eq!(peaks.level(BLOCK + 1), Some(1));
+        assert_eq!(peaks.level(100), Some(3));
+        assert_eq!(peaks.level(1 << 20), Some(6));
+    }
+}
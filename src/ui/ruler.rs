@@ -0,0 +1,153 @@
+//! Time rulers with tick marks at readable intervals.
+
+/// Fewest columns between ticks, enough to fit a label and a space after each tick.
+pub const TICK_SPACING: u16 = 10;
+
+/// Mantissas of tick intervals, which are these times a power of ten seconds.
+const STEPS: [f64; 3] = [1.0, 2.0, 5.0];
+
+/// Choose the shortest readable tick interval in seconds for a visible duration and width.
+///
+/// Intervals are 1, 2, or 5 times a power of ten from a millisecond up, so ticks land on round
+/// times, and are long enough that ticks are at least [`TICK_SPACING`] columns apart.
+#[must_use]
+pub fn tick_interval(seconds: f64, width: u16) -> f64 {
+    let fit = f64::from((width / TICK_SPACING).max(1));
+    let wanted = seconds.max(0.0) / fit;
+
+    let mut decade = 0.001;
+    loop {
+        for step in STEPS {
+            let interval = step * decade;
+            if interval >= wanted || !interval.is_finite() {
+                return interval;
+            }
+        }
+        decade *= 10.0;
+    }
+}
+
+/// Get the frame positions of ticks at multiples of an interval within visible frame bounds.
+#[must_use]
+pub fn ticks(bounds: (f64, f64), sample_rate: u32, interval: f64) -> Vec<f64> {
+    let frames = interval * f64::from(sample_rate.max(1));
+    if frames.is_nan() || frames <= 0.0 || bounds.1 < bounds.0 {
+        return Vec::new();
+    }
+
+    let first = (bounds.0.max(0.0) / frames).ceil();
+    let last = (bounds.1 / frames).floor();
+    // Tick counts are bounded by the width the interval was chosen for, so truncation is not
+    // possible.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let count = (last - first + 1.0).max(0.0) as usize;
+    (0..count)
+        .map(|index| (first + index as f64) * frames)
+        .collect()
+}
+
+/// Format the time of a tick with as many decimals as its interval needs.
+///
+/// Times of a minute or more show minutes and seconds, such as `1:30` or `1:30.5`.
+#[must_use]
+pub fn tick_label(seconds: f64, interval: f64) -> String {
+    // Intervals of a millisecond or more need at most three decimals.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let decimals = (-interval.log10().floor()).clamp(0.0, 3.0) as usize;
+    if seconds < 60.0 {
+        return format!("{:.*}s", decimals, seconds);
+    }
+
+    // Times are checked to be at least a minute and positive, so sign loss is not possible.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let minutes = (seconds / 60.0).floor() as u64;
+    let rest = seconds - 60.0 * minutes as f64;
+    let width = if decimals > 0 { decimals + 3 } else { 2 };
+    format!(
+        "{}:{:0width$.decimals$}",
+        minutes,
+        rest,
+        width = width,
+        decimals = decimals
+    )
+}
+
+/// Draw a ruler row of a width with a `|` at each tick column followed by its label if it fits
+/// before the next tick, given ticks sorted by column.
+#[must_use]
+pub fn ruler(ticks: &[(u16, String)], width: u16) -> String {
+    let mut row = vec![' '; usize::from(width)];
+    for (index, (column, label)) in ticks.iter().enumerate() {
+        let start = usize::from(*column);
+        let end = ticks
+            .get(index + 1)
+            .map_or(row.len(), |(next, _label)| usize::from(*next));
+        if start >= row.len() {
+            continue;
+        }
+
+        row[start] = '|';
+        if label.chars().count() < end - start {
+            for (offset, character) in label.chars().enumerate() {
+                row[start + 1 + offset] = character;
+            }
+        }
+    }
+    row.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intervals_fit_width() {
+        for (seconds, width) in [
+            (0.005, 80),
+            (0.25, 80),
+            (1.0, 120),
+            (7.3, 60),
+            (95.0, 200),
+            (3600.0, 80),
+        ] {
+            let interval = tick_interval(seconds, width);
+            let count = seconds / interval;
+            let most = f64::from(width / TICK_SPACING);
+            assert!(count <= most, "{} ticks for {} s", count, seconds);
+            assert!(count * 5.0 > most, "{} ticks for {} s", count, seconds);
+        }
+
+        assert_eq!(tick_interval(1.0, 100), 0.1);
+        assert_eq!(tick_interval(0.0, 100), 0.001);
+        assert_eq!(tick_interval(30.0, 40), 10.0);
+    }
+
+    #[test]
+    fn ticks_lie_within_bounds() {
+        let bounds = (12_345.0, 98_765.0);
+        let positions = ticks(bounds, 44_100, 0.5);
+
+        assert_eq!(positions, vec![22_050.0, 44_100.0, 66_150.0, 88_200.0]);
+        assert!(positions
+            .iter()
+            .all(|frame| *frame >= bounds.0 && *frame <= bounds.1));
+        assert_eq!(ticks((-100.0, 10.0), 10, 1.0), vec![0.0, 10.0]);
+        assert!(ticks((5.0, 6.0), 10, 1.0).is_empty());
+    }
+
+    #[test]
+    fn labels_and_row() {
+        assert_eq!(tick_label(0.25, 0.05), "0.25s");
+        assert_eq!(tick_label(3.0, 1.0), "3s");
+        assert_eq!(tick_label(90.0, 10.0), "1:30");
+        assert_eq!(tick_label(90.5, 0.5), "1:30.5");
+
+        let labeled = [
+            (0, String::from("0s")),
+            (3, String::from("1s")),
+            (5, String::from("2s")),
+        ];
+        assert_eq!(ruler(&labeled, 10), "|0s| |2s  ");
+        assert_eq!(ruler(&[(12, String::from("9s"))], 10), "          ");
+    }
+}
@@ -0,0 +1,462 @@
+//! Color schemes for the terminal interface.
+
+use clap::ValueEnum;
+use color_eyre::eyre;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use tui::style::{Color, Modifier, Style};
+
+/// ANSI colors and their typical RGB values used to approximate true colors.
+const ANSI_COLORS: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+/// Configuration names of ANSI colors.
+const COLOR_NAMES: [(Color, &str); 17] = [
+    (Color::Black, "black"),
+    (Color::Blue, "blue"),
+    (Color::Cyan, "cyan"),
+    (Color::DarkGray, "dark-gray"),
+    (Color::Gray, "gray"),
+    (Color::Green, "green"),
+    (Color::LightBlue, "light-blue"),
+    (Color::LightCyan, "light-cyan"),
+    (Color::LightGreen, "light-green"),
+    (Color::LightMagenta, "light-magenta"),
+    (Color::LightRed, "light-red"),
+    (Color::LightYellow, "light-yellow"),
+    (Color::Magenta, "magenta"),
+    (Color::Red, "red"),
+    (Color::Reset, "reset"),
+    (Color::White, "white"),
+    (Color::Yellow, "yellow"),
+];
+
+/// Colors used when drawing UI views.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Theme {
+    pub background: Color,
+    pub clip: Color,
+    pub highlight: Color,
+    pub marker: Color,
+    pub menu: Color,
+    pub playhead: Color,
+    pub status: Color,
+    pub text: Color,
+    pub warning: Color,
+    pub waveform: Color,
+}
+
+impl Theme {
+    /// Light text on a dark background.
+    #[must_use]
+    pub const fn dark() -> Self {
+        Self {
+            background: Color::Black,
+            clip: Color::LightRed,
+            highlight: Color::Yellow,
+            marker: Color::LightMagenta,
+            menu: Color::LightCyan,
+            playhead: Color::LightRed,
+            status: Color::DarkGray,
+            text: Color::Gray,
+            warning: Color::LightRed,
+            waveform: Color::Cyan,
+        }
+    }
+
+    /// Maximum contrast black and white with saturated accents.
+    #[must_use]
+    pub const fn high_contrast() -> Self {
+        Self {
+            background: Color::Black,
+            clip: Color::LightRed,
+            highlight: Color::LightYellow,
+            marker: Color::LightCyan,
+            menu: Color::LightYellow,
+            playhead: Color::LightRed,
+            status: Color::White,
+            text: Color::White,
+            warning: Color::LightMagenta,
+            waveform: Color::LightGreen,
+        }
+    }
+
+    /// Dark text on a light background.
+    #[must_use]
+    pub const fn light() -> Self {
+        Self {
+            background: Color::White,
+            clip: Color::Red,
+            highlight: Color::Magenta,
+            marker: Color::DarkGray,
+            menu: Color::Blue,
+            playhead: Color::Red,
+            status: Color::Gray,
+            text: Color::Black,
+            warning: Color::Red,
+            waveform: Color::Blue,
+        }
+    }
+
+    /// Style for regular text and backgrounds.
+    #[must_use]
+    pub fn base(&self) -> Style {
+        Style::default().fg(self.text).bg(self.background)
+    }
+
+    /// Style for plotted samples at or above the clipping threshold.
+    #[must_use]
+    pub fn clip(&self) -> Style {
+        Style::default().fg(self.clip)
+    }
+
+    /// Style for timeline markers and their labels.
+    #[must_use]
+    pub fn marker(&self) -> Style {
+        Style::default().fg(self.marker)
+    }
+
+    /// Style for the selected menu option.
+    #[must_use]
+    pub fn menu(&self) -> Style {
+        Style::default().fg(self.menu).add_modifier(Modifier::BOLD)
+    }
+
+    /// Style for the playback position marker.
+    #[must_use]
+    pub fn playhead(&self) -> Style {
+        Style::default().fg(self.playhead)
+    }
+
+    /// Style for selected list entries.
+    #[must_use]
+    pub fn selection(&self) -> Style {
+        Style::default()
+            .fg(self.highlight)
+            .add_modifier(Modifier::BOLD)
+    }
+
+    /// Style for plotted signals from before the last edit.
+    #[must_use]
+    pub fn preview(&self) -> Style {
+        Style::default().fg(self.status).add_modifier(Modifier::DIM)
+    }
+
+    /// Style for the status bar.
+    #[must_use]
+    pub fn status(&self) -> Style {
+        Style::default().fg(self.background).bg(self.status)
+    }
+
+    /// Style for error messages.
+    #[must_use]
+    pub fn warning(&self) -> Style {
+        Style::default().fg(self.warning).bg(self.background)
+    }
+
+    /// Style for plotted signals.
+    #[must_use]
+    pub fn waveform(&self) -> Style {
+        Style::default().fg(self.waveform)
+    }
+
+    /// Replace true colors with their nearest ANSI colors.
+    #[must_use]
+    pub fn degrade(self) -> Self {
+        Self {
+            background: nearest_ansi(self.background),
+            clip: nearest_ansi(self.clip),
+            highlight: nearest_ansi(self.highlight),
+            marker: nearest_ansi(self.marker),
+            menu: nearest_ansi(self.menu),
+            playhead: nearest_ansi(self.playhead),
+            status: nearest_ansi(self.status),
+            text: nearest_ansi(self.text),
+            warning: nearest_ansi(self.warning),
+            waveform: nearest_ansi(self.waveform),
+        }
+    }
+
+    /// Get a mutable reference to the color with the given configuration name.
+    fn color_mut(&mut self, name: &str) -> Option<&mut Color> {
+        match name {
+            "background" => Some(&mut self.background),
+            "clip" => Some(&mut self.clip),
+            "highlight" => Some(&mut self.highlight),
+            "marker" => Some(&mut self.marker),
+            "menu" => Some(&mut self.menu),
+            "playhead" => Some(&mut self.playhead),
+            "status" => Some(&mut self.status),
+            "text" => Some(&mut self.text),
+            "warning" => Some(&mut self.warning),
+            "waveform" => Some(&mut self.waveform),
+            _ => None,
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+/// Built in themes selectable by name.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemeName {
+    #[default]
+    Dark,
+    HighContrast,
+    Light,
+}
+
+impl ThemeName {
+    /// Get the following theme name, wrapping around after the last one.
+    #[must_use]
+    pub const fn next(self) -> Self {
+        match self {
+            Self::Dark => Self::HighContrast,
+            Self::HighContrast => Self::Light,
+            Self::Light => Self::Dark,
+        }
+    }
+
+    /// Get the colors of the named theme.
+    #[must_use]
+    pub const fn theme(self) -> Theme {
+        match self {
+            Self::Dark => Theme::dark(),
+            Self::HighContrast => Theme::high_contrast(),
+            Self::Light => Theme::light(),
+        }
+    }
+}
+
+/// A built in theme with user color overrides, adapted to terminal capabilities.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Palette {
+    colors: BTreeMap<String, Color>,
+    name: ThemeName,
+    truecolor: bool,
+}
+
+impl Palette {
+    /// Create a Palette from a theme name and configured colors.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if a configured color name or value is invalid.
+    pub fn try_new(
+        name: ThemeName,
+        colors: &BTreeMap<String, String>,
+        truecolor: bool,
+    ) -> eyre::Result<Self> {
+        let mut parsed = BTreeMap::new();
+        let mut theme = Theme::default();
+
+        for (key, value) in colors {
+            let color = parse_color(value)
+                .map_err(|error| eyre::eyre!("Theme color colors.{}: {}", key, error))?;
+            if theme.color_mut(key).is_none() {
+                eyre::bail!("Theme color colors.{}: unknown theme color", key);
+            }
+            parsed.insert(key.clone(), color);
+        }
+
+        Ok(Self {
+            colors: parsed,
+            name,
+            truecolor,
+        })
+    }
+
+    /// Switch to the following built in theme, keeping color overrides.
+    pub fn next(&mut self) {
+        self.name = self.name.next();
+    }
+
+    /// Get the resulting theme colors.
+    #[must_use]
+    pub fn theme(&self) -> Theme {
+        let mut theme = self.name.theme();
+
+        for (key, color) in &self.colors {
+            if let Some(slot) = theme.color_mut(key) {
+                *slot = *color;
+            }
+        }
+
+        if self.truecolor {
+            theme
+        } else {
+            theme.degrade()
+        }
+    }
+}
+
+/// Format a color as a configuration value.
+#[must_use]
+pub fn format_color(color: Color) -> String {
+    match color {
+        Color::Rgb(red, green, blue) => format!("#{:02x}{:02x}{:02x}", red, green, blue),
+        Color::Indexed(index) => format!("{}", index),
+        color => COLOR_NAMES
+            .iter()
+            .find(|(named, _name)| *named == color)
+            .map_or_else(String::new, |(_named, name)| (*name).to_owned()),
+    }
+}
+
+/// Find the ANSI color closest to a true color.
+///
+/// Other colors are returned unchanged.
+#[must_use]
+pub fn nearest_ansi(color: Color) -> Color {
+    match color {
+        Color::Rgb(red, green, blue) => {
+            let distance = |(other_red, other_green, other_blue): (u8, u8, u8)| {
+                let red = i32::from(red) - i32::from(other_red);
+                let green = i32::from(green) - i32::from(other_green);
+                let blue = i32::from(blue) - i32::from(other_blue);
+                red * red + green * green + blue * blue
+            };
+
+            ANSI_COLORS
+                .iter()
+                .min_by_key(|(_ansi, rgb)| distance(*rgb))
+                .map_or(color, |(ansi, _rgb)| *ansi)
+        }
+        color => color,
+    }
+}
+
+/// Parse a color from an ANSI color name or an `#rrggbb` hex code.
+///
+/// # Errors
+///
+/// Will return `Err` if `text` is neither a known color name nor a hex code.
+pub fn parse_color(text: &str) -> eyre::Result<Color> {
+    let normalized = text.trim().to_lowercase().replace('_', "-");
+
+    if let Some(hex) = normalized.strip_prefix('#') {
+        if hex.len() == 6 && hex.chars().all(|char| char.is_ascii_hexdigit()) {
+            let channel = |index: usize| u8::from_str_radix(&hex[index..index + 2], 16);
+            return Ok(Color::Rgb(channel(0)?, channel(2)?, channel(4)?));
+        }
+    } else if let Some((color, _name)) = COLOR_NAMES
+        .iter()
+        .find(|(_color, name)| *name == normalized.as_str())
+    {
+        return Ok(*color);
+    }
+
+    eyre::bail!("Invalid color {:?}, expected a color name or #rrggbb", text)
+}
+
+/// Check whether the terminal advertises 24 bit color support.
+#[must_use]
+pub fn supports_truecolor() -> bool {
+    matches!(
+        env::var("COLORTERM").as_deref(),
+        Ok("truecolor") | Ok("24bit")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_themes_are_distinct() {
+        let names = [ThemeName::Dark, ThemeName::HighContrast, ThemeName::Light];
+        let themes: Vec<Theme> = names.iter().map(|name| name.theme()).collect();
+
+        for (index, theme) in themes.iter().enumerate() {
+            assert!(themes[index + 1..].iter().all(|other| other != theme));
+
+            // Text and plotted signals must remain visible over the background.
+            for color in [
+                theme.clip,
+                theme.highlight,
+                theme.text,
+                theme.warning,
+                theme.waveform,
+            ] {
+                assert_ne!(color, theme.background);
+                assert_ne!(color, Color::Reset);
+            }
+        }
+    }
+
+    #[test]
+    fn cycle_visits_all_themes() {
+        let mut name = ThemeName::default();
+        let mut visited = Vec::new();
+
+        for _ in 0..3 {
+            visited.push(name);
+            name = name.next();
+        }
+
+        assert_eq!(name, ThemeName::default());
+        assert_eq!(
+            visited,
+            vec![ThemeName::Dark, ThemeName::HighContrast, ThemeName::Light]
+        );
+    }
+
+    #[test]
+    fn hex_color_round_trip() {
+        for text in ["#000000", "#ff8800", "#12abef"] {
+            let color = parse_color(text).unwrap();
+            assert_eq!(format_color(color), text);
+        }
+
+        assert_eq!(parse_color("#FF8800").unwrap(), Color::Rgb(255, 136, 0));
+        assert_eq!(parse_color("Light_Red").unwrap(), Color::LightRed);
+        assert!(parse_color("#ff88").is_err());
+        assert!(parse_color("chartreuse").is_err());
+    }
+
+    #[test]
+    fn nearest_ansi_fallback() {
+        assert_eq!(nearest_ansi(Color::Rgb(250, 10, 10)), Color::LightRed);
+        assert_eq!(nearest_ansi(Color::Rgb(20, 20, 20)), Color::Black);
+        assert_eq!(nearest_ansi(Color::Rgb(0, 190, 200)), Color::Cyan);
+        assert_eq!(nearest_ansi(Color::Magenta), Color::Magenta);
+    }
+
+    #[test]
+    fn palette_overrides_colors() {
+        let colors = [(String::from("waveform"), String::from("#ff0000"))].into();
+
+        let truecolor = Palette::try_new(ThemeName::Light, &colors, true).unwrap();
+        assert_eq!(truecolor.theme().waveform, Color::Rgb(255, 0, 0));
+        assert_eq!(truecolor.theme().text, Theme::light().text);
+
+        let ansi = Palette::try_new(ThemeName::Light, &colors, false).unwrap();
+        assert_eq!(ansi.theme().waveform, Color::LightRed);
+
+        let unknown = [(String::from("sparkle"), String::from("red"))].into();
+        let error = Palette::try_new(ThemeName::Dark, &unknown, true).unwrap_err();
+        assert!(format!("{}", error).contains("colors.sparkle"));
+    }
+}
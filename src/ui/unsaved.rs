@@ -0,0 +1,63 @@
+//! Prompt for deciding what happens to unsaved changes.
+
+use crate::ui;
+use crate::ui::theme::Theme;
+use crossterm::event::{KeyCode, KeyEvent};
+use tui::backend::Backend;
+use tui::layout::Rect;
+use tui::terminal::Frame;
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
+
+/// Answers to the unsaved changes prompt.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Choice {
+    Cancel,
+    Discard,
+    Save,
+}
+
+impl Choice {
+    /// Get the answer chosen by a key press, if any.
+    #[must_use]
+    pub const fn from_key(event: KeyEvent) -> Option<Self> {
+        match event.code {
+            KeyCode::Char('c') | KeyCode::Esc => Some(Self::Cancel),
+            KeyCode::Char('d') => Some(Self::Discard),
+            KeyCode::Char('s') => Some(Self::Save),
+            _ => None,
+        }
+    }
+}
+
+/// Draw the unsaved changes prompt for the named buffers and the error of a failed save.
+pub fn render<B: Backend>(
+    frame: &mut Frame<'_, B>,
+    area: Rect,
+    theme: &Theme,
+    action: &str,
+    names: &[&str],
+    error: Option<&str>,
+) {
+    let area = ui::util::centered_rectangle(60, 30, area);
+    frame.render_widget(Clear, area);
+
+    let mut lines = vec![
+        Spans::from(format!("{} has unsaved changes.", names.join(", "))),
+        Spans::from(format!(
+            "(s)ave and {0}, (d)iscard and {0}, or (c)ancel?",
+            action
+        )),
+    ];
+    if let Some(error) = error {
+        lines.push(Spans::from(Span::styled(error.to_owned(), theme.warning())));
+    }
+
+    let block = Block::default()
+        .title("Unsaved changes")
+        .borders(Borders::ALL)
+        .style(theme.base());
+    let text = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+
+    frame.render_widget(text, area);
+}
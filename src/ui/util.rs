@@ -1,6 +1,8 @@
 //! Utilities for drawing visuals.
 
+use color_eyre::eyre;
 use tui::layout::{Constraint, Direction, Layout, Rect};
+use tui::text::Span;
 
 /// Create a centered interior rectangle to a given rectangle.
 #[must_use]
@@ -30,10 +32,229 @@ pub fn centered_rectangle(percent_x: u16, percent_y: u16, rectangle: Rect) -> Re
         .split(popup_layout[1])[1]
 }
 
+/// Check if a terminal cell lies within a rectangle.
+#[must_use]
+pub const fn contains(rectangle: Rect, column: u16, row: u16) -> bool {
+    column >= rectangle.x
+        && column - rectangle.x < rectangle.width
+        && row >= rectangle.y
+        && row - rectangle.y < rectangle.height
+}
+
+/// Find the title of a TUI Tabs widget drawn at a column offset from its inner left edge.
+///
+/// Each title owns its surrounding padding and the divider after it.
+#[must_use]
+pub fn tab_at(titles: &[&str], offset: u16) -> Option<usize> {
+    let mut start = 0;
+
+    for (index, title) in titles.iter().enumerate() {
+        let end = start + Span::raw(*title).width() + 3;
+        if (start..end).contains(&usize::from(offset)) {
+            return Some(index);
+        }
+        start = end;
+    }
+
+    None
+}
+
+/// Draw a text progress bar of a number of cells followed by the completed percentage.
+#[must_use]
+pub fn progress_bar(fraction: f32, width: usize) -> String {
+    let fraction = fraction.clamp(0.0, 1.0);
+    // Fraction is clamped to be non negative, so sign loss is not possible.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let filled = (fraction * width as f32).round() as usize;
+    format!(
+        "[{}{}] {:.0}%",
+        "#".repeat(filled),
+        "-".repeat(width - filled),
+        fraction * 100.0
+    )
+}
+
+/// Format the time of a frame at a sample rate as hours, minutes, seconds, and milliseconds.
+///
+/// Milliseconds are truncated, so a frame shows the time at or before it. Hours keep counting
+/// past 99 instead of wrapping.
+#[must_use]
+pub fn timecode(frame: usize, sample_rate: u32) -> String {
+    let milliseconds = frame as u128 * 1000 / u128::from(sample_rate.max(1));
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        milliseconds / 3_600_000,
+        milliseconds / 60_000 % 60,
+        milliseconds / 1000 % 60,
+        milliseconds % 1000
+    )
+}
+
+/// Parse a typed position as seconds, a timecode, or a frame index at a sample rate.
+///
+/// Positions are either seconds such as `221.25`, a timecode of minutes and seconds such as
+/// `3:41.250`, optionally preceded by hours like [`timecode`] shows them, or a frame index
+/// followed by `s` such as `44100s`. Times are rounded to the nearest frame.
+///
+/// # Errors
+///
+/// Will return `Err` if the text is none of these, or if minutes or seconds of a timecode are 60
+/// or more.
+pub fn parse_position(text: &str, sample_rate: u32) -> eyre::Result<usize> {
+    let text = text.trim();
+    let invalid = || {
+        eyre::eyre!(
+            "Position {:?} is not seconds, a mm:ss.mmm timecode, or a frame index like 44100s",
+            text
+        )
+    };
+    let digits = |part: &str| !part.is_empty() && part.bytes().all(|byte| byte.is_ascii_digit());
+
+    if let Some(index) = text.strip_suffix('s') {
+        return if digits(index) {
+            index.parse().map_err(|_| invalid())
+        } else {
+            Err(invalid())
+        };
+    }
+
+    let parts: Vec<&str> = text.split(':').collect();
+    let (seconds, whole) = parts.split_last().ok_or_else(invalid)?;
+    if whole.len() > 2 || !whole.iter().all(|part| digits(part)) {
+        return Err(invalid());
+    }
+    let (integer, fraction) = seconds.split_once('.').unwrap_or((seconds, "0"));
+    if !digits(integer) || !digits(fraction) {
+        return Err(invalid());
+    }
+
+    let seconds: f64 = seconds.parse().map_err(|_| invalid())?;
+    let mut units = whole.iter().rev().map(|part| part.parse::<f64>());
+    let minutes = units
+        .next()
+        .transpose()
+        .map_err(|_| invalid())?
+        .unwrap_or(0.0);
+    let hours = units
+        .next()
+        .transpose()
+        .map_err(|_| invalid())?
+        .unwrap_or(0.0);
+    // Plain seconds may exceed a minute, but seconds and minutes of timecodes may not.
+    if (!whole.is_empty() && seconds >= 60.0) || (whole.len() == 2 && minutes >= 60.0) {
+        eyre::bail!("Position {:?} has minutes or seconds of 60 or more", text);
+    }
+
+    let total = (hours * 60.0 + minutes).mul_add(60.0, seconds) * f64::from(sample_rate);
+    if total > usize::MAX as f64 {
+        return Err(invalid());
+    }
+    // Totals are non negative and checked against the largest index, so no truncation occurs.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    Ok(total.round() as usize)
+}
+
+/// Draw a text level meter of a number of cells filled linearly in decibels above a floor.
+#[must_use]
+pub fn level_meter(level: f32, floor: f32, width: usize) -> String {
+    let decibels = 20.0 * level.abs().log10();
+    let fraction = if decibels > floor {
+        (1.0 - decibels / floor).min(1.0)
+    } else {
+        0.0
+    };
+    // Fraction is clamped between zero and one, so truncation is not possible.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let filled = (fraction * width as f32).round() as usize;
+    format!("[{}{}]", "|".repeat(filled), " ".repeat(width - filled))
+}
+
+/// Modular move a list selection one entry forward or backward.
+#[must_use]
+pub const fn step(selected: Option<usize>, length: usize, forward: bool) -> Option<usize> {
+    if length == 0 {
+        return None;
+    }
+
+    match selected {
+        Some(index) if forward => Some((index + 1) % length),
+        Some(index) => Some((index + length - 1) % length),
+        None => Some(0),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn parse_position_frames_and_timecodes() {
+        assert_eq!(parse_position("0", 44_100).unwrap(), 0);
+        assert_eq!(parse_position(" 12345s ", 44_100).unwrap(), 12_345);
+        assert_eq!(parse_position("0s", 44_100).unwrap(), 0);
+        assert_eq!(parse_position("221.25", 8000).unwrap(), 1_770_000);
+        assert_eq!(parse_position("90", 10).unwrap(), 900);
+        assert_eq!(parse_position("1.5", 3).unwrap(), 5);
+        assert_eq!(parse_position("3:41", 1000).unwrap(), 221_000);
+        assert_eq!(parse_position("3:41.250", 1000).unwrap(), 221_250);
+        assert_eq!(parse_position("00:01.000", 44_100).unwrap(), 44_100);
+        assert_eq!(parse_position("1:30", 8000).unwrap(), 720_000);
+        assert_eq!(parse_position("0:00.5", 8000).unwrap(), 4000);
+        assert_eq!(parse_position("00:00:00.001", 48_000).unwrap(), 48);
+        assert_eq!(
+            parse_position("01:02:03.500", 8000).unwrap(),
+            8000 * 3_723 + 4000
+        );
+        assert_eq!(parse_position("90:00", 10).unwrap(), 54_000);
+        assert_eq!(
+            parse_position(&timecode(123_456, 44_100), 44_100).unwrap(),
+            123_436
+        );
+    }
+
+    #[test]
+    fn parse_position_rejects_invalid_text() {
+        for text in [
+            "",
+            "-5",
+            "+5",
+            "1.5s",
+            "s",
+            "12ss",
+            "1:30s",
+            ".5",
+            "2.",
+            "abc",
+            "1:",
+            ":30",
+            "1:60",
+            "1:2:3:4",
+            "1:-2",
+            "1:30.",
+            "1:3e1",
+            "1:60:00",
+            "99999999999999999999999",
+        ] {
+            assert!(parse_position(text, 44_100).is_err(), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn level_meter_fills_in_decibels() {
+        assert_eq!(level_meter(0.0, -60.0, 4), "[    ]");
+        assert_eq!(level_meter(0.001, -60.0, 4), "[    ]");
+        assert_eq!(level_meter(-0.031_622_8, -60.0, 4), "[||  ]");
+        assert_eq!(level_meter(1.0, -60.0, 4), "[||||]");
+        assert_eq!(level_meter(2.0, -60.0, 4), "[||||]");
+    }
+
+    #[test]
+    fn progress_bar_fills() {
+        assert_eq!(progress_bar(0.0, 4), "[----] 0%");
+        assert_eq!(progress_bar(0.5, 4), "[##--] 50%");
+        assert_eq!(progress_bar(1.5, 4), "[####] 100%");
+    }
+
     #[test]
     fn rectangle_dimensions() {
         let rectangle = Rect::new(0, 0, 80, 120);
@@ -42,4 +263,36 @@ mod tests {
         let actual = centered_rectangle(30, 20, rectangle);
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn tab_at_columns() {
+        let titles = ["Chart", "File", "Filters"];
+
+        assert_eq!(tab_at(&titles, 0), Some(0));
+        assert_eq!(tab_at(&titles, 7), Some(0));
+        assert_eq!(tab_at(&titles, 8), Some(1));
+        assert_eq!(tab_at(&titles, 15), Some(2));
+        assert_eq!(tab_at(&titles, 25), None);
+    }
+
+    #[test]
+    fn timecode_formats_frames() {
+        assert_eq!(timecode(0, 44_100), "00:00:00.000");
+        assert_eq!(timecode(44_100, 44_100), "00:00:01.000");
+        assert_eq!(timecode(44_099, 44_100), "00:00:00.999");
+        assert_eq!(timecode(66, 44_100), "00:00:00.001");
+        assert_eq!(timecode(48_000 * 61 + 24, 48_000), "00:01:01.000");
+        assert_eq!(timecode(48_000 * 3_600 - 1, 48_000), "00:59:59.999");
+        assert_eq!(timecode(8000 * 3_723 + 4004, 8000), "01:02:03.500");
+        assert_eq!(timecode(96_000 * 360_000, 96_000), "100:00:00.000");
+        assert_eq!(timecode(5, 0), "00:00:05.000");
+    }
+
+    #[test]
+    fn step_wraps_around() {
+        assert_eq!(step(Some(2), 3, true), Some(0));
+        assert_eq!(step(Some(0), 3, false), Some(2));
+        assert_eq!(step(None, 3, false), Some(0));
+        assert_eq!(step(None, 0, true), None);
+    }
 }
@@ -4,6 +4,8 @@
 pub mod test {
     use crate::dsp::Samples;
     use crate::io::audio;
+    use crate::tab::Tab;
+    use crate::ui::theme::Theme;
     use crate::view::View;
     use color_eyre::eyre;
     use crossterm::event::KeyEvent;
@@ -54,7 +56,7 @@ pub mod test {
         Ok(path)
     }
 
-    #[derive(Debug)]
+    #[derive(Debug, Default)]
     pub struct MockView {
         pub error: bool,
     }
@@ -65,22 +67,16 @@ pub mod test {
         }
     }
 
-    impl Default for MockView {
-        fn default() -> Self {
-            Self { error: false }
-        }
-    }
-
     impl<B: Backend> View<B> for MockView {
         fn key_event(&mut self, _event: KeyEvent) {}
-        fn process(&mut self, _samples: &mut Samples) -> eyre::Result<()> {
+        fn process(&mut self, _tab: &mut Tab) -> eyre::Result<()> {
             if self.error {
                 Err(eyre::eyre!("The view is in a bad state"))
             } else {
                 Ok(())
             }
         }
-        fn render<'b>(&mut self, _frame: &mut Frame<'b, B>, _area: Rect) {}
+        fn render<'b>(&mut self, _frame: &mut Frame<'b, B>, _area: Rect, _theme: &Theme) {}
         fn reset(&mut self) {
             self.error = false;
         }
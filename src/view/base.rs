@@ -1,24 +1,37 @@
 //! Fundemental traits for user interface components.
 
-use crate::dsp::Samples;
+use crate::tab::Tab;
+use crate::ui::theme::Theme;
 use color_eyre::eyre;
-use crossterm::event::KeyEvent;
+use crossterm::event::{KeyEvent, MouseEvent};
 use tui::backend::Backend;
 use tui::layout::Rect;
 use tui::terminal::Frame;
 
 /// Base requirements for a UI view.
 pub trait View<B: Backend> {
+    /// Whether the view is consuming raw keyboard input, such as text typed into a prompt.
+    ///
+    /// Global key bindings are suspended while a view captures input.
+    fn captures_input(&self) -> bool {
+        false
+    }
     /// Update view state based on keyboard input.
     fn key_event(&mut self, event: KeyEvent);
-    /// Get or set the current signal state.
+    /// Take a tab opened by the view to add after the existing tabs.
+    fn opened(&mut self) -> Option<Tab> {
+        None
+    }
+    /// Update view and active tab state based on mouse input within the view area.
+    fn mouse_event(&mut self, _event: MouseEvent, _tab: &mut Tab) {}
+    /// Get or set the state of the active tab.
     ///
     /// # Errors
     ///
     /// Will return `Err` if unable to process.
-    fn process(&mut self, samples: &mut Samples) -> eyre::Result<()>;
-    /// Draw UI view in area of given frame.
-    fn render<'b>(&mut self, frame: &mut Frame<'b, B>, area: Rect);
+    fn process(&mut self, tab: &mut Tab) -> eyre::Result<()>;
+    /// Draw UI view in area of given frame with colors from a theme.
+    fn render<'b>(&mut self, frame: &mut Frame<'b, B>, area: Rect, theme: &Theme);
     /// Reset internal state to a non erroneous case.
     fn reset(&mut self);
 }
@@ -1,21 +1,91 @@
 //! Components for plotting audio signals.
 
-use crate::dsp::Samples;
+use crate::dsp::{self, Samples, Selection, Signal};
+use crate::io::png;
+use crate::tab::Tab;
+use crate::ui;
 use crate::ui::axes::Axes;
+use crate::ui::peaks::Peaks;
+use crate::ui::theme::Theme;
 use crate::view::View;
 use color_eyre::eyre;
-use crossterm::event::KeyEvent;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use std::convert::TryFrom;
+use std::ops::Range;
+use std::path::Path;
 use tui::backend::Backend;
+use tui::layout::Alignment;
 use tui::layout::Rect;
+use tui::style::Style;
 use tui::symbols::Marker;
 use tui::terminal::Frame;
-use tui::widgets::{Block, Borders, Dataset, GraphType};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, Clear, Dataset, GraphType, Paragraph};
+
+/// Default level in decibels below full scale drawn at the bottom of the decibel scale.
+pub const DECIBEL_FLOOR: f32 = -60.0;
+
+/// Fewest unclipped seconds between clipped samples that separate clipped regions.
+const CLIP_GAP: f64 = 0.01;
+
+/// Largest number of points plotted per channel before frames are decimated.
+const MAX_POINTS: usize = 4096;
+
+/// Colors of the background, zero lines, and waveforms of rendered images.
+const IMAGE_COLORS: [[u8; 3]; 3] = [[255, 255, 255], [200, 200, 200], [32, 96, 192]];
+
+/// Characters of the background, zero lines, and waveforms of rendered text.
+const ASCII_CHARACTERS: [char; 3] = [' ', '-', '#'];
+
+/// Animation frames drawn while samples are read in the background.
+const SPINNER: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
 
 /// UI view for plotting audio Chart with shift and zoom features.
 pub struct Chart<'a> {
+    anchor: Option<usize>,
     axes: Axes,
+    /// Bracket key starting a jump to a clipped region, waiting for the following key.
+    bracket: Option<char>,
+    /// Magnitude from which samples are drawn as clipped.
+    clip_threshold: f32,
+    /// Plot points at or above the clipping threshold.
+    clipped: Vec<Vec<(f64, f64)>>,
     dataset: Dataset<'a>,
+    /// Whether amplitudes are drawn as decibels above the floor instead of linear levels.
+    decibel: bool,
+    /// Level in decibels below full scale drawn at the bottom of the decibel scale.
+    decibel_floor: f32,
+    frame_count: usize,
+    /// Typed position of the go to prompt and its parse error, if the prompt is shown.
+    goto: Option<(String, Option<String>)>,
+    graph: Rect,
+    /// Frame to move the playhead to on the next process.
+    jump: Option<usize>,
+    loading: bool,
+    /// Label to give the marker at the playhead on the next process, renaming it if true.
+    mark: Option<(bool, String)>,
+    /// Markers of the current samples.
+    markers: Vec<dsp::Marker>,
+    /// Whether the marker prompt renames the marker at the playhead, its typed label, and its
+    /// error, if the prompt is shown.
+    marking: Option<(bool, String, Option<String>)>,
+    /// Brief message shown after the title until the next key press.
+    notice: Option<String>,
+    playhead: f64,
     points: Vec<Vec<(f64, f64)>>,
+    /// Plot points of samples from before the last edit, if they are shown.
+    preview: Vec<Vec<(f64, f64)>>,
+    sample_rate: u32,
+    /// Direction of the clipped region to move the playhead to on the next process, forward if
+    /// true.
+    seek_clip: Option<bool>,
+    /// Direction of the marker to move the playhead to on the next process, forward if true.
+    seek_marker: Option<bool>,
+    selection: Option<Selection>,
+    spinner: usize,
+    split: bool,
+    /// Identifier of the tab processed last, to restore its zoom when switching tabs.
+    tab: Option<usize>,
     title: String,
 }
 
@@ -34,68 +104,812 @@ impl<'a> Chart<'a> {
             .collect();
 
         Chart {
+            anchor: None,
             axes,
+            bracket: None,
+            clip_threshold: 1.0,
+            clipped: Vec::new(),
             dataset,
+            decibel: false,
+            decibel_floor: DECIBEL_FLOOR,
+            frame_count,
+            goto: None,
+            graph: Rect::default(),
+            jump: None,
+            loading: false,
+            mark: None,
+            markers: Vec::new(),
+            marking: None,
+            notice: None,
+            playhead: 0.0_f64,
             points: vec![points; channels],
+            preview: Vec::new(),
+            sample_rate: 1,
+            seek_clip: None,
+            seek_marker: None,
+            selection: None,
+            spinner: 0,
+            split: false,
+            tab: None,
             title,
         }
     }
+
+    /// Set the level in decibels at the bottom of the decibel scale, which is kept below zero.
+    #[must_use]
+    pub fn with_decibel_floor(mut self, floor: f32) -> Self {
+        self.decibel_floor = floor.min(-1.0);
+        self
+    }
+
+    /// Set the magnitude from which samples are drawn as clipped.
+    #[must_use]
+    pub const fn with_clip_threshold(mut self, threshold: f32) -> Self {
+        self.clip_threshold = threshold;
+        self
+    }
+
+    /// Get the decibel floor if amplitudes are drawn on the decibel scale.
+    fn floor(&self) -> Option<f32> {
+        self.decibel.then_some(self.decibel_floor)
+    }
+
+    /// Create datasets for the waveforms of a range of channels and vertical marker lines.
+    ///
+    /// Waveforms from before the last edit are drawn first so current waveforms cover them, and
+    /// clipped points are drawn over the current waveforms.
+    fn datasets<'c>(
+        &'c self,
+        channels: Range<usize>,
+        lines: &'c [([(f64, f64); 2], Style)],
+        theme: &Theme,
+    ) -> Vec<Dataset<'c>> {
+        let preview = self.preview.get(channels.clone()).unwrap_or(&[]);
+        let points = self.points.get(channels.clone()).unwrap_or(&[]);
+        let clipped = self.clipped.get(channels).unwrap_or(&[]);
+
+        preview
+            .iter()
+            .map(|points| self.dataset.clone().style(theme.preview()).data(points))
+            .chain(
+                points
+                    .iter()
+                    .map(|points| self.dataset.clone().style(theme.waveform()).data(points)),
+            )
+            .chain(
+                clipped
+                    .iter()
+                    .filter(|points| !points.is_empty())
+                    .map(|points| {
+                        self.dataset
+                            .clone()
+                            .graph_type(GraphType::Scatter)
+                            .style(theme.clip())
+                            .data(points)
+                    }),
+            )
+            .chain(
+                lines
+                    .iter()
+                    .map(|(line, style)| self.dataset.clone().style(*style).data(line)),
+            )
+            .collect()
+    }
+
+    /// Draw the go to or marker prompt, if one is shown.
+    fn render_prompt<B: Backend>(&self, frame: &mut Frame<'_, B>, area: Rect, theme: &Theme) {
+        let (title, text, error) = match (&self.goto, &self.marking) {
+            (Some((text, error)), _) => (
+                "Go to seconds, mm:ss.mmm, or frame like 44100s",
+                text,
+                error,
+            ),
+            (None, Some((true, text, error))) => ("Rename marker, or clear to delete", text, error),
+            (None, Some((false, text, error))) => ("Marker label", text, error),
+            (None, None) => return,
+        };
+
+        let area = ui::util::centered_rectangle(60, 20, area);
+        frame.render_widget(Clear, area);
+
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .style(theme.base());
+        let mut lines = vec![Spans::from(text.as_str())];
+        if let Some(error) = error {
+            lines.push(Spans::from(Span::styled(error.as_str(), theme.warning())));
+        }
+
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+    }
+
+    /// Draw marker labels along the top of a graph area, starting at their marker lines.
+    fn render_labels<B: Backend>(&self, frame: &mut Frame<'_, B>, graph: Rect, theme: &Theme) {
+        for marker in &self.markers {
+            let column = match self.axes.column(marker.frame as f64, graph.width) {
+                Some(column) if !marker.label.is_empty() => column,
+                _ => continue,
+            };
+            let x = graph.x + column;
+            let length = u16::try_from(marker.label.chars().count()).unwrap_or(u16::MAX);
+            let area = Rect::new(
+                x,
+                graph.y,
+                length.min(graph.right() - x),
+                1.min(graph.height),
+            );
+            frame.render_widget(
+                Paragraph::new(Span::styled(marker.label.as_str(), theme.marker())),
+                area,
+            );
+        }
+    }
+
+    /// Draw time ticks and their labels above a graph area in the row before it.
+    fn render_ruler<B: Backend>(
+        &self,
+        frame: &mut Frame<'_, B>,
+        graph: Rect,
+        row: u16,
+        theme: &Theme,
+    ) {
+        let rate = f64::from(self.sample_rate.max(1));
+        let bounds = self.axes.bounds();
+        let interval = ui::ruler::tick_interval((bounds.1 - bounds.0) / rate, graph.width);
+        let ticks: Vec<(u16, String)> = ui::ruler::ticks(bounds, self.sample_rate, interval)
+            .into_iter()
+            .filter_map(|position| {
+                let column = self.axes.column(position, graph.width)?;
+                Some((column, ui::ruler::tick_label(position / rate, interval)))
+            })
+            .collect();
+
+        frame.render_widget(
+            Paragraph::new(ui::ruler::ruler(&ticks, graph.width)).style(theme.base()),
+            Rect::new(graph.x, row, graph.width, 1),
+        );
+    }
+
+    /// Draw the waveforms of every channel, or a spinner while samples are loading.
+    fn render_signal<B: Backend>(&mut self, frame: &mut Frame<'_, B>, area: Rect, theme: &Theme) {
+        let mut title = vec![Span::raw(self.title.clone())];
+        if let Some(notice) = &self.notice {
+            title.push(Span::styled(format!(" ({})", notice), theme.warning()));
+        }
+        let block = Block::default()
+            .title(Spans::from(title))
+            .borders(Borders::ALL);
+
+        if self.loading {
+            let inner = block.inner(area);
+            let middle = Rect::new(inner.x, inner.y + inner.height / 2, inner.width, 1);
+            let text = format!("{} Loading", SPINNER[self.spinner % SPINNER.len()]);
+            self.spinner = self.spinner.wrapping_add(1);
+
+            frame.render_widget(block.style(theme.base()), area);
+            frame.render_widget(
+                Paragraph::new(text)
+                    .alignment(Alignment::Center)
+                    .style(theme.base()),
+                middle,
+            );
+            return;
+        }
+
+        let line = |position: f64| [(position, -1.0_f64), (position, 1.0_f64)];
+        let mut lines: Vec<_> = self
+            .markers
+            .iter()
+            .map(|marker| (line(marker.frame as f64), theme.marker()))
+            .collect();
+        lines.push((line(self.playhead), theme.playhead()));
+        if let Some(selection) = self.selection {
+            for frame in [selection.start, selection.end] {
+                lines.push((line(frame as f64), theme.selection()));
+            }
+        }
+
+        // The first row inside the border holds the time ruler above the graphs.
+        let inner = block.inner(area);
+        let plots = Rect::new(
+            inner.x,
+            inner.y + 1.min(inner.height),
+            inner.width,
+            inner.height.saturating_sub(1),
+        );
+        frame.render_widget(block.style(theme.base()), area);
+
+        if !self.split {
+            let graph = self.axes.graph_area(plots);
+            let (x_axis, y_axis) = self.axes.axes();
+            let chart =
+                tui::widgets::Chart::new(self.datasets(0..self.points.len(), &lines, theme))
+                    .style(theme.base())
+                    .x_axis(x_axis)
+                    .y_axis(y_axis);
+
+            frame.render_widget(chart, plots);
+            if inner.height > 0 {
+                self.render_ruler(frame, graph, inner.y, theme);
+            }
+            self.render_labels(frame, graph, theme);
+            self.graph = graph;
+            return;
+        }
+
+        let rectangles = lanes(plots, self.points.len());
+
+        let mut graphs = Vec::with_capacity(rectangles.len());
+        for (channel, rectangle) in rectangles.iter().enumerate() {
+            let block = Block::default().title(lane_label(channel, self.points.len()));
+            graphs.push(self.axes.graph_area(block.inner(*rectangle)));
+
+            let (x_axis, y_axis) = self.axes.axes();
+            let datasets = self.datasets(channel..channel + 1, &lines, theme);
+            let chart = tui::widgets::Chart::new(datasets)
+                .block(block)
+                .style(theme.base())
+                .x_axis(x_axis)
+                .y_axis(y_axis);
+            frame.render_widget(chart, *rectangle);
+        }
+
+        // Clicks in any lane map to the same frames, so the graph spans all lanes.
+        if let (Some(first), Some(last)) = (graphs.first(), graphs.last()) {
+            if inner.height > 0 {
+                self.render_ruler(frame, *first, inner.y, theme);
+            }
+            self.render_labels(frame, *first, theme);
+            self.graph = Rect::new(first.x, first.y, first.width, last.bottom() - first.y);
+        }
+    }
+
+    /// Handle key events while the go to prompt is shown.
+    fn key_event_goto(&mut self, event: KeyEvent) {
+        let (text, error) = match &mut self.goto {
+            Some(goto) => goto,
+            None => return,
+        };
+
+        match event.code {
+            KeyCode::Backspace | KeyCode::Delete => {
+                text.pop();
+            }
+            KeyCode::Enter => match ui::util::parse_position(text, self.sample_rate) {
+                Ok(frame) => {
+                    self.jump = Some(frame);
+                    self.goto = None;
+                }
+                Err(report) => *error = Some(format!("{}", report)),
+            },
+            KeyCode::Esc => self.goto = None,
+            KeyCode::Char(char) => text.push(char),
+            _ => (),
+        }
+    }
+
+    /// Handle key events while the marker prompt is shown.
+    fn key_event_marking(&mut self, event: KeyEvent) {
+        let (rename, text, _error) = match &mut self.marking {
+            Some(marking) => marking,
+            None => return,
+        };
+
+        match event.code {
+            KeyCode::Backspace | KeyCode::Delete => {
+                text.pop();
+            }
+            KeyCode::Enter => {
+                self.mark = Some((*rename, text.trim().to_owned()));
+                self.marking = None;
+            }
+            KeyCode::Esc => self.marking = None,
+            KeyCode::Char(char) => text.push(char),
+            _ => (),
+        }
+    }
+
+    /// Compute plot points for a signal.
+    ///
+    /// Signals with more than [`MAX_POINTS`] frames are reduced to the minimum and maximum sample
+    /// of evenly sized buckets within the visible bounds, so only visible frames are read. Buckets
+    /// are read from peaks computed from the same signal instead, if they are given.
+    pub fn plot(&mut self, signal: &dyn Signal, peaks: Option<&Peaks>) {
+        let frames = signal.frames();
+        // Keep showing the whole signal when its length changes, unless zoomed in on part of it.
+        let whole = self.axes.bounds() == (0.0, self.frame_count as f64);
+        if frames != self.frame_count && (whole || self.frame_count == 0) {
+            self.axes.fit(frames as f64);
+        }
+        self.frame_count = frames;
+        self.axes.set_decibel_floor(self.floor().map(f64::from));
+        self.points = points(signal, self.axes.bounds(), self.floor(), peaks);
+        self.clipped = clipped_points(&self.points, self.clip_threshold, self.floor());
+    }
+
+    /// Move the playhead to the next or previous marker.
+    ///
+    /// The playhead stays put if there is no marker in the direction.
+    fn seek_marker(tab: &mut Tab, forward: bool) {
+        let markers = &tab.samples.markers;
+        let target = if forward {
+            markers.iter().find(|marker| marker.frame > tab.playhead)
+        } else {
+            markers
+                .iter()
+                .rev()
+                .find(|marker| marker.frame < tab.playhead)
+        };
+
+        if let Some(marker) = target {
+            tab.playhead = marker.frame;
+        }
+    }
+
+    /// Move the playhead to the start of the next or previous clipped region of samples.
+    ///
+    /// Clipped samples closer than [`CLIP_GAP`] seconds belong to the same region. The playhead
+    /// stays put if there is no region in the direction.
+    fn seek_clip(&self, tab: &mut Tab, forward: bool) {
+        let gap = (CLIP_GAP * f64::from(tab.samples.sample_rate)) as usize;
+        let regions = dsp::clip_regions(&tab.samples, self.clip_threshold, gap);
+        let target = if forward {
+            regions.iter().find(|region| region.start > tab.playhead)
+        } else {
+            regions
+                .iter()
+                .rev()
+                .find(|region| region.start < tab.playhead)
+        };
+
+        if let Some(region) = target {
+            tab.playhead = region.start;
+        }
+    }
 }
 
 impl<'a, B: Backend> View<B> for Chart<'a> {
+    fn captures_input(&self) -> bool {
+        self.goto.is_some() || self.marking.is_some()
+    }
+
     fn key_event(&mut self, event: KeyEvent) {
-        self.axes.key_event(event);
+        self.notice = None;
+        if self.goto.is_some() {
+            self.key_event_goto(event);
+            return;
+        }
+        if self.marking.is_some() {
+            self.key_event_marking(event);
+            return;
+        }
+
+        // Clipped regions and markers are reached with a bracket key followed by c or m, like ]c
+        // and [m.
+        if let Some(bracket) = self.bracket.take() {
+            match event.code {
+                KeyCode::Char('c') => {
+                    self.seek_clip = Some(bracket == ']');
+                    return;
+                }
+                KeyCode::Char('m') => {
+                    self.seek_marker = Some(bracket == ']');
+                    return;
+                }
+                _ => (),
+            }
+        }
+
+        match event.code {
+            KeyCode::Char(bracket @ ('[' | ']')) => self.bracket = Some(bracket),
+            KeyCode::Char('d') => self.decibel = !self.decibel,
+            KeyCode::Char('g' | 't') => self.goto = Some((String::new(), None)),
+            KeyCode::Char('l') => self.split = !self.split,
+            KeyCode::Char('m') => self.marking = Some((false, String::new(), None)),
+            KeyCode::Char('M') => {
+                // The playhead is drawn at a whole frame, so truncation is not possible.
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                let playhead = self.playhead as usize;
+                self.marking = Some(
+                    match self.markers.iter().find(|marker| marker.frame == playhead) {
+                        Some(marker) => (true, marker.label.clone(), None),
+                        None => (
+                            true,
+                            String::new(),
+                            Some(String::from("There is no marker at the playhead")),
+                        ),
+                    },
+                );
+            }
+            _ => self.axes.key_event(event),
+        }
     }
 
-    fn process(&mut self, buffer: &mut Samples) -> eyre::Result<()> {
-        let channels: usize = buffer.channels.into();
-        let frame_count = buffer.data.len() / channels;
+    fn mouse_event(&mut self, event: MouseEvent, tab: &mut Tab) {
+        let inside = ui::util::contains(self.graph, event.column, event.row);
+        let zoom = event.modifiers.contains(KeyModifiers::CONTROL);
 
-        let points = (0..frame_count)
-            .map(|index| (index as f64, 0.0_f64))
-            .collect();
-        self.points = vec![points; channels];
+        match event.kind {
+            MouseEventKind::Down(MouseButton::Left) if inside => {
+                let frame = self
+                    .axes
+                    .frame_at(self.graph, event.column, self.frame_count);
+                tab.playhead = frame;
+                tab.selection = None;
+                self.anchor = Some(frame);
+            }
+            MouseEventKind::Drag(MouseButton::Left) => {
+                if let Some(anchor) = self.anchor {
+                    let selection = Selection::new(
+                        anchor,
+                        self.axes
+                            .frame_at(self.graph, event.column, self.frame_count),
+                    );
+                    tab.selection = (!selection.is_empty()).then_some(selection);
+                }
+            }
+            MouseEventKind::Up(MouseButton::Left) => self.anchor = None,
+            MouseEventKind::ScrollDown if zoom => self.axes.magnify(false),
+            MouseEventKind::ScrollUp if zoom => self.axes.magnify(true),
+            MouseEventKind::ScrollDown => self.axes.scroll(true),
+            MouseEventKind::ScrollUp => self.axes.scroll(false),
+            _ => (),
+        }
+    }
+
+    fn process(&mut self, tab: &mut Tab) -> eyre::Result<()> {
+        if self.tab != Some(tab.id()) {
+            self.tab = Some(tab.id());
+            // Bounds kept with the tab belong to its length, so they are not refit to it.
+            if let Some(axes) = &tab.zoom {
+                self.axes = axes.clone();
+                self.frame_count = tab.frames();
+            }
+        }
+        if let Some(frame) = self.jump.take() {
+            let frames = tab.frames();
+            if frame > frames {
+                self.notice = Some(format!(
+                    "Moved to the end at {}",
+                    ui::util::timecode(frames, tab.samples.sample_rate)
+                ));
+            }
+            tab.playhead = frame.min(frames);
+            self.axes.center(tab.playhead as f64);
+        }
+        if let Some(forward) = self.seek_clip.take() {
+            self.seek_clip(tab, forward);
+            self.axes.reveal(tab.playhead as f64);
+        }
+        if let Some(forward) = self.seek_marker.take() {
+            Self::seek_marker(tab, forward);
+            self.axes.reveal(tab.playhead as f64);
+        }
+        if let Some((rename, label)) = self.mark.take() {
+            if rename {
+                tab.rename_marker(label)?;
+            } else {
+                tab.add_marker(label)?;
+            }
+        }
+        tab.zoom = Some(self.axes.clone());
+        self.sample_rate = tab.samples.sample_rate;
 
-        for (outer_index, points) in self.points.iter_mut().enumerate() {
-            for (inner_index, element) in points.iter_mut() {
-                // Variable inner_index should always be positive, so sign loss should not be
-                // possible.
-                #[allow(clippy::cast_sign_loss)]
-                let index = channels * (*inner_index as usize) + outer_index;
-                *element = buffer.data[index].into();
+        self.title = tab.name().to_owned();
+        self.playhead = tab.playhead as f64;
+        self.selection = tab.selection;
+        self.markers.clone_from(&tab.samples.markers);
+        self.loading = tab.is_loading();
+        if let Some(windowed) = &mut tab.windowed {
+            // Zoomed out views of windowed files are drawn from their peaks alone.
+            let bounds = self.axes.bounds();
+            windowed.fetch_visible(bounds)?;
+            self.plot(&windowed.window, Some(&windowed.peaks));
+            // Plotting fits the axes to newly opened files, which may need other frames.
+            if self.axes.bounds() != bounds {
+                windowed.fetch_visible(self.axes.bounds())?;
+                self.plot(&windowed.window, Some(&windowed.peaks));
             }
+        } else {
+            // Peaks are only worth computing for signals that are decimated.
+            if tab.samples.frames() > MAX_POINTS
+                && !tab
+                    .peaks
+                    .as_ref()
+                    .is_some_and(|peaks| peaks.fits(&tab.samples))
+            {
+                tab.peaks = Some(Peaks::new(&tab.samples));
+            }
+            self.plot(&tab.samples, tab.peaks.as_ref());
         }
+        self.preview = match &tab.preview {
+            Some(preview) if !tab.preview_hidden => {
+                points(preview, self.axes.bounds(), self.floor(), None)
+            }
+            _ => Vec::new(),
+        };
 
         Ok(())
     }
 
-    fn render<'b>(&mut self, frame: &mut Frame<'b, B>, area: Rect) {
-        let block = Block::default()
-            .title(self.title.as_str())
-            .borders(Borders::ALL);
+    fn render<'b>(&mut self, frame: &mut Frame<'b, B>, area: Rect, theme: &Theme) {
+        self.render_signal(frame, area, theme);
+        self.render_prompt(frame, area, theme);
+    }
 
-        let datasets = self
-            .points
-            .iter()
-            .map(|points| self.dataset.clone().data(points))
+    fn reset(&mut self) {
+        self.bracket = None;
+        self.goto = None;
+        self.mark = None;
+        self.marking = None;
+        self.notice = None;
+    }
+}
+
+/// Map the magnitude of a sample to a height between zero at a decibel floor and one at full
+/// scale.
+///
+/// Levels are linear in decibels between the floor and full scale. Silence, levels below the
+/// floor, and NaN samples map to zero, and samples beyond full scale map to one.
+#[must_use]
+pub fn decibel_height(sample: f32, floor: f32) -> f64 {
+    let level = 20.0 * f64::from(sample.abs()).log10();
+    let floor = f64::from(floor);
+
+    if level > floor {
+        (1.0 - level / floor).min(1.0)
+    } else {
+        0.0
+    }
+}
+
+/// Compute plot points of every channel of a signal within horizontal bounds.
+///
+/// With a decibel floor, each frame or bucket is drawn as a column from the bottom of the plot
+/// up to the [`decibel_height`] of its largest magnitude. Peaks of the signal with blocks at least
+/// as large as the buckets replace them, at up to twice the bucket size.
+fn points(
+    signal: &dyn Signal,
+    bounds: (f64, f64),
+    floor: Option<f32>,
+    peaks: Option<&Peaks>,
+) -> Vec<Vec<(f64, f64)>> {
+    let channels = usize::from(signal.channels());
+    let frame_count = signal.frames();
+    let column = |position: f64, low: f32, high: f32| match floor {
+        Some(floor) => {
+            let height = decibel_height(low.abs().max(high.abs()), floor);
+            [(position, -1.0), (position, 2.0f64.mul_add(height, -1.0))]
+        }
+        None => [(position, low.into()), (position, high.into())],
+    };
+
+    if frame_count <= MAX_POINTS {
+        return (0..channels)
+            .map(|channel| match floor {
+                Some(_) => (0..frame_count)
+                    .flat_map(|index| {
+                        let sample = signal.sample(index, channel);
+                        column(index as f64, sample, sample)
+                    })
+                    .collect(),
+                None => (0..frame_count)
+                    .map(|index| (index as f64, signal.sample(index, channel).into()))
+                    .collect(),
+            })
             .collect();
+    }
 
-        let (x_axis, y_axis) = self.axes.axes();
-        let chart = tui::widgets::Chart::new(datasets)
-            .block(block)
-            .x_axis(x_axis)
-            .y_axis(y_axis);
+    // Bounds are clamped to the frame range, so sign loss and truncation are not possible.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let (start, end) = (
+        (bounds.0.max(0.0) as usize).min(frame_count),
+        (bounds.1.max(0.0).ceil() as usize).min(frame_count),
+    );
+    let buckets = MAX_POINTS / 2;
+    let step = ((end - start) / buckets).max(1);
 
-        frame.render_widget(chart, area);
+    // Aligning the start to a block adds a bucket, so peaks are read for one bucket less.
+    let cached = peaks.filter(|peaks| peaks.fits(signal)).and_then(|peaks| {
+        let step = ((end - start) / (buckets - 1)).max(1);
+        peaks.level(step).map(|level| (peaks, level))
+    });
+    if let Some((peaks, level)) = cached {
+        let size = Peaks::block_size(level);
+        return (0..channels)
+            .map(|channel| {
+                (start / size..end.div_ceil(size))
+                    .filter_map(|index| {
+                        peaks
+                            .block(level, index, channel)
+                            .map(|block| (index, block))
+                    })
+                    .flat_map(|(index, (low, high))| column((index * size) as f64, low, high))
+                    .collect()
+            })
+            .collect();
+    }
+
+    (0..channels)
+        .map(|channel| {
+            let mut points = Vec::with_capacity(MAX_POINTS);
+            for bucket in (start..end).step_by(step) {
+                let (low, high) = (bucket..(bucket + step).min(end))
+                    .map(|index| signal.sample(index, channel))
+                    .fold((f32::MAX, f32::MIN), |(low, high), sample| {
+                        (low.min(sample), high.max(sample))
+                    });
+                points.extend(column(bucket as f64, low, high));
+            }
+            points
+        })
+        .collect()
+}
+
+/// Select the plot points of every channel that reach a clipping threshold.
+///
+/// Linear points are clipped if their magnitude is at least the threshold. With a decibel floor,
+/// the tops of columns are clipped if they reach the height of the threshold.
+fn clipped_points(
+    points: &[Vec<(f64, f64)>],
+    threshold: f32,
+    floor: Option<f32>,
+) -> Vec<Vec<(f64, f64)>> {
+    let clips = |value: f64| match floor {
+        Some(floor) => {
+            value > -1.0 && value >= 2.0f64.mul_add(decibel_height(threshold, floor), -1.0)
+        }
+        None => value.abs() >= f64::from(threshold),
+    };
+
+    points
+        .iter()
+        .map(|points| {
+            points
+                .iter()
+                .copied()
+                .filter(|point| clips(point.1))
+                .collect()
+        })
+        .collect()
+}
+
+/// Get the label of a channel lane, using left and right for stereo signals.
+fn lane_label(channel: usize, channels: usize) -> String {
+    match (channels, channel) {
+        (2, 0) => String::from("L"),
+        (2, 1) => String::from("R"),
+        _ => format!("{}", channel + 1),
+    }
+}
+
+/// Divide an area into horizontal lanes of even height, one per channel.
+///
+/// Rows that do not divide evenly are given to the topmost lanes.
+#[must_use]
+pub fn lanes(area: Rect, channels: usize) -> Vec<Rect> {
+    let count = u16::try_from(channels).unwrap_or(u16::MAX).max(1);
+    let (height, remainder) = (area.height / count, area.height % count);
+
+    let mut top = area.y;
+    (0..count)
+        .map(|index| {
+            let lane_height = height + u16::from(index < remainder);
+            let lane = Rect::new(area.x, top, area.width, lane_height);
+            top += lane_height;
+            lane
+        })
+        .collect()
+}
+
+/// Part of a cell in a rendered waveform.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Ink {
+    Background,
+    Wave,
+    Zero,
+}
+
+impl Ink {
+    /// Get the index of the ink in tables of colors or characters.
+    const fn index(self) -> usize {
+        match self {
+            Self::Background => 0,
+            Self::Zero => 1,
+            Self::Wave => 2,
+        }
+    }
+}
+
+/// Draw the waveforms of samples into a grid of cells, one lane of even height per channel.
+///
+/// Lanes span full scale from -1 at the bottom to 1 at the top, as in the chart, and each column
+/// covers the range between the smallest and largest sample of its frames.
+fn raster(samples: &Samples, width: usize, height: usize) -> Vec<Vec<Ink>> {
+    let mut grid = vec![vec![Ink::Background; width]; height];
+    let channels = usize::from(samples.channels.max(1));
+    let frames = samples.frames();
+    let (lane_height, remainder) = (height / channels, height % channels);
+
+    let mut top = 0;
+    for channel in 0..channels {
+        let rows = lane_height + usize::from(channel < remainder);
+        if rows == 0 {
+            continue;
+        }
+        // Amplitudes are clamped to full scale, so rows stay within the lane.
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let row = |amplitude: f32| {
+            let fraction = (1.0 - f64::from(amplitude.clamp(-1.0, 1.0))) / 2.0;
+            top + (fraction * (rows - 1) as f64).round() as usize
+        };
+
+        for cell in &mut grid[row(0.0)] {
+            *cell = Ink::Zero;
+        }
+        for column in 0..width {
+            let start = column * frames / width;
+            let end = ((column + 1) * frames / width).max(start + 1).min(frames);
+            if start >= end {
+                continue;
+            }
+            let (low, high) = (start..end).fold((f32::MAX, f32::MIN), |(low, high), frame| {
+                let sample = samples.sample(frame, channel);
+                (low.min(sample), high.max(sample))
+            });
+            for cells in &mut grid[row(high)..=row(low)] {
+                cells[column] = Ink::Wave;
+            }
+        }
+        top += rows;
     }
+    grid
+}
+
+/// Render the waveforms of samples to a PNG image, one lane per channel, for sharing them without
+/// the audio.
+///
+/// # Errors
+///
+/// Will return `Err` if either dimension is zero or `path` is unwritable.
+pub fn render_to_png(samples: &Samples, width: u32, height: u32, path: &Path) -> eyre::Result<()> {
+    let (width, height) = (usize::try_from(width)?, usize::try_from(height)?);
+    let pixels: Vec<u8> = raster(samples, width, height)
+        .into_iter()
+        .flatten()
+        .flat_map(|ink| IMAGE_COLORS[ink.index()])
+        .collect();
+    png::write_rgb(path, width, height, &pixels)
+}
 
-    fn reset(&mut self) {}
+/// Render the waveforms of samples as lines of ASCII art, one lane per channel, for embedding
+/// them in text.
+#[must_use]
+pub fn render_to_ascii(samples: &Samples, width: usize, height: usize) -> String {
+    raster(samples, width, height)
+        .into_iter()
+        .map(|row| {
+            let mut line: String = row
+                .into_iter()
+                .map(|ink| ASCII_CHARACTERS[ink.index()])
+                .collect();
+            line.push('\n');
+            line
+        })
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::io::audio;
+    use crate::io::window::{Windowed, WINDOW_FRAMES};
+    use crate::util;
+    use std::fs;
+    use std::sync::atomic::AtomicBool;
     use tui::backend::TestBackend;
+    use tui::Terminal;
 
     #[test]
     fn new_points() {
@@ -108,19 +922,616 @@ mod tests {
         assert_eq!(chart.points, expected);
     }
 
+    #[test]
+    fn drag_normalizes_selection() {
+        let mut tab = Tab::new(Samples::new(1, 20, vec![0.0; 100]), None);
+        let mut chart = Chart::new(String::new(), 1, 100);
+        View::<TestBackend>::process(&mut chart, &mut tab).unwrap();
+
+        let mut terminal = Terminal::new(TestBackend::new(60, 20)).unwrap();
+        terminal
+            .draw(|frame| View::render(&mut chart, frame, frame.size(), &Theme::default()))
+            .unwrap();
+
+        let mut mouse = |kind, column| {
+            let event = MouseEvent {
+                kind,
+                column,
+                row: 5,
+                modifiers: KeyModifiers::NONE,
+            };
+            View::<TestBackend>::mouse_event(&mut chart, event, &mut tab);
+        };
+
+        // Plot starts at column 7 and is 52 columns wide for 100 frames.
+        mouse(MouseEventKind::Down(MouseButton::Left), 40);
+        mouse(MouseEventKind::Drag(MouseButton::Left), 2);
+        mouse(MouseEventKind::Up(MouseButton::Left), 2);
+
+        assert_eq!(tab.playhead, 64);
+        assert_eq!(tab.selection, Some(Selection { start: 0, end: 64 }));
+
+        let mut mouse = |kind, column| {
+            let event = MouseEvent {
+                kind,
+                column,
+                row: 5,
+                modifiers: KeyModifiers::NONE,
+            };
+            View::<TestBackend>::mouse_event(&mut chart, event, &mut tab);
+        };
+        mouse(MouseEventKind::Down(MouseButton::Left), 20);
+        mouse(MouseEventKind::Drag(MouseButton::Left), 59);
+
+        assert_eq!(
+            tab.selection,
+            Some(Selection {
+                start: 26,
+                end: 100
+            })
+        );
+    }
+
+    #[test]
+    fn scroll_pans_and_zooms() {
+        let mut tab = Tab::default();
+        let mut chart = Chart::new(String::new(), 1, 100);
+        let mut scroll = |modifiers| {
+            let event = MouseEvent {
+                kind: MouseEventKind::ScrollUp,
+                column: 0,
+                row: 0,
+                modifiers,
+            };
+            View::<TestBackend>::mouse_event(&mut chart, event, &mut tab);
+        };
+
+        scroll(KeyModifiers::NONE);
+        scroll(KeyModifiers::CONTROL);
+        assert_eq!(chart.axes.bounds(), (15.0, 65.0));
+    }
+
+    #[test]
+    fn render_spinner_while_loading() {
+        let folder = tempfile::tempdir().unwrap();
+        let mut tab = Tab::load(folder.path().join("missing.wav"));
+        let mut chart = Chart::new(String::new(), 1, 0);
+        View::<TestBackend>::process(&mut chart, &mut tab).unwrap();
+
+        let mut terminal = Terminal::new(TestBackend::new(40, 12)).unwrap();
+        terminal
+            .draw(|frame| View::render(&mut chart, frame, frame.size(), &Theme::default()))
+            .unwrap();
+
+        let actual = util::test::buffer_view(terminal.backend().buffer());
+        assert!(actual.contains("⠋ Loading"));
+    }
+
+    #[test]
+    fn render_uses_theme_colors() {
+        let samples = Samples::new(1, 20, vec![-1.0, 0.0, 1.0, 0.0, -1.0, 0.0, 1.0, 0.0]);
+        let mut tab = Tab::new(samples, None);
+        let mut chart = Chart::new(String::new(), 1, 8);
+        View::<TestBackend>::process(&mut chart, &mut tab).unwrap();
+
+        for theme in [Theme::dark(), Theme::light()] {
+            let mut terminal = Terminal::new(TestBackend::new(40, 12)).unwrap();
+            terminal
+                .draw(|frame| View::render(&mut chart, frame, frame.size(), &theme))
+                .unwrap();
+
+            let buffer = terminal.backend().buffer();
+            assert!(buffer.content.iter().any(|cell| cell.fg == theme.waveform));
+            assert!(buffer
+                .content
+                .iter()
+                .all(|cell| cell.bg == theme.background));
+        }
+    }
+
+    #[test]
+    fn plot_decimates_long_signals() {
+        let frames = MAX_POINTS * 4;
+        let data = (0..frames)
+            .map(|index| if index % 2 == 0 { -0.5 } else { 0.75 })
+            .collect();
+        let samples = Samples::new(1, 20, data);
+        let mut chart = Chart::new(String::from(""), 1, frames);
+
+        chart.plot(&samples, None);
+        assert_eq!(chart.points.len(), 1);
+        assert!(chart.points[0].len() <= MAX_POINTS);
+        assert!(chart.points[0]
+            .chunks(2)
+            .all(|pair| pair[0].1 == -0.5 && pair[1].1 == 0.75));
+    }
+
+    #[test]
+    fn plot_reads_peaks() {
+        let frames = MAX_POINTS * 10;
+        let data = (0..frames * 2)
+            .map(|index| ((index * 7919) % 201) as f32 / 100.0 - 1.0)
+            .collect();
+        let samples = Samples::new(2, 20, data);
+        let peaks = Peaks::new(&samples);
+        let mut chart = Chart::new(String::new(), 2, frames);
+
+        chart.plot(&samples, Some(&peaks));
+        let size = chart.points[0][2].0 as usize;
+        assert!(size >= frames / (MAX_POINTS / 2));
+        for (channel, points) in chart.points.iter().enumerate() {
+            assert!(points.len() <= MAX_POINTS);
+            for pair in points.chunks(2) {
+                let start = pair[0].0 as usize;
+                let (low, high) = (start..(start + size).min(frames))
+                    .map(|frame| samples.sample(frame, channel))
+                    .fold((f32::MAX, f32::MIN), |(low, high), sample| {
+                        (low.min(sample), high.max(sample))
+                    });
+                assert_eq!(
+                    pair,
+                    [(start as f64, low.into()), (start as f64, high.into())]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn render_waveform_as_ascii_and_png() {
+        let samples = Samples::new(
+            2,
+            8000,
+            vec![
+                1.0, 0.0, 0.5, 0.0, -1.0, 0.25, 0.0, -0.25, 0.25, 0.0, -0.25, 0.0,
+            ],
+        );
+        let expected = [
+            "#     ", " #  # ", "---#-#", "  #   ", "      ", "  #   ", "##-###", "      ",
+        ];
+        assert_eq!(render_to_ascii(&samples, 6, 8), expected.join("\n") + "\n");
+        assert_eq!(render_to_ascii(&samples, 3, 1), "###\n");
+        assert_eq!(render_to_ascii(&Samples::default(), 4, 1), "----\n");
+
+        let folder = tempfile::tempdir().unwrap();
+        let path = folder.path().join("take.png");
+        render_to_png(&samples, 6, 8, &path).unwrap();
+        let pixels = raster(&samples, 6, 8);
+        assert_eq!(
+            fs::read(&path).unwrap(),
+            png::encode_rgb(
+                6,
+                8,
+                &pixels
+                    .into_iter()
+                    .flatten()
+                    .flat_map(|ink| IMAGE_COLORS[ink.index()])
+                    .collect::<Vec<u8>>()
+            )
+            .unwrap()
+        );
+        assert!(render_to_png(&samples, 0, 8, &path).is_err());
+    }
+
+    #[test]
+    fn process_plots_windowed_files() {
+        let folder = tempfile::tempdir().unwrap();
+        let path = folder.path().join("long.wav");
+        let frames = WINDOW_FRAMES + 100_000;
+        let data = (0..frames)
+            .map(|index| ((index * 7919) % 201) as f32 / 100.0 - 1.0)
+            .collect();
+        let samples = Samples::new(1, 8000, data);
+        audio::write_samples(&path, &samples).unwrap();
+
+        let mut tab = Tab::new(Samples::new(1, 8000, Vec::new()), Some(path.clone()));
+        tab.windowed =
+            Some(Windowed::open(&path, &mut |_fraction| (), &AtomicBool::new(false)).unwrap());
+        let mut chart = Chart::new(String::new(), 1, 0);
+
+        View::<TestBackend>::process(&mut chart, &mut tab).unwrap();
+        let windowed = tab.windowed.as_ref().unwrap();
+        assert_eq!(chart.frame_count, frames);
+        assert!(windowed.window.loaded().is_empty());
+        assert!(chart.points[0].len() <= MAX_POINTS);
+        assert!(chart.points[0].iter().any(|point| point.1 == 1.0));
+
+        chart.axes.center(1_000_000.0);
+        while chart.axes.bounds().1 - chart.axes.bounds().0 > (MAX_POINTS / 2) as f64 {
+            chart.axes.magnify(true);
+        }
+        View::<TestBackend>::process(&mut chart, &mut tab).unwrap();
+        let windowed = tab.windowed.as_ref().unwrap();
+        assert!(windowed.window.loaded().contains(&1_000_000));
+        let (start, _value) = chart.points[0][0];
+        // Truncation is not possible for plotted frame positions.
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let start = start as usize;
+        assert_eq!(chart.points[0][0].1, f64::from(samples.sample(start, 0)));
+
+        assert!(tab.insert_silence(10.0).is_err());
+        assert!(tab.save().is_err());
+        assert_eq!(tab.frames(), frames);
+    }
+
+    #[test]
+    fn process_rebuilds_peaks_after_crop() {
+        let frames = MAX_POINTS * 4;
+        let data = (0..frames)
+            .map(|index| if index < 100 { 0.5 } else { -0.25 })
+            .collect();
+        let mut tab = Tab::new(Samples::new(1, 20, data), None);
+        let mut chart = Chart::new(String::new(), 1, frames);
+
+        View::<TestBackend>::process(&mut chart, &mut tab).unwrap();
+        assert!(tab
+            .peaks
+            .as_ref()
+            .is_some_and(|peaks| peaks.fits(&tab.samples)));
+
+        tab.apply(|samples| {
+            *samples = samples.slice(Selection::new(MAX_POINTS, 3 * MAX_POINTS));
+            Ok(())
+        })
+        .unwrap();
+        assert!(tab.peaks.is_none());
+
+        View::<TestBackend>::process(&mut chart, &mut tab).unwrap();
+        assert!(tab
+            .peaks
+            .as_ref()
+            .is_some_and(|peaks| peaks.fits(&tab.samples)));
+        assert!(chart.points[0].iter().all(|point| point.1 == -0.25));
+    }
+
+    #[test]
+    fn decibel_height_between_floor_and_full_scale() {
+        assert!((decibel_height(1.0, -60.0) - 1.0).abs() < 1e-9);
+        assert!((decibel_height(-1.0, -60.0) - 1.0).abs() < 1e-9);
+        assert!((decibel_height(2.0, -60.0) - 1.0).abs() < 1e-9);
+        assert!(decibel_height(0.001, -60.0).abs() < 1e-6);
+        assert!((decibel_height(0.031_622_78, -60.0) - 0.5).abs() < 1e-6);
+
+        for sample in [0.0, -0.0, 1e-9, f32::NAN] {
+            let height = decibel_height(sample, -60.0);
+            assert!(height == 0.0, "height {} for {}", height, sample);
+        }
+    }
+
+    #[test]
+    fn plot_decibel_columns() {
+        let samples = Samples::new(1, 20, vec![1.0, 0.0, -0.031_622_78]);
+        let mut chart = Chart::new(String::new(), 1, 3);
+        View::<TestBackend>::key_event(
+            &mut chart,
+            KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE),
+        );
+
+        chart.plot(&samples, None);
+        let heights: Vec<f64> = chart.points[0].iter().map(|point| point.1).collect();
+        assert!(chart.points[0]
+            .chunks(2)
+            .all(|pair| pair[0] == (pair[1].0, -1.0)));
+        assert!([1.0, -1.0, 0.0]
+            .iter()
+            .zip(heights.iter().skip(1).step_by(2))
+            .all(|(expected, actual)| (expected - actual).abs() < 1e-6));
+    }
+
+    #[test]
+    fn plot_clipped_points() {
+        let samples = Samples::new(2, 20, vec![1.0, 0.5, -0.2, -0.95, 0.0, 0.9]);
+        let mut chart = Chart::new(String::new(), 2, 3).with_clip_threshold(0.9);
+
+        chart.plot(&samples, None);
+        assert_eq!(
+            chart.clipped,
+            vec![
+                vec![(0.0, 1.0)],
+                vec![(1.0, f64::from(-0.95_f32)), (2.0, f64::from(0.9_f32))]
+            ]
+        );
+
+        View::<TestBackend>::key_event(
+            &mut chart,
+            KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE),
+        );
+        chart.plot(&samples, None);
+        assert_eq!(chart.clipped[0].len(), 1);
+        assert_eq!(chart.clipped[1].len(), 2);
+        assert!(chart.clipped.iter().flatten().all(|point| point.1 > -1.0));
+    }
+
+    #[test]
+    fn bracket_c_jumps_between_clipped_regions() {
+        let mut data = vec![0.0; 100];
+        for frame in [10, 11, 40, 80] {
+            data[frame] = 1.0;
+        }
+        let mut tab = Tab::new(Samples::new(1, 1000, data), None);
+        let mut chart = Chart::new(String::new(), 1, 100);
+        let press = |chart: &mut Chart, keys: &str| {
+            for char in keys.chars() {
+                View::<TestBackend>::key_event(chart, KeyEvent::from(KeyCode::Char(char)));
+            }
+        };
+
+        for expected in [10, 40, 80, 80] {
+            press(&mut chart, "]c");
+            View::<TestBackend>::process(&mut chart, &mut tab).unwrap();
+            assert_eq!(tab.playhead, expected);
+        }
+        for expected in [40, 10, 10] {
+            press(&mut chart, "[c");
+            View::<TestBackend>::process(&mut chart, &mut tab).unwrap();
+            assert_eq!(tab.playhead, expected);
+        }
+
+        // Other keys after a bracket keep their meaning and cancel the jump.
+        press(&mut chart, "]dc");
+        View::<TestBackend>::process(&mut chart, &mut tab).unwrap();
+        assert_eq!(tab.playhead, 10);
+        assert!(chart.decibel);
+    }
+
+    #[test]
+    fn bracket_m_jumps_between_markers() {
+        let mut tab = Tab::new(Samples::new(1, 1000, vec![0.0; 100]), None);
+        tab.samples.markers = vec![
+            dsp::Marker::new(20, String::from("verse")),
+            dsp::Marker::new(60, String::from("chorus")),
+        ];
+        let mut chart = Chart::new(String::new(), 1, 100);
+        let press = |chart: &mut Chart, keys: &str| {
+            for char in keys.chars() {
+                View::<TestBackend>::key_event(chart, KeyEvent::from(KeyCode::Char(char)));
+            }
+        };
+
+        for expected in [20, 60, 60] {
+            press(&mut chart, "]m");
+            View::<TestBackend>::process(&mut chart, &mut tab).unwrap();
+            assert_eq!(tab.playhead, expected);
+        }
+        press(&mut chart, "[m");
+        View::<TestBackend>::process(&mut chart, &mut tab).unwrap();
+        assert_eq!(tab.playhead, 20);
+    }
+
+    #[test]
+    fn marker_prompts_add_rename_and_delete() {
+        let mut tab = Tab::new(Samples::new(1, 1000, vec![0.0; 100]), None);
+        tab.playhead = 30;
+        let mut chart = Chart::new(String::new(), 1, 100);
+        let press = |chart: &mut Chart, code: KeyCode| {
+            View::<TestBackend>::key_event(chart, KeyEvent::from(code));
+        };
+        let type_text = |chart: &mut Chart, text: &str| {
+            for char in text.chars() {
+                press(chart, KeyCode::Char(char));
+            }
+        };
+        View::<TestBackend>::process(&mut chart, &mut tab).unwrap();
+
+        press(&mut chart, KeyCode::Char('M'));
+        assert!(matches!(chart.marking, Some((true, _, Some(_)))));
+        press(&mut chart, KeyCode::Esc);
+
+        type_text(&mut chart, "mbad click");
+        assert!(View::<TestBackend>::captures_input(&chart));
+        press(&mut chart, KeyCode::Enter);
+        View::<TestBackend>::process(&mut chart, &mut tab).unwrap();
+        assert_eq!(
+            tab.samples.markers,
+            vec![dsp::Marker::new(30, String::from("bad click"))]
+        );
+
+        press(&mut chart, KeyCode::Char('M'));
+        assert!(matches!(&chart.marking, Some((true, label, None)) if label == "bad click"));
+        for _ in 0.."click".len() {
+            press(&mut chart, KeyCode::Backspace);
+        }
+        type_text(&mut chart, "take");
+        press(&mut chart, KeyCode::Enter);
+        View::<TestBackend>::process(&mut chart, &mut tab).unwrap();
+        assert_eq!(tab.samples.markers[0].label, "bad take");
+
+        press(&mut chart, KeyCode::Char('M'));
+        for _ in 0.."bad take".len() {
+            press(&mut chart, KeyCode::Backspace);
+        }
+        press(&mut chart, KeyCode::Enter);
+        View::<TestBackend>::process(&mut chart, &mut tab).unwrap();
+        assert!(tab.samples.markers.is_empty());
+    }
+
+    #[test]
+    fn render_draws_marker_labels() {
+        let mut tab = Tab::new(Samples::new(1, 1000, vec![0.0; 100]), None);
+        tab.samples.markers = vec![dsp::Marker::new(50, String::from("drop"))];
+        let mut chart = Chart::new(String::new(), 1, 100);
+        View::<TestBackend>::process(&mut chart, &mut tab).unwrap();
+
+        let mut terminal = Terminal::new(TestBackend::new(60, 12)).unwrap();
+        terminal
+            .draw(|frame| View::render(&mut chart, frame, frame.size(), &Theme::default()))
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let row: String = (0..60)
+            .map(|x| buffer.get(x, chart.graph.y).symbol.clone())
+            .collect();
+        assert!(row.contains("drop"), "{}", row);
+    }
+
+    #[test]
+    fn render_draws_time_ruler() {
+        let mut tab = Tab::new(Samples::new(1, 100, vec![0.0; 300]), None);
+        let mut chart = Chart::new(String::new(), 1, 300);
+        View::<TestBackend>::process(&mut chart, &mut tab).unwrap();
+
+        let mut terminal = Terminal::new(TestBackend::new(60, 12)).unwrap();
+        terminal
+            .draw(|frame| View::render(&mut chart, frame, frame.size(), &Theme::default()))
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let row: String = (0..60)
+            .map(|x| buffer.get(x, chart.graph.y - 1).symbol.clone())
+            .collect();
+        assert!(row.contains("|0s") && row.contains("|2s"), "{}", row);
+    }
+
+    #[test]
+    fn lanes_divide_height() {
+        let area = Rect::new(2, 1, 40, 22);
+        let heights = |channels| {
+            lanes(area, channels)
+                .iter()
+                .map(|lane| (lane.y, lane.height))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(lanes(area, 1), vec![area]);
+        assert_eq!(heights(2), vec![(1, 11), (12, 11)]);
+        assert_eq!(heights(5), vec![(1, 5), (6, 5), (11, 4), (15, 4), (19, 4)]);
+        assert!(lanes(area, 5)
+            .iter()
+            .all(|lane| lane.x == 2 && lane.width == 40));
+    }
+
+    #[test]
+    fn render_split_lanes() {
+        let samples = Samples::new(2, 20, vec![0.0; 20]);
+        let mut tab = Tab::new(samples, None);
+        let mut chart = Chart::new(String::new(), 2, 10);
+        View::<TestBackend>::key_event(
+            &mut chart,
+            KeyEvent::new(KeyCode::Char('l'), KeyModifiers::NONE),
+        );
+        View::<TestBackend>::process(&mut chart, &mut tab).unwrap();
+
+        let mut terminal = Terminal::new(TestBackend::new(40, 22)).unwrap();
+        terminal
+            .draw(|frame| View::render(&mut chart, frame, frame.size(), &Theme::default()))
+            .unwrap();
+
+        // The time ruler takes the first row inside the border.
+        let buffer = terminal.backend().buffer();
+        assert_eq!(buffer.get(chart.graph.x, 1).symbol, "|");
+        assert_eq!(buffer.get(1, 2).symbol, "L");
+        assert_eq!(buffer.get(1, 12).symbol, "R");
+        assert_eq!(chart.graph.y, 3);
+        assert_eq!(chart.graph.bottom(), 19);
+    }
+
+    #[test]
+    fn process_preview_points() {
+        let mut tab = Tab::new(Samples::new(1, 20, vec![0.5, 1.0]), None);
+        tab.preview = Some(Samples::new(1, 20, vec![0.25, 0.5]));
+        let mut chart = Chart::new(String::new(), 1, 2);
+
+        View::<TestBackend>::process(&mut chart, &mut tab).unwrap();
+        assert_eq!(chart.preview, vec![vec![(0.0, 0.25), (1.0, 0.5)]]);
+        // The full scale sample adds a dataset of clipped points.
+        assert_eq!(chart.datasets(0..1, &[], &Theme::default()).len(), 3);
+
+        tab.preview_hidden = true;
+        View::<TestBackend>::process(&mut chart, &mut tab).unwrap();
+        assert!(chart.preview.is_empty());
+    }
+
+    #[test]
+    fn process_keeps_zoom_per_tab() {
+        let mut first = Tab::new(Samples::new(1, 20, vec![0.0; 100]), None);
+        let mut second = Tab::new(Samples::new(1, 20, vec![0.0; 100]), None);
+        let mut chart = Chart::new(String::new(), 1, 100);
+        let zoom = KeyEvent::new(KeyCode::Up, KeyModifiers::SHIFT);
+
+        View::<TestBackend>::process(&mut chart, &mut first).unwrap();
+        View::<TestBackend>::key_event(&mut chart, zoom);
+        View::<TestBackend>::process(&mut chart, &mut first).unwrap();
+        let zoomed = chart.axes.clone();
+
+        View::<TestBackend>::process(&mut chart, &mut second).unwrap();
+        chart.axes = Axes::new([0.0, 100.0], [-1.0, 1.0], 1.0);
+        View::<TestBackend>::process(&mut chart, &mut second).unwrap();
+
+        View::<TestBackend>::process(&mut chart, &mut first).unwrap();
+        assert_eq!(chart.axes, zoomed);
+        View::<TestBackend>::process(&mut chart, &mut second).unwrap();
+        assert_eq!(chart.axes.bounds(), (0.0, 100.0));
+    }
+
+    #[test]
+    fn goto_moves_playhead_into_view() {
+        let mut tab = Tab::new(Samples::new(1, 10, vec![0.0; 1000]), None);
+        let mut chart = Chart::new(String::new(), 1, 1000);
+        View::<TestBackend>::process(&mut chart, &mut tab).unwrap();
+        for _ in 0..4 {
+            let zoom = KeyEvent::new(KeyCode::Up, KeyModifiers::SHIFT);
+            View::<TestBackend>::key_event(&mut chart, zoom);
+        }
+
+        let type_text = |chart: &mut Chart, text: &str| {
+            View::<TestBackend>::key_event(chart, KeyEvent::from(KeyCode::Char('g')));
+            for char in text.chars() {
+                View::<TestBackend>::key_event(chart, KeyEvent::from(KeyCode::Char(char)));
+            }
+            View::<TestBackend>::key_event(chart, KeyEvent::from(KeyCode::Enter));
+        };
+
+        type_text(&mut chart, "1:30.5");
+        assert!(!View::<TestBackend>::captures_input(&chart));
+        View::<TestBackend>::process(&mut chart, &mut tab).unwrap();
+        assert_eq!(tab.playhead, 905);
+        let (start, end) = chart.axes.bounds();
+        assert!(((start + end) / 2.0 - 905.0).abs() < 1e-9);
+
+        type_text(&mut chart, "12.25");
+        View::<TestBackend>::process(&mut chart, &mut tab).unwrap();
+        assert_eq!(tab.playhead, 123);
+        type_text(&mut chart, "400s");
+        View::<TestBackend>::process(&mut chart, &mut tab).unwrap();
+        assert_eq!(tab.playhead, 400);
+        let (start, end) = chart.axes.bounds();
+        assert!(((start + end) / 2.0 - 400.0).abs() < 1e-9);
+        assert_eq!(chart.notice, None);
+
+        type_text(&mut chart, "5000");
+        View::<TestBackend>::process(&mut chart, &mut tab).unwrap();
+        assert_eq!(tab.playhead, 1000);
+        assert!(chart.notice.is_some());
+
+        type_text(&mut chart, "later");
+        assert!(View::<TestBackend>::captures_input(&chart));
+        assert!(matches!(&chart.goto, Some((_, Some(_)))));
+        View::<TestBackend>::key_event(&mut chart, KeyEvent::from(KeyCode::Esc));
+        assert!(!View::<TestBackend>::captures_input(&chart));
+        assert_eq!(tab.playhead, 1000);
+        assert_eq!(chart.notice, None);
+
+        View::<TestBackend>::key_event(&mut chart, KeyEvent::from(KeyCode::Char('t')));
+        assert!(View::<TestBackend>::captures_input(&chart));
+    }
+
     #[test]
     fn process_points() {
-        let mut chart = Chart::new(String::from(""), 1, 1);
+        let mut chart = Chart::new(String::from(""), 1, 3);
         let axes = chart.axes.clone();
         let expected = vec![
             vec![(0.0, -1.0), (1.0, -0.25), (2.0, 0.5)],
             vec![(0.0, -0.5), (1.0, 0.25), (2.0, 1.0)],
         ];
 
-        let mut buffer = Samples::new(2, 20, vec![-1.0, -0.5, -0.25, 0.25, 0.5, 1.0]);
-        View::<TestBackend>::process(&mut chart, &mut buffer).unwrap();
+        let samples = Samples::new(2, 20, vec![-1.0, -0.5, -0.25, 0.25, 0.5, 1.0]);
+        let mut tab = Tab::new(samples, None);
+        View::<TestBackend>::process(&mut chart, &mut tab).unwrap();
 
         assert_eq!(chart.axes, axes);
         assert_eq!(chart.points, expected);
+
+        tab.samples.data.extend([0.0; 4]);
+        View::<TestBackend>::process(&mut chart, &mut tab).unwrap();
+        assert_eq!(chart.axes.bounds(), (0.0, 5.0));
     }
 }
@@ -1,12 +1,19 @@
 //! Components for navigating file systems.
 
-use crate::dsp::Samples;
-use crate::io::{audio, path};
+use crate::config::Config;
+use crate::dsp::{self, Samples, Waveform};
+use crate::io::audio::WriteOptions;
+use crate::io::watch::{self, Watcher};
+use crate::io::{aiff, audio, csv, labels, path};
+use crate::tab::Tab;
 use crate::ui;
+use crate::ui::theme::Theme;
 use crate::view::View;
-use crossterm::event::{KeyCode, KeyEvent};
+use clap::ValueEnum;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind};
 use std::borrow::ToOwned;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 use tui::backend::Backend;
 use tui::layout::Rect;
 use tui::style::{Modifier, Style};
@@ -14,13 +21,52 @@ use tui::terminal::Frame;
 use tui::text::Text;
 use tui::widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph};
 
+/// Mark shown before bookmarked directories in the file list.
+const BOOKMARK_GLYPH: char = '★';
+
 /// A UI view for navigating the file system, reading audio files, and writing audio files.
 pub struct File {
+    /// File being read in the background to append to the active tab with a crossfade in
+    /// milliseconds.
+    appending: Option<(Tab, f64)>,
+    /// Typed bits per sample for saving AIFF files.
+    bits_buffer: String,
+    bookmark_state: ListState,
+    config: Config,
+    config_changed: bool,
+    config_path: Option<PathBuf>,
+    /// Typed crossfade length in milliseconds for appending a file.
+    crossfade_buffer: String,
     cwd: PathBuf,
     files: Vec<(String, bool)>,
+    /// Typed waveform, frequency, amplitude, duration, channel count, and sample rate for
+    /// generating a signal.
+    generate_buffer: String,
+    hidden: bool,
+    /// Headerless PCM or CSV file waiting for its parameters to be typed.
+    import_path: Option<PathBuf>,
     mode: Mode,
+    /// Tab reading a file in the background, to be added after the existing tabs.
+    opened: Option<Tab>,
+    /// File being read in the background, to be remembered once reading succeeds.
+    pending: Option<PathBuf>,
+    prompt_error: Option<String>,
+    /// Typed quality level for saving OGG files.
+    quality_buffer: String,
+    /// Typed sample rate for reading CSV files.
+    rate_buffer: String,
+    /// Typed sample format, channel count, and sample rate for reading headerless PCM files.
+    raw_buffer: String,
+    recent_state: ListState,
+    /// Typed filename substring that filters the listed inodes while searching.
+    search_buffer: String,
     state: ListState,
     type_buffer: String,
+    /// Message to report on the next processing, such as bookmarks of missing directories that
+    /// were removed.
+    warning: Option<String>,
+    /// Watcher of the working directory, for listing created and removed inodes.
+    watcher: Watcher,
 }
 
 impl File {
@@ -30,30 +76,302 @@ impl File {
     ///
     /// Will return `Err` if `path` does not exist or contains invalid audio data.
     pub fn try_new(cwd: PathBuf) -> eyre::Result<Self> {
-        let files = path::sorted_names(&cwd)?;
+        let files = path::sorted_names(&cwd, false)?;
+        let watcher = Watcher::new(cwd.clone(), watch::DEBOUNCE);
 
         Ok(Self {
+            appending: None,
+            bits_buffer: String::from("16"),
+            bookmark_state: ListState::default(),
+            config: Config::default(),
+            config_changed: false,
+            config_path: None,
+            crossfade_buffer: String::from("0"),
             cwd,
             files,
+            generate_buffer: String::from("sine 440 0.5 1 1 44100"),
+            hidden: false,
+            import_path: None,
             mode: Mode::Nagivate,
+            opened: None,
+            pending: None,
+            prompt_error: None,
+            quality_buffer: String::from("0.5"),
+            rate_buffer: String::from("44100"),
+            raw_buffer: String::from("s16le 1 44100"),
+            recent_state: ListState::default(),
+            search_buffer: String::new(),
             state: ListState::default(),
             type_buffer: String::new(),
+            warning: None,
+            watcher,
         })
     }
 
+    /// Use configuration settings and persist changes to them at the given path.
+    ///
+    /// Bookmarks of directories that no longer exist are removed with a warning.
+    #[must_use]
+    pub fn with_config(mut self, mut config: Config, path: Option<PathBuf>) -> Self {
+        let missing = config.prune_bookmarks();
+        if !missing.is_empty() {
+            let names: Vec<String> = missing
+                .iter()
+                .map(|path| path.to_string_lossy().into_owned())
+                .collect();
+            self.config_changed = true;
+            self.warning = Some(format!(
+                "Removed bookmarks of missing directories {}",
+                names.join(", ")
+            ));
+        }
+
+        self.config = config;
+        self.config_path = path;
+        self
+    }
+
+    /// Remove a bookmark and keep the selection on a neighboring bookmark, if any are left.
+    fn remove_bookmark(&mut self, index: usize) {
+        self.config.bookmarks.remove(index);
+        self.config_changed = true;
+
+        let length = self.config.bookmarks.len();
+        self.bookmark_state
+            .select((length > 0).then(|| index.min(length - 1)));
+    }
+
+    /// Record an opened audio file in the recent files list.
+    pub fn remember(&mut self, path: PathBuf) {
+        self.config.recent_files.push(path);
+        self.config_changed = true;
+    }
+
+    /// Add the highlighted directory, or else the working directory, to bookmarks if it is not
+    /// already present.
+    fn bookmark(&mut self) {
+        let directory = match self.state.selected().map(|index| &self.files[index]) {
+            Some((name, true)) => self.cwd.join(name),
+            _ => self.cwd.clone(),
+        };
+        if !self.config.bookmarks.contains(&directory) {
+            self.config.bookmarks.push(directory);
+            self.config_changed = true;
+        }
+    }
+
     /// Change working directory and load its files.
     fn chdir(&mut self, cwd: PathBuf) {
         self.cwd = cwd;
-        self.files = path::sorted_names(&self.cwd)
+        self.files = path::sorted_names(&self.cwd, self.hidden)
             .unwrap_or_else(|error| vec![(format!("{}", error), false)]);
         self.mode = Mode::Nagivate;
         self.state = ListState::default();
+        self.watcher = Watcher::new(self.cwd.clone(), watch::DEBOUNCE);
+    }
+
+    /// Jump to the directory typed into the go to prompt.
+    ///
+    /// Paths to files jump to their parent directory and highlight the file.
+    fn goto(&mut self) {
+        let result = path::expand(&self.type_buffer)
+            .and_then(|expanded| path::locate(&self.cwd.join(expanded)));
+
+        match result {
+            Ok((directory, file)) => {
+                self.chdir(directory);
+                if let Some(file) = file {
+                    self.select(&file);
+                }
+                self.prompt_error = None;
+                self.type_buffer.clear();
+            }
+            Err(error) => self.prompt_error = Some(format!("{}", error)),
+        }
+    }
+
+    /// Handle key events while in bookmarks mode.
+    fn key_event_bookmarks(&mut self, event: KeyEvent) {
+        let length = self.config.bookmarks.len();
+
+        match event.code {
+            KeyCode::Char('B') | KeyCode::Esc => self.mode = Mode::Nagivate,
+            KeyCode::Char('d') | KeyCode::Delete => {
+                if let Some(index) = self.bookmark_state.selected() {
+                    self.remove_bookmark(index);
+                }
+            }
+            KeyCode::Down => self.bookmark_state.select(ui::util::step(
+                self.bookmark_state.selected(),
+                length,
+                true,
+            )),
+            KeyCode::Enter => {
+                if let Some(index) = self.bookmark_state.selected() {
+                    let directory = self.config.bookmarks[index].clone();
+
+                    if directory.is_dir() {
+                        self.chdir(directory);
+                    } else {
+                        // Bookmarks of directories removed since startup are dropped once picked.
+                        self.remove_bookmark(index);
+                        self.warning = Some(format!(
+                            "Removed bookmark {:?} that no longer exists",
+                            directory
+                        ));
+                    }
+                }
+            }
+            KeyCode::Up => self.bookmark_state.select(ui::util::step(
+                self.bookmark_state.selected(),
+                length,
+                false,
+            )),
+            _ => (),
+        }
+    }
+
+    /// Handle key events while in go to mode.
+    fn key_event_goto(&mut self, event: KeyEvent) {
+        match event.code {
+            KeyCode::Backspace | KeyCode::Delete => {
+                self.type_buffer.pop();
+            }
+            KeyCode::Enter => self.goto(),
+            KeyCode::Esc => {
+                self.prompt_error = None;
+                self.type_buffer.clear();
+                self.mode = Mode::Nagivate;
+            }
+            KeyCode::Tab => {
+                if let Some(completion) = path::complete(&self.cwd, &self.type_buffer) {
+                    self.type_buffer = completion;
+                }
+            }
+            KeyCode::Char(char) => {
+                self.type_buffer.push(char);
+            }
+            _ => (),
+        }
+    }
+
+    /// Handle key events while in search mode.
+    ///
+    /// The list state indexes the matching inodes while searching and the full listing otherwise,
+    /// so leaving search mode highlights the same inode in the full listing.
+    fn key_event_search(&mut self, event: KeyEvent) {
+        let length = self.matches().len();
+
+        match event.code {
+            KeyCode::Backspace | KeyCode::Delete => {
+                self.search_buffer.pop();
+                self.state.select((!self.matches().is_empty()).then_some(0));
+            }
+            KeyCode::Down => self
+                .state
+                .select(ui::util::step(self.state.selected(), length, true)),
+            KeyCode::Enter => {
+                let selected = self.leave_search();
+                if let Some(index) = selected {
+                    let (name, is_dir) = &self.files[index];
+
+                    if *is_dir {
+                        let path = self.cwd.join(name);
+                        self.chdir(path);
+                    } else {
+                        self.mode = Mode::Read;
+                    }
+                }
+            }
+            KeyCode::Esc => {
+                self.leave_search();
+            }
+            KeyCode::Up => self
+                .state
+                .select(ui::util::step(self.state.selected(), length, false)),
+            KeyCode::Char(char) => {
+                self.search_buffer.push(char);
+                self.state.select((!self.matches().is_empty()).then_some(0));
+            }
+            _ => (),
+        }
+    }
+
+    /// Clear the search filter and return to navigate mode, highlighting the inode that was
+    /// highlighted among the matches.
+    fn leave_search(&mut self) -> Option<usize> {
+        let selected = self
+            .state
+            .selected()
+            .and_then(|index| self.matches().get(index).copied());
+
+        self.search_buffer.clear();
+        self.mode = Mode::Nagivate;
+        self.state.select(selected);
+        selected
+    }
+
+    /// Get the indices of inodes whose names contain the search text, ignoring case.
+    fn matches(&self) -> Vec<usize> {
+        let needle = self.search_buffer.to_lowercase();
+        self.files
+            .iter()
+            .enumerate()
+            .filter(|(_index, (name, _is_dir))| name.to_lowercase().contains(&needle))
+            .map(|(index, _file)| index)
+            .collect()
     }
 
     /// Handle key events while in navigate mode.
     fn key_event_navigate(&mut self, event: KeyEvent) {
         match event.code {
-            KeyCode::Char('w') => self.mode = Mode::Type,
+            KeyCode::Char('/') => self.mode = Mode::Search,
+            KeyCode::Char('.') => {
+                let selected = self
+                    .state
+                    .selected()
+                    .map(|index| self.files[index].0.clone());
+
+                self.hidden = !self.hidden;
+                self.chdir(self.cwd.clone());
+                if let Some(name) = selected {
+                    self.select(&name);
+                }
+            }
+            KeyCode::Char('a') => {
+                if let Some(index) = self.state.selected() {
+                    if !self.files[index].1 {
+                        self.mode = Mode::Crossfade;
+                    }
+                }
+            }
+            KeyCode::Char('b') => self.bookmark(),
+            KeyCode::Char('B') => {
+                let length = self.config.bookmarks.len();
+                self.bookmark_state.select((length > 0).then_some(0));
+                self.mode = Mode::Bookmarks;
+            }
+            KeyCode::Char('g') => self.mode = Mode::Goto,
+            KeyCode::Char('i') => self.mode = Mode::Aiff,
+            KeyCode::Char('n') => self.mode = Mode::Generate,
+            KeyCode::Char('o') => self.mode = Mode::Ogg,
+            KeyCode::Char('p') => {
+                if let Some(index) = self.state.selected() {
+                    let (name, is_dir) = &self.files[index];
+
+                    if !*is_dir {
+                        self.import_path = Some(self.cwd.join(name));
+                        self.mode = Mode::Raw;
+                    }
+                }
+            }
+            KeyCode::Char('r') => {
+                let length = self.config.recent_files.paths.len();
+                self.recent_state.select((length > 0).then_some(0));
+                self.mode = Mode::Recent;
+            }
+            KeyCode::Char('w') => self.mode = Mode::StartWrite,
+            KeyCode::Char('x') => self.mode = Mode::StartExport,
             KeyCode::Down => self.next(),
             KeyCode::Enter => {
                 if let Some(index) = self.state.selected() {
@@ -64,12 +382,7 @@ impl File {
                     }
                 };
             }
-            KeyCode::Left => {
-                let option = self.cwd.parent().map(ToOwned::to_owned);
-                if let Some(path_ref) = option {
-                    self.chdir(path_ref);
-                }
-            }
+            KeyCode::Backspace | KeyCode::Left => self.parent(),
             KeyCode::Right => {
                 if let Some(index) = self.state.selected() {
                     let (name, is_dir) = &self.files[index];
@@ -85,13 +398,238 @@ impl File {
         }
     }
 
-    /// Handle key events while in type mode.
+    /// Read a file in the background into the tab if it is blank, or else into a new tab.
+    ///
+    /// CSV files prompt for a sample rate and files without a recognized audio header prompt for
+    /// raw PCM parameters instead.
+    fn open(&mut self, tab: &mut Tab, path: PathBuf) {
+        if path::has_extension(&path, "csv") {
+            self.import_path = Some(path);
+            self.mode = Mode::Csv;
+            return;
+        }
+        if matches!(audio::has_header(&path), Ok(false)) {
+            self.import_path = Some(path);
+            self.mode = Mode::Raw;
+            return;
+        }
+
+        if tab.is_blank() {
+            *tab = Tab::load(path.clone());
+        } else {
+            self.opened = Some(Tab::load(path.clone()));
+        }
+        self.pending = Some(path);
+    }
+
+    /// Handle key events while typing the crossfade length for appending a file.
+    fn key_event_crossfade(&mut self, event: KeyEvent) {
+        match event.code {
+            KeyCode::Backspace | KeyCode::Delete => {
+                self.crossfade_buffer.pop();
+            }
+            KeyCode::Enter => match self.crossfade_buffer.parse::<f64>() {
+                Ok(milliseconds) if milliseconds.is_finite() && milliseconds >= 0.0 => {
+                    self.prompt_error = None;
+                    self.mode = Mode::Append;
+                }
+                _ => {
+                    self.prompt_error =
+                        Some(String::from("Crossfade must be zero or more milliseconds"));
+                }
+            },
+            KeyCode::Esc => {
+                self.prompt_error = None;
+                self.mode = Mode::Nagivate;
+            }
+            KeyCode::Char(char) => self.crossfade_buffer.push(char),
+            _ => (),
+        }
+    }
+
+    /// Handle key events while typing the bits per sample for saving an AIFF file.
+    fn key_event_bits(&mut self, event: KeyEvent) {
+        match event.code {
+            KeyCode::Backspace | KeyCode::Delete => {
+                self.bits_buffer.pop();
+            }
+            KeyCode::Enter => match self.bits_buffer.parse::<u16>() {
+                Ok(16 | 24) => {
+                    self.prompt_error = None;
+                    self.mode = Mode::WriteAiff;
+                }
+                _ => self.prompt_error = Some(String::from("Bits per sample must be 16 or 24")),
+            },
+            KeyCode::Esc => {
+                self.prompt_error = None;
+                self.mode = Mode::Aiff;
+            }
+            KeyCode::Char(char) => self.bits_buffer.push(char),
+            _ => (),
+        }
+    }
+
+    /// Handle key events while typing the quality level for saving an OGG file.
+    fn key_event_quality(&mut self, event: KeyEvent) {
+        match event.code {
+            KeyCode::Backspace | KeyCode::Delete => {
+                self.quality_buffer.pop();
+            }
+            KeyCode::Enter => match self.quality_buffer.parse::<f32>() {
+                Ok(quality) if (-1.0..=1.0).contains(&quality) => {
+                    self.prompt_error = None;
+                    self.mode = Mode::WriteOgg;
+                }
+                _ => {
+                    self.prompt_error = Some(String::from("Quality must be between -1.0 and 1.0"));
+                }
+            },
+            KeyCode::Esc => {
+                self.prompt_error = None;
+                self.mode = Mode::Ogg;
+            }
+            KeyCode::Char(char) => self.quality_buffer.push(char),
+            _ => (),
+        }
+    }
+
+    /// Handle key events while typing the sample rate for reading a CSV file.
+    fn key_event_csv(&mut self, event: KeyEvent) {
+        match event.code {
+            KeyCode::Backspace | KeyCode::Delete => {
+                self.rate_buffer.pop();
+            }
+            KeyCode::Enter => match self.rate_buffer.parse::<u32>() {
+                Ok(rate) if rate > 0 => {
+                    self.prompt_error = None;
+                    self.mode = Mode::ReadCsv;
+                }
+                _ => {
+                    self.prompt_error = Some(String::from(
+                        "Sample rate must be a whole number above zero",
+                    ));
+                }
+            },
+            KeyCode::Esc => {
+                self.prompt_error = None;
+                self.import_path = None;
+                self.mode = Mode::Nagivate;
+            }
+            KeyCode::Char(char) => self.rate_buffer.push(char),
+            _ => (),
+        }
+    }
+
+    /// Handle key events while typing the parameters for generating a signal.
+    fn key_event_generate(&mut self, event: KeyEvent) {
+        match event.code {
+            KeyCode::Backspace | KeyCode::Delete => {
+                self.generate_buffer.pop();
+            }
+            KeyCode::Enter => match self.generate_parameters() {
+                Ok(_parameters) => {
+                    self.prompt_error = None;
+                    self.mode = Mode::OpenGenerated;
+                }
+                Err(error) => self.prompt_error = Some(format!("{}", error)),
+            },
+            KeyCode::Esc => {
+                self.prompt_error = None;
+                self.mode = Mode::Nagivate;
+            }
+            KeyCode::Char(char) => self.generate_buffer.push(char),
+            _ => (),
+        }
+    }
+
+    /// Handle key events while typing the parameters for reading a headerless PCM file.
+    fn key_event_raw(&mut self, event: KeyEvent) {
+        match event.code {
+            KeyCode::Backspace | KeyCode::Delete => {
+                self.raw_buffer.pop();
+            }
+            KeyCode::Enter => match self.raw_parameters() {
+                Ok(_parameters) => {
+                    self.prompt_error = None;
+                    self.mode = Mode::ReadRaw;
+                }
+                Err(error) => self.prompt_error = Some(format!("{}", error)),
+            },
+            KeyCode::Esc => {
+                self.prompt_error = None;
+                self.import_path = None;
+                self.mode = Mode::Nagivate;
+            }
+            KeyCode::Char(char) => self.raw_buffer.push(char),
+            _ => (),
+        }
+    }
+
+    /// Handle key events while in recent files mode.
+    fn key_event_recent(&mut self, event: KeyEvent) {
+        let length = self.config.recent_files.paths.len();
+
+        match event.code {
+            KeyCode::Char('r') | KeyCode::Esc => self.mode = Mode::Nagivate,
+            KeyCode::Down => {
+                self.recent_state
+                    .select(ui::util::step(self.recent_state.selected(), length, true))
+            }
+            KeyCode::Enter if self.recent_state.selected().is_some() => {
+                self.mode = Mode::ReadRecent;
+            }
+            KeyCode::Up => self.recent_state.select(ui::util::step(
+                self.recent_state.selected(),
+                length,
+                false,
+            )),
+            _ => (),
+        }
+    }
+
+    /// Handle key events while confirming to overwrite an existing file.
+    fn key_event_confirm(&mut self, event: KeyEvent) {
+        let export = self.mode == Mode::ConfirmExport;
+        match event.code {
+            KeyCode::Char('y') if export => self.mode = Mode::WriteExport,
+            KeyCode::Char('y') => self.mode = Mode::Write,
+            KeyCode::Char('n') | KeyCode::Esc if export => self.mode = Mode::Export,
+            KeyCode::Char('n') | KeyCode::Esc => self.mode = Mode::Type,
+            _ => (),
+        }
+    }
+
+    /// Handle key events while in type, export, AIFF, or OGG mode.
     fn key_event_type(&mut self, event: KeyEvent) {
         match event.code {
             KeyCode::Backspace | KeyCode::Delete => {
                 self.type_buffer.pop();
             }
-            KeyCode::Enter => self.mode = Mode::Write,
+            KeyCode::Enter if self.mode == Mode::Export => {
+                self.mode = if self.cwd.join(&self.type_buffer).exists() {
+                    Mode::ConfirmExport
+                } else {
+                    Mode::WriteExport
+                };
+            }
+            KeyCode::Enter if self.mode == Mode::Aiff => self.mode = Mode::Bits,
+            KeyCode::Enter if self.mode == Mode::Ogg => self.mode = Mode::Quality,
+            KeyCode::Enter => match path::save_target(&self.cwd, &self.type_buffer) {
+                Ok(path) => {
+                    self.prompt_error = None;
+                    self.mode = if path.exists() {
+                        Mode::ConfirmWrite
+                    } else {
+                        Mode::Write
+                    };
+                }
+                Err(error) => self.prompt_error = Some(format!("{}", error)),
+            },
+            KeyCode::Esc => {
+                self.prompt_error = None;
+                self.type_buffer.clear();
+                self.mode = Mode::Nagivate;
+            }
             KeyCode::Char(char) => {
                 self.type_buffer.push(char);
             }
@@ -99,119 +637,1332 @@ impl File {
         }
     }
 
+    /// Parse the typed waveform, frequency, amplitude, duration, channel count, and sample rate
+    /// for generating a signal.
+    fn generate_parameters(&self) -> eyre::Result<(Waveform, f32, f32, f32, u16, u32)> {
+        let parts: Vec<&str> = self.generate_buffer.split_whitespace().collect();
+        if let [waveform, frequency, amplitude, duration, channels, rate] = parts.as_slice() {
+            let waveform = Waveform::from_str(waveform, true).map_err(|_error| {
+                eyre::eyre!(
+                    "Waveform must be sawtooth, silence, sine, square, sweep, or white-noise"
+                )
+            })?;
+            let number = |text: &str, name: &str| {
+                text.parse::<f32>()
+                    .map_err(|_error| eyre::eyre!("{} must be a number", name))
+            };
+            let channels = channels
+                .parse()
+                .ok()
+                .filter(|channels| *channels > 0)
+                .ok_or_else(|| eyre::eyre!("Channels must be a whole number above zero"))?;
+            let rate = rate
+                .parse()
+                .ok()
+                .filter(|rate| *rate > 0)
+                .ok_or_else(|| eyre::eyre!("Sample rate must be a whole number above zero"))?;
+            Ok((
+                waveform,
+                number(frequency, "Frequency")?,
+                number(amplitude, "Amplitude")?,
+                number(duration, "Duration")?,
+                channels,
+                rate,
+            ))
+        } else {
+            eyre::bail!(
+                "Type a waveform, frequency, amplitude, seconds, channels, and rate such as {:?}",
+                "sine 440 0.5 1 1 44100"
+            )
+        }
+    }
+
+    /// Parse the typed sample format, channel count, and sample rate for headerless PCM files.
+    fn raw_parameters(&self) -> eyre::Result<(audio::RawFormat, u16, u32)> {
+        let parts: Vec<&str> = self.raw_buffer.split_whitespace().collect();
+        if let [format, channels, rate] = parts.as_slice() {
+            let channels = channels
+                .parse()
+                .ok()
+                .filter(|channels| *channels > 0)
+                .ok_or_else(|| eyre::eyre!("Channels must be a whole number above zero"))?;
+            let rate = rate
+                .parse()
+                .ok()
+                .filter(|rate| *rate > 0)
+                .ok_or_else(|| eyre::eyre!("Sample rate must be a whole number above zero"))?;
+            Ok((format.parse()?, channels, rate))
+        } else {
+            eyre::bail!("Type a format, channel count, and sample rate such as \"s16le 2 44100\"")
+        }
+    }
+
+    /// Write samples to the typed file name in the format of its extension.
+    ///
+    /// Returns the written path and whether it is a WAV file.
+    fn save_as(&self, samples: &Samples) -> eyre::Result<(PathBuf, bool)> {
+        let path = path::save_target(&self.cwd, &self.type_buffer)?;
+        let wave = self.write(&path, samples)?;
+        Ok((path, wave))
+    }
+
+    /// Place an imported tab into the tab if it is blank, or else into a new tab.
+    ///
+    /// Imported files cannot be read back without their typed parameters, so the tab is untitled
+    /// and unsaved until written in another format.
+    fn import(&mut self, tab: &mut Tab, samples: Samples) {
+        let mut imported = Tab::new(samples, None);
+        imported.modified = true;
+        if tab.is_blank() {
+            *tab = imported;
+        } else {
+            self.opened = Some(imported);
+        }
+    }
+
+    /// Write samples to a file in the format of its extension.
+    ///
+    /// AIFF files use the last entered bits per sample, OGG files use the last entered quality
+    /// level, CSV files use the configured index column, and other files are written as WAV.
+    /// Markers are kept in a marker file next to the written file in every format. Returns
+    /// whether the file is a WAV file.
+    fn write(&self, path: &Path, samples: &Samples) -> eyre::Result<bool> {
+        let options = WriteOptions {
+            aiff_bits: self.bits_buffer.parse()?,
+            csv_index: self.config.csv_index,
+            ogg_quality: self.quality_buffer.parse()?,
+        };
+        let wave = audio::write_by_extension(path, samples, &options)?;
+        labels::write_sidecar(path, samples)?;
+        Ok(wave)
+    }
+
     /// Modular move list state to next inode.
     fn next(&mut self) {
-        let index = match self.state.selected() {
-            Some(index) => {
-                if index >= self.files.len() - 1 {
-                    0
-                } else {
-                    index + 1
-                }
+        let index = ui::util::step(self.state.selected(), self.files.len(), true);
+        self.state.select(index);
+    }
+
+    /// Change working directory to its parent, highlighting the previous directory.
+    ///
+    /// Does nothing at the file system root.
+    fn parent(&mut self) {
+        let option = self.cwd.parent().map(ToOwned::to_owned);
+        if let Some(path_ref) = option {
+            let previous = path::name(&self.cwd).map(ToOwned::to_owned);
+            self.chdir(path_ref);
+
+            if let Ok(name) = previous {
+                self.select(&name);
+            }
+        }
+    }
+
+    /// List the inodes of the working directory again, keeping the mode and the highlighted inode
+    /// if it still exists.
+    fn refresh(&mut self) {
+        let searching = self.mode == Mode::Search;
+        let position = |file: &Self, index: usize| {
+            if searching {
+                file.matches().get(index).copied()
+            } else {
+                Some(index)
             }
-            None => 0,
         };
-        self.state.select(Some(index));
+        let selected = self
+            .state
+            .selected()
+            .and_then(|index| position(self, index))
+            .map(|index| self.files[index].0.clone());
+
+        self.files = path::sorted_names(&self.cwd, self.hidden)
+            .unwrap_or_else(|error| vec![(format!("{}", error), false)]);
+        self.state.select(None);
+        if let Some(name) = selected {
+            if searching {
+                let index = self
+                    .matches()
+                    .iter()
+                    .position(|index| self.files[*index].0 == name);
+                self.state.select(index);
+            } else {
+                self.select(&name);
+            }
+        }
+    }
+
+    /// Highlight the inode with the given name if it is listed.
+    fn select(&mut self, name: &str) {
+        if let Some(index) = self.files.iter().position(|(file, _is_dir)| file == name) {
+            self.state.select(Some(index));
+        }
     }
 
     /// Modular move list state to previous inode.
     fn previous(&mut self) {
-        let index = match self.state.selected() {
-            Some(index) => {
-                if index == 0 {
-                    self.files.len() - 1
-                } else {
-                    index - 1
-                }
-            }
-            None => 0,
-        };
-        self.state.select(Some(index));
+        let index = ui::util::step(self.state.selected(), self.files.len(), false);
+        self.state.select(index);
     }
 }
 
 impl<B: Backend> View<B> for File {
+    fn captures_input(&self) -> bool {
+        matches!(
+            self.mode,
+            Mode::Aiff
+                | Mode::Bits
+                | Mode::Bookmarks
+                | Mode::ConfirmExport
+                | Mode::ConfirmWrite
+                | Mode::Crossfade
+                | Mode::Csv
+                | Mode::Export
+                | Mode::Generate
+                | Mode::Goto
+                | Mode::Ogg
+                | Mode::Quality
+                | Mode::Raw
+                | Mode::Recent
+                | Mode::Search
+                | Mode::Type
+        )
+    }
+
     fn key_event(&mut self, event: KeyEvent) {
         match self.mode {
+            Mode::Bits => self.key_event_bits(event),
+            Mode::Bookmarks => self.key_event_bookmarks(event),
+            Mode::Goto => self.key_event_goto(event),
             Mode::Nagivate => self.key_event_navigate(event),
-            Mode::Type => self.key_event_type(event),
+            Mode::Recent => self.key_event_recent(event),
+            Mode::Search => self.key_event_search(event),
+            Mode::ConfirmExport | Mode::ConfirmWrite => self.key_event_confirm(event),
+            Mode::Crossfade => self.key_event_crossfade(event),
+            Mode::Csv => self.key_event_csv(event),
+            Mode::Generate => self.key_event_generate(event),
+            Mode::Quality => self.key_event_quality(event),
+            Mode::Raw => self.key_event_raw(event),
+            Mode::Aiff | Mode::Export | Mode::Ogg | Mode::Type => self.key_event_type(event),
             _ => (),
         }
     }
 
-    fn process(&mut self, samples: &mut Samples) -> eyre::Result<()> {
+    fn mouse_event(&mut self, event: MouseEvent, _tab: &mut Tab) {
+        let code = match event.kind {
+            MouseEventKind::ScrollDown => KeyCode::Down,
+            MouseEventKind::ScrollUp => KeyCode::Up,
+            _ => return,
+        };
+
+        if matches!(
+            self.mode,
+            Mode::Bookmarks | Mode::Nagivate | Mode::Recent | Mode::Search
+        ) {
+            View::<B>::key_event(self, KeyEvent::new(code, KeyModifiers::NONE));
+        }
+    }
+
+    fn process(&mut self, tab: &mut Tab) -> eyre::Result<()> {
+        if let Some(path) = &self.pending {
+            if tab.path.as_ref() == Some(path) {
+                self.remember(path.clone());
+                self.pending = None;
+            } else if !tab.is_loading() {
+                self.pending = None;
+            }
+        }
+
+        if self.config_changed {
+            self.config_changed = false;
+
+            if let Some(config_path) = &self.config_path {
+                self.config.write(config_path)?;
+            }
+        }
+        if let Some(warning) = self.warning.take() {
+            eyre::bail!(warning);
+        }
+        if self.watcher.poll(Instant::now()) {
+            self.refresh();
+        }
+
+        if let Some((mut incoming, crossfade)) = self.appending.take() {
+            incoming.poll()?;
+            if incoming.is_loading() {
+                self.appending = Some((incoming, crossfade));
+            } else {
+                let rate = f64::from(tab.samples.sample_rate);
+                // Crossfade lengths are non negative, so sign loss is not possible.
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                let frames = (crossfade / 1000.0 * rate).round() as usize;
+                tab.append(&incoming.samples, frames)?;
+            }
+        }
+
+        // Windowed files hold no samples to write.
+        if matches!(
+            self.mode,
+            Mode::StartExport
+                | Mode::StartWrite
+                | Mode::Write
+                | Mode::WriteAiff
+                | Mode::WriteExport
+                | Mode::WriteOgg
+        ) {
+            if let Err(error) = tab.ensure_editable() {
+                self.mode = Mode::Nagivate;
+                return Err(error);
+            }
+        }
+
         match self.mode {
+            Mode::Append => {
+                self.mode = Mode::Nagivate;
+                if let (Some(index), Ok(crossfade)) =
+                    (self.state.selected(), self.crossfade_buffer.parse())
+                {
+                    let path = self.cwd.join(&self.files[index].0);
+                    self.appending = Some((Tab::load(path), crossfade));
+                }
+            }
+            Mode::StartExport => {
+                if tab.selection.is_none() {
+                    self.mode = Mode::Nagivate;
+                    eyre::bail!("Select frames in the chart before exporting them");
+                }
+                let stem = Path::new(tab.name()).file_stem().map_or_else(
+                    || String::from("untitled"),
+                    |stem| stem.to_string_lossy().into_owned(),
+                );
+                self.type_buffer = format!("{}.selection.wav", stem);
+                self.mode = Mode::Export;
+            }
+            Mode::OpenGenerated => {
+                self.mode = Mode::Nagivate;
+                let (waveform, frequency, amplitude, duration, channels, rate) =
+                    self.generate_parameters()?;
+                let samples =
+                    dsp::generate(waveform, frequency, amplitude, duration, channels, rate)?;
+                self.import(tab, samples);
+            }
             Mode::Read => {
+                self.mode = Mode::Nagivate;
                 if let Some(index) = self.state.selected() {
                     let (name, _is_dir) = &self.files[index];
                     let path = self.cwd.join(name);
-                    *samples = audio::read_samples(&path)?;
+                    self.open(tab, path);
                 };
+            }
+            Mode::ReadCsv => {
+                self.mode = Mode::Nagivate;
+                if let Some(path) = self.import_path.take() {
+                    let samples = csv::read_csv(&path, self.rate_buffer.parse()?)?;
+                    self.import(tab, samples);
+                }
+            }
+            Mode::ReadRaw => {
+                self.mode = Mode::Nagivate;
+                if let Some(path) = self.import_path.take() {
+                    let (format, channels, rate) = self.raw_parameters()?;
+                    let (samples, dropped) = audio::read_raw(&path, format, channels, rate)?;
+                    self.import(tab, samples);
 
+                    if dropped > 0 {
+                        eyre::bail!(
+                            "Dropped {} trailing bytes of {} that do not fill a whole frame",
+                            dropped,
+                            path::name(&path)?
+                        );
+                    }
+                }
+            }
+            Mode::ReadRecent => {
                 self.mode = Mode::Nagivate;
+                if let Some(index) = self.recent_state.selected() {
+                    let path = self.config.recent_files.paths[index].clone();
+                    if !path.is_file() {
+                        // Files removed since the list was read are dropped once picked.
+                        self.config.recent_files.paths.remove(index);
+                        self.config_changed = true;
+                        eyre::bail!("Removed recent file {:?} that no longer exists", path);
+                    }
+                    self.open(tab, path);
+                }
             }
-            Mode::Write => {
+            Mode::StartWrite => {
+                self.type_buffer = match &tab.path {
+                    Some(_) => tab.name().to_owned(),
+                    None => format!("{}.wav", tab.name()),
+                };
+                self.mode = Mode::Type;
+            }
+            Mode::Write => match self.save_as(&tab.samples) {
+                Ok((path, wave)) => {
+                    // Tabs save as WAV, so they only take over the path of WAV files.
+                    if wave {
+                        tab.path = Some(path);
+                        tab.modified = false;
+                    }
+                    self.prompt_error = None;
+                    self.type_buffer.clear();
+                    self.chdir(self.cwd.clone());
+                    self.mode = Mode::Nagivate;
+                }
+                // Failed writes keep the prompt open to pick another name.
+                Err(error) => {
+                    self.prompt_error = Some(format!("{}", error));
+                    self.mode = Mode::Type;
+                }
+            },
+            Mode::WriteExport => {
+                let selection = tab
+                    .selection
+                    .ok_or_else(|| eyre::eyre!("Select frames in the chart to export them"))?;
                 let path = self.cwd.join(&self.type_buffer);
-                audio::write_samples(&path, samples)?;
+                self.write(&path, &tab.samples.slice(selection))?;
 
                 self.type_buffer.clear();
                 self.chdir(self.cwd.clone());
                 self.mode = Mode::Nagivate;
             }
+            Mode::WriteAiff => {
+                let bits = self.bits_buffer.parse()?;
+                let path = self.cwd.join(&self.type_buffer);
+                self.mode = Mode::Nagivate;
+                aiff::write_aiff(&path, &tab.samples, bits)?;
+                labels::write_sidecar(&path, &tab.samples)?;
+
+                self.type_buffer.clear();
+                self.chdir(self.cwd.clone());
+            }
+            Mode::WriteOgg => {
+                let quality = self.quality_buffer.parse()?;
+                let path = self.cwd.join(&self.type_buffer);
+                self.mode = Mode::Nagivate;
+                audio::write_ogg(&path, &tab.samples, quality)?;
+                labels::write_sidecar(&path, &tab.samples)?;
+
+                self.type_buffer.clear();
+                self.chdir(self.cwd.clone());
+            }
             _ => (),
         }
 
         Ok(())
     }
 
-    fn render<'b>(&mut self, frame: &mut Frame<'b, B>, area: Rect) {
-        let entries: Vec<ListItem> = self
-            .files
-            .iter()
+    fn render<'b>(&mut self, frame: &mut Frame<'b, B>, area: Rect, theme: &Theme) {
+        let visible: Vec<&(String, bool)> = if self.mode == Mode::Search {
+            let files = &self.files;
+            self.matches()
+                .into_iter()
+                .map(|index| &files[index])
+                .collect()
+        } else {
+            self.files.iter().collect()
+        };
+        let entries: Vec<ListItem> = visible
+            .into_iter()
             .map(|(file, is_dir)| {
-                let item = ListItem::new(file.as_ref());
+                if !*is_dir {
+                    return ListItem::new(file.as_ref());
+                }
 
-                if *is_dir {
-                    item.style(Style::default().add_modifier(Modifier::BOLD))
+                let bookmarked = self.config.bookmarks.contains(&self.cwd.join(file));
+                let item = if bookmarked {
+                    ListItem::new(format!("{} {}", BOOKMARK_GLYPH, file))
                 } else {
-                    item
-                }
+                    ListItem::new(file.as_ref())
+                };
+                item.style(Style::default().add_modifier(Modifier::BOLD))
             })
             .collect();
 
-        let block = Block::default().borders(Borders::ALL);
+        let search = if self.mode == Mode::Search {
+            format!(" /{}", self.search_buffer)
+        } else {
+            String::new()
+        };
+        let width = usize::from(area.width.saturating_sub(2)).saturating_sub(search.len());
+        let title = path::breadcrumbs(&self.cwd, width).join(path::BREADCRUMB_SEPARATOR) + &search;
+        let block = Block::default().title(title).borders(Borders::ALL);
 
         let list = List::new(entries)
             .block(block)
-            .highlight_style(Style::default().add_modifier(Modifier::ITALIC))
+            .highlight_style(theme.selection())
             .highlight_symbol("> ");
 
         frame.render_stateful_widget(list, area, &mut self.state);
 
-        if self.mode == Mode::Type {
-            let area = ui::util::centered_rectangle(60, 20, area);
-            frame.render_widget(Clear, area);
+        match self.mode {
+            Mode::Bookmarks => {
+                let bookmarks = &self.config.bookmarks;
+                render_paths(
+                    frame,
+                    area,
+                    theme,
+                    "Bookmarks",
+                    bookmarks,
+                    Path::is_dir,
+                    &mut self.bookmark_state,
+                );
+                return;
+            }
+            Mode::Recent => {
+                let recent = &self.config.recent_files.paths;
+                render_paths(
+                    frame,
+                    area,
+                    theme,
+                    "Recent Files",
+                    recent,
+                    Path::is_file,
+                    &mut self.recent_state,
+                );
+                return;
+            }
+            _ => (),
+        }
+
+        let title = match self.mode {
+            Mode::Aiff => "Save as AIFF",
+            Mode::Bits => "AIFF bits per sample, 16 or 24",
+            Mode::ConfirmExport | Mode::ConfirmWrite => "Confirm overwrite",
+            Mode::Crossfade => "Append with crossfade in milliseconds",
+            Mode::Csv => "CSV sample rate",
+            Mode::Export => "Export selection",
+            Mode::Generate => "Generate waveform, frequency, amplitude, seconds, channels, rate",
+            Mode::Goto => "Go to",
+            Mode::Ogg => "Save as OGG",
+            Mode::Quality => "OGG quality from -1.0 to 1.0",
+            Mode::Raw => "Raw PCM format (s16le, s24le, f32le, u8), channels, and rate",
+            Mode::Type => "Save as .wav, .aiff, .ogg, or .csv",
+            _ => return,
+        };
 
-            let block = Block::default().title("Write").borders(Borders::ALL);
-            let text = Text::from(self.type_buffer.as_ref());
-            let line = Paragraph::new(text).block(block);
+        let area = ui::util::centered_rectangle(60, 20, area);
+        frame.render_widget(Clear, area);
 
-            frame.render_widget(line, area);
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .style(theme.base());
+        let mut text = if matches!(self.mode, Mode::ConfirmExport | Mode::ConfirmWrite) {
+            Text::from(format!(
+                "File {} already exists. Overwrite it? (y/n)",
+                self.type_buffer
+            ))
+        } else if self.mode == Mode::Bits {
+            Text::from(self.bits_buffer.as_ref())
+        } else if self.mode == Mode::Crossfade {
+            Text::from(self.crossfade_buffer.as_ref())
+        } else if self.mode == Mode::Generate {
+            Text::from(self.generate_buffer.as_ref())
+        } else if self.mode == Mode::Csv {
+            Text::from(self.rate_buffer.as_ref())
+        } else if self.mode == Mode::Quality {
+            Text::from(self.quality_buffer.as_ref())
+        } else if self.mode == Mode::Raw {
+            Text::from(self.raw_buffer.as_ref())
+        } else {
+            Text::from(self.type_buffer.as_ref())
+        };
+        if let Some(error) = &self.prompt_error {
+            text.extend(Text::from(error.as_ref()));
         }
+        let line = Paragraph::new(text).block(block);
+
+        frame.render_widget(line, area);
+    }
+
+    fn opened(&mut self) -> Option<Tab> {
+        self.opened.take()
     }
 
     fn reset(&mut self) {
+        self.appending = None;
         self.mode = Mode::Nagivate;
+        self.prompt_error = None;
+        self.import_path = None;
     }
 }
 
+/// Draw a list of paths as a popup, dimming paths that fail the existence check.
+fn render_paths<B: Backend>(
+    frame: &mut Frame<'_, B>,
+    area: Rect,
+    theme: &Theme,
+    title: &str,
+    paths: &[PathBuf],
+    exists: fn(&Path) -> bool,
+    state: &mut ListState,
+) {
+    let area = ui::util::centered_rectangle(60, 60, area);
+    frame.render_widget(Clear, area);
+
+    let entries: Vec<ListItem> = paths
+        .iter()
+        .map(|path| {
+            if exists(path) {
+                ListItem::new(path.to_string_lossy())
+            } else {
+                ListItem::new(format!("{} (missing)", path.to_string_lossy()))
+                    .style(Style::default().add_modifier(Modifier::DIM))
+            }
+        })
+        .collect();
+
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .style(theme.base());
+    let list = List::new(entries)
+        .block(block)
+        .highlight_style(theme.selection())
+        .highlight_symbol("> ");
+
+    frame.render_stateful_widget(list, area, state);
+}
+
 #[derive(Debug, Eq, PartialEq)]
 enum Mode {
+    Aiff,
+    Append,
+    Bits,
+    Bookmarks,
+    ConfirmExport,
+    ConfirmWrite,
+    Crossfade,
+    Csv,
+    Export,
+    Generate,
+    Goto,
+    Ogg,
+    OpenGenerated,
+    Quality,
+    Raw,
     Read,
+    ReadCsv,
+    ReadRaw,
+    ReadRecent,
+    Recent,
     Nagivate,
+    Search,
+    StartExport,
+    StartWrite,
     Type,
     Write,
+    WriteAiff,
+    WriteExport,
+    WriteOgg,
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+    use crate::util;
+    use std::fs;
+    use std::time::Duration;
+    use tui::backend::TestBackend;
+    use tui::Terminal;
+
+    fn type_text(file: &mut File, text: &str) {
+        for char in text.chars() {
+            View::<TestBackend>::key_event(
+                file,
+                KeyEvent::new(KeyCode::Char(char), KeyModifiers::NONE),
+            );
+        }
+    }
+
+    #[test]
+    fn bookmark_persist_and_delete() {
+        let folder = tempfile::tempdir().unwrap();
+        let config_path = folder.path().join("config.toml");
+        let mut tab = Tab::default();
+
+        let mut file = File::try_new(folder.path().to_owned())
+            .unwrap()
+            .with_config(Config::default(), Some(config_path.clone()));
+        type_text(&mut file, "bb");
+        View::<TestBackend>::process(&mut file, &mut tab).unwrap();
+
+        let config = Config::read(&config_path).unwrap();
+        assert_eq!(config.bookmarks, vec![folder.path().to_owned()]);
+
+        type_text(&mut file, "Bd");
+        View::<TestBackend>::process(&mut file, &mut tab).unwrap();
+
+        let config = Config::read(&config_path).unwrap();
+        assert!(config.bookmarks.is_empty());
+    }
+
+    #[test]
+    fn bookmark_highlighted_directory_and_prune_missing() {
+        let folder = tempfile::tempdir().unwrap();
+        let config_path = folder.path().join("config.toml");
+        let drums = folder.path().join("drums");
+        fs::create_dir(&drums).unwrap();
+        let config = Config {
+            bookmarks: vec![folder.path().join("gone")],
+            ..Config::default()
+        };
+        let mut tab = Tab::default();
+
+        let mut file = File::try_new(folder.path().to_owned())
+            .unwrap()
+            .with_config(config, Some(config_path.clone()));
+        let error = View::<TestBackend>::process(&mut file, &mut tab).unwrap_err();
+        assert!(format!("{}", error).contains("gone"));
+        assert!(Config::read(&config_path).unwrap().bookmarks.is_empty());
+
+        View::<TestBackend>::key_event(&mut file, KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+        type_text(&mut file, "b");
+        View::<TestBackend>::process(&mut file, &mut tab).unwrap();
+        assert_eq!(
+            Config::read(&config_path).unwrap().bookmarks,
+            vec![drums.clone()]
+        );
+
+        let mut terminal = Terminal::new(TestBackend::new(40, 6)).unwrap();
+        terminal
+            .draw(|frame| View::render(&mut file, frame, frame.size(), &Theme::default()))
+            .unwrap();
+        let actual = util::test::buffer_view(terminal.backend().buffer());
+        assert!(actual.contains("★ drums"));
+
+        type_text(&mut file, "B");
+        View::<TestBackend>::key_event(
+            &mut file,
+            KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE),
+        );
+        assert_eq!(file.cwd, drums);
+    }
+
+    #[test]
+    fn bookmark_of_removed_directory_reports_missing() {
+        let folder = tempfile::tempdir().unwrap();
+        let config_path = folder.path().join("config.toml");
+        let drums = folder.path().join("drums");
+        fs::create_dir(&drums).unwrap();
+        let config = Config {
+            bookmarks: vec![drums.clone()],
+            ..Config::default()
+        };
+        let mut tab = Tab::default();
+
+        let mut file = File::try_new(folder.path().to_owned())
+            .unwrap()
+            .with_config(config, Some(config_path.clone()));
+        fs::remove_dir(&drums).unwrap();
+        type_text(&mut file, "B");
+        View::<TestBackend>::key_event(
+            &mut file,
+            KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE),
+        );
+        assert_eq!(file.cwd, folder.path());
+
+        let error = View::<TestBackend>::process(&mut file, &mut tab).unwrap_err();
+        assert!(format!("{}", error).contains("no longer exists"));
+        assert!(Config::read(&config_path).unwrap().bookmarks.is_empty());
+        assert_eq!(file.bookmark_state.selected(), None);
+    }
+
+    #[test]
+    fn process_lists_created_and_removed_files() {
+        let folder = tempfile::tempdir().unwrap();
+        for name in ["b.wav", "d.wav"] {
+            fs::write(folder.path().join(name), b"").unwrap();
+        }
+        let mut tab = Tab::default();
+        let touch = |seconds| {
+            let directory = fs::File::open(folder.path()).unwrap();
+            let modified = directory.metadata().unwrap().modified().unwrap();
+            directory
+                .set_modified(modified + Duration::from_secs(seconds))
+                .unwrap();
+        };
+
+        let mut file = File::try_new(folder.path().to_owned()).unwrap();
+        file.watcher = Watcher::new(folder.path().to_owned(), Duration::ZERO);
+        file.select("d.wav");
+        fs::write(folder.path().join("a.wav"), b"").unwrap();
+        fs::write(folder.path().join("c.wav"), b"").unwrap();
+        touch(1);
+        View::<TestBackend>::process(&mut file, &mut tab).unwrap();
+        let names: Vec<&str> = file.files.iter().map(|(name, _)| name.as_ref()).collect();
+        assert_eq!(names, vec!["a.wav", "b.wav", "c.wav", "d.wav"]);
+        assert_eq!(file.state.selected(), Some(3));
+
+        type_text(&mut file, "/c");
+        fs::remove_file(folder.path().join("a.wav")).unwrap();
+        touch(2);
+        View::<TestBackend>::process(&mut file, &mut tab).unwrap();
+        assert_eq!(file.mode, Mode::Search);
+        assert_eq!(file.files.len(), 3);
+        assert_eq!(file.matches(), vec![1]);
+        assert_eq!(file.state.selected(), Some(0));
+
+        fs::remove_file(folder.path().join("c.wav")).unwrap();
+        touch(3);
+        View::<TestBackend>::process(&mut file, &mut tab).unwrap();
+        assert_eq!(file.state.selected(), None);
+
+        fs::create_dir(folder.path().join("takes")).unwrap();
+        file.chdir(folder.path().join("takes"));
+        assert_eq!(file.watcher.directory(), folder.path().join("takes"));
+    }
+
+    #[test]
+    fn search_filters_and_opens_matches() {
+        let folder = tempfile::tempdir().unwrap();
+        let samples = Samples::new(1, 8000, vec![0.0, 0.5]);
+        for name in ["Kick.wav", "snare.wav", "kick_soft.wav"] {
+            audio::write_samples(&folder.path().join(name), &samples).unwrap();
+        }
+        fs::create_dir(folder.path().join("kicks")).unwrap();
+        let mut tab = Tab::default();
+        let key = |file: &mut File, code| {
+            View::<TestBackend>::key_event(file, KeyEvent::new(code, KeyModifiers::NONE));
+        };
+
+        let mut file = File::try_new(folder.path().to_owned()).unwrap();
+        type_text(&mut file, "/KICK");
+        let names: Vec<&str> = file
+            .matches()
+            .into_iter()
+            .map(|index| file.files[index].0.as_ref())
+            .collect();
+        assert_eq!(names, vec!["kicks", "Kick.wav", "kick_soft.wav"]);
+        assert_eq!(file.state.selected(), Some(0));
+
+        key(&mut file, KeyCode::Down);
+        key(&mut file, KeyCode::Down);
+        key(&mut file, KeyCode::Down);
+        assert_eq!(file.state.selected(), Some(0));
+        key(&mut file, KeyCode::Up);
+        key(&mut file, KeyCode::Enter);
+        assert!(file.search_buffer.is_empty());
+        View::<TestBackend>::process(&mut file, &mut tab).unwrap();
+        while tab.is_loading() {
+            tab.poll().unwrap();
+        }
+        assert_eq!(tab.path, Some(folder.path().join("kick_soft.wav")));
+
+        type_text(&mut file, "/sn");
+        key(&mut file, KeyCode::Esc);
+        assert_eq!(file.mode, Mode::Nagivate);
+        assert_eq!(file.matches().len(), 4);
+        assert_eq!(file.files[file.state.selected().unwrap()].0, "snare.wav");
+
+        type_text(&mut file, "/kicks");
+        key(&mut file, KeyCode::Enter);
+        assert_eq!(file.cwd, folder.path().join("kicks"));
+        assert_eq!(file.mode, Mode::Nagivate);
+
+        // Moving through an empty listing selects nothing.
+        assert!(file.files.is_empty());
+        key(&mut file, KeyCode::Down);
+        key(&mut file, KeyCode::Up);
+        assert_eq!(file.state.selected(), None);
+    }
+
+    #[test]
+    fn recent_files_open_and_update() {
+        let folder = tempfile::tempdir().unwrap();
+        let config_path = folder.path().join("config.toml");
+        let first = folder.path().join("first.wav");
+        let second = folder.path().join("second.wav");
+        let samples = Samples::new(1, 8000, vec![0.0, 0.5]);
+        audio::write_samples(&first, &samples).unwrap();
+        audio::write_samples(&second, &samples).unwrap();
+        let mut tab = Tab::default();
+
+        let mut file = File::try_new(folder.path().to_owned())
+            .unwrap()
+            .with_config(Config::default(), Some(config_path.clone()));
+        file.remember(first.clone());
+        file.remember(second.clone());
+
+        type_text(&mut file, "r");
+        View::<TestBackend>::key_event(&mut file, KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+        View::<TestBackend>::key_event(
+            &mut file,
+            KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE),
+        );
+        View::<TestBackend>::process(&mut file, &mut tab).unwrap();
+        while tab.is_loading() {
+            tab.poll().unwrap();
+        }
+        View::<TestBackend>::process(&mut file, &mut tab).unwrap();
+
+        assert_eq!(tab.path, Some(first.clone()));
+        let config = Config::read(&config_path).unwrap();
+        assert_eq!(
+            config.recent_files.paths,
+            vec![first.clone(), second.clone()]
+        );
+
+        fs::remove_file(&second).unwrap();
+        type_text(&mut file, "r");
+        View::<TestBackend>::key_event(&mut file, KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+        View::<TestBackend>::key_event(
+            &mut file,
+            KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE),
+        );
+        assert!(View::<TestBackend>::process(&mut file, &mut tab).is_err());
+        View::<TestBackend>::process(&mut file, &mut tab).unwrap();
+        assert_eq!(tab.path, Some(first.clone()));
+        let config = Config::read(&config_path).unwrap();
+        assert_eq!(config.recent_files.paths, vec![first]);
+    }
+
+    #[test]
+    fn export_confirms_overwrite() {
+        let folder = tempfile::tempdir().unwrap();
+        let target = folder.path().join("out.wav");
+        audio::write_samples(&target, &Samples::new(1, 8, vec![0.0])).unwrap();
+
+        let samples = Samples::new(1, 8, vec![0.0, 0.25, 0.5, 0.75]);
+        let mut tab = Tab::new(samples, None);
+        tab.selection = Some(crate::dsp::Selection::new(1, 3));
+        let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+
+        let mut file = File::try_new(folder.path().to_owned()).unwrap();
+        type_text(&mut file, "x");
+        View::<TestBackend>::process(&mut file, &mut tab).unwrap();
+        assert_eq!(file.type_buffer, "untitled.selection.wav");
+        file.type_buffer = String::from("out.wav");
+        View::<TestBackend>::key_event(&mut file, enter);
+        assert_eq!(file.mode, Mode::ConfirmExport);
+
+        type_text(&mut file, "n");
+        assert_eq!(file.mode, Mode::Export);
+        View::<TestBackend>::process(&mut file, &mut tab).unwrap();
+        assert_eq!(audio::read_samples(&target).unwrap().data.len(), 1);
+
+        View::<TestBackend>::key_event(&mut file, enter);
+        type_text(&mut file, "y");
+        View::<TestBackend>::process(&mut file, &mut tab).unwrap();
+
+        assert_eq!(file.mode, Mode::Nagivate);
+        assert_eq!(audio::read_samples(&target).unwrap().data.len(), 2);
+        assert_eq!(tab.samples.data.len(), 4);
+    }
+
+    #[test]
+    fn export_stereo_selection() {
+        let folder = tempfile::tempdir().unwrap();
+        let data = (0..20).map(|index| index as f32 / 20.0).collect();
+        let mut tab = Tab::new(
+            Samples::new(2, 8000, data),
+            Some(folder.path().join("take.wav")),
+        );
+        let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+
+        let mut file = File::try_new(folder.path().to_owned()).unwrap();
+        type_text(&mut file, "x");
+        assert!(View::<TestBackend>::process(&mut file, &mut tab).is_err());
+        assert_eq!(file.mode, Mode::Nagivate);
+
+        tab.selection = Some(crate::dsp::Selection::new(7, 3));
+        type_text(&mut file, "x");
+        View::<TestBackend>::process(&mut file, &mut tab).unwrap();
+        View::<TestBackend>::key_event(&mut file, enter);
+        View::<TestBackend>::process(&mut file, &mut tab).unwrap();
+
+        let actual = audio::read_samples(&folder.path().join("take.selection.wav")).unwrap();
+        let expected: Vec<f32> = (6..14).map(|index| index as f32 / 20.0).collect();
+        assert_eq!(actual.channels, 2);
+        assert_eq!(actual.sample_rate, 8000);
+        assert_eq!(actual.data.len(), expected.len());
+        assert!(actual
+            .data
+            .iter()
+            .zip(&expected)
+            .all(|(actual, expected)| (actual - expected).abs() < 1e-4));
+        assert_eq!(tab.samples.data.len(), 20);
+    }
+
+    #[test]
+    fn save_as_prompts_and_confirms_overwrite() {
+        let folder = tempfile::tempdir().unwrap();
+        let original = folder.path().join("take.wav");
+        let samples = Samples::new(2, 8000, vec![0.0, 0.25, 0.5, 0.75]);
+        audio::write_samples(&original, &samples).unwrap();
+        let mut tab = Tab::new(samples, Some(original.clone()));
+        tab.modified = true;
+        let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+        let backspace = KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE);
+
+        let mut file = File::try_new(folder.path().to_owned()).unwrap();
+        type_text(&mut file, "w");
+        View::<TestBackend>::process(&mut file, &mut tab).unwrap();
+        assert_eq!(file.mode, Mode::Type);
+        assert_eq!(file.type_buffer, "take.wav");
+
+        View::<TestBackend>::key_event(&mut file, enter);
+        assert_eq!(file.mode, Mode::ConfirmWrite);
+        type_text(&mut file, "n");
+        assert_eq!(file.mode, Mode::Type);
+
+        for _ in 0.."take.wav".len() {
+            View::<TestBackend>::key_event(&mut file, backspace);
+        }
+        View::<TestBackend>::key_event(&mut file, enter);
+        assert_eq!(file.mode, Mode::Type);
+        assert!(file.prompt_error.is_some());
+
+        type_text(&mut file, "missing/copy.wav");
+        View::<TestBackend>::key_event(&mut file, enter);
+        assert_eq!(file.mode, Mode::Type);
+
+        file.type_buffer = String::from("copy.wav");
+        View::<TestBackend>::key_event(&mut file, enter);
+        assert_eq!(file.mode, Mode::Write);
+        View::<TestBackend>::process(&mut file, &mut tab).unwrap();
+
+        let copy = folder.path().join("copy.wav");
+        assert_eq!(file.mode, Mode::Nagivate);
+        assert!(file.prompt_error.is_none());
+        assert_eq!(tab.path, Some(copy.clone()));
+        assert_eq!(tab.name(), "copy.wav");
+        assert!(!tab.modified);
+        let written = audio::read_samples(&copy).unwrap();
+        assert!(written
+            .data
+            .iter()
+            .zip(&tab.samples.data)
+            .all(|(written, expected)| (written - expected).abs() < 1e-4));
+    }
+
+    #[test]
+    fn save_ogg_prompts_for_quality() {
+        let folder = tempfile::tempdir().unwrap();
+        let samples = Samples::new(1, 8000, vec![0.0; 8000]);
+        let mut tab = Tab::new(samples, None);
+        let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+        let backspace = KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE);
+
+        let mut file = File::try_new(folder.path().to_owned()).unwrap();
+        type_text(&mut file, "oout.ogg");
+        View::<TestBackend>::key_event(&mut file, enter);
+        assert_eq!(file.mode, Mode::Quality);
+
+        type_text(&mut file, "x");
+        View::<TestBackend>::key_event(&mut file, enter);
+        assert_eq!(file.mode, Mode::Quality);
+        assert!(file.prompt_error.is_some());
+
+        View::<TestBackend>::key_event(&mut file, backspace);
+        View::<TestBackend>::key_event(&mut file, enter);
+        View::<TestBackend>::process(&mut file, &mut tab).unwrap();
+
+        assert_eq!(file.mode, Mode::Nagivate);
+        assert!(folder.path().join("out.ogg").is_file());
+        assert!(!labels::sidecar(&folder.path().join("out.ogg")).exists());
+
+        tab.samples.markers = vec![crate::dsp::Marker::new(10, String::from("hit"))];
+        type_text(&mut file, "oout.ogg");
+        View::<TestBackend>::key_event(&mut file, enter);
+        View::<TestBackend>::key_event(&mut file, enter);
+        View::<TestBackend>::process(&mut file, &mut tab).unwrap();
+        assert!(labels::sidecar(&folder.path().join("out.ogg")).is_file());
+    }
+
+    #[test]
+    fn save_aiff_prompts_for_bits() {
+        let folder = tempfile::tempdir().unwrap();
+        let samples = Samples::new(2, 8000, vec![0.25; 8000]);
+        let mut tab = Tab::new(samples, None);
+        let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+        let backspace = KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE);
+
+        let mut file = File::try_new(folder.path().to_owned()).unwrap();
+        type_text(&mut file, "iout.aiff");
+        View::<TestBackend>::key_event(&mut file, enter);
+        assert_eq!(file.mode, Mode::Bits);
+
+        View::<TestBackend>::key_event(&mut file, backspace);
+        View::<TestBackend>::key_event(&mut file, enter);
+        assert_eq!(file.mode, Mode::Bits);
+        assert!(file.prompt_error.is_some());
+
+        View::<TestBackend>::key_event(&mut file, backspace);
+        type_text(&mut file, "24");
+        View::<TestBackend>::key_event(&mut file, enter);
+        View::<TestBackend>::process(&mut file, &mut tab).unwrap();
+
+        assert_eq!(file.mode, Mode::Nagivate);
+        let actual = audio::read_samples(&folder.path().join("out.aiff")).unwrap();
+        assert_eq!(actual.channels, 2);
+        assert_eq!(actual.data, tab.samples.data);
+    }
+
+    #[test]
+    fn append_file_with_crossfade() {
+        let folder = tempfile::tempdir().unwrap();
+        let samples = Samples::new(1, 1000, vec![0.0; 20]);
+        audio::write_samples(&folder.path().join("a.wav"), &samples).unwrap();
+
+        let mut tab = Tab::new(Samples::new(1, 1000, vec![1.0; 10]), None);
+        let mut file = File::try_new(folder.path().to_owned()).unwrap();
+        let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+        let backspace = KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE);
+        View::<TestBackend>::key_event(&mut file, KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+
+        type_text(&mut file, "a");
+        View::<TestBackend>::key_event(&mut file, backspace);
+        type_text(&mut file, "-3");
+        View::<TestBackend>::key_event(&mut file, enter);
+        assert!(file.prompt_error.is_some());
+        View::<TestBackend>::key_event(&mut file, backspace);
+        View::<TestBackend>::key_event(&mut file, backspace);
+        type_text(&mut file, "4");
+        View::<TestBackend>::key_event(&mut file, enter);
+
+        for _ in 0..500 {
+            View::<TestBackend>::process(&mut file, &mut tab).unwrap();
+            if file.mode == Mode::Nagivate && file.appending.is_none() {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+
+        assert_eq!(tab.samples.frames(), 26);
+        assert!((tab.samples.data[6] - 0.8).abs() < 1e-6);
+        assert!(tab.modified);
+        assert!(tab.history.can_undo());
+    }
+
+    #[test]
+    fn generate_opens_signal_unless_tab_is_used() {
+        let folder = tempfile::tempdir().unwrap();
+        let mut file = File::try_new(folder.path().to_owned()).unwrap();
+        let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+        let mut tab = Tab::default();
+
+        type_text(&mut file, "n");
+        assert_eq!(file.mode, Mode::Generate);
+        file.generate_buffer = String::from("beep 440 0.5 1 1 8000");
+        View::<TestBackend>::key_event(&mut file, enter);
+        assert!(file.prompt_error.is_some());
+        assert_eq!(file.mode, Mode::Generate);
+
+        file.generate_buffer.clear();
+        type_text(&mut file, "sweep 1000 0.5 0.5 2 8000");
+        View::<TestBackend>::key_event(&mut file, enter);
+        View::<TestBackend>::process(&mut file, &mut tab).unwrap();
+        assert_eq!(file.mode, Mode::Nagivate);
+        assert_eq!(tab.samples.channels, 2);
+        assert_eq!(tab.samples.sample_rate, 8000);
+        assert_eq!(tab.samples.frames(), 4000);
+        assert!(tab.modified);
+        assert!(tab.path.is_none());
+
+        type_text(&mut file, "n");
+        View::<TestBackend>::key_event(&mut file, enter);
+        View::<TestBackend>::process(&mut file, &mut tab).unwrap();
+        assert_eq!(tab.samples.frames(), 4000);
+        let opened = View::<TestBackend>::opened(&mut file).unwrap();
+        assert_eq!(opened.samples.channels, 2);
+    }
+
+    #[test]
+    fn open_adds_tab_unless_blank() {
+        let folder = tempfile::tempdir().unwrap();
+        let samples = Samples::new(1, 8, vec![0.25; 4]);
+        audio::write_samples(&folder.path().join("a.wav"), &samples).unwrap();
+
+        let mut file = File::try_new(folder.path().to_owned()).unwrap();
+        let down = KeyEvent::new(KeyCode::Down, KeyModifiers::NONE);
+        let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+        View::<TestBackend>::key_event(&mut file, down);
+
+        let mut tab = Tab::default();
+        View::<TestBackend>::key_event(&mut file, enter);
+        View::<TestBackend>::process(&mut file, &mut tab).unwrap();
+        assert!(tab.is_loading());
+        assert!(View::<TestBackend>::opened(&mut file).is_none());
+
+        let mut tab = Tab::new(Samples::new(1, 8, vec![0.5; 2]), None);
+        tab.modified = true;
+        View::<TestBackend>::key_event(&mut file, enter);
+        View::<TestBackend>::process(&mut file, &mut tab).unwrap();
+        assert_eq!(tab.samples.data, vec![0.5; 2]);
+        assert!(tab.modified);
+        assert!(View::<TestBackend>::opened(&mut file).unwrap().is_loading());
+        assert!(View::<TestBackend>::opened(&mut file).is_none());
+    }
+
+    #[test]
+    fn open_headerless_prompts_for_raw_parameters() {
+        let folder = tempfile::tempdir().unwrap();
+        let bytes = [0x00, 0x40, 0x00, 0xC0, 0x00, 0x20, 0x00];
+        fs::write(folder.path().join("capture.raw"), bytes).unwrap();
+
+        let mut file = File::try_new(folder.path().to_owned()).unwrap();
+        let mut tab = Tab::default();
+        let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+        View::<TestBackend>::key_event(&mut file, KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+        View::<TestBackend>::key_event(&mut file, enter);
+        View::<TestBackend>::process(&mut file, &mut tab).unwrap();
+        assert_eq!(file.mode, Mode::Raw);
+        assert!(!tab.is_loading());
+
+        file.raw_buffer = String::from("s16be 1 8000");
+        View::<TestBackend>::key_event(&mut file, enter);
+        assert!(file.prompt_error.is_some());
+        file.raw_buffer = String::from("s16le 0 8000");
+        View::<TestBackend>::key_event(&mut file, enter);
+        assert_eq!(file.mode, Mode::Raw);
+
+        file.raw_buffer = String::from("s16le 1 8000");
+        View::<TestBackend>::key_event(&mut file, enter);
+        let error = View::<TestBackend>::process(&mut file, &mut tab).unwrap_err();
+        assert!(format!("{}", error).contains("Dropped 1 trailing bytes"));
+        assert_eq!(tab.samples, Samples::new(1, 8000, vec![0.5, -0.5, 0.25]));
+        assert!(tab.modified);
+        assert_eq!(file.mode, Mode::Nagivate);
+
+        file.raw_buffer = String::from("u8 7 8000");
+        type_text(&mut file, "p");
+        assert_eq!(file.mode, Mode::Raw);
+        View::<TestBackend>::key_event(&mut file, enter);
+        View::<TestBackend>::process(&mut file, &mut tab).unwrap();
+        let opened = View::<TestBackend>::opened(&mut file).unwrap();
+        assert_eq!(opened.samples.frames(), 1);
+        assert_eq!(opened.samples.channels, 7);
+    }
+
+    #[test]
+    fn save_and_open_csv() {
+        let folder = tempfile::tempdir().unwrap();
+        let config = Config {
+            csv_index: csv::IndexColumn::Frame,
+            ..Config::default()
+        };
+        let mut file = File::try_new(folder.path().to_owned())
+            .unwrap()
+            .with_config(config, None);
+        let samples = Samples::new(2, 8000, vec![0.5, -0.25, 0.125, 1.0]);
+        let mut tab = Tab::new(samples.clone(), None);
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+
+        type_text(&mut file, "w");
+        View::<TestBackend>::process(&mut file, &mut tab).unwrap();
+        file.type_buffer = String::from("values.csv");
+        View::<TestBackend>::key_event(&mut file, key(KeyCode::Enter));
+        View::<TestBackend>::process(&mut file, &mut tab).unwrap();
+        assert_eq!(file.mode, Mode::Nagivate);
+        assert_eq!(tab.path, None);
+        let text = fs::read_to_string(folder.path().join("values.csv")).unwrap();
+        assert!(text.starts_with("frame,channel 1,channel 2\n0,0.5,-0.25\n"));
+
+        let mut tab = Tab::default();
+        View::<TestBackend>::key_event(&mut file, key(KeyCode::Down));
+        View::<TestBackend>::key_event(&mut file, key(KeyCode::Enter));
+        View::<TestBackend>::process(&mut file, &mut tab).unwrap();
+        assert_eq!(file.mode, Mode::Csv);
+
+        View::<TestBackend>::key_event(&mut file, key(KeyCode::Backspace));
+        type_text(&mut file, "x");
+        View::<TestBackend>::key_event(&mut file, key(KeyCode::Enter));
+        assert!(file.prompt_error.is_some());
+        file.rate_buffer = String::from("8000");
+        View::<TestBackend>::key_event(&mut file, key(KeyCode::Enter));
+        View::<TestBackend>::process(&mut file, &mut tab).unwrap();
+        assert_eq!(tab.samples, samples);
+        assert!(tab.modified);
+    }
+
+    #[test]
+    fn goto_file_highlights_entry() {
+        let folder = tempfile::tempdir().unwrap();
+        fs::create_dir(folder.path().join("loops")).unwrap();
+        fs::File::create(folder.path().join("loops").join("a.wav")).unwrap();
+        fs::File::create(folder.path().join("loops").join("b.wav")).unwrap();
+
+        let mut file = File::try_new(folder.path().to_owned()).unwrap();
+        type_text(&mut file, "gloops/b.wav");
+        View::<TestBackend>::key_event(
+            &mut file,
+            KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE),
+        );
+
+        assert_eq!(file.mode, Mode::Nagivate);
+        assert_eq!(file.cwd, folder.path().join("loops"));
+        assert_eq!(file.state.selected(), Some(1));
+    }
+
+    #[test]
+    fn scroll_moves_selection() {
+        let folder = tempfile::tempdir().unwrap();
+        fs::write(folder.path().join("a.wav"), "").unwrap();
+        fs::write(folder.path().join("b.wav"), "").unwrap();
+        let mut file = File::try_new(folder.path().to_owned()).unwrap();
+        let mut tab = Tab::default();
+
+        let mut scroll = |kind| {
+            let event = MouseEvent {
+                kind,
+                column: 0,
+                row: 0,
+                modifiers: KeyModifiers::NONE,
+            };
+            View::<TestBackend>::mouse_event(&mut file, event, &mut tab);
+        };
+        scroll(MouseEventKind::ScrollDown);
+        scroll(MouseEventKind::ScrollDown);
+        assert_eq!(file.state.selected(), Some(1));
+
+        let mut scroll = |kind| {
+            let event = MouseEvent {
+                kind,
+                column: 0,
+                row: 0,
+                modifiers: KeyModifiers::NONE,
+            };
+            View::<TestBackend>::mouse_event(&mut file, event, &mut tab);
+        };
+        scroll(MouseEventKind::ScrollUp);
+        assert_eq!(file.state.selected(), Some(0));
+    }
+
+    #[test]
+    fn parent_at_root() {
+        let mut file = File::try_new(PathBuf::from("/")).unwrap();
+        View::<TestBackend>::key_event(&mut file, KeyEvent::new(KeyCode::Left, KeyModifiers::NONE));
+
+        assert_eq!(file.cwd, PathBuf::from("/"));
+    }
+
+    #[test]
+    fn parent_highlights_previous() {
+        let folder = tempfile::tempdir().unwrap();
+        fs::create_dir(folder.path().join("a")).unwrap();
+        fs::create_dir(folder.path().join("b")).unwrap();
+
+        let mut file = File::try_new(folder.path().join("b")).unwrap();
+        View::<TestBackend>::key_event(&mut file, KeyEvent::new(KeyCode::Left, KeyModifiers::NONE));
+
+        assert_eq!(file.cwd, folder.path());
+        assert_eq!(file.state.selected(), Some(1));
+    }
+
+    #[test]
+    fn goto_missing_keeps_prompt() {
+        let folder = tempfile::tempdir().unwrap();
+
+        let mut file = File::try_new(folder.path().to_owned()).unwrap();
+        type_text(&mut file, "gmissing");
+        View::<TestBackend>::key_event(
+            &mut file,
+            KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE),
+        );
+
+        assert_eq!(file.mode, Mode::Goto);
+        assert!(file.prompt_error.is_some());
+        assert_eq!(file.cwd, folder.path());
+    }
+}
@@ -1,6 +1,7 @@
 //! Fundemental traits for digital signal processing user interface components.
 
 use crate::dsp::buffer::Samples;
+use color_eyre::eyre;
 
 pub trait Knob {
     fn decrement(&mut self);
@@ -10,5 +11,10 @@ pub trait Knob {
 
 pub trait Filter {
     fn knobs(&mut self) -> Vec<(&str, &mut dyn Knob)>;
-    fn process(&mut self, samples: &mut Samples);
+    /// Apply the filter with the current knob settings.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the filter cannot be applied to the samples.
+    fn process(&mut self, samples: &mut Samples) -> eyre::Result<()>;
 }
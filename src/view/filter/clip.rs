@@ -0,0 +1,55 @@
+//! User interfaces for distorting a signal by clipping its peaks.
+
+use crate::dsp::buffer::Samples;
+use crate::dsp::filters::{hard_clip, soft_clip};
+use crate::view::filter::base::{Filter, Knob};
+use crate::view::filter::knobs::FloatKnob;
+use color_eyre::eyre;
+
+#[derive(Debug)]
+pub struct HardClip {
+    threshold: FloatKnob,
+}
+
+impl Default for HardClip {
+    fn default() -> Self {
+        Self {
+            threshold: FloatKnob::new(0.05, 1.0, 0.05, 0.5),
+        }
+    }
+}
+
+impl Filter for HardClip {
+    fn knobs(&mut self) -> Vec<(&str, &mut dyn Knob)> {
+        vec![("Threshold", &mut self.threshold)]
+    }
+
+    fn process(&mut self, samples: &mut Samples) -> eyre::Result<()> {
+        hard_clip(self.threshold.value, samples);
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct SoftClip {
+    drive: FloatKnob,
+}
+
+impl Default for SoftClip {
+    fn default() -> Self {
+        Self {
+            drive: FloatKnob::new(0.5, 20.0, 0.5, 2.0),
+        }
+    }
+}
+
+impl Filter for SoftClip {
+    fn knobs(&mut self) -> Vec<(&str, &mut dyn Knob)> {
+        vec![("Drive", &mut self.drive)]
+    }
+
+    fn process(&mut self, samples: &mut Samples) -> eyre::Result<()> {
+        soft_clip(self.drive.value, samples);
+        Ok(())
+    }
+}
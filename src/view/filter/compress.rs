@@ -0,0 +1,57 @@
+//! User interface for compressing and limiting the dynamics of a signal.
+
+use crate::dsp::buffer::Samples;
+use crate::dsp::filters::compress;
+use crate::view::filter::base::{Filter, Knob};
+use crate::view::filter::knobs::FloatKnob;
+use color_eyre::eyre;
+
+#[derive(Debug)]
+pub struct Compressor {
+    /// Milliseconds for the envelope to follow rising levels.
+    attack: FloatKnob,
+    ratio: FloatKnob,
+    /// Milliseconds for the envelope to follow falling levels.
+    release: FloatKnob,
+    /// Level in decibels above which samples are compressed.
+    threshold: FloatKnob,
+}
+
+impl Default for Compressor {
+    fn default() -> Self {
+        Self {
+            attack: FloatKnob::new(0.0, 200.0, 1.0, 5.0),
+            ratio: FloatKnob::new(1.0, 1000.0, 1.0, 4.0),
+            release: FloatKnob::new(0.0, 2000.0, 10.0, 100.0),
+            threshold: FloatKnob::new(-60.0, 0.0, 1.0, -12.0),
+        }
+    }
+}
+
+impl Filter for Compressor {
+    fn knobs(&mut self) -> Vec<(&str, &mut dyn Knob)> {
+        vec![
+            ("Threshold dB", &mut self.threshold),
+            ("Ratio", &mut self.ratio),
+            ("Attack ms", &mut self.attack),
+            ("Release ms", &mut self.release),
+        ]
+    }
+
+    fn process(&mut self, samples: &mut Samples) -> eyre::Result<()> {
+        let rate = samples.sample_rate as f32 / 1000.0;
+        // Knob bounds keep the times non negative, so sign loss is not possible.
+        #[allow(clippy::cast_sign_loss)]
+        let (attack, release) = (
+            (self.attack.value * rate).round() as usize,
+            (self.release.value * rate).round() as usize,
+        );
+        compress(
+            samples,
+            self.threshold.value,
+            self.ratio.value,
+            attack,
+            release,
+        )
+    }
+}
@@ -0,0 +1,44 @@
+//! User interface for reducing the bit depth and sample rate of a signal.
+
+use crate::dsp::buffer::Samples;
+use crate::dsp::filters::bitcrush;
+use crate::view::filter::base::{Filter, Knob};
+use crate::view::filter::knobs::FloatKnob;
+use color_eyre::eyre;
+
+#[derive(Debug)]
+pub struct BitCrusher {
+    /// Bit depth that samples are quantized to, where 24 keeps their resolution.
+    bits: FloatKnob,
+    /// Number of frames that each frame is held for.
+    downsample: FloatKnob,
+}
+
+impl Default for BitCrusher {
+    fn default() -> Self {
+        Self {
+            bits: FloatKnob::new(1.0, 24.0, 1.0, 8.0),
+            downsample: FloatKnob::new(1.0, 64.0, 1.0, 4.0),
+        }
+    }
+}
+
+impl Filter for BitCrusher {
+    fn knobs(&mut self) -> Vec<(&str, &mut dyn Knob)> {
+        vec![
+            ("Bits", &mut self.bits),
+            ("Downsample", &mut self.downsample),
+        ]
+    }
+
+    fn process(&mut self, samples: &mut Samples) -> eyre::Result<()> {
+        // Knob bounds keep both values positive and small, so truncation is not possible.
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let (bits, factor) = (
+            self.bits.value.round() as u32,
+            self.downsample.value.round() as u32,
+        );
+        bitcrush(samples, bits, factor);
+        Ok(())
+    }
+}
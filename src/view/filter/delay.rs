@@ -0,0 +1,44 @@
+//! User interface for adding echoes to a signal.
+
+use crate::dsp::buffer::Samples;
+use crate::dsp::filters::delay;
+use crate::view::filter::base::{Filter, Knob};
+use crate::view::filter::knobs::FloatKnob;
+use color_eyre::eyre;
+
+#[derive(Debug)]
+pub struct Delay {
+    /// Fraction of each echo that is repeated again.
+    feedback: FloatKnob,
+    /// Milliseconds between echoes.
+    time: FloatKnob,
+    /// Balance between the dry signal at 0.0 and the echoes at 1.0.
+    wet: FloatKnob,
+}
+
+impl Default for Delay {
+    fn default() -> Self {
+        Self {
+            feedback: FloatKnob::new(0.0, 0.95, 0.05, 0.4),
+            time: FloatKnob::new(10.0, 2000.0, 10.0, 250.0),
+            wet: FloatKnob::new(0.0, 1.0, 0.05, 0.3),
+        }
+    }
+}
+
+impl Filter for Delay {
+    fn knobs(&mut self) -> Vec<(&str, &mut dyn Knob)> {
+        vec![
+            ("Time ms", &mut self.time),
+            ("Feedback", &mut self.feedback),
+            ("Wet", &mut self.wet),
+        ]
+    }
+
+    fn process(&mut self, samples: &mut Samples) -> eyre::Result<()> {
+        // Knob bounds keep the time positive, so sign loss is not possible.
+        #[allow(clippy::cast_sign_loss)]
+        let frames = (self.time.value * samples.sample_rate as f32 / 1000.0).round() as usize;
+        delay(samples, frames.max(1), self.feedback.value, self.wet.value)
+    }
+}
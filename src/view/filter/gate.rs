@@ -0,0 +1,54 @@
+//! User interface for silencing quiet sections of a signal.
+
+use crate::dsp::buffer::Samples;
+use crate::dsp::filters::noise_gate;
+use crate::view::filter::base::{Filter, Knob};
+use crate::view::filter::knobs::FloatKnob;
+use color_eyre::eyre;
+
+#[derive(Debug)]
+pub struct NoiseGate {
+    /// Milliseconds for the gain to rise once frames are loud again.
+    attack: FloatKnob,
+    /// Milliseconds that quiet frames are kept before they fade out.
+    hold: FloatKnob,
+    /// Milliseconds for the gain to fall to silence after the hold time.
+    release: FloatKnob,
+    /// Level in decibels below which frames count as quiet.
+    threshold: FloatKnob,
+}
+
+impl Default for NoiseGate {
+    fn default() -> Self {
+        Self {
+            attack: FloatKnob::new(0.0, 200.0, 1.0, 1.0),
+            hold: FloatKnob::new(0.0, 1000.0, 10.0, 50.0),
+            release: FloatKnob::new(0.0, 2000.0, 10.0, 50.0),
+            threshold: FloatKnob::new(-96.0, 0.0, 1.0, -50.0),
+        }
+    }
+}
+
+impl Filter for NoiseGate {
+    fn knobs(&mut self) -> Vec<(&str, &mut dyn Knob)> {
+        vec![
+            ("Threshold dB", &mut self.threshold),
+            ("Hold ms", &mut self.hold),
+            ("Attack ms", &mut self.attack),
+            ("Release ms", &mut self.release),
+        ]
+    }
+
+    fn process(&mut self, samples: &mut Samples) -> eyre::Result<()> {
+        let rate = samples.sample_rate as f32 / 1000.0;
+        // Knob bounds keep the times non negative, so sign loss is not possible.
+        #[allow(clippy::cast_sign_loss)]
+        let (hold, attack, release) = (
+            (self.hold.value * rate).round() as usize,
+            (self.attack.value * rate).round() as usize,
+            (self.release.value * rate).round() as usize,
+        );
+        noise_gate(samples, self.threshold.value, hold, attack, release);
+        Ok(())
+    }
+}
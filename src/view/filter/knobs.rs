@@ -11,6 +11,20 @@ pub struct FloatKnob {
     pub value: f32,
 }
 
+impl FloatKnob {
+    /// Create a new FloatKnob between bounds that starts at a value.
+    #[must_use]
+    pub fn new(minimum: f32, maximum: f32, step: f32, value: f32) -> Self {
+        Self {
+            maximum,
+            minimum,
+            step,
+            text: value.to_string(),
+            value,
+        }
+    }
+}
+
 impl Default for FloatKnob {
     fn default() -> Self {
         Self {
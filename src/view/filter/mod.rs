@@ -1,19 +1,45 @@
 //! Components for fitlering signals.
 
 pub mod base;
+pub mod clip;
+pub mod compress;
+pub mod crush;
+pub mod delay;
+pub mod gate;
 mod knobs;
 pub mod normalize;
+pub mod pan;
+pub mod pass;
+pub mod pitch;
+pub mod ring;
+pub mod stretch;
+pub mod tremolo;
+pub mod width;
 
 pub use base::{Filter, Knob};
+pub use clip::{HardClip, SoftClip};
+pub use compress::Compressor;
+pub use crush::BitCrusher;
+pub use delay::Delay;
+pub use gate::NoiseGate;
 pub use normalize::Normalize;
+pub use pan::Pan;
+pub use pass::{HighPass, LowPass};
+pub use pitch::PitchShift;
+pub use ring::RingModulator;
+pub use stretch::TimeStretch;
+pub use tremolo::Tremolo;
+pub use width::StereoWidth;
 
-use crate::dsp::Samples;
+use crate::dsp::Selection;
+use crate::tab::Tab;
+use crate::ui::theme::Theme;
 use crate::view::View;
 use crossterm::event::{KeyCode, KeyEvent};
+use std::mem;
 use tui::backend::Backend;
 use tui::layout::Constraint::{Length, Percentage};
 use tui::layout::{Direction, Layout, Rect};
-use tui::style::{Modifier, Style};
 use tui::terminal::Frame;
 use tui::widgets::{Block, Borders, List, ListItem, ListState, Row, Table};
 
@@ -22,6 +48,7 @@ pub struct Filters<'a> {
     filters: &'a mut [(&'a str, &'a mut dyn Filter)],
     mode: Mode,
     filter_state: ListState,
+    /// Index of the knob of the selected filter that edit mode changes.
     knob_state: usize,
 }
 
@@ -55,7 +82,11 @@ impl<'a> Filters<'a> {
             let knob: &mut dyn Knob = knobs[self.knob_state].1;
 
             match event.code {
+                KeyCode::Char('e') => self.mode = Mode::Nagivate,
                 KeyCode::Down => knob.decrement(),
+                KeyCode::Enter => self.mode = Mode::Filter,
+                KeyCode::Left => self.knob_state = self.knob_state.saturating_sub(1),
+                KeyCode::Right => self.knob_state = (self.knob_state + 1).min(knobs.len() - 1),
                 KeyCode::Up => knob.increment(),
                 _ => (),
             }
@@ -75,6 +106,7 @@ impl<'a> Filters<'a> {
             None => 0,
         };
         self.filter_state.select(Some(index));
+        self.knob_state = 0;
     }
 
     /// Modular move list state to previous inode.
@@ -90,6 +122,7 @@ impl<'a> Filters<'a> {
             None => 0,
         };
         self.filter_state.select(Some(index));
+        self.knob_state = 0;
     }
 }
 
@@ -102,15 +135,45 @@ impl<'a, B: Backend> View<B> for Filters<'a> {
         }
     }
 
-    fn process(&mut self, _samples: &mut Samples) -> eyre::Result<()> {
+    fn process(&mut self, tab: &mut Tab) -> eyre::Result<()> {
         if self.mode == Mode::Filter {
             self.mode = Mode::Nagivate;
+
+            if let (Some(index), false) = (self.filter_state.selected(), tab.is_loading()) {
+                tab.ensure_editable()?;
+                let filter = &mut self.filters[index].1;
+                let mut edited = tab.samples.clone();
+                match tab.selection {
+                    // Only the selected frames are filtered and then put back in their place.
+                    Some(selection) => {
+                        let mut part = tab.samples.slice(selection);
+                        filter.process(&mut part)?;
+                        if part.channels != edited.channels {
+                            eyre::bail!("Filters cannot change the channels of a selection");
+                        }
+                        let start = selection.start.min(edited.frames());
+                        // Filters such as time stretching change the length of the selection.
+                        let frames = part.frames();
+                        edited.splice(selection, part)?;
+                        tab.selection = Some(Selection::new(start, start + frames))
+                            .filter(|selection| !selection.is_empty());
+                    }
+                    None => filter.process(&mut edited)?,
+                }
+                tab.playhead = tab.playhead.min(edited.frames());
+                tab.history.push(&tab.samples)?;
+                tab.preview = Some(mem::replace(&mut tab.samples, edited));
+                tab.modified = true;
+                tab.peaks = None;
+                tab.stats = None;
+                tab.tempo = None;
+            }
         }
 
         Ok(())
     }
 
-    fn render<'b>(&mut self, frame: &mut Frame<'b, B>, area: Rect) {
+    fn render<'b>(&mut self, frame: &mut Frame<'b, B>, area: Rect, theme: &Theme) {
         let chunks = Layout::default()
             .direction(Direction::Horizontal)
             .constraints([Percentage(16), Percentage(84)].as_ref())
@@ -126,16 +189,25 @@ impl<'a, B: Backend> View<B> for Filters<'a> {
 
         let list = List::new(entries)
             .block(block)
-            .highlight_style(Style::default().add_modifier(Modifier::ITALIC))
+            .highlight_style(theme.selection())
             .highlight_symbol("> ");
 
         frame.render_stateful_widget(list, chunks[0], &mut self.filter_state);
 
         if let Some(index) = self.filter_state.selected() {
+            let selected = (self.mode == Mode::Edit).then_some(self.knob_state);
             let knobs = self.filters[index].1.knobs();
             let rows: Vec<Row> = knobs
                 .iter()
-                .map(|(name, filter)| Row::new(vec![*name, filter.text()]))
+                .enumerate()
+                .map(|(position, (name, filter))| {
+                    let row = Row::new(vec![*name, filter.text()]);
+                    if selected == Some(position) {
+                        row.style(theme.selection())
+                    } else {
+                        row
+                    }
+                })
                 .collect();
 
             let block = Block::default().borders(Borders::ALL);
@@ -157,3 +229,117 @@ enum Mode {
     Filter,
     Nagivate,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsp::{Samples, Selection};
+    use crate::view::filter::{Compressor, LowPass, NoiseGate, TimeStretch};
+    use crossterm::event::KeyModifiers;
+    use tui::backend::TestBackend;
+
+    #[test]
+    fn edit_selects_knobs() {
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let mut gate = NoiseGate::default();
+        let mut pairs: Vec<(&str, &mut dyn Filter)> = vec![("Noise gate", &mut gate)];
+        let mut filters = Filters::new(&mut pairs);
+
+        for code in [
+            KeyCode::Down,
+            KeyCode::Char('e'),
+            KeyCode::Right,
+            KeyCode::Up,
+        ] {
+            View::<TestBackend>::key_event(&mut filters, key(code));
+        }
+        for _ in 0..10 {
+            View::<TestBackend>::key_event(&mut filters, key(KeyCode::Right));
+        }
+        View::<TestBackend>::key_event(&mut filters, key(KeyCode::Up));
+        View::<TestBackend>::key_event(&mut filters, key(KeyCode::Left));
+        View::<TestBackend>::key_event(&mut filters, key(KeyCode::Down));
+
+        let knobs = filters.filters[0].1.knobs();
+        let texts: Vec<&str> = knobs.iter().map(|(_name, knob)| knob.text()).collect();
+        assert_eq!(texts, vec!["-50", "60", "0", "60"]);
+        drop(knobs);
+
+        View::<TestBackend>::key_event(&mut filters, key(KeyCode::Enter));
+        let mut tab = Tab::new(Samples::new(1, 8000, vec![0.5; 8]), None);
+        View::<TestBackend>::process(&mut filters, &mut tab).unwrap();
+        assert!(tab.modified);
+        assert_eq!(filters.mode, Mode::Nagivate);
+    }
+
+    #[test]
+    fn edit_compressor_knobs() {
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let mut compressor = Compressor::default();
+        let mut pairs: Vec<(&str, &mut dyn Filter)> = vec![("Compressor", &mut compressor)];
+        let mut filters = Filters::new(&mut pairs);
+
+        for code in [
+            KeyCode::Down,
+            KeyCode::Char('e'),
+            KeyCode::Right,
+            KeyCode::Up,
+        ] {
+            View::<TestBackend>::key_event(&mut filters, key(code));
+        }
+        for _ in 0..10 {
+            View::<TestBackend>::key_event(&mut filters, key(KeyCode::Right));
+        }
+        View::<TestBackend>::key_event(&mut filters, key(KeyCode::Down));
+
+        let knobs = filters.filters[0].1.knobs();
+        let texts: Vec<&str> = knobs.iter().map(|(_name, knob)| knob.text()).collect();
+        assert_eq!(texts, vec!["-12", "5", "5", "90"]);
+    }
+
+    #[test]
+    fn filter_applies_to_selection() {
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let mut low_pass = LowPass::default();
+        let mut pairs: Vec<(&str, &mut dyn Filter)> = vec![("Low pass", &mut low_pass)];
+        let mut filters = Filters::new(&mut pairs);
+
+        let data: Vec<f32> = [0.5, -0.5].iter().cycle().take(40).copied().collect();
+        let mut tab = Tab::new(Samples::new(2, 44_100, data.clone()), None);
+        tab.selection = Some(Selection::new(5, 15));
+        View::<TestBackend>::key_event(&mut filters, key(KeyCode::Down));
+        View::<TestBackend>::key_event(&mut filters, key(KeyCode::Enter));
+        View::<TestBackend>::process(&mut filters, &mut tab).unwrap();
+
+        assert_eq!(tab.samples.data.len(), 40);
+        assert_eq!(tab.samples.data[..10], data[..10]);
+        assert_eq!(tab.samples.data[30..], data[30..]);
+        assert_ne!(tab.samples.data[10..30], data[10..30]);
+    }
+
+    #[test]
+    fn stretch_resizes_selection() {
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let mut time_stretch = TimeStretch::default();
+        let mut pairs: Vec<(&str, &mut dyn Filter)> = vec![("Time stretch", &mut time_stretch)];
+        let mut filters = Filters::new(&mut pairs);
+
+        View::<TestBackend>::key_event(&mut filters, key(KeyCode::Down));
+        View::<TestBackend>::key_event(&mut filters, key(KeyCode::Char('e')));
+        for _ in 0..20 {
+            View::<TestBackend>::key_event(&mut filters, key(KeyCode::Up));
+        }
+        View::<TestBackend>::key_event(&mut filters, key(KeyCode::Char('e')));
+
+        let mut tab = Tab::new(Samples::new(1, 8000, vec![0.25; 8000]), None);
+        tab.selection = Some(Selection::new(1000, 3000));
+        tab.playhead = 1000;
+        View::<TestBackend>::key_event(&mut filters, key(KeyCode::Enter));
+        View::<TestBackend>::process(&mut filters, &mut tab).unwrap();
+
+        let selection = tab.selection.unwrap();
+        assert_eq!(selection.start, 1000);
+        assert!((selection.len() as f64 - 4000.0).abs() <= 1.0);
+        assert_eq!(tab.samples.frames(), 6000 + selection.len());
+    }
+}
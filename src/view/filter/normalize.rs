@@ -4,6 +4,7 @@ use crate::dsp::buffer::Samples;
 use crate::dsp::filters::normalize;
 use crate::view::filter::base::{Filter, Knob};
 use crate::view::filter::knobs::FloatKnob;
+use color_eyre::eyre;
 
 #[derive(Debug, Default)]
 pub struct Normalize {
@@ -15,7 +16,8 @@ impl Filter for Normalize {
         vec![("Amplitude", &mut self.amplitude)]
     }
 
-    fn process(&mut self, samples: &mut Samples) {
+    fn process(&mut self, samples: &mut Samples) -> eyre::Result<()> {
         normalize(self.amplitude.value, samples);
+        Ok(())
     }
 }
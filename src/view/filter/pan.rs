@@ -0,0 +1,30 @@
+//! User interface for moving the stereo image of a signal.
+
+use crate::dsp::buffer::Samples;
+use crate::dsp::filters::pan;
+use crate::view::filter::base::{Filter, Knob};
+use crate::view::filter::knobs::FloatKnob;
+use color_eyre::eyre;
+
+#[derive(Debug)]
+pub struct Pan {
+    position: FloatKnob,
+}
+
+impl Default for Pan {
+    fn default() -> Self {
+        Self {
+            position: FloatKnob::new(-1.0, 1.0, 0.1, 0.0),
+        }
+    }
+}
+
+impl Filter for Pan {
+    fn knobs(&mut self) -> Vec<(&str, &mut dyn Knob)> {
+        vec![("Position", &mut self.position)]
+    }
+
+    fn process(&mut self, samples: &mut Samples) -> eyre::Result<()> {
+        pan(self.position.value, samples)
+    }
+}
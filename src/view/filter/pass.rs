@@ -0,0 +1,55 @@
+//! User interface for removing high or low frequencies of a signal.
+
+use crate::dsp::buffer::Samples;
+use crate::dsp::filters::{high_pass, low_pass};
+use crate::view::filter::base::{Filter, Knob};
+use crate::view::filter::knobs::FloatKnob;
+use color_eyre::eyre;
+
+#[derive(Debug)]
+pub struct HighPass {
+    /// Frequency in hertz below which the signal is attenuated.
+    cutoff: FloatKnob,
+}
+
+impl Default for HighPass {
+    fn default() -> Self {
+        Self {
+            cutoff: FloatKnob::new(5.0, 5000.0, 5.0, 100.0),
+        }
+    }
+}
+
+impl Filter for HighPass {
+    fn knobs(&mut self) -> Vec<(&str, &mut dyn Knob)> {
+        vec![("Cutoff Hz", &mut self.cutoff)]
+    }
+
+    fn process(&mut self, samples: &mut Samples) -> eyre::Result<()> {
+        high_pass(self.cutoff.value, samples)
+    }
+}
+
+#[derive(Debug)]
+pub struct LowPass {
+    /// Frequency in hertz above which the signal is attenuated.
+    cutoff: FloatKnob,
+}
+
+impl Default for LowPass {
+    fn default() -> Self {
+        Self {
+            cutoff: FloatKnob::new(100.0, 20000.0, 100.0, 5000.0),
+        }
+    }
+}
+
+impl Filter for LowPass {
+    fn knobs(&mut self) -> Vec<(&str, &mut dyn Knob)> {
+        vec![("Cutoff Hz", &mut self.cutoff)]
+    }
+
+    fn process(&mut self, samples: &mut Samples) -> eyre::Result<()> {
+        low_pass(self.cutoff.value, samples)
+    }
+}
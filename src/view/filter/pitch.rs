@@ -0,0 +1,35 @@
+//! User interface for transposing a signal.
+
+use crate::dsp::buffer::Samples;
+use crate::dsp::filters::pitch_shift;
+use crate::view::filter::base::{Filter, Knob};
+use crate::view::filter::knobs::FloatKnob;
+use color_eyre::eyre;
+
+/// Largest transposition in semitones offered in either direction.
+const SEMITONES: f32 = 24.0;
+
+#[derive(Debug)]
+pub struct PitchShift {
+    /// Transposition in semitones, negative to lower the pitch.
+    semitones: FloatKnob,
+}
+
+impl Default for PitchShift {
+    fn default() -> Self {
+        Self {
+            semitones: FloatKnob::new(-SEMITONES, SEMITONES, 1.0, 0.0),
+        }
+    }
+}
+
+impl Filter for PitchShift {
+    fn knobs(&mut self) -> Vec<(&str, &mut dyn Knob)> {
+        vec![("Semitones", &mut self.semitones)]
+    }
+
+    fn process(&mut self, samples: &mut Samples) -> eyre::Result<()> {
+        pitch_shift(self.semitones.value, samples);
+        Ok(())
+    }
+}
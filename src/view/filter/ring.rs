@@ -0,0 +1,31 @@
+//! User interface for ring modulating a signal with a sine carrier.
+
+use crate::dsp::buffer::Samples;
+use crate::dsp::filters::ring_modulate;
+use crate::view::filter::base::{Filter, Knob};
+use crate::view::filter::knobs::FloatKnob;
+use color_eyre::eyre;
+
+#[derive(Debug)]
+pub struct RingModulator {
+    /// Frequency in hertz of the carrier that samples are multiplied by.
+    carrier: FloatKnob,
+}
+
+impl Default for RingModulator {
+    fn default() -> Self {
+        Self {
+            carrier: FloatKnob::new(10.0, 10000.0, 10.0, 440.0),
+        }
+    }
+}
+
+impl Filter for RingModulator {
+    fn knobs(&mut self) -> Vec<(&str, &mut dyn Knob)> {
+        vec![("Carrier Hz", &mut self.carrier)]
+    }
+
+    fn process(&mut self, samples: &mut Samples) -> eyre::Result<()> {
+        ring_modulate(samples, self.carrier.value)
+    }
+}
@@ -0,0 +1,32 @@
+//! User interface for changing the length of a signal.
+
+use crate::dsp::buffer::Samples;
+use crate::dsp::filters::time_stretch;
+use crate::view::filter::base::{Filter, Knob};
+use crate::view::filter::knobs::FloatKnob;
+use color_eyre::eyre;
+
+#[derive(Debug)]
+pub struct TimeStretch {
+    /// Factor of the resulting duration, above one to lengthen.
+    ratio: FloatKnob,
+}
+
+impl Default for TimeStretch {
+    fn default() -> Self {
+        Self {
+            ratio: FloatKnob::new(0.25, 4.0, 0.05, 1.0),
+        }
+    }
+}
+
+impl Filter for TimeStretch {
+    fn knobs(&mut self) -> Vec<(&str, &mut dyn Knob)> {
+        vec![("Ratio", &mut self.ratio)]
+    }
+
+    fn process(&mut self, samples: &mut Samples) -> eyre::Result<()> {
+        time_stretch(self.ratio.value, samples);
+        Ok(())
+    }
+}
@@ -0,0 +1,34 @@
+//! User interface for modulating the amplitude of a signal.
+
+use crate::dsp::buffer::Samples;
+use crate::dsp::filters::tremolo;
+use crate::view::filter::base::{Filter, Knob};
+use crate::view::filter::knobs::FloatKnob;
+use color_eyre::eyre;
+
+#[derive(Debug)]
+pub struct Tremolo {
+    /// Fraction of the amplitude removed at the bottom of each cycle.
+    depth: FloatKnob,
+    /// Cycles per second.
+    rate: FloatKnob,
+}
+
+impl Default for Tremolo {
+    fn default() -> Self {
+        Self {
+            depth: FloatKnob::new(0.0, 1.0, 0.05, 0.5),
+            rate: FloatKnob::new(0.1, 20.0, 0.1, 5.0),
+        }
+    }
+}
+
+impl Filter for Tremolo {
+    fn knobs(&mut self) -> Vec<(&str, &mut dyn Knob)> {
+        vec![("Rate Hz", &mut self.rate), ("Depth", &mut self.depth)]
+    }
+
+    fn process(&mut self, samples: &mut Samples) -> eyre::Result<()> {
+        tremolo(samples, self.rate.value, self.depth.value)
+    }
+}
@@ -0,0 +1,30 @@
+//! User interface for widening or narrowing the stereo image of a signal.
+
+use crate::dsp::buffer::Samples;
+use crate::dsp::filters::adjust_stereo_width;
+use crate::view::filter::base::{Filter, Knob};
+use crate::view::filter::knobs::FloatKnob;
+use color_eyre::eyre;
+
+#[derive(Debug)]
+pub struct StereoWidth {
+    width: FloatKnob,
+}
+
+impl Default for StereoWidth {
+    fn default() -> Self {
+        Self {
+            width: FloatKnob::new(0.0, 3.0, 0.1, 1.0),
+        }
+    }
+}
+
+impl Filter for StereoWidth {
+    fn knobs(&mut self) -> Vec<(&str, &mut dyn Knob)> {
+        vec![("Width", &mut self.width)]
+    }
+
+    fn process(&mut self, samples: &mut Samples) -> eyre::Result<()> {
+        adjust_stereo_width(samples, self.width.value)
+    }
+}
@@ -0,0 +1,42 @@
+use sampitor::dsp::Samples;
+use sampitor::io::{aiff, audio};
+use std::f32::consts::PI;
+
+#[test]
+fn write_aiff_and_read() {
+    let folder = tempfile::tempdir().unwrap();
+    let sample_rate = 44_100;
+    let data: Vec<f32> = (0..sample_rate / 10)
+        .flat_map(|index| {
+            let sample = (2.0 * PI * 440.0 * index as f32 / sample_rate as f32).sin() * 0.5;
+            [sample, -sample]
+        })
+        .collect();
+    let samples = Samples::new(2, sample_rate, data);
+
+    for (bits, tolerance) in [(16, 1.0 / 32768.0), (24, 1.0 / 8_388_608.0)] {
+        let path = folder.path().join(format!("tone{}.aiff", bits));
+        aiff::write_aiff(&path, &samples, bits).unwrap();
+
+        let actual = audio::read_samples(&path).unwrap();
+        assert_eq!(actual.channels, 2);
+        assert_eq!(actual.sample_rate, sample_rate);
+        assert_eq!(actual.frames(), samples.frames());
+        for (actual, expected) in actual.data.iter().zip(&samples.data) {
+            assert!((actual - expected).abs() <= tolerance);
+        }
+        assert!(audio::has_header(&path).unwrap());
+    }
+}
+
+#[test]
+fn write_aiff_bit_depths() {
+    let folder = tempfile::tempdir().unwrap();
+    let samples = Samples::new(1, 8000, vec![2.0, -2.0]);
+
+    assert!(aiff::write_aiff(&folder.path().join("low.aiff"), &samples, 8).is_err());
+    let path = folder.path().join("clipped.aiff");
+    aiff::write_aiff(&path, &samples, 16).unwrap();
+    let actual = audio::read_samples(&path).unwrap();
+    assert_eq!(actual.data, vec![32767.0 / 32768.0, -1.0]);
+}
@@ -1,11 +1,376 @@
 use assert_cmd::assert::OutputAssertExt;
 use assert_cmd::cargo::CommandCargoExt;
+use clap::Parser;
+use predicates::prelude::PredicateBooleanExt;
+use predicates::str::contains;
+use sampitor::cli::{self, Operation, Options, Startup, ViewName};
+use sampitor::dsp::{self, Samples};
+use sampitor::io::audio;
+use sampitor::ui::theme::ThemeName;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 #[test]
 fn missing_file_error() {
     let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
 
-    let actual = cmd.args(&["-f", "this_file_does_not_exist.wav"]).assert();
+    let actual = cmd.args(["-f", "this_file_does_not_exist.wav"]).assert();
     actual.failure().code(1);
 }
+
+#[test]
+fn missing_path_error() {
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+
+    let actual = cmd.arg("this_path_does_not_exist.wav").assert();
+    actual.failure().code(1).stderr(contains("does not exist"));
+}
+
+#[test]
+fn startup_from_directory() {
+    let folder = tempfile::tempdir().unwrap();
+    fs::create_dir(folder.path().join("loops")).unwrap();
+
+    let options = Options::try_parse_from([
+        "sampitor",
+        "loops",
+        "--theme",
+        "high-contrast",
+        "--view",
+        "file",
+        "--volume",
+        "0.5",
+    ])
+    .unwrap();
+    let expected = Startup {
+        directory: folder.path().join("loops"),
+        file: None,
+        theme: Some(ThemeName::HighContrast),
+        view: Some(ViewName::File),
+        volume: 0.5,
+    };
+
+    assert_eq!(options.startup(folder.path()).unwrap(), expected);
+}
+
+#[test]
+fn startup_from_file() {
+    let folder = tempfile::tempdir().unwrap();
+    File::create(folder.path().join("kick.wav")).unwrap();
+
+    let options = Options::try_parse_from(["sampitor", "kick.wav"]).unwrap();
+    let expected = Startup {
+        directory: folder.path().to_owned(),
+        file: Some(folder.path().join("kick.wav")),
+        theme: None,
+        view: None,
+        volume: 1.0,
+    };
+
+    assert_eq!(options.startup(folder.path()).unwrap(), expected);
+}
+
+#[test]
+fn startup_rejects_negative_volume() {
+    let folder = tempfile::tempdir().unwrap();
+
+    let options = Options::try_parse_from(["sampitor", "--volume=-1"]).unwrap();
+    assert!(options.startup(folder.path()).is_err());
+}
+
+#[test]
+fn batch_matches_dsp_calls() {
+    let folder = tempfile::tempdir().unwrap();
+    let input = folder.path().join("input.wav");
+    let output = folder.path().join("output.wav");
+
+    let mut data = vec![0.0; 200];
+    data.extend((0..800).map(|index| (index as f32 * 0.05).sin() * 0.4));
+    data.extend(vec![0.0; 200]);
+    audio::write_samples(&input, &Samples::new(2, 8000, data)).unwrap();
+
+    let options = Options::try_parse_from([
+        "sampitor",
+        "batch",
+        input.to_str().unwrap(),
+        output.to_str().unwrap(),
+        "trim-silence=-50",
+        "to-mono",
+        "resample=4000",
+        "gain=-6",
+        "normalize=0.8",
+    ])
+    .unwrap();
+    match options.command {
+        Some(cli::Command::Batch(batch)) => batch.run().unwrap(),
+        _ => panic!("Batch command was not parsed"),
+    }
+
+    let mut expected = audio::read_samples(&input).unwrap();
+    dsp::trim_silence(-50.0, &mut expected);
+    dsp::to_mono(&mut expected);
+    dsp::change_sample_rate(4000, &mut expected).unwrap();
+    dsp::gain(-6.0, &mut expected);
+    dsp::normalize(0.8, &mut expected);
+
+    // Reading quantizes samples to 16 bits, so the written output is compared with a tolerance.
+    let actual = audio::read_samples(&output).unwrap();
+    assert_eq!(actual.channels, 1);
+    assert_eq!(actual.sample_rate, 4000);
+    assert_eq!(actual.frames(), expected.frames());
+    assert!(actual
+        .data
+        .iter()
+        .zip(&expected.data)
+        .all(|(actual, expected)| (actual - expected).abs() < 1e-3));
+}
+
+#[test]
+fn batch_parses_operations() {
+    let cases = [
+        ("gain=-3.5", Operation::Gain(-3.5)),
+        ("normalize", Operation::Normalize(1.0)),
+        ("resample=22050", Operation::Resample(22050)),
+        ("to-mono", Operation::ToMono),
+        ("trim-silence", Operation::TrimSilence(-60.0)),
+    ];
+    for (text, expected) in cases {
+        assert_eq!(text.parse::<Operation>().unwrap(), expected);
+    }
+
+    for text in [
+        "gain",
+        "gain=loud",
+        "normalize=0",
+        "resample=0",
+        "to-mono=1",
+        "reverse",
+    ] {
+        assert!(text.parse::<Operation>().is_err(), "{}", text);
+    }
+}
+
+#[test]
+fn batch_command_exit_codes() {
+    let folder = tempfile::tempdir().unwrap();
+    let input = folder.path().join("input.wav");
+    let output = folder.path().join("output.wav");
+    audio::write_samples(&input, &Samples::new(1, 8000, vec![0.25; 8])).unwrap();
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    cmd.arg("batch").arg(&input).arg(&output).arg("normalize");
+    cmd.assert().success();
+    assert_eq!(audio::read_samples(&output).unwrap().data, vec![1.0; 8]);
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    let missing = folder.path().join("missing.wav");
+    cmd.arg("batch").arg(&missing).arg(&output).arg("to-mono");
+    cmd.assert().failure().code(1);
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    cmd.arg("batch").arg(&input).arg(&output).arg("reverse");
+    cmd.assert().failure().stderr(contains("reverse"));
+}
+
+#[test]
+fn no_tui_applies_operations() {
+    let folder = tempfile::tempdir().unwrap();
+    let input = folder.path().join("input.wav");
+    let output = folder.path().join("output.wav");
+    audio::write_samples(&input, &Samples::new(2, 8000, vec![0.25, 0.75, 0.5, 0.5])).unwrap();
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    cmd.arg("--no-tui")
+        .arg("--input")
+        .arg(&input)
+        .arg("--output")
+        .arg(&output);
+    cmd.args(["--operation", "to-mono", "--operation", "normalize=0.8"]);
+    cmd.assert().success();
+    let actual = audio::read_samples(&output).unwrap();
+    assert_eq!(actual.channels, 1);
+    assert!(actual.data.iter().all(|sample| (sample - 0.8).abs() < 1e-3));
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    let missing = folder.path().join("missing.wav");
+    cmd.arg("--no-tui")
+        .arg("-i")
+        .arg(&missing)
+        .arg("-o")
+        .arg(&output);
+    cmd.assert().failure().code(1).stdout("");
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    cmd.arg("--no-tui")
+        .arg("-i")
+        .arg(&input)
+        .arg("-o")
+        .arg(&output);
+    cmd.args(["--operation", "reverse"]);
+    cmd.assert().failure().stderr(contains("reverse"));
+}
+
+#[test]
+fn no_tui_requires_files() {
+    let options = Options::try_parse_from(["sampitor", "--no-tui", "-i", "a.wav", "-o", "b.wav"]);
+    let batch = options.unwrap().headless().unwrap();
+    assert_eq!(
+        (batch.input.to_str(), batch.output.to_str()),
+        (Some("a.wav"), Some("b.wav"))
+    );
+    assert!(batch.operations.is_empty());
+    assert!(Options::try_parse_from(["sampitor"])
+        .unwrap()
+        .headless()
+        .is_none());
+
+    for args in [
+        vec!["sampitor", "--no-tui", "-i", "a.wav"],
+        vec!["sampitor", "-i", "a.wav", "-o", "b.wav"],
+        vec!["sampitor", "--operation", "to-mono"],
+        vec![
+            "sampitor", "--no-tui", "-i", "a.wav", "-o", "b.wav", "loops",
+        ],
+    ] {
+        assert!(Options::try_parse_from(&args).is_err(), "{:?}", args);
+    }
+}
+
+#[test]
+fn generate_writes_wav() {
+    let folder = tempfile::tempdir().unwrap();
+    let output = folder.path().join("tone.wav");
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    cmd.args(["generate", "sine"]).arg(&output).args([
+        "--amplitude",
+        "0.25",
+        "--channels",
+        "2",
+        "--duration",
+        "0.5",
+        "--frequency",
+        "100",
+        "--sample-rate",
+        "8000",
+    ]);
+    cmd.assert().success();
+
+    let actual = audio::read_samples(&output).unwrap();
+    let expected = dsp::generate(dsp::Waveform::Sine, 100.0, 0.25, 0.5, 2, 8000).unwrap();
+    assert_eq!((actual.channels, actual.sample_rate), (2, 8000));
+    assert_eq!(actual.data.len(), expected.data.len());
+    assert!(actual
+        .data
+        .iter()
+        .zip(&expected.data)
+        .all(|(actual, expected)| (actual - expected).abs() < 1e-3));
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    cmd.args(["generate", "square"])
+        .arg(&output)
+        .args(["-f", "5000", "-r", "8000"]);
+    cmd.assert().failure().stderr(contains("Nyquist"));
+}
+
+#[test]
+fn convert_parses_inputs_and_amplitude() {
+    let parse = |args: &[&str]| match Options::try_parse_from(args).unwrap().command {
+        Some(cli::Command::Convert(convert)) => convert,
+        _ => panic!("Convert command was not parsed"),
+    };
+
+    let convert = parse(&["sampitor", "convert", "--normalize", "in.flac", "out.wav"]);
+    assert!(convert.normalize);
+    assert_eq!(convert.amplitude, 1.0);
+    assert_eq!(convert.inputs(), [PathBuf::from("in.flac")]);
+    assert_eq!(convert.output(), Path::new("out.wav"));
+    assert_eq!(convert.bits, 32);
+
+    let args = [
+        "convert",
+        "--normalize",
+        "--amplitude",
+        "0.5",
+        "a",
+        "b",
+        "out",
+    ];
+    let convert = parse(&[&["sampitor"], &args[..]].concat());
+    assert_eq!(convert.amplitude, 0.5);
+    assert_eq!(convert.inputs().len(), 2);
+    assert_eq!(convert.output(), Path::new("out"));
+    assert!(Options::try_parse_from(["sampitor", "convert", "out.wav"]).is_err());
+    assert!(
+        Options::try_parse_from(["sampitor", "convert", "--amplitude", "0.5", "a", "b"]).is_err()
+    );
+}
+
+#[test]
+fn convert_resamples_normalizes_and_quantizes() {
+    let folder = tempfile::tempdir().unwrap();
+    let input = folder.path().join("input.wav");
+    let output = folder.path().join("output.wav");
+    let data = (0..800)
+        .map(|index| (index as f32 * 0.05).sin() * 0.4)
+        .collect();
+    audio::write_samples(&input, &Samples::new(1, 8000, data)).unwrap();
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    cmd.args(["convert", "--normalize", "--rate", "16000", "--bits", "16"])
+        .arg(&input)
+        .arg(&output);
+    cmd.assert()
+        .success()
+        .stdout(contains("output.wav: 1 ch, 16000 Hz, 16 bit, 0.10 s"));
+
+    let spec = hound::WavReader::open(&output).unwrap().spec();
+    assert_eq!(spec.bits_per_sample, 16);
+    assert_eq!(spec.sample_rate, 16000);
+    let actual = audio::read_samples(&output).unwrap();
+    let peak = actual
+        .data
+        .iter()
+        .fold(0.0_f32, |peak, sample| peak.max(sample.abs()));
+    assert!((peak - 1.0).abs() < 1e-3, "{}", peak);
+
+    let aiff = folder.path().join("output.aiff");
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    cmd.args(["convert", "--bits", "24"]).arg(&input).arg(&aiff);
+    cmd.assert().success();
+    assert_eq!(audio::read_samples(&aiff).unwrap().frames(), 800);
+}
+
+#[test]
+fn convert_globs_into_directory_and_reports_failures() {
+    let folder = tempfile::tempdir().unwrap();
+    let inputs = folder.path().join("takes");
+    fs::create_dir(&inputs).unwrap();
+    for name in ["b.wav", "c.wav"] {
+        audio::write_samples(&inputs.join(name), &Samples::new(2, 8000, vec![0.5; 16])).unwrap();
+    }
+    // A compressed AIFF header fails to read without reaching the decoders of other formats.
+    fs::write(inputs.join("a.wav"), b"FORM\0\0\0\x04AIFC").unwrap();
+    let pattern = inputs.join("*.wav");
+
+    let output = folder.path().join("converted");
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    cmd.arg("convert").arg(&pattern).arg(&output);
+    cmd.assert()
+        .failure()
+        .code(1)
+        .stdout(contains("b.wav -> ").and(contains("c.wav -> ")))
+        .stderr(contains("a.wav: ").and(contains("1 of 3 files failed to convert")));
+    assert!(output.join("b.wav").is_file());
+    assert!(output.join("c.wav").is_file());
+    assert!(!output.join("a.wav").exists());
+
+    let output = folder.path().join("fail-fast");
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    cmd.args(["convert", "--fail-fast"])
+        .arg(&pattern)
+        .arg(&output);
+    cmd.assert().failure().code(1).stdout("");
+    assert!(!output.join("b.wav").exists());
+}
@@ -0,0 +1,34 @@
+use sampitor::dsp::Samples;
+use sampitor::io::audio;
+use std::f32::consts::PI;
+
+#[test]
+fn write_ogg_and_read() {
+    let folder = tempfile::tempdir().unwrap();
+    let path = folder.path().join("tone.ogg");
+
+    let sample_rate = 44_100;
+    let data = (0..sample_rate * 2)
+        .flat_map(|index| {
+            let sample = (2.0 * PI * 440.0 * index as f32 / sample_rate as f32).sin() * 0.5;
+            [sample, -sample]
+        })
+        .collect();
+    let samples = Samples::new(2, sample_rate, data);
+    audio::write_ogg(&path, &samples, 0.4).unwrap();
+
+    let actual = audio::read_samples(&path).unwrap();
+    assert_eq!(actual.channels, 2);
+    assert_eq!(actual.sample_rate, sample_rate);
+    assert!(actual.frames() >= samples.frames());
+    assert!(actual.data.iter().all(|sample| sample.abs() <= 1.0));
+}
+
+#[test]
+fn write_ogg_quality_range() {
+    let folder = tempfile::tempdir().unwrap();
+    let samples = Samples::new(1, 8000, vec![0.0; 100]);
+
+    assert!(audio::write_ogg(&folder.path().join("low.ogg"), &samples, -1.0).is_ok());
+    assert!(audio::write_ogg(&folder.path().join("high.ogg"), &samples, 1.5).is_err());
+}
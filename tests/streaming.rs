@@ -0,0 +1,53 @@
+use sampitor::dsp::{Marker, Samples, Selection};
+use sampitor::io::audio;
+
+#[test]
+fn streaming_ranges_match_full_read() {
+    let folder = tempfile::tempdir().unwrap();
+    let path = folder.path().join("long.wav");
+
+    let frames = 1_000_000;
+    let data = (0..frames * 2)
+        .map(|index| ((index % 2000) as f32 / 1000.0) - 1.0)
+        .collect();
+    let mut samples = Samples::new(2, 48_000, data);
+    // Markers add chunks after the sample data that the reader has to skip.
+    samples.markers = vec![Marker::new(10, String::from("cue"))];
+    audio::write_samples(&path, &samples).unwrap();
+
+    let expected = audio::read_samples(&path).unwrap();
+    let mut streaming = audio::read_samples_streaming(&path).unwrap();
+    assert_eq!(streaming.channels(), 2);
+    assert_eq!(streaming.frames(), frames);
+    assert_eq!(streaming.sample_rate(), 48_000);
+
+    for (start, end) in [
+        (0, 1),
+        (12_345, 67_890),
+        (999_000, 1_000_000),
+        (500_000, 500_000),
+    ] {
+        let selection = Selection::new(start, end);
+        let actual = streaming.read_frames(start..end).unwrap();
+        // Float samples are read exactly, while the decoder rounds them through 16 bit integers.
+        assert_eq!(actual.data, samples.slice(selection).data);
+        let decoded = expected.slice(selection).data;
+        assert!(actual
+            .data
+            .iter()
+            .zip(decoded)
+            .all(|(actual, decoded)| (actual - decoded).abs() < 1e-3));
+    }
+
+    let clamped = streaming.read_frames(999_990..2_000_000).unwrap();
+    assert_eq!(clamped.frames(), 10);
+}
+
+#[test]
+fn streaming_rejects_non_wave_file() {
+    let folder = tempfile::tempdir().unwrap();
+    let path = folder.path().join("tone.ogg");
+    audio::write_ogg(&path, &Samples::new(1, 8000, vec![0.0; 100]), 0.5).unwrap();
+
+    assert!(audio::read_samples_streaming(&path).is_err());
+}
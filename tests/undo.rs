@@ -0,0 +1,35 @@
+use sampitor::dsp::filters::normalize;
+use sampitor::dsp::Samples;
+use sampitor::tab::Tab;
+
+fn bits(samples: &Samples) -> Vec<u32> {
+    samples.data.iter().map(|sample| sample.to_bits()).collect()
+}
+
+#[test]
+fn undo_restores_exact_samples() {
+    let sample_rate = 44_100;
+    let data = (0..sample_rate * 10 * 2)
+        .map(|index| (index as f32 * 0.001).sin() * 0.5)
+        .collect();
+    let mut tab = Tab::new(Samples::new(2, sample_rate, data), None);
+
+    let mut states = Vec::new();
+    for amplitude in [0.9, 0.3, 0.7, 0.1, 1.0] {
+        states.push(bits(&tab.samples));
+        tab.history.push(&tab.samples).unwrap();
+        normalize(amplitude, &mut tab.samples);
+    }
+    let edited = bits(&tab.samples);
+
+    while let Some(expected) = states.pop() {
+        assert!(tab.undo().unwrap());
+        assert_eq!(bits(&tab.samples), expected);
+    }
+    assert!(!tab.undo().unwrap());
+
+    for _ in 0..5 {
+        assert!(tab.redo().unwrap());
+    }
+    assert_eq!(bits(&tab.samples), edited);
+}